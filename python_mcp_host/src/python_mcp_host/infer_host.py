@@ -2,11 +2,14 @@
 import os
 import time
 import json
+import hmac
+import hashlib
 import zmq
 import msgpack
 import logging
 import numpy as np
-from .model_operator import LLMLoader, AltTensor
+from collections import defaultdict, deque
+from .model_operator import LLMLoader, AltTensor, SCHEMA_VERSION
 
 logging.basicConfig(
     level=logging.INFO,
@@ -15,7 +18,132 @@ logging.basicConfig(
 logger = logging.getLogger("InferServer")
 
 
-def alt_tensor_to_prompt(alt_tensor: AltTensor, trading_style: str = None) -> str:
+# 每个模型保留最近的执行拒绝反馈，用于下一次 prompt
+MAX_FEEDBACK_PER_MODEL = 5
+
+# Rust 端缺少 delta 所引用的全量帧时回复此错误，Rust 会重发全量帧
+DELTA_BASELINE_ERROR = "ERROR_DELTA_BASELINE"
+
+# 本端处理的 Rust 消息类型与可能发出的命令，用于和 Rust 端协议规范（--protocol-spec）比对
+KNOWN_MSG_TYPES = {
+    "heartbeat", "trade_rejection", "execution_report",
+    "query_response", "portfolio_response", "protocol_spec",
+}
+EMITTED_COMMANDS = {"adjust_position", "risk_alert", "query", "noop"}
+
+
+class DeltaBaselines:
+    """
+    delta 编码（schema_version 2）的解码状态，按 (model_id, inst) 保存最近一次全量帧
+    - encoding=full: 保存 data 与 col_names 作为基线
+    - encoding=delta: 只包含 changed_idx 中列的新值，基于 base_seq 对应的全量帧还原
+    """
+
+    def __init__(self):
+        self.frames = {}
+
+    def decode(self, data_raw, shape, metadata):
+        """还原为全量 (data, shape, metadata)；基线缺失时返回 None"""
+        encoding = metadata.get("encoding")
+        if not encoding:
+            return data_raw, shape, metadata
+
+        key = (metadata.get("model_id", ""), metadata.get("inst", ""))
+        if encoding == "full":
+            self.frames[key] = (
+                metadata.get("seq"),
+                list(data_raw),
+                metadata.get("col_names", "[]"),
+            )
+            return data_raw, shape, metadata
+
+        base = self.frames.get(key)
+        if base is None or base[0] != metadata.get("base_seq"):
+            return None
+
+        _, base_data, col_names = base
+        data = list(base_data)
+        changed_idx = json.loads(metadata.get("changed_idx", "[]") or "[]")
+        for idx, value in zip(changed_idx, data_raw):
+            data[idx] = value
+
+        metadata = dict(metadata)
+        metadata["col_names"] = col_names
+        return data, [len(data)], metadata
+
+
+def noop_metadata(model_id: str) -> dict:
+    """反馈类消息的 noop 回复，字段满足 Rust 端 prediction 契约"""
+    return {
+        "model_id": model_id,
+        "cmd": "noop",
+        "schema_version": SCHEMA_VERSION,
+        "ts": str(int(time.time() * 1000)),
+    }
+
+
+def protocol_query_metadata(model_id: str) -> dict:
+    """握手：向 Rust 端请求协议规范（query scope=protocol），字段满足 prediction 契约"""
+    metadata = noop_metadata(model_id)
+    metadata["cmd"] = "query"
+    metadata["scope"] = "protocol"
+    return metadata
+
+
+def check_protocol_spec(spec: dict) -> list:
+    """比对 Rust 端协议规范与本端实现，返回不一致之处（空列表表示一致）"""
+    drift = []
+    if spec.get("schema_version") != SCHEMA_VERSION:
+        drift.append(f"schema_version: rust={spec.get('schema_version')} host={SCHEMA_VERSION}")
+    unknown_types = set(spec.get("message_types", [])) - KNOWN_MSG_TYPES
+    if unknown_types:
+        drift.append(f"unhandled msg_type: {sorted(unknown_types)}")
+    unsupported = EMITTED_COMMANDS - set(spec.get("commands", []))
+    if unsupported:
+        drift.append(f"commands unknown to rust: {sorted(unsupported)}")
+    return drift
+
+
+def load_auth_secrets(config_path: str, port: int) -> dict:
+    """读取本端口模型的 auth_secret（与 Rust 端 model_config 相同），"env:NAME" 从环境变量读取"""
+    with open(config_path, "r") as f:
+        config = json.load(f)
+
+    secrets = {}
+    for c in config:
+        secret = c.get("auth_secret")
+        if c.get("port") != port or not secret:
+            continue
+        if secret.startswith("env:"):
+            secret = os.environ.get(secret[len("env:"):], "")
+        if not secret:
+            logger.warning(f"[Auth] auth_secret of '{c['model_id']}' unavailable, commands will be refused")
+        secrets[c["model_id"]] = secret
+    return secrets
+
+
+def sign_metadata(metadata: dict, secret: str) -> None:
+    """
+    按 Rust 端 CommandAuth 的规则签名：除 signature/token 外的字段按 key 排序，
+    拼接为 key=value 行（换行分隔），HMAC-SHA256 后以 hex 写入 signature
+    """
+    metadata.pop("token", None)
+    payload = "\n".join(
+        f"{key}={value}" for key, value in sorted(metadata.items()) if key != "signature"
+    )
+    metadata["signature"] = hmac.new(
+        secret.encode("utf-8"), payload.encode("utf-8"), hashlib.sha256
+    ).hexdigest()
+
+
+def alt_tensor_to_prompt(
+    alt_tensor: AltTensor,
+    trading_style: str = None,
+    rejections: list = None,
+    exec_state: dict = None,
+    executions: list = None,
+    portfolio_state: dict = None,
+) -> str:
     """
     将 AltTensor 中的信息转换为交易 agent 的 prompt
     用于全自动化交易决策
@@ -65,11 +193,68 @@ def alt_tensor_to_prompt(alt_tensor: AltTensor, trading_style: str = None) -> st
     
     # 当前市场信息
     prompt_parts.append("## 当前市场信息")
-    prompt_parts.append(f"- 交易对: DOGE_USDT_PERP")
+    prompt_parts.append(f"- 交易对: {metadata.get('inst', 'DOGE_USDT_PERP')}")
     prompt_parts.append(f"- 当前价格: {price}")
     prompt_parts.append(f"- 当前仓位权重: {pos_weight} (-1到1之间，1表示满仓做多，0表示空仓，-1表示满仓做空)")
     prompt_parts.append("")
+
+    # 执行反馈：之前的目标仓位未能成交
+    if rejections:
+        prompt_parts.append("## 执行反馈")
+        prompt_parts.append("(以下目标仓位未能执行，请调整决策，不要盲目重复相同指令)")
+        for r in rejections:
+            prompt_parts.append(
+                f"- 账户 {r.get('account_id', 'N/A')} | {r.get('inst', 'N/A')} | "
+                f"原因: {r.get('reason', 'N/A')} | 连续失败: {r.get('failures', 'N/A')} 次 | "
+                f"详情: {r.get('detail', '')}"
+            )
+        prompt_parts.append("")
     
+    # 成交回报：上一轮调仓的实际成交价格与决策价格的滑点
+    if executions:
+        prompt_parts.append("## 成交回报")
+        prompt_parts.append("(滑点为正表示成交价劣于决策价格，单位 bps)")
+        for e in executions:
+            estimated = " (估算)" if e.get("estimated") == "true" else ""
+            prompt_parts.append(
+                f"- 账户 {e.get('account_id', 'N/A')} | {e.get('inst', 'N/A')} | {e.get('side', 'N/A')} | "
+                f"成交数量: {e.get('filled_size', 'N/A')} | 成交均价: {e.get('avg_price', 'N/A')} | "
+                f"决策价格: {e.get('decision_price', 'N/A')} | 滑点: {float(e.get('slippage_bps', 0)):.2f} bps{estimated}"
+            )
+        prompt_parts.append("")
+
+    # 执行状态：query 指令返回的目标仓位与实际执行之间的差异
+    if exec_state:
+        prompt_parts.append("## 执行状态")
+        prompt_parts.append(
+            f"- 账户 {exec_state.get('account_id', 'N/A')} | 杠杆缩放系数: {exec_state.get('scale', 'N/A')} | "
+            f"暂停: {exec_state.get('paused', 'false')} | 强制平仓: {exec_state.get('flattened', 'false')} | "
+            f"下次刷新: {exec_state.get('next_update_sec', 'N/A')} 秒"
+        )
+        failures = json.loads(exec_state.get("exec_failures", "{}") or "{}")
+        for inst, row in exec_state.get("rows", {}).items():
+            raw, scaled, consolidated, current, pending = row
+            prompt_parts.append(
+                f"- {inst}: 原始目标 {raw:.4f} | 缩放后 {scaled:.4f} | 合并目标 {consolidated:.4f} | "
+                f"当前仓位 {current:.4f} | 待执行差额 {pending:.4f} | 连续失败 {failures.get(inst, 0)} 次"
+            )
+        prompt_parts.append("")
+
+    # 组合快照：query(scope=portfolio) 返回的价格、合并目标与各账户实际仓位
+    if portfolio_state:
+        prompt_parts.append("## 组合快照")
+        equity = json.loads(portfolio_state.get("equity", "{}") or "{}")
+        for account_id in portfolio_state.get("accounts", []):
+            prompt_parts.append(f"- 账户 {account_id} | 权益: {equity.get(account_id, 'N/A')}")
+        for inst, row in portfolio_state.get("rows", {}).items():
+            price, target, *weights = row
+            actual = " | ".join(
+                f"{account_id} {weight:.4f}"
+                for account_id, weight in zip(portfolio_state.get("accounts", []), weights)
+            )
+            prompt_parts.append(f"- {inst}: 价格 {price} | 合并目标 {target:.4f} | 实际仓位 {actual}")
+        prompt_parts.append("")
+
     # 特征数据 - 分类展示
     if col_names and len(col_names) == len(data_values):
         # 分类特征：原始特征 vs z-score 特征
@@ -197,7 +382,8 @@ def load_models_for_port(config_path: str, port: int) -> dict:
 def run_server(port: int, config_path: str, trading_style: str = None):
     logger.info(f"[Agent] 🚀 Starting server on port {port}")
     models = load_models_for_port(config_path, port)
-    logger.info(f"[Agent] ✅ Loaded {len(models)} model(s)")
+    auth_secrets = load_auth_secrets(config_path, port)
+    logger.info(f"[Agent] ✅ Loaded {len(models)} model(s), {len(auth_secrets)} signing")
 
     ctx = zmq.Context()
     socket = ctx.socket(zmq.REP)
@@ -205,6 +391,14 @@ def run_server(port: int, config_path: str, trading_style: str = None):
     logger.info(f"[Agent] 🔌 ZMQ bound to tcp://127.0.0.1:{port}")
     logger.info(f"[Agent] ⏳ Waiting for data from Rust MCP server...")
 
+    feedback = defaultdict(lambda: deque(maxlen=MAX_FEEDBACK_PER_MODEL))
+    baselines = DeltaBaselines()
+    exec_state = {}
+    portfolio_state = {}
+    executions = defaultdict(lambda: deque(maxlen=MAX_FEEDBACK_PER_MODEL))
+    # 已完成协议握手的模型
+    handshaken = set()
+
     while True:
         raw = socket.recv()
         try:
@@ -236,6 +430,126 @@ def run_server(port: int, config_path: str, trading_style: str = None):
                 socket.send(msgpack.packb(fallback, use_bin_type=True))
                 continue
 
+            # 心跳：直接回复 noop，证明模型仍在线；首个心跳改为回复协议握手请求
+            if metadata.get("msg_type") == "heartbeat":
+                if model_id in handshaken:
+                    reply_metadata = noop_metadata(model_id)
+                else:
+                    reply_metadata = protocol_query_metadata(model_id)
+                    if auth_secrets.get(model_id):
+                        sign_metadata(reply_metadata, auth_secrets[model_id])
+                ack = AltTensor(
+                    timestamp=int(time.time() * 1000),
+                    data=np.zeros([1], dtype=np.float32),
+                    shape=[1],
+                    metadata=reply_metadata
+                ).to_dict()
+                socket.send(msgpack.packb(ack, use_bin_type=True))
+                continue
+
+            # 协议规范：与本端实现比对，不一致时报错，回复 noop
+            if metadata.get("msg_type") == "protocol_spec":
+                handshaken.add(model_id)
+                drift = check_protocol_spec(json.loads(metadata.get("spec", "{}") or "{}"))
+                if drift:
+                    logger.error(f"[Agent] ❌ Protocol drift | model_id={model_id} | {drift}")
+                else:
+                    logger.info(f"[Agent] 🤝 Protocol matches | schema_version={SCHEMA_VERSION}")
+                ack = AltTensor(
+                    timestamp=int(time.time() * 1000),
+                    data=np.zeros([1], dtype=np.float32),
+                    shape=[1],
+                    metadata=noop_metadata(model_id)
+                ).to_dict()
+                socket.send(msgpack.packb(ack, use_bin_type=True))
+                continue
+
+            # 执行拒绝反馈：记录下来供下一次决策使用，直接回复 noop，不调用 LLM
+            if metadata.get("msg_type") == "trade_rejection":
+                feedback[model_id].append(dict(metadata))
+                logger.warning(
+                    f"[Agent] ⚠️ Trade rejected | inst={metadata.get('inst')} | "
+                    f"reason={metadata.get('reason')} | failures={metadata.get('failures')}"
+                )
+                ack = AltTensor(
+                    timestamp=int(time.time() * 1000),
+                    data=np.zeros([1], dtype=np.float32),
+                    shape=[1],
+                    metadata=noop_metadata(model_id)
+                ).to_dict()
+                socket.send(msgpack.packb(ack, use_bin_type=True))
+                continue
+
+            # 成交回报：记录下来供下一次决策使用，直接回复 noop
+            if metadata.get("msg_type") == "execution_report":
+                executions[model_id].append(dict(metadata))
+                logger.info(
+                    f"[Agent] 💹 Execution | inst={metadata.get('inst')} | side={metadata.get('side')} | "
+                    f"avg_px={metadata.get('avg_price')} | slippage={metadata.get('slippage_bps')}bps"
+                )
+                ack = AltTensor(
+                    timestamp=int(time.time() * 1000),
+                    data=np.zeros([1], dtype=np.float32),
+                    shape=[1],
+                    metadata=noop_metadata(model_id)
+                ).to_dict()
+                socket.send(msgpack.packb(ack, use_bin_type=True))
+                continue
+
+            # query 返回的执行状态：保存到下一次 prompt，直接回复 noop
+            if metadata.get("msg_type") == "query_response":
+                insts = json.loads(metadata.get("insts", "[]") or "[]")
+                rows = np.array(data_raw, dtype=np.float32).reshape(len(insts), 5) if insts else []
+                state = dict(metadata)
+                state["rows"] = {inst: [float(v) for v in row] for inst, row in zip(insts, rows)}
+                exec_state[model_id] = state
+                logger.info(f"[Agent] 🔎 Query response | account={metadata.get('account_id')} | insts={len(insts)}")
+                ack = AltTensor(
+                    timestamp=int(time.time() * 1000),
+                    data=np.zeros([1], dtype=np.float32),
+                    shape=[1],
+                    metadata=noop_metadata(model_id)
+                ).to_dict()
+                socket.send(msgpack.packb(ack, use_bin_type=True))
+                continue
+
+            # query(scope=portfolio) 返回的组合快照：保存到下一次 prompt，直接回复 noop
+            if metadata.get("msg_type") == "portfolio_response":
+                insts = json.loads(metadata.get("insts", "[]") or "[]")
+                accounts = json.loads(metadata.get("accounts", "[]") or "[]")
+                cols = 2 + len(accounts)
+                rows = np.array(data_raw, dtype=np.float32).reshape(len(insts), cols) if insts else []
+                state = dict(metadata)
+                state["accounts"] = accounts
+                state["rows"] = {inst: [float(v) for v in row] for inst, row in zip(insts, rows)}
+                portfolio_state[model_id] = state
+                logger.info(f"[Agent] 🔎 Portfolio response | accounts={len(accounts)} | insts={len(insts)}")
+                ack = AltTensor(
+                    timestamp=int(time.time() * 1000),
+                    data=np.zeros([1], dtype=np.float32),
+                    shape=[1],
+                    metadata=noop_metadata(model_id)
+                ).to_dict()
+                socket.send(msgpack.packb(ack, use_bin_type=True))
+                continue
+
+            # delta 编码的特征帧先还原为全量帧
+            decoded = baselines.decode(data_raw, shape, metadata)
+            if decoded is None:
+                logger.warning(
+                    f"[Agent] ⚠️ Missing delta baseline | model_id={model_id} | "
+                    f"inst={metadata.get('inst')} | base_seq={metadata.get('base_seq')}"
+                )
+                fallback = AltTensor(
+                    timestamp=int(time.time() * 1000),
+                    data=np.zeros([1], dtype=np.float32),
+                    shape=[1],
+                    metadata={"error": DELTA_BASELINE_ERROR, "model_id": model_id}
+                ).to_dict()
+                socket.send(msgpack.packb(fallback, use_bin_type=True))
+                continue
+            data_raw, shape, metadata = decoded
+
             # 将数据转换为numpy数组（必须是浮点数）
             data_np = np.array(data_raw, dtype=np.float32).reshape(shape)
             
@@ -268,7 +582,16 @@ def run_server(port: int, config_path: str, trading_style: str = None):
             # 自动将 AltTensor 信息转换为 prompt（全自动化交易 agent）
             # 如果 metadata 中已经有 prompt，则使用已有的；否则自动生成
             if "prompt" not in metadata or not metadata.get("prompt"):
-                auto_prompt = alt_tensor_to_prompt(alt_tensor_input, trading_style=trading_style)
+                auto_prompt = alt_tensor_to_prompt(
+                    alt_tensor_input,
+                    trading_style=trading_style,
+                    rejections=list(feedback[model_id]),
+                    exec_state=exec_state.pop(model_id, None),
+                    executions=list(executions[model_id]),
+                    portfolio_state=portfolio_state.pop(model_id, None),
+                )
+                feedback[model_id].clear()
+                executions[model_id].clear()
                 metadata["prompt"] = auto_prompt
                 # 更新 alt_tensor_input 的 metadata
                 alt_tensor_input.metadata = metadata
@@ -294,6 +617,10 @@ def run_server(port: int, config_path: str, trading_style: str = None):
             # 显示交易决策
             logger.info(f"[Agent] ✅ Decision | cmd={cmd} | inst={inst} | target_pos={target_pos} | latency={latency:.0f}ms")
 
+            # 配置了 auth_secret 的模型，其命令须签名后 Rust 端才会执行
+            if auth_secrets.get(model_id):
+                sign_metadata(result_dict["metadata"], auth_secrets[model_id])
+
             socket.send(msgpack.packb(result_dict, use_bin_type=True))
 
         except Exception as e:
@@ -2,12 +2,16 @@ import os
 import json
 import logging
 import re
+import time
 import numpy as np
 from typing import Dict, List, Optional
 import google.generativeai as genai
 
 logger = logging.getLogger("ModelLoader")
 
+# 与 Rust 端 server_contract::SCHEMA_VERSION 保持一致
+SCHEMA_VERSION = "1"
+
 
 # ============================
 #  LLM Base Model
@@ -79,7 +83,7 @@ class AltTensor:
 # ============================
 #  LLM 响应解析器 - 提取 Rust Mediator 需要的字段
 # ============================
-def parse_llm_response_for_mediator(response_text: str) -> Dict[str, str]:
+def parse_llm_response_for_mediator(response_text: str, default_inst: str = "DOGE_USDT_PERP") -> Dict[str, str]:
     """
     解析 LLM 响应，提取 Rust MCP Mediator 需要的字段
     支持的命令: adjust_position, risk_alert, query, noop
@@ -88,6 +92,9 @@ def parse_llm_response_for_mediator(response_text: str) -> Dict[str, str]:
     - cmd: 命令类型
     - inst: 交易对名称（如 "DOGE_USDT_PERP"）
     - target_position 或 pos_weight: 目标仓位权重（浮点数，转为字符串）
+    - limit_price / max_slippage_bps（可选）: 成交价格上限，超出时跳过下单
+    - expires_at（可选）: 毫秒时间戳，到期后目标仓位恢复为之前的值
+    - price（可选）: 预测所依据的价格，偏离本地成交价过多时被拒绝
     """
     metadata = {}
     
@@ -106,6 +113,9 @@ def parse_llm_response_for_mediator(response_text: str) -> Dict[str, str]:
                     metadata["target_position"] = str(parsed["target_position"])
                 if "pos_weight" in parsed:
                     metadata["pos_weight"] = str(parsed["pos_weight"])
+                for key in ("price", "limit_price", "max_slippage_bps", "expires_at"):
+                    if key in parsed:
+                        metadata[key] = str(parsed[key])
                 logger.info(f"[Parser] Extracted JSON fields: {metadata}")
                 return metadata
         except json.JSONDecodeError:
@@ -211,8 +221,8 @@ def parse_llm_response_for_mediator(response_text: str) -> Dict[str, str]:
     # 如果没有找到交易对，使用默认值（Rust mediator 需要 inst 字段）
     # 只要 cmd 是 adjust_position，就需要 inst 字段
     if "inst" not in metadata and metadata.get("cmd") == "adjust_position":
-        metadata["inst"] = "DOGE_USDT_PERP"  # Rust mediator 的默认值
-        logger.info("[Parser] No instrument found in response, using default: DOGE_USDT_PERP")
+        metadata["inst"] = default_inst  # 默认使用本次输入数据对应的交易对
+        logger.info(f"[Parser] No instrument found in response, using default: {default_inst}")
     
     if metadata:
         logger.info(f"[Agent] 🔍 Parsed | {', '.join([f'{k}={v}' for k, v in metadata.items()])}")
@@ -287,7 +297,10 @@ class LLMLoader:
         response_data = np.array([b / 255.0 for b in response_bytes], dtype=np.float32)
         
         # 解析 LLM 响应，提取 Rust Mediator 需要的字段
-        mediator_fields = parse_llm_response_for_mediator(response_text)
+        mediator_fields = parse_llm_response_for_mediator(
+            response_text,
+            default_inst=alt_tensor.metadata.get("inst", "DOGE_USDT_PERP"),
+        )
         
         # 构造返回AltTensor
         metadata = alt_tensor.metadata.copy()
@@ -303,6 +316,10 @@ class LLMLoader:
         # 合并 mediator 字段到 metadata（确保都是字符串）
         for key, value in mediator_fields.items():
             metadata[key] = str(value)
+
+        # 协议契约字段：schema_version 与毫秒时间戳
+        metadata["schema_version"] = SCHEMA_VERSION
+        metadata["ts"] = str(int(time.time() * 1000))
         
         logger.debug(f"[LLMLoader] Final metadata keys: {list(metadata.keys())}")
         
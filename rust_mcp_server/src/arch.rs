@@ -1,3 +1,6 @@
 pub mod account_module;
+pub mod control_module;
+pub mod profile;
+pub mod risk_module;
 pub mod server_module;
 mod feats;
\ No newline at end of file
@@ -1,3 +1,24 @@
+pub mod acc_advisory;
 pub mod acc_base;
+pub mod acc_cache;
 pub mod acc_core;
+pub mod acc_drawdown;
+pub mod acc_errors;
+pub mod acc_exposure;
+pub mod acc_funding;
+pub mod acc_hyperliquid;
+pub mod acc_intent;
+pub mod acc_journal;
+pub mod acc_limits;
+pub mod acc_orders;
+pub mod acc_own_orders;
+pub mod acc_paper;
+pub mod acc_ratelimit;
+pub mod acc_requests;
+pub mod acc_rest;
+pub mod acc_retry;
+pub mod acc_skips;
+pub mod acc_tracking;
+pub mod acc_transfers;
+pub mod acc_twap;
 pub mod acc_utils;
\ No newline at end of file
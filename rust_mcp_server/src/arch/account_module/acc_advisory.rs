@@ -0,0 +1,172 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{collections::HashMap, fs::OpenOptions, io::Write, sync::Arc};
+use tracing::{error, info, warn};
+
+/// Target moves smaller than this do not notify the same rebalance again.
+const ADVISORY_TARGET_TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Where one severity's notifications go.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifyChannel {
+    Log,
+    /// JSON POST with a human-readable `text`, accepted as is by Slack-style incoming webhooks.
+    Webhook {
+        url: String,
+    },
+    /// Appends one JSON line per notification.
+    File {
+        path: String,
+    },
+}
+
+/// Rebalances of at least `min_weight` of equity notify `channels` at `severity`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AdvisoryRule {
+    pub severity: Severity,
+    pub min_weight: f64,
+    pub channels: Vec<NotifyChannel>,
+}
+
+/// Advisory mode: large rebalances are reported to a human before they are sent.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AdvisoryConfig {
+    pub rules: Vec<AdvisoryRule>,
+}
+
+impl AdvisoryConfig {
+    fn rule(&self, diff: f64) -> Option<&AdvisoryRule> {
+        self.rules
+            .iter()
+            .filter(|rule| diff.abs() >= rule.min_weight)
+            .max_by(|a, b| a.min_weight.total_cmp(&b.min_weight))
+    }
+}
+
+/// One rebalance as reported to the advisory channels.
+#[derive(Clone, Debug, Serialize)]
+pub struct Advisory {
+    pub account_id: String,
+    pub inst: String,
+    pub severity: Severity,
+    /// Weight change of the rebalance, signed.
+    pub diff: f64,
+    pub notional: f64,
+    pub total_equity: f64,
+    pub current_weight: f64,
+    pub target_weight: f64,
+    pub mark_price: Option<f64>,
+    pub reduce_only: bool,
+    pub execution: String,
+    pub timestamp: u64,
+}
+
+impl Advisory {
+    fn text(&self) -> String {
+        format!(
+            "[{:?}] {} {} {} {:.2}% of equity ({:.2} notional): weight {:.4} -> {:.4}, {}",
+            self.severity,
+            self.account_id,
+            if self.diff > 0.0 { "BUY" } else { "SELL" },
+            self.inst,
+            self.diff.abs() * 100.0,
+            self.notional,
+            self.current_weight,
+            self.target_weight,
+            self.execution,
+        )
+    }
+}
+
+/// Sends advisories of one account, once per target weight of an instrument.
+#[derive(Clone, Debug)]
+pub struct AdvisoryNotifier {
+    config: AdvisoryConfig,
+    client: Arc<Client>,
+    /// Target weight last notified per instrument.
+    notified: HashMap<String, f64>,
+}
+
+impl AdvisoryNotifier {
+    pub fn new(config: AdvisoryConfig, client: Arc<Client>) -> Self {
+        Self {
+            config,
+            client,
+            notified: HashMap::new(),
+        }
+    }
+
+    /// Severity of a rebalance of `diff`, unless below every rule or already notified.
+    pub fn severity(&self, inst: &str, diff: f64, target: f64) -> Option<Severity> {
+        if self
+            .notified
+            .get(inst)
+            .is_some_and(|last| (last - target).abs() < ADVISORY_TARGET_TOLERANCE)
+        {
+            return None;
+        }
+
+        self.config.rule(diff).map(|rule| rule.severity)
+    }
+
+    /// Sends `advisory` on every channel of its rule.
+    pub fn notify(&mut self, advisory: Advisory) {
+        let Some(rule) = self.config.rule(advisory.diff) else {
+            return;
+        };
+        self.notified
+            .insert(advisory.inst.clone(), advisory.target_weight);
+
+        let text = advisory.text();
+        for channel in &rule.channels {
+            match channel {
+                NotifyChannel::Log => match advisory.severity {
+                    Severity::Info => info!("[Advisory] {}", text),
+                    Severity::Warning => warn!("[Advisory] {}", text),
+                    Severity::Critical => error!("[Advisory] {}", text),
+                },
+                NotifyChannel::Webhook { url } => {
+                    let body = json!({ "text": text, "advisory": advisory });
+                    let client = self.client.clone();
+                    let url = url.clone();
+                    tokio::spawn(async move {
+                        let sent = client
+                            .post(&url)
+                            .json(&body)
+                            .send()
+                            .await
+                            .and_then(|res| res.error_for_status());
+                        if let Err(e) = sent {
+                            warn!("[Advisory] Webhook delivery failed: {}", e);
+                        }
+                    });
+                },
+                NotifyChannel::File { path } => {
+                    let written = OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .and_then(|mut f| writeln!(f, "{}", json!(advisory)));
+                    if let Err(e) = written {
+                        warn!("[Advisory] Failed to append to {}: {}", path, e);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Forgets instruments whose rebalance completed, so their next one notifies again.
+    pub fn retain(&mut self, pending: &HashMap<String, f64>) {
+        self.notified.retain(|inst, _| pending.contains_key(inst));
+    }
+}
@@ -1,44 +1,167 @@
 use dashmap::DashMap;
 use reqwest::Client;
+use serde_json::{Value, json};
 use std::{
-    collections::{HashMap, HashSet},
-    sync::Arc,
-    time::Duration,
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
-use tokio::{sync::oneshot, time::sleep};
-use tracing::{info, warn};
+use tokio::{
+    sync::oneshot,
+    time::{sleep, timeout},
+};
+use tracing::{error, info, warn};
 
 use extrema_infra::{
     arch::market_assets::{
-        api_data::utils_data::InstrumentInfo, api_general::OrderParams, exchange::prelude::*,
+        api_data::utils_data::InstrumentInfo,
+        api_general::{OrderParams, get_micros_timestamp, normalize_to_string},
+        exchange::prelude::*,
     },
     prelude::*,
 };
 
-use super::acc_utils::*;
+use super::{
+    acc_advisory::{Advisory, AdvisoryNotifier},
+    acc_cache::{METADATA_TTL, MetaCache},
+    acc_drawdown::{DRAWDOWN_STATE_PREFIX, DrawdownBreaker},
+    acc_errors::{BudgetChange, ErrorBudget},
+    acc_exposure::{ExposureReport, underlying},
+    acc_funding::FundingLedger,
+    acc_intent::*, acc_journal::*,
+    acc_limits::{POSITION_CAP_RETRY, PositionCaps, cap_order, tier_cap},
+    acc_hyperliquid::{HyperliquidClient, hl_position_cap, hyperliquid_instrument_info},
+    acc_orders::*, acc_own_orders::OwnOrderBook, acc_paper::simulate_rejection,
+    acc_ratelimit::{RateLimiter, RateVenue, binance_weight},
+    acc_requests::{AccountRequest, AccountRequests},
+    acc_rest::*,
+    acc_retry::{RetryPolicy, with_retry},
+    acc_skips::{SkipLog, SkipReason},
+    acc_tracking::TrackingReport,
+    acc_transfers::TransferLedger,
+    acc_twap::{TwapConfig, TwapScheduler},
+    acc_utils::*,
+};
+use crate::arch::{
+    control_module::{
+        ctl_admin::audit,
+        ctl_audit::{CommandAudit, command_id},
+        ctl_auth::CommandAuth,
+        ctl_base::{AccountMigration, ControlState, MigrationStage},
+        ctl_portfolio::PortfolioView,
+        ctl_risk::RiskLimits,
+        ctl_watchdog::{TaskHeartbeats, WatchdogConfig},
+    },
+    feats::order_flow::ToxicityMap,
+    profile::active_profile,
+    risk_module::risk_alert::RiskAlerts,
+};
 
-type InstKey = (String, Market);
+pub type InstKey = (String, Market);
 pub type TargetWeights = Arc<DashMap<String, (f64, f64)>>;
+/// Model price limits per instrument, see `PriceLimit`.
+pub type PriceLimits = Arc<DashMap<String, PriceLimit>>;
+pub type RejectionQueue = Arc<Mutex<VecDeque<TradeRejection>>>;
+pub type ExecutionQueue = Arc<Mutex<VecDeque<ExecutionReport>>>;
+pub type AccountSnapshots = Arc<DashMap<String, AccountSnapshot>>;
+
+/// Wait for a WS task to accept a ping before the channel counts as unresponsive.
+const WS_PING_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Binance listen keys lapse after an hour without a renewal.
+const LISTEN_KEY_RENEWAL: Duration = Duration::from_secs(30 * 60);
+/// Target moves smaller than this do not reprocess the accounts.
+const TARGET_CHANGE_TOLERANCE: f64 = 1e-9;
+/// Time `McpServer` gets to publish the targets of a reply it handles concurrently.
+const TARGET_SETTLE: Duration = Duration::from_millis(100);
+
+/// What became of an order handed to `submit_order`.
+enum Submission {
+    /// Sent, with the weight it filled once known.
+    Sent(Option<f64>),
+    /// An identical intent is still open, so nothing was sent.
+    Duplicate,
+}
 
 #[derive(Clone, Debug)]
 pub struct AccountManager {
     pub target_weights: TargetWeights,
+    pub rejections: RejectionQueue,
+    pub executions: ExecutionQueue,
+    pub snapshots: AccountSnapshots,
+    pub portfolio: PortfolioView,
     pub task_index: HashMap<u64, String>,
     pub account_infos: HashMap<String, AccountInfo>,
     pub instrument_infos: HashMap<InstKey, InstrumentInfo>,
     pub command_handles: Vec<Arc<CommandHandle>>,
     pub config: AccountInitConfig,
+    pub intent_log: IntentLog,
+    pub journal: JournalExporter,
+    pub tracking: TrackingReport,
+    pub intervals: SchedulerIntervals,
+    pub last_ws_ping: Option<Instant>,
+    pub last_listen_key_renewal: Option<Instant>,
+    pub control: ControlState,
+    pub risk: RiskLimits,
+    pub kill_engaged: bool,
+    pub heartbeats: TaskHeartbeats,
+    pub watchdog: WatchdogConfig,
+    /// Venue metadata shared by all accounts, kept across reloads.
+    pub meta_cache: MetaCache,
+    /// REST budgets per venue shared by all accounts, kept across reloads.
+    pub rate_limiter: RateLimiter,
+    pub toxicity: ToxicityMap,
+    pub price_limits: PriceLimits,
+    /// Resting orders of all accounts per venue.
+    pub own_orders: OwnOrderBook,
+    pub risk_alerts: RiskAlerts,
+    /// Order management requests from MCP clients.
+    pub account_requests: AccountRequests,
+    pub command_auth: CommandAuth,
+    pub command_audit: CommandAudit,
+    /// Assets over `max_asset_net_weight` with the blocked side, as last logged.
+    pub asset_breaches: HashMap<String, f64>,
+    /// Recent update failures, against the risk config's `account_error_budget`.
+    pub error_budget: ErrorBudget,
+    /// Consolidated weights the last pass over all accounts ran against.
+    pub processed_targets: HashMap<String, f64>,
 }
 
 impl AccountManager {
     pub fn new(config: AccountInitConfig) -> Self {
         Self {
             target_weights: Arc::new(DashMap::new()),
+            rejections: Arc::new(Mutex::new(VecDeque::new())),
+            executions: Arc::new(Mutex::new(VecDeque::new())),
+            snapshots: Arc::new(DashMap::new()),
+            portfolio: PortfolioView::default(),
             task_index: HashMap::new(),
             account_infos: HashMap::new(),
             instrument_infos: HashMap::new(),
             command_handles: Vec::new(),
+            rate_limiter: RateLimiter::new(config.rate_limits.clone()),
+            intent_log: IntentLog::new(&config.intent_log_path),
+            journal: JournalExporter::new(&config.journal_dir),
+            tracking: TrackingReport::new(&config.tracking_report_path),
             config,
+            intervals: SchedulerIntervals::default(),
+            last_ws_ping: None,
+            last_listen_key_renewal: None,
+            control: ControlState::default(),
+            risk: RiskLimits::default(),
+            kill_engaged: false,
+            heartbeats: TaskHeartbeats::default(),
+            watchdog: WatchdogConfig::default(),
+            meta_cache: MetaCache::default(),
+            toxicity: Arc::new(DashMap::new()),
+            price_limits: Arc::new(DashMap::new()),
+            own_orders: OwnOrderBook::default(),
+            risk_alerts: RiskAlerts::default(),
+            account_requests: AccountRequests::default(),
+            command_auth: CommandAuth::default(),
+            command_audit: CommandAudit::default(),
+            asset_breaches: HashMap::new(),
+            error_budget: ErrorBudget::default(),
+            processed_targets: HashMap::new(),
         }
     }
 
@@ -47,33 +170,670 @@ impl AccountManager {
         self
     }
 
-    pub async fn init_inst_info(&mut self) -> InfraResult<()> {
-        let okx_cli = OkxCli::default();
-        let binance_cli = BinanceUmCli::default();
+    pub fn with_toxicity(&mut self, toxicity: ToxicityMap) -> &mut Self {
+        self.toxicity = toxicity;
+        self
+    }
 
-        let okx_inst_info = okx_cli
-            .get_instrument_info(InstrumentType::Perpetual)
-            .await?;
-        let binance_inst_info = binance_cli
-            .get_instrument_info(InstrumentType::Perpetual)
-            .await?;
+    pub fn with_price_limits(&mut self, price_limits: PriceLimits) -> &mut Self {
+        self.price_limits = price_limits;
+        self
+    }
+
+    pub fn with_portfolio_view(&mut self, portfolio: PortfolioView) -> &mut Self {
+        self.portfolio = portfolio;
+        self
+    }
+
+    pub fn with_own_orders(&mut self, own_orders: OwnOrderBook) -> &mut Self {
+        self.own_orders = own_orders;
+        self
+    }
+
+    pub fn with_risk_alerts(&mut self, risk_alerts: RiskAlerts) -> &mut Self {
+        self.risk_alerts = risk_alerts;
+        self
+    }
+
+    pub fn with_account_requests(&mut self, account_requests: AccountRequests) -> &mut Self {
+        self.account_requests = account_requests;
+        self
+    }
+
+    pub fn with_command_auth(&mut self, command_auth: CommandAuth) -> &mut Self {
+        self.command_auth = command_auth;
+        self
+    }
+
+    pub fn with_command_audit(&mut self, command_audit: CommandAudit) -> &mut Self {
+        self.command_audit = command_audit;
+        self
+    }
+
+    pub fn with_scheduler_intervals(&mut self, intervals: SchedulerIntervals) -> &mut Self {
+        self.intervals = intervals;
+        self
+    }
+
+    pub fn with_rejection_queue(&mut self, rejections: RejectionQueue) -> &mut Self {
+        self.rejections = rejections;
+        self
+    }
+
+    pub fn with_execution_queue(&mut self, executions: ExecutionQueue) -> &mut Self {
+        self.executions = executions;
+        self
+    }
+
+    pub fn with_control_state(&mut self, control: ControlState) -> &mut Self {
+        self.control = control;
+        self
+    }
+
+    pub fn with_risk_limits(&mut self, risk: RiskLimits) -> &mut Self {
+        self.risk = risk;
+        self
+    }
+
+    pub fn with_account_snapshots(&mut self, snapshots: AccountSnapshots) -> &mut Self {
+        self.snapshots = snapshots;
+        self
+    }
+
+    pub fn with_watchdog(
+        &mut self,
+        heartbeats: TaskHeartbeats,
+        config: WatchdogConfig,
+    ) -> &mut Self {
+        self.heartbeats = heartbeats;
+        self.watchdog = config;
+        self
+    }
+
+    /// Reconnects both WS channels of accounts whose order or balance stream went silent.
+    pub async fn restart_stalled_ws(&mut self) {
+        let mut account_ids = HashSet::new();
+        for task in self.heartbeats.due_restarts("ws:acc:", &self.watchdog) {
+            let account_id = task
+                .strip_prefix("ws:acc:")
+                .and_then(|id| id.parse::<u64>().ok())
+                .and_then(|id| self.task_index.get(&id));
+
+            match account_id {
+                Some(id) => {
+                    account_ids.insert(id.clone());
+                },
+                None => self.heartbeats.forget(&task),
+            }
+        }
+
+        for account_id in account_ids {
+            let Some(acc) = self.account_infos.get(&account_id).cloned() else {
+                continue;
+            };
+
+            info!("[Watchdog] Reconnecting WS for account_id={}", account_id);
+            if let Err(e) = self.ws_disconnect_account(&acc).await {
+                warn!("[Watchdog] WS disconnect failed for {}: {:?}", account_id, e);
+            }
+            match self.ws_connect_account(&acc).await {
+                Ok(()) => self.mark_reconcile(&account_id),
+                Err(e) => warn!("[Watchdog] WS reconnect failed for {}: {:?}", account_id, e),
+            }
+        }
+        self.reconcile_accounts().await;
+    }
+
+    /// Pings every private WS channel and beats its heartbeat once the ping went out.
+    pub async fn ping_private_ws(&mut self) {
+        // Testnet user streams are opened here rather than by the exchange client
+        if interval_elapsed(&mut self.last_listen_key_renewal, LISTEN_KEY_RENEWAL) {
+            for acc in self.account_infos.values() {
+                if let Some(rest) = acc.testnet_rest()
+                    && *rest.venue() == RestVenue::BinanceUm
+                    && let Err(e) = rest.binance_user_stream_url().await
+                {
+                    warn!("[WS] Listen key renewal failed for {}: {:?}", acc.account_id, e);
+                }
+            }
+        }
+
+        let every = Duration::from_secs(self.watchdog.account_ws_ping_sec);
+        if !interval_elapsed(&mut self.last_ws_ping, every) {
+            return;
+        }
+
+        let mut pings = Vec::new();
+        for acc in self.account_infos.values() {
+            let Some(msg) = private_ws_ping(acc) else {
+                continue;
+            };
+            pings.push((
+                acc.account_id.clone(),
+                WsChannel::AccountOrders,
+                acc.account_orders_task_id,
+                msg.clone(),
+            ));
+            pings.push((
+                acc.account_id.clone(),
+                WsChannel::AccountBalAndPos,
+                acc.account_bal_pos_task_id,
+                msg,
+            ));
+        }
+
+        for (account_id, channel, task_id, msg) in pings {
+            let Some(handle) = self.find_ws_handle(&channel, task_id) else {
+                continue;
+            };
+            let (tx, rx) = oneshot::channel();
+            let cmd = TaskCommand::WsMessage {
+                msg,
+                ack: AckHandle::new(tx),
+            };
+            let sent = handle.send_command(cmd, Some((AckStatus::WsMessage, rx)));
+            match timeout(WS_PING_ACK_TIMEOUT, sent).await {
+                Ok(Ok(())) => self.beat_private_ws(task_id),
+                Ok(Err(e)) => warn!(
+                    "[Watchdog] WS ping failed for account={} task_id={}: {:?}",
+                    account_id, task_id, e
+                ),
+                Err(_) => warn!(
+                    "[Watchdog] WS ping unacknowledged for account={} task_id={}",
+                    account_id, task_id
+                ),
+            }
+        }
+    }
+
+    fn beat_private_ws(&self, task_id: u64) {
+        let stall_after = self.watchdog.account_ws_stall_sec;
+        self.heartbeats.beat(&format!("ws:acc:{}", task_id), stall_after);
+    }
+
+    fn mark_reconcile(&mut self, account_id: &str) {
+        if let Some(account) = self.account_infos.get_mut(account_id) {
+            account.reconcile_pending = true;
+        }
+    }
+
+    /// Re-syncs every account that reconnected its private WS since the last pass.
+    pub async fn reconcile_accounts(&mut self) {
+        let mut reconciled = false;
+        for account in self.account_infos.values_mut() {
+            if !account.reconcile_pending {
+                continue;
+            }
+            match account.reconcile(&self.instrument_infos).await {
+                Ok(()) => reconciled = true,
+                Err(e) => warn!(
+                    "[Reconcile] Account={} snapshot failed, retrying on next update: {}",
+                    account.account_id, e
+                ),
+            }
+        }
+
+        if reconciled {
+            self.flush_feedback();
+            self.publish_snapshots();
+        }
+    }
+
+    /// Moves an operator migration through its stages and assigns the per-account roles.
+    pub fn advance_migration(&mut self) {
+        self.account_infos
+            .values_mut()
+            .for_each(|acc| acc.migration_role = MigrationRole::Normal);
+
+        let Some(mut migration) = self.control.migration() else {
+            return;
+        };
+        let stage = migration.stage;
+
+        match migration.stage {
+            MigrationStage::Requested => match self.check_migration(&migration) {
+                Ok(()) => {
+                    migration.stage = MigrationStage::Flattening;
+                    info!(
+                        "[Migration] {} -> {}: risk checks passed, flattening source",
+                        migration.from, migration.to
+                    );
+                },
+                Err(reason) => {
+                    warn!(
+                        "[Migration] {} -> {} refused: {}",
+                        migration.from, migration.to, reason
+                    );
+                    migration.stage = MigrationStage::Failed;
+                    migration.detail = reason;
+                },
+            },
+            MigrationStage::Flattening => {
+                let tolerance = self.risk.get().converged_tolerance;
+                let source_flat = self.account_infos.get(&migration.from).is_some_and(|acc| {
+                    acc.acc_weights.values().all(|w| w.abs() < tolerance)
+                });
+
+                if source_flat {
+                    migration.stage = MigrationStage::Done;
+                    migration.detail = "source flat, destination following targets".into();
+                    info!(
+                        "[Migration] {} -> {}: source flat, releasing destination",
+                        migration.from, migration.to
+                    );
+                }
+            },
+            MigrationStage::Done | MigrationStage::Failed => {},
+        }
+
+        let roles = match migration.stage {
+            MigrationStage::Flattening => [MigrationRole::Flatten, MigrationRole::Hold],
+            MigrationStage::Done => [MigrationRole::Flatten, MigrationRole::Normal],
+            _ => [MigrationRole::Normal, MigrationRole::Normal],
+        };
+        for (account_id, role) in [&migration.from, &migration.to].into_iter().zip(roles) {
+            if let Some(acc) = self.account_infos.get_mut(account_id) {
+                acc.migration_role = role;
+            }
+        }
+
+        if migration.stage != stage {
+            self.control.set_migration(Some(migration));
+        }
+    }
+
+    fn check_migration(&self, migration: &AccountMigration) -> Result<(), String> {
+        let from = self
+            .account_infos
+            .get(&migration.from)
+            .ok_or_else(|| format!("unknown source account {}", migration.from))?;
+        let to = self
+            .account_infos
+            .get(&migration.to)
+            .ok_or_else(|| format!("unknown destination account {}", migration.to))?;
+
+        if to.total_equity <= f64::EPSILON {
+            return Err(format!("destination {} has no equity", to.account_id));
+        }
+        if !from.margin_asset.eq_ignore_ascii_case(&to.margin_asset) {
+            return Err(format!(
+                "margin asset mismatch: {} vs {}",
+                from.margin_asset, to.margin_asset
+            ));
+        }
+        if from.paper != to.paper {
+            return Err("cannot migrate between paper and live accounts".into());
+        }
+        let tolerance = self.risk.get().converged_tolerance;
+        if to.acc_weights.values().any(|w| w.abs() >= tolerance) {
+            return Err(format!("destination {} already holds positions", to.account_id));
+        }
+
+        Ok(())
+    }
+
+    pub async fn cancel_stale_orders(&mut self) {
+        let timeout = Duration::from_secs(self.config.limit_order_timeout_sec);
+        for account in self.account_infos.values_mut() {
+            account
+                .cancel_stale_orders(timeout, &self.instrument_infos)
+                .await;
+        }
+    }
+
+    /// Runs a model's order command; true when one was executed.
+    pub async fn handle_order_command(&mut self, alt_tensor: &AltTensor) -> InfraResult<bool> {
+        let meta = &alt_tensor.metadata;
+        let cmd = meta.get("cmd").map(|x| x.as_str()).unwrap_or("noop");
+        if !matches!(cmd, "cancel_all" | "cancel_order" | "amend_order") {
+            return Ok(false);
+        }
+        self.command_auth.authorize(meta)?;
+
+        let result = self.run_order_command(cmd, meta).await;
+        self.command_audit.record_action(
+            Some(command_id(meta, alt_tensor.timestamp)),
+            cmd,
+            meta.get("account_id").map(|x| x.as_str()),
+            &result,
+        );
+        result.map(|_| true)
+    }
+
+    async fn run_order_command(
+        &mut self,
+        cmd: &str,
+        meta: &HashMap<String, String>,
+    ) -> InfraResult<Value> {
+        if cmd == "cancel_all" {
+            let (cancelled, failed) = self
+                .cancel_all(
+                    meta.get("account_id").map(|x| x.as_str()),
+                    meta.get("venue").map(|x| x.as_str()),
+                    meta.get("inst").map(|x| x.as_str()),
+                )
+                .await;
+            return Ok(json!({ "cancelled": cancelled, "failed": failed }));
+        }
+
+        let account_id = meta
+            .get("account_id")
+            .ok_or_else(|| InfraError::Msg(format!("{}: account_id missing", cmd)))?;
+        let inst = meta
+            .get("inst")
+            .ok_or_else(|| InfraError::Msg(format!("{}: inst missing", cmd)))?;
+        let order = match (meta.get("order_id"), meta.get("client_order_id")) {
+            (Some(id), _) => OrderRef::Id(id.clone()),
+            (None, Some(id)) => OrderRef::ClientId(id.clone()),
+            (None, None) => {
+                return Err(InfraError::Msg(format!(
+                    "{}: order_id or client_order_id required",
+                    cmd
+                )));
+            },
+        };
+
+        let account = self
+            .account_infos
+            .get_mut(account_id)
+            .ok_or_else(|| InfraError::Msg(format!("{}: unknown account {}", cmd, account_id)))?;
+
+        match cmd {
+            "cancel_order" => account.cancel_order(inst, &order).await?,
+            _ => {
+                let size = meta.get("size").map(|x| x.as_str());
+                let price = meta.get("price").map(|x| x.as_str());
+                account.amend_order(inst, &order, size, price).await?
+            },
+        }
+        Ok(json!({ "inst": inst, "order": order.key() }))
+    }
+
+    /// Cancels resting own orders, narrowed by account, venue or instrument when given.
+    pub async fn cancel_all(
+        &mut self,
+        account_id: Option<&str>,
+        venue: Option<&str>,
+        inst: Option<&str>,
+    ) -> (usize, usize) {
+        let (mut cancelled, mut failed) = (0, 0);
+        for account in self.account_infos.values_mut() {
+            if account_id.is_some_and(|id| id != account.account_id)
+                || venue.is_some_and(|v| account.orders.venue() != Some(v))
+            {
+                continue;
+            }
+
+            for tracked in account.orders.stale(Duration::ZERO) {
+                if inst.is_some_and(|i| i != tracked.inst) {
+                    continue;
+                }
+                match account.cancel_order(&tracked.inst, &tracked.order).await {
+                    Ok(()) => cancelled += 1,
+                    Err(e) => {
+                        failed += 1;
+                        warn!(
+                            "[Order] cancel_all failed account={} order={:?}: {}",
+                            account.account_id, tracked.order, e
+                        );
+                    },
+                }
+            }
+        }
+
+        info!(
+            "[Order] cancel_all account={:?} venue={:?} inst={:?}: {} cancelled, {} failed",
+            account_id, venue, inst, cancelled, failed
+        );
+        (cancelled, failed)
+    }
+
+    /// Answers the order management requests MCP clients queued since the last tick.
+    pub async fn handle_account_requests(&mut self) {
+        for pending in self.account_requests.take() {
+            let result = match &pending.request {
+                AccountRequest::ListOpenOrders { account_id } => {
+                    self.list_open_orders(account_id)
+                },
+                AccountRequest::CancelAll { account_id, inst } => {
+                    let result = if self.account_infos.contains_key(account_id) {
+                        let (cancelled, failed) =
+                            self.cancel_all(Some(account_id), None, inst.as_deref()).await;
+                        Ok(json!({
+                            "account_id": account_id,
+                            "inst": inst,
+                            "cancelled": cancelled,
+                            "failed": failed,
+                        }))
+                    } else {
+                        Err(InfraError::Msg(format!("unknown account {}", account_id)))
+                    };
+                    self.command_audit.record_action(
+                        None,
+                        "cancel_all_orders",
+                        Some(account_id),
+                        &result,
+                    );
+                    result
+                },
+            };
+
+            if pending.reply.send(result).is_err() {
+                warn!("[Order] Requester gone before {:?} was answered", pending.request);
+            }
+        }
+    }
+
+    fn list_open_orders(&self, account_id: &str) -> InfraResult<Value> {
+        let account = self
+            .account_infos
+            .get(account_id)
+            .ok_or_else(|| InfraError::Msg(format!("unknown account {}", account_id)))?;
+
+        let mut orders = account.orders.stale(Duration::ZERO);
+        orders.sort_by(|a, b| a.inst.cmp(&b.inst).then(b.submitted_at.cmp(&a.submitted_at)));
+        let orders: Vec<Value> = orders
+            .iter()
+            .map(|o| {
+                json!({
+                    "inst": o.inst,
+                    "order": o.order.key(),
+                    "buy": matches!(o.side, OrderSide::BUY),
+                    "size": o.size,
+                    "price": o.price,
+                    "age_sec": o.submitted_at.elapsed().as_secs(),
+                    "reprices": o.reprices,
+                })
+            })
+            .collect();
+
+        Ok(json!({ "account_id": account_id, "orders": orders }))
+    }
+
+    /// Cancels resting orders on alerted instruments; true when any alert was raised.
+    pub async fn handle_risk_alerts(&mut self) -> bool {
+        let alerts = self.risk_alerts.take_events();
+        for alert in alerts.iter() {
+            warn!(
+                "[Risk] {:?} alert from model={}, cancelling resting orders on {:?}",
+                alert.severity, alert.model_id, alert.insts
+            );
+            if alert.insts.is_empty() {
+                self.cancel_all(None, None, None).await;
+            }
+            for inst in alert.insts.iter() {
+                self.cancel_all(None, None, Some(inst)).await;
+            }
+        }
+
+        !alerts.is_empty()
+    }
 
-        self.insert_inst_info(Market::Okx, okx_inst_info);
-        self.insert_inst_info(Market::BinanceUmFutures, binance_inst_info);
+    /// Sets the configured leverage of named instruments on every account.
+    pub async fn init_leverage(&mut self) {
+        for account in self.account_infos.values_mut() {
+            let insts: Vec<String> = account
+                .leverage
+                .keys()
+                .filter(|inst| inst.as_str() != "*")
+                .cloned()
+                .collect();
+            account.apply_leverage(&insts).await;
+        }
+    }
+
+    /// Voids intents left open by a previous run.
+    pub fn recover_intents(&self) -> InfraResult<()> {
+        for intent in self.intent_log.recover()? {
+            warn!(
+                "[Intent] Voiding in-flight intent from previous run: id={} account={} inst={} side={} size={}",
+                intent.intent_id, intent.account_id, intent.inst, intent.side, intent.size,
+            );
+        }
 
         Ok(())
     }
 
-    fn insert_inst_info(&mut self, market: Market, infos: Vec<InstrumentInfo>) {
-        for inst in infos {
-            let key = (inst.inst.clone(), market.clone());
-            self.instrument_infos.insert(key, inst);
+    fn publish_snapshots(&self) {
+        let snapshots: HashMap<String, AccountSnapshot> = self
+            .account_infos
+            .values()
+            .map(|account| (account.account_id.clone(), account.snapshot()))
+            .collect();
+
+        for (account_id, snapshot) in &snapshots {
+            self.snapshots.insert(account_id.clone(), snapshot.clone());
+        }
+        self.snapshots
+            .retain(|account_id, _| self.account_infos.contains_key(account_id));
+        self.own_orders
+            .retain_accounts(|account_id| self.account_infos.contains_key(account_id));
+
+        // All accounts swap in together, so readers never mix two passes
+        self.portfolio.publish_accounts(snapshots);
+    }
+
+    /// Holds orders adding to assets beyond `max_asset_net_weight` across accounts.
+    fn apply_asset_cap(&mut self) {
+        let breaches = match self.risk.get().max_asset_net_weight {
+            Some(cap) => {
+                let snapshots: Vec<(String, AccountSnapshot)> = self
+                    .account_infos
+                    .values()
+                    .map(|account| (account.account_id.clone(), account.snapshot()))
+                    .collect();
+                let report =
+                    ExposureReport::from_accounts(snapshots.iter().map(|(id, s)| (id, s)));
+
+                let breaches = report.breaches(cap);
+                for (name, side) in breaches.iter() {
+                    if self.asset_breaches.get(name) != Some(side) {
+                        warn!(
+                            "[Risk] {} net weight {:.4} across accounts beyond cap {}, holding {}",
+                            name,
+                            report.assets[name].net_weight,
+                            cap,
+                            if *side > 0.0 { "buys" } else { "sells" }
+                        );
+                    }
+                }
+                breaches
+            },
+            None => HashMap::new(),
+        };
+
+        for name in self.asset_breaches.keys() {
+            if !breaches.contains_key(name) {
+                info!("[Risk] {} net weight back within max_asset_net_weight", name);
+            }
+        }
+        for account in self.account_infos.values_mut() {
+            account.asset_breaches = breaches.clone();
+        }
+        self.asset_breaches = breaches;
+    }
+
+    /// Kill switch: flattens every target once any account breaches its daily loss limit.
+    pub fn enforce_loss_limit(&mut self) {
+        if self.control.is_killed() {
+            self.kill_engaged = true;
+            return;
+        }
+        if self.kill_engaged {
+            self.kill_engaged = false;
+            for account in self.account_infos.values_mut() {
+                account.day_start_equity = None;
+                account.drawdown.reset();
+            }
+            info!("[Risk] Kill switch re-armed, daily loss and drawdown baselines reset");
+        }
+
+        let breached: Vec<String> = self
+            .account_infos
+            .values_mut()
+            .filter_map(|account| {
+                account
+                    .daily_loss_breached()
+                    .then(|| account.account_id.clone())
+            })
+            .collect();
+        if breached.is_empty() {
+            return;
         }
+
+        error!("[Risk] Loss limit hit on {:?}, kill switch engaged", breached);
+        self.control.kill(
+            &self.target_weights,
+            &format!("daily loss limit breached on {}", breached.join(",")),
+        );
+        self.kill_engaged = true;
+    }
+
+    fn flush_feedback(&mut self) {
+        match self.rejections.lock() {
+            Ok(mut queue) => {
+                for account in self.account_infos.values_mut() {
+                    queue.extend(account.pending_rejections.drain(..));
+                }
+                while queue.len() > MAX_PENDING_REJECTIONS {
+                    queue.pop_front();
+                }
+            },
+            Err(_) => warn!("[Account] Rejection queue poisoned — dropping feedback"),
+        };
+
+        match self.executions.lock() {
+            Ok(mut queue) => {
+                for account in self.account_infos.values_mut() {
+                    queue.extend(account.pending_executions.drain(..));
+                }
+                while queue.len() > MAX_PENDING_EXECUTIONS {
+                    queue.pop_front();
+                }
+            },
+            Err(_) => warn!("[Account] Execution queue poisoned — dropping reports"),
+        };
+    }
+
+    /// Adds the instruments listed since the last call.
+    pub async fn init_inst_info(&mut self) -> InfraResult<()> {
+        self.instrument_infos.extend(fetch_inst_infos(&self.meta_cache).await?);
+        Ok(())
     }
 
     pub async fn process_weights(&mut self) -> InfraResult<()> {
-        sleep(Duration::from_millis(100)).await;
+        self.settle_targets().await;
+        self.process_settled_weights().await
+    }
+
+    pub async fn settle_targets(&self) {
+        sleep(TARGET_SETTLE).await;
+    }
 
+    /// Rebalances every account to the targets as published now, without settling first.
+    pub async fn process_settled_weights(&mut self) -> InfraResult<()> {
+        self.processed_targets = self.current_targets();
+        self.apply_asset_cap();
         for account in self.account_infos.values_mut() {
             if let Err(e) = account
                 .process_weight(&self.target_weights, &self.instrument_infos)
@@ -86,11 +846,47 @@ impl AccountManager {
                 continue;
             }
         }
+        self.flush_feedback();
+        self.publish_snapshots();
+        self.record_tracking(None);
 
         Ok(())
     }
 
-    pub async fn process_ws_event(&self, msg: &InfraMsg<WsTaskInfo>) -> InfraResult<()> {
+    /// True when the targets moved since the last pass over all accounts, whoever moved them.
+    pub fn targets_changed(&self) -> bool {
+        let targets = self.current_targets();
+        targets.len() != self.processed_targets.len()
+            || targets.iter().any(|(inst, weight)| {
+                self.processed_targets
+                    .get(inst)
+                    .is_none_or(|processed| (processed - weight).abs() > TARGET_CHANGE_TOLERANCE)
+            })
+    }
+
+    fn current_targets(&self) -> HashMap<String, f64> {
+        self.target_weights
+            .iter()
+            .map(|r| (r.key().clone(), r.value().1))
+            .collect()
+    }
+
+    /// Samples the tracking error of the given accounts, or of all of them.
+    fn record_tracking(&mut self, accounts: Option<&HashSet<String>>) {
+        let targets = self.current_targets();
+
+        let tolerance = self.risk.get().converged_tolerance;
+        for account in self.account_infos.values() {
+            if accounts.is_some_and(|ids| !ids.contains(&account.account_id)) {
+                continue;
+            }
+            self.tracking
+                .record(&account.account_id, &targets, &account.acc_weights, tolerance);
+        }
+    }
+
+    /// Connects the private stream the framework (re)started.
+    pub async fn process_ws_event(&mut self, msg: &InfraMsg<WsTaskInfo>) -> InfraResult<()> {
         let task_id = msg.task_id;
 
         let account_id = match self.task_index.get(&task_id) {
@@ -123,12 +919,24 @@ impl AccountManager {
                 self.handle_okx_account_event(account, &msg.data.ws_channel)
                     .await?;
             },
-            _ => warn!(
-                "[WS] Unsupported market for account={} task_id={} channel={:?}",
-                account.account_id, task_id, msg.data.ws_channel,
-            ),
+            _ if account.bybit_rest().is_some() => {
+                self.handle_bybit_account_event(account, &msg.data.ws_channel)
+                    .await?;
+            },
+            _ if account.hyperliquid.is_some() => {
+                self.handle_hyperliquid_account_event(account, &msg.data.ws_channel)
+                    .await?;
+            },
+            _ => {
+                warn!(
+                    "[WS] Unsupported market for account={} task_id={} channel={:?}",
+                    account.account_id, task_id, msg.data.ws_channel,
+                );
+                return Ok(());
+            },
         };
 
+        self.mark_reconcile(&account_id);
         Ok(())
     }
 
@@ -173,6 +981,12 @@ impl AccountManager {
         };
 
         for bal_pos in msg.data.iter() {
+            // Equity first so position weights below use the fresh balance;
+            // the periodic REST refresh remains the source of truth.
+            for bal in bal_pos.balances.iter() {
+                account.ws_update_acc_balance(bal);
+            }
+
             for pos in bal_pos.positions.iter() {
                 let inst_key: InstKey = (pos.inst.clone(), bal_pos.market.clone());
                 if let Some(inst_info) = self.instrument_infos.get(&inst_key) {
@@ -210,7 +1024,12 @@ impl AccountManager {
             "[WS Connect Start] Binance account={} channel={:?} task_id={}",
             account.account_id, channel, task_id,
         );
-        let ws_url = account.client.get_private_connect_msg(channel).await?;
+        // The connect message opens a listen key over REST
+        self.rate_limiter.acquire(RateVenue::Binance, 1.0).await;
+        let ws_url = match account.testnet_rest() {
+            Some(rest) => rest.binance_user_stream_url().await?,
+            None => account.client.get_private_connect_msg(channel).await?,
+        };
         let (tx, rx) = oneshot::channel();
         let cmd = TaskCommand::WsConnect {
             msg: ws_url,
@@ -258,7 +1077,7 @@ impl AccountManager {
         );
 
         // Step 1: Connect
-        let ws_url = account.client.get_private_connect_msg(channel).await?;
+        let ws_url = profile_ws_url(account.client.get_private_connect_msg(channel).await?)?;
         let (tx, rx) = oneshot::channel();
         let cmd = TaskCommand::WsConnect {
             msg: ws_url,
@@ -307,40 +1126,276 @@ impl AccountManager {
         Ok(())
     }
 
+    async fn handle_bybit_account_event(
+        &self,
+        account: &AccountInfo,
+        channel: &WsChannel,
+    ) -> InfraResult<()> {
+        let task_id = match channel {
+            WsChannel::AccountOrders => account.account_orders_task_id,
+            WsChannel::AccountBalAndPos => account.account_bal_pos_task_id,
+            _ => {
+                return Err(InfraError::Msg(format!(
+                    "[WS] Unsupported WS channel for Bybit: account={} channel={:?}",
+                    account.account_id, channel,
+                )));
+            },
+        };
+
+        let Some(rest) = account.bybit_rest() else {
+            return Err(InfraError::Msg(format!(
+                "[WS] Bybit account={} has no private REST credentials",
+                account.account_id,
+            )));
+        };
+
+        let Some(handle) = self.find_ws_handle(channel, task_id) else {
+            warn!(
+                "[WS] No WS handle found for Bybit account={} channel={:?} task_id={}",
+                account.account_id, channel, task_id,
+            );
+            return Ok(());
+        };
+
+        info!(
+            "[WS Connect Start] Bybit account={} channel={:?} task_id={}",
+            account.account_id, channel, task_id,
+        );
+
+        // Step 1: Connect
+        let (tx, rx) = oneshot::channel();
+        let cmd = TaskCommand::WsConnect {
+            msg: rest.bybit_ws_url(),
+            ack: AckHandle::new(tx),
+        };
+        handle
+            .send_command(cmd, Some((AckStatus::WsConnect, rx)))
+            .await?;
+
+        // Step 2: Login
+        let (tx, rx) = oneshot::channel();
+        let cmd = TaskCommand::WsMessage {
+            msg: rest.bybit_ws_login_msg().await?,
+            ack: AckHandle::new(tx),
+        };
+        handle
+            .send_command(cmd, Some((AckStatus::WsMessage, rx)))
+            .await?;
+        sleep(Duration::from_millis(100)).await;
+
+        // Step 3: Subscribe
+        let (tx, rx) = oneshot::channel();
+        let cmd = TaskCommand::WsMessage {
+            msg: bybit_ws_sub_msg(channel)?,
+            ack: AckHandle::new(tx),
+        };
+        handle
+            .send_command(cmd, Some((AckStatus::WsMessage, rx)))
+            .await?;
+
+        info!(
+            "[WS Done] Account={} channel={:?} task_id={} connected and subscribed",
+            account.account_id, channel, task_id
+        );
+
+        Ok(())
+    }
+
+    /// Hyperliquid user streams are keyed by address and need no login.
+    async fn handle_hyperliquid_account_event(
+        &self,
+        account: &AccountInfo,
+        channel: &WsChannel,
+    ) -> InfraResult<()> {
+        let task_id = match channel {
+            WsChannel::AccountOrders => account.account_orders_task_id,
+            WsChannel::AccountBalAndPos => account.account_bal_pos_task_id,
+            _ => {
+                return Err(InfraError::Msg(format!(
+                    "[WS] Unsupported WS channel for Hyperliquid: account={} channel={:?}",
+                    account.account_id, channel,
+                )));
+            },
+        };
+
+        let Some(hl) = account.hyperliquid.as_ref() else {
+            return Err(InfraError::Msg(format!(
+                "[WS] Hyperliquid account={} has no wallet client",
+                account.account_id,
+            )));
+        };
+
+        let Some(handle) = self.find_ws_handle(channel, task_id) else {
+            warn!(
+                "[WS] No WS handle found for Hyperliquid account={} channel={:?} task_id={}",
+                account.account_id, channel, task_id,
+            );
+            return Ok(());
+        };
+
+        info!(
+            "[WS Connect Start] Hyperliquid account={} channel={:?} task_id={}",
+            account.account_id, channel, task_id,
+        );
+
+        // Step 1: Connect
+        let (tx, rx) = oneshot::channel();
+        let cmd = TaskCommand::WsConnect {
+            msg: hl.ws_url(),
+            ack: AckHandle::new(tx),
+        };
+        handle
+            .send_command(cmd, Some((AckStatus::WsConnect, rx)))
+            .await?;
+
+        // Step 2: Subscribe
+        let (tx, rx) = oneshot::channel();
+        let cmd = TaskCommand::WsMessage {
+            msg: hl.ws_sub_msg(channel)?,
+            ack: AckHandle::new(tx),
+        };
+        handle
+            .send_command(cmd, Some((AckStatus::WsMessage, rx)))
+            .await?;
+
+        info!(
+            "[WS Done] Account={} channel={:?} task_id={} connected and subscribed",
+            account.account_id, channel, task_id
+        );
+
+        Ok(())
+    }
+
     pub async fn update_accounts(&mut self) -> InfraResult<()> {
+        self.apply_asset_cap();
         for account in self.account_infos.values_mut() {
-            if let Err(e) = account.rest_update_acc_balance().await {
-                warn!(
-                    "Failed to update balance for account {}: {} — skipping",
-                    account.account_id, e,
-                );
+            if account
+                .update_and_process(&self.target_weights, &self.instrument_infos)
+                .await
+                .is_err()
+            {
+                self.error_budget.record_failure(&account.account_id, now_millis());
+            }
+        }
+        self.check_error_budget();
+        self.flush_feedback();
+        self.publish_snapshots();
+
+        Ok(())
+    }
+
+    /// Refreshes only the accounts whose staggered update slot is due.
+    pub async fn update_due_accounts(&mut self) -> InfraResult<()> {
+        let now = Instant::now();
+        let interval = self.intervals.account_update();
 
+        self.apply_asset_cap();
+        let mut updated = HashSet::new();
+        for account in self.account_infos.values_mut() {
+            let due = *account
+                .next_update
+                .get_or_insert_with(|| now + stagger_offset(&account.account_id, interval));
+
+            if now < due {
                 continue;
             }
 
-            if let Err(e) = account
-                .rest_update_acc_pos_weight(&self.instrument_infos)
+            account.next_update = Some(now + interval + jitter(self.config.update_jitter_ms));
+            updated.insert(account.account_id.clone());
+            if account
+                .update_and_process(&self.target_weights, &self.instrument_infos)
                 .await
+                .is_err()
             {
-                warn!(
-                    "Failed to update position weights for account {}: {} — skipping",
-                    account.account_id, e,
+                self.error_budget.record_failure(&account.account_id, now_millis());
+            }
+        }
+        self.check_error_budget();
+        self.flush_feedback();
+        self.publish_snapshots();
+        if !updated.is_empty() {
+            self.record_tracking(Some(&updated));
+        }
+
+        Ok(())
+    }
+
+    /// Alerts, and pauses when configured, once account failures exceed the error budget.
+    fn check_error_budget(&mut self) {
+        let Some(budget) = self.risk.get().account_error_budget else {
+            return;
+        };
+
+        match self.error_budget.evaluate(&budget, now_millis()) {
+            Some(BudgetChange::Exhausted(accounts)) => {
+                let detail = format!(
+                    "{} accounts failed updates within {}s: {:?}",
+                    accounts.len(),
+                    budget.window_sec,
+                    accounts
                 );
+                error!("[Account] ALERT error budget exhausted, {}", detail);
+                if budget.pause_trading {
+                    self.control.pause();
+                    error!("[Account] Trading paused until an operator resumes it");
+                    audit("error_budget", None, "paused", &detail);
+                } else {
+                    audit("error_budget", None, "alerted", &detail);
+                }
+            },
+            Some(BudgetChange::Recovered) => {
+                info!("[Account] Account failures back within the error budget");
+            },
+            None => {},
+        }
+    }
+
+    /// Drops per-instrument state of every account for instruments idle past `idle_inst_ttl_sec`.
+    pub fn collect_idle_insts(&mut self) {
+        let ttl = Duration::from_secs(self.config.idle_inst_ttl_sec);
+        for account in self.account_infos.values_mut() {
+            account.collect_idle_insts(&self.target_weights, ttl);
+        }
+    }
+
+    /// Writes the previous UTC day's journal for every account with private REST access.
+    pub async fn export_journals(&mut self) -> InfraResult<()> {
+        let Some(day) = self.journal.pending_day() else {
+            return Ok(());
+        };
+
+        let mut all_ok = true;
+        for account in self.account_infos.values() {
+            let Some(rest) = &account.rest else {
                 continue;
-            }
+            };
 
-            if let Err(e) = account
-                .process_weight(&self.target_weights, &self.instrument_infos)
+            // Held and targeted instruments, so positions closed during the day still export
+            let insts: Vec<String> = account
+                .acc_weights
+                .keys()
+                .cloned()
+                .chain(self.target_weights.iter().map(|r| r.key().clone()))
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            if let Err(e) = self
+                .journal
+                .export_account(&account.account_id, rest, &insts, day)
                 .await
             {
                 warn!(
-                    "Failed to process account {}: {} — skipping",
+                    "[Journal] Export failed for account {}: {}",
                     account.account_id, e
                 );
-                continue;
+                all_ok = false;
             }
         }
 
+        if all_ok {
+            self.journal.mark_exported(day);
+        }
+
         Ok(())
     }
 
@@ -350,7 +1405,12 @@ impl AccountManager {
 
         let mut new_map = HashMap::new();
         for cfg in new_cfgs.iter() {
-            let acc = AccountInfo::from_config(cfg, shared_client.clone())?;
+            let acc = AccountInfo::from_config(
+                cfg,
+                shared_client.clone(),
+                &self.meta_cache,
+                &self.rate_limiter,
+            )?;
             new_map.insert(cfg.account_id.clone(), acc);
         }
 
@@ -371,9 +1431,14 @@ impl AccountManager {
         for acc_id in old_ids.difference(&new_ids) {
             info!("[Account] Account deleted from config: {}", acc_id);
 
+            self.error_budget.forget(acc_id);
             if let Some(old_acc) = self.account_infos.remove(acc_id) {
                 self.task_index.remove(&old_acc.account_orders_task_id);
                 self.task_index.remove(&old_acc.account_bal_pos_task_id);
+                self.heartbeats
+                    .forget(&format!("ws:acc:{}", old_acc.account_orders_task_id));
+                self.heartbeats
+                    .forget(&format!("ws:acc:{}", old_acc.account_bal_pos_task_id));
                 self.ws_disconnect_account(&old_acc).await?;
             }
         }
@@ -401,15 +1466,7 @@ impl AccountManager {
             if new_acc.config_changed(&old_acc) {
                 info!("[Account] Account updated: {} (diff detected)", acc_id);
 
-                self.account_infos.insert(acc_id.clone(), new_acc.clone());
-                self.task_index.remove(&old_acc.account_orders_task_id);
-                self.task_index.remove(&old_acc.account_bal_pos_task_id);
-
-                self.task_index
-                    .insert(new_acc.account_orders_task_id, acc_id.clone());
-                self.task_index
-                    .insert(new_acc.account_bal_pos_task_id, acc_id.clone());
-
+                let new_acc = self.replace_account(old_acc.clone(), new_acc);
                 self.ws_disconnect_account(&old_acc).await?;
                 self.ws_connect_account(&new_acc).await?;
             }
@@ -462,287 +1519,2083 @@ impl AccountManager {
                 self.handle_okx_account_event(acc, &WsChannel::AccountBalAndPos)
                     .await?;
             },
-            _ => warn!("Unsupported exchange for auto connect: {:?}", acc.client),
+            _ if acc.bybit_rest().is_some() => {
+                self.handle_bybit_account_event(acc, &WsChannel::AccountOrders)
+                    .await?;
+                self.handle_bybit_account_event(acc, &WsChannel::AccountBalAndPos)
+                    .await?;
+            },
+            _ if acc.hyperliquid.is_some() => {
+                self.handle_hyperliquid_account_event(acc, &WsChannel::AccountOrders)
+                    .await?;
+                self.handle_hyperliquid_account_event(acc, &WsChannel::AccountBalAndPos)
+                    .await?;
+            },
+            _ => {
+                warn!("Unsupported exchange for auto connect: {:?}", acc.client);
+                return Ok(());
+            },
         };
 
+        // Pings keep the heartbeats from here on; a channel that never answers one stalls
+        self.beat_private_ws(acc.account_orders_task_id);
+        self.beat_private_ws(acc.account_bal_pos_task_id);
+
         Ok(())
     }
 
     pub fn load_all_accounts(&mut self, shared_client: Arc<Client>) -> InfraResult<()> {
         for cfg in load_account_config()? {
-            let acc = AccountInfo::from_config(&cfg, shared_client.clone())?;
+            let acc = AccountInfo::from_config(
+                &cfg,
+                shared_client.clone(),
+                &self.meta_cache,
+                &self.rate_limiter,
+            )?;
             self.add_account(acc);
         }
-        Ok(())
-    }
+        Ok(())
+    }
+
+    /// Swaps in the reloaded `account_info`, taking over the shared state and what `old` was doing.
+    fn replace_account(&mut self, old: AccountInfo, mut account_info: AccountInfo) -> AccountInfo {
+        let new_task_ids = [
+            account_info.account_orders_task_id,
+            account_info.account_bal_pos_task_id,
+        ];
+        for task_id in [old.account_orders_task_id, old.account_bal_pos_task_id] {
+            self.task_index.remove(&task_id);
+            if !new_task_ids.contains(&task_id) {
+                self.heartbeats.forget(&format!("ws:acc:{}", task_id));
+            }
+        }
+
+        account_info.carry_runtime_state(old);
+        let account_id = account_info.account_id.clone();
+        self.add_account(account_info);
+        self.account_infos[&account_id].clone()
+    }
+
+    fn add_account(&mut self, mut account_info: AccountInfo) {
+        account_info.intent_log = self.intent_log.clone();
+        account_info.command_audit = self.command_audit.clone();
+        account_info.control = self.control.clone();
+        account_info.risk = self.risk.clone();
+        account_info.toxicity = self.toxicity.clone();
+        account_info.price_limits = self.price_limits.clone();
+        account_info.retry = self.config.retry.clone();
+        if let Some(market) = account_info.inst_market() {
+            account_info.orders.attach(
+                self.own_orders.clone(),
+                &format!("{:?}", market),
+                &account_info.account_id,
+            );
+        }
+
+        self.task_index.insert(
+            account_info.account_orders_task_id,
+            account_info.account_id.clone(),
+        );
+
+        self.task_index.insert(
+            account_info.account_bal_pos_task_id,
+            account_info.account_id.clone(),
+        );
+
+        self.account_infos
+            .insert(account_info.account_id.clone(), account_info);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountInfo {
+    pub account_id: String,
+    pub client: CexClients,
+    pub acc_weights: HashMap<String, f64>,
+    pub inst_mark_price: HashMap<String, f64>,
+    pub margin_asset: String,
+    /// Equity net of accrued funding and fees the venue has not settled into the balance yet.
+    pub total_equity: f64,
+    /// Equity as last reported by the venue.
+    pub reported_equity: f64,
+    /// Funding rate and next settlement time per held instrument.
+    pub funding: HashMap<String, (f64, u64)>,
+    pub funding_interval_ms: u64,
+    /// Settled funding read from the venue's bills, against the accrual estimate.
+    pub funding_bills: FundingLedger,
+    pub taker_fees: HashMap<String, f64>,
+    /// Estimated fees of fills since the last balance refresh.
+    pub unsettled_fees: f64,
+    pub account_orders_task_id: u64,
+    pub account_bal_pos_task_id: u64,
+    pub next_update: Option<Instant>,
+    pub exec_failures: HashMap<String, u32>,
+    pub pending_rejections: Vec<TradeRejection>,
+    pub pending_executions: Vec<ExecutionReport>,
+    pub intent_log: IntentLog,
+    pub command_audit: CommandAudit,
+    pub rest: Option<PrivateRest>,
+    /// Wallet-signed client for Hyperliquid accounts, which have no `CexClients` variant.
+    pub hyperliquid: Option<HyperliquidClient>,
+    pub orders: OrderTracker,
+    pub position_caps: PositionCaps,
+    pub control: ControlState,
+    pub risk: RiskLimits,
+    /// Order-flow toxicity per instrument, which widens the no-trade band.
+    pub toxicity: ToxicityMap,
+    /// Execution price bounds of the models per instrument.
+    pub price_limits: PriceLimits,
+    pub retry: RetryPolicy,
+    pub rate_limiter: RateLimiter,
+    /// Last successful rebalance order per instrument, for `order_cooldown_sec`.
+    pub last_order_at: HashMap<String, Instant>,
+    /// UTC day, starting equity and net transfers at that point, for `daily_loss_limit`.
+    pub day_start_equity: Option<(u64, f64, f64)>,
+    /// Deposits and withdrawals, netted out of the loss limits.
+    pub transfers: TransferLedger,
+    /// Rolling equity high-water mark for `max_drawdown`.
+    pub drawdown: DrawdownBreaker,
+    pub pending_diffs: HashMap<String, f64>,
+    /// Weight left over per instrument after an order was clamped below its diff.
+    pub remainders: HashMap<String, f64>,
+    /// Instruments the rebalancer skipped, summarized once per pass.
+    pub skips: SkipLog,
+    /// Assets held on one side by `max_asset_net_weight`, set by the account manager.
+    pub asset_breaches: HashMap<String, f64>,
+    /// Instruments with an order whose fill is not confirmed yet.
+    pub in_flight: InFlightOrders,
+    /// Set when a private WS stream (re)connects; cleared after the REST reconcile.
+    pub reconcile_pending: bool,
+    pub paper: bool,
+    pub migration_role: MigrationRole,
+    pub execution_mode: ExecutionMode,
+    pub limit_offset_bps: f64,
+    pub twap_config: Option<TwapConfig>,
+    pub twap: TwapScheduler,
+    pub advisory: Option<AdvisoryNotifier>,
+    /// Configured leverage per instrument, `*` for the rest.
+    pub leverage: HashMap<String, u32>,
+    /// Instruments whose configured leverage is set on the venue.
+    pub leverage_set: HashSet<String>,
+    pub max_leverage: Option<f64>,
+    /// Requested target last reported as beyond the account's leverage, per instrument.
+    pub leverage_rejected: HashMap<String, f64>,
+    pub client_ids: ClientOrderIds,
+    pub idle_insts: IdleInstruments,
+    /// Entry of `account_config.json` the account was built from, compared on reload.
+    pub file_config: AccountFileConfig,
+}
+
+impl AccountInfo {
+    async fn update_and_process(
+        &mut self,
+        target_weights: &DashMap<String, (f64, f64)>,
+        inst_infos: &HashMap<InstKey, InstrumentInfo>,
+    ) -> InfraResult<()> {
+        if self.reconcile_pending
+            && let Err(e) = self.reconcile(inst_infos).await
+        {
+            warn!(
+                "[Reconcile] Account={} snapshot failed: {} — skipping",
+                self.account_id, e,
+            );
+            return Err(e);
+        }
+
+        if let Err(e) = self.rest_update_acc_balance().await {
+            warn!(
+                "Failed to update balance for account {}: {} — skipping",
+                self.account_id, e,
+            );
+            return Err(e);
+        }
+        self.refresh_transfers().await;
+        self.check_drawdown();
+
+        if let Err(e) = self.rest_update_acc_pos_weight(inst_infos).await {
+            warn!(
+                "Failed to update position weights for account {}: {} — skipping",
+                self.account_id, e,
+            );
+            return Err(e);
+        }
+
+        if let Err(e) = self.process_weight(target_weights, inst_infos).await {
+            warn!(
+                "Failed to process account {}: {} — skipping",
+                self.account_id, e
+            );
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Takes over positions, orders and guards of the account this one replaces on a reload.
+    fn carry_runtime_state(&mut self, old: AccountInfo) {
+        if self.inst_market() != old.inst_market() {
+            warn!(
+                "[Account] {} moved venue on reload, starting from a fresh snapshot",
+                self.account_id
+            );
+            self.reconcile_pending = true;
+            return;
+        }
+
+        self.acc_weights = old.acc_weights;
+        self.inst_mark_price = old.inst_mark_price;
+        self.total_equity = old.total_equity;
+        self.reported_equity = old.reported_equity;
+        self.funding = old.funding;
+        self.funding_bills = old.funding_bills;
+        self.taker_fees = old.taker_fees;
+        self.unsettled_fees = old.unsettled_fees;
+        self.next_update = old.next_update;
+        self.exec_failures = old.exec_failures;
+        self.pending_rejections = old.pending_rejections;
+        self.pending_executions = old.pending_executions;
+        self.orders = old.orders;
+        self.position_caps = old.position_caps;
+        self.last_order_at = old.last_order_at;
+        self.day_start_equity = old.day_start_equity;
+        self.transfers = old.transfers;
+        self.drawdown = old.drawdown;
+        self.pending_diffs = old.pending_diffs;
+        self.remainders = old.remainders;
+        self.skips = old.skips;
+        self.asset_breaches = old.asset_breaches;
+        self.in_flight = old.in_flight;
+        self.migration_role = old.migration_role;
+        self.twap = old.twap;
+        self.leverage_rejected = old.leverage_rejected;
+        self.client_ids = old.client_ids;
+        self.idle_insts = old.idle_insts;
+        // Changed leverage is set on the venue again before the next order
+        if self.leverage == old.leverage {
+            self.leverage_set = old.leverage_set;
+        }
+        // The private streams reconnect, so the positions are re-read over REST
+        self.reconcile_pending = true;
+    }
+
+    /// Applies a fill or cancel from the private order stream to the tracked order it belongs to.
+    fn ws_update_acc_order(&mut self, acc_order: &WsAccOrder, _inst_info: &InstrumentInfo) {
+        let state = OrderState::from(&acc_order.order_status);
+        let Some(tracked) = self.orders.get(&acc_order.client_order_id).cloned() else {
+            // Market orders are settled by the position stream
+            return;
+        };
+
+        let filled = acc_order.filled_size;
+        match state {
+            OrderState::Open => self.orders.update_filled(tracked.order.key(), filled),
+            OrderState::Filled => {
+                info!(
+                    "[WS-Order] Account={} order {} on {} filled {} at {}",
+                    self.account_id,
+                    tracked.order.key(),
+                    tracked.inst,
+                    filled,
+                    acc_order.avg_price
+                );
+                self.book_limit_fill(&tracked, filled, acc_order.avg_price);
+                // The position stream sets the weight the fill moved
+                self.in_flight.begin(&tracked.inst);
+            },
+            OrderState::Cancelled => {
+                info!(
+                    "[WS-Order] Account={} order {} on {} closed, filled {} of {}",
+                    self.account_id,
+                    tracked.order.key(),
+                    tracked.inst,
+                    filled,
+                    tracked.size
+                );
+                self.close_order(tracked.order.key(), OrderState::Cancelled);
+                if filled > 0.0 {
+                    self.in_flight.begin(&tracked.inst);
+                } else if !self.orders.has_open(&tracked.inst) {
+                    // Nothing traded, so no position update will release the instrument
+                    self.in_flight.finish(&tracked.inst);
+                }
+            },
+        }
+    }
+
+    fn ws_update_acc_balance(&mut self, bal: &WsAccBalance) {
+        if !bal.asset.eq_ignore_ascii_case(&self.margin_asset) || bal.balance <= f64::EPSILON {
+            return;
+        }
+
+        let prev_equity = self.total_equity;
+        self.apply_reported_equity(bal.balance);
+
+        if prev_equity > f64::EPSILON {
+            let ratio = prev_equity / self.total_equity;
+            self.acc_weights
+                .values_mut()
+                .for_each(|weight| *weight *= ratio);
+        }
+
+        info!(
+            "[WS-BP] Account={} equity {} -> {}",
+            self.account_id, prev_equity, self.total_equity
+        );
+    }
+
+    fn ws_update_acc_position(&mut self, pos: &WsAccPosition, inst_info: &InstrumentInfo) {
+        if self.paper {
+            return;
+        }
+
+        let mark_price = self
+            .inst_mark_price
+            .get(&pos.inst)
+            .unwrap_or(&pos.avg_price);
+
+        let ct_val = inst_info.contract_value.unwrap_or(1.0);
+        let pos_notional = match &self.client {
+            CexClients::BinanceUm(_) => calc_position_notional(pos.size, *mark_price, 1.0, false),
+            CexClients::BinanceCm(_) => calc_position_notional(pos.size, *mark_price, ct_val, true),
+            CexClients::Okx(_) => {
+                calc_position_notional(pos.size, *mark_price, ct_val, is_inverse_inst(&pos.inst))
+            },
+            _ if self.bybit_rest().is_some() || self.hyperliquid.is_some() => {
+                calc_position_notional(pos.size, *mark_price, 1.0, false)
+            },
+            _ => 0.0,
+        };
+
+        let weight = if self.total_equity > f64::EPSILON {
+            pos_notional / self.total_equity
+        } else {
+            0.0
+        };
+        self.acc_weights.insert(pos.inst.clone(), weight);
+        self.in_flight.finish(&pos.inst);
+    }
+
+    pub async fn rest_update_acc_balance(&mut self) -> InfraResult<()> {
+        let reported = with_retry(&self.retry, "Balance", true, || self.fetch_equity()).await?;
+
+        self.rest_update_equity_adjustments().await;
+        self.refresh_funding_bills().await;
+        self.apply_reported_equity(reported);
+        Ok(())
+    }
+
+    async fn fetch_equity(&self) -> InfraResult<f64> {
+        let reported = match (self.bybit_rest(), &self.hyperliquid, self.testnet_rest()) {
+            (Some(rest), _, _) => rest.bybit_equity(&self.margin_asset).await?,
+            (_, Some(hl), _) => hl.equity().await?,
+            (_, _, Some(rest)) if *rest.venue() == RestVenue::Okx => {
+                rest.okx_equity(&self.margin_asset).await?
+            },
+            (_, _, Some(rest)) => rest.binance_equity(&self.margin_asset).await?,
+            _ => {
+                self.acquire_rate("/fapi/v2/balance").await;
+                let balances = self
+                    .client
+                    .get_balance(Some(std::slice::from_ref(&self.margin_asset)))
+                    .await?;
+
+                let margin_balance = balances
+                    .iter()
+                    .find(|b| b.asset.eq_ignore_ascii_case(&self.margin_asset))
+                    .ok_or_else(|| {
+                        InfraError::Msg(format!(
+                            "Rest update account bal err: {} balance missing",
+                            self.margin_asset,
+                        ))
+                    })?;
+                info!("[WS] Rest update acc_order={:?}", margin_balance);
+                margin_balance.total
+            },
+        };
+        Ok(reported)
+    }
+
+    /// Refreshes funding and taker fee rates of the instruments held or targeted.
+    async fn rest_update_equity_adjustments(&mut self) {
+        let Some(rest) = self.rest.clone() else {
+            return;
+        };
+
+        // Flat instruments accrue no funding; their fee rate is loaded when an order goes out
+        let held: Vec<String> = self
+            .acc_weights
+            .iter()
+            .filter(|(_, weight)| weight.abs() > f64::EPSILON)
+            .map(|(inst, _)| inst.clone())
+            .collect();
+        for inst in held.iter() {
+            match rest.funding_rate(inst).await {
+                Ok(funding) => {
+                    self.funding.insert(inst.clone(), funding);
+                },
+                Err(e) => warn!(
+                    "[Equity] {} funding rate of {} unavailable, using the last: {}",
+                    self.account_id, inst, e
+                ),
+            }
+            self.load_taker_fee(inst).await;
+        }
+
+        self.funding.retain(|inst, _| held.contains(inst));
+        self.taker_fees.retain(|inst, _| {
+            held.contains(inst) || self.orders.has_open(inst) || self.in_flight.contains(inst)
+        });
+    }
+
+    /// Taker fee rate of `inst` for the fill estimates, from the metadata cache.
+    async fn load_taker_fee(&mut self, inst: &str) {
+        let Some(rest) = self.rest.clone() else {
+            return;
+        };
+
+        match rest.taker_fee_rate(inst).await {
+            Ok(rate) => {
+                self.taker_fees.insert(inst.to_string(), rate);
+            },
+            Err(e) => warn!(
+                "[Equity] {} taker fee of {} unavailable, using the last: {}",
+                self.account_id, inst, e
+            ),
+        }
+    }
+
+    /// A fresh balance already includes settled fees, so the fee estimate restarts from zero.
+    fn apply_reported_equity(&mut self, reported: f64) {
+        self.reported_equity = reported;
+        self.unsettled_fees = 0.0;
+
+        let now = now_millis();
+        let mut expected = Vec::new();
+        let funding: f64 = self
+            .acc_weights
+            .iter()
+            .filter_map(|(inst, weight)| {
+                let (rate, next) = self.funding.get(inst)?;
+                // Past its settlement the payment is in the balance once its bill was read
+                if now >= *next && self.funding_bills.is_billed(inst, *next) {
+                    return None;
+                }
+                let notional = weight * reported;
+                expected.push((inst.clone(), *next, notional * rate));
+                Some(accrued_funding(notional, *rate, *next, self.funding_interval_ms, now))
+            })
+            .sum();
+        for (inst, next, amount) in expected {
+            self.funding_bills.expect(&inst, next, amount);
+        }
+
+        self.total_equity = reported - funding;
+        if funding.abs() > f64::EPSILON {
+            info!(
+                "[Equity] {} reported={} accrued_funding={} net={}",
+                self.account_id, reported, funding, self.total_equity
+            );
+        }
+    }
+
+    /// Position cap of `inst` in venue size units; failed lookups block the instrument.
+    async fn position_cap(&mut self, inst: &str) -> Option<f64> {
+        if let Some(hl) = self.hyperliquid.clone() {
+            return match hl.max_leverage(inst).await {
+                Ok(max_leverage) => {
+                    let leverage = self.configured_leverage(inst).map(f64::from);
+                    Some(hl_position_cap(self.total_equity, max_leverage, leverage))
+                },
+                Err(e) => {
+                    warn!(
+                        "[Limits] {} {} max leverage lookup failed: {}",
+                        self.account_id, inst, e
+                    );
+                    None
+                },
+            };
+        }
+        if let Some(cap) = self.position_caps.get(inst) {
+            return cap;
+        }
+        let rest = self.rest.clone()?;
+
+        match (rest.position_tiers(inst).await, rest.leverage(inst).await) {
+            (Ok(tiers), Ok(leverage)) => {
+                let cap = tier_cap(&tiers, leverage);
+                info!(
+                    "[Limits] {} {} leverage={}x position cap={:?}",
+                    self.account_id, inst, leverage, cap
+                );
+                self.position_caps.insert(inst, cap);
+                cap
+            },
+            (Err(e), _) | (_, Err(e)) => {
+                warn!(
+                    "[Limits] {} {} tier lookup failed, blocked for {:?}: {}",
+                    self.account_id, inst, POSITION_CAP_RETRY, e
+                );
+                self.position_caps.insert_failed(inst);
+                None
+            },
+        }
+    }
+
+    /// Shrinks an order so the resulting position stays within the tier cap.
+    async fn apply_position_cap(
+        &mut self,
+        inst: &str,
+        size: String,
+        diff: f64,
+        units_per_weight: f64,
+        cap_to_units: f64,
+        inst_info: &InstrumentInfo,
+    ) -> Option<String> {
+        let cap = self.position_cap(inst).await? * cap_to_units;
+
+        let order = size.parse::<f64>().unwrap_or_default() * diff.signum();
+        let current = self.acc_weights.get(inst).copied().unwrap_or_default() * units_per_weight;
+        let capped = cap_order(current, order, cap);
+        if capped.abs() + f64::EPSILON >= order.abs() {
+            return Some(size);
+        }
+
+        warn!(
+            "[Limits] {} {} target exceeds position cap {}: position {:.4}, order {} -> {:.4}",
+            self.account_id, inst, cap, current, order, capped
+        );
+
+        let min_size = inst_info.min_mkt_size.max(inst_info.lot_size);
+        if capped.abs() < min_size {
+            return None;
+        }
+        Some(normalize_to_string(capped.abs(), inst_info.lot_size))
+    }
+
+    /// Reads the transfers since the last refresh.
+    async fn refresh_transfers(&mut self) {
+        if self.paper {
+            return;
+        }
+        let Some(rest) = &self.rest else {
+            return;
+        };
+        let Some((start, end)) = self.transfers.due(now_millis()) else {
+            return;
+        };
+
+        match fetch_net_transfers(rest, &self.margin_asset, start, end).await {
+            Ok(amount) => {
+                if amount != 0.0 {
+                    info!(
+                        "[Transfers] {} net transfers {:+} {}",
+                        self.account_id, amount, self.margin_asset
+                    );
+                }
+                self.transfers.add(amount, end);
+            },
+            Err(e) => warn!("[Transfers] {} refresh failed: {}", self.account_id, e),
+        }
+    }
+
+    /// Reconciles the funding bills since the last refresh with their accrual estimates.
+    async fn refresh_funding_bills(&mut self) {
+        if self.paper {
+            return;
+        }
+        let Some(rest) = &self.rest else {
+            return;
+        };
+        let Some((start, end)) = self.funding_bills.due(now_millis()) else {
+            return;
+        };
+
+        let bills = match fetch_funding_bills(rest, &self.margin_asset, start, end).await {
+            Ok(bills) => bills,
+            Err(e) => {
+                warn!("[Funding] {} bill refresh failed: {}", self.account_id, e);
+                return;
+            },
+        };
+        for s in self.funding_bills.settle(&bills, end) {
+            if s.drifted() {
+                warn!(
+                    "[Funding] {} {} paid {:+} {}, estimated {:+}",
+                    self.account_id, s.inst, s.billed, self.margin_asset, s.estimated
+                );
+            } else {
+                info!(
+                    "[Funding] {} {} paid {:+} {}, estimated {:+}",
+                    self.account_id, s.inst, s.billed, self.margin_asset, s.estimated
+                );
+            }
+        }
+    }
+
+    /// True once equity, net of transfers, fell more than `daily_loss_limit` today.
+    fn daily_loss_breached(&mut self) -> bool {
+        if self.total_equity <= f64::EPSILON {
+            return false;
+        }
+
+        let day = now_millis() / 86_400_000;
+        let net = self.transfers.net();
+        let (start_day, start_equity, start_net) = *self
+            .day_start_equity
+            .get_or_insert((day, self.total_equity, net));
+        if start_day != day {
+            self.day_start_equity = Some((day, self.total_equity, net));
+            return false;
+        }
+
+        let Some(limit) = self.risk.get().daily_loss_limit else {
+            return false;
+        };
+        let loss = 1.0 - (self.total_equity - (net - start_net)) / start_equity;
+        if loss < limit {
+            return false;
+        }
+
+        error!(
+            "[Risk] {} daily loss {:.2}% breached limit {:.2}%: equity {} -> {}",
+            self.account_id,
+            loss * 100.0,
+            limit * 100.0,
+            start_equity,
+            self.total_equity
+        );
+        true
+    }
+
+    /// Trips the drawdown breaker once equity fell `max_drawdown` below its peak.
+    fn check_drawdown(&mut self) {
+        if self.total_equity <= f64::EPSILON {
+            return;
+        }
+
+        let risk = self.risk.get();
+        let drawdown = self.drawdown.record(
+            now_millis(),
+            self.total_equity - self.transfers.net(),
+            risk.drawdown_window_sec * 1000,
+        );
+
+        if self.drawdown.release_if_relaxed(drawdown, risk.max_drawdown) {
+            let detail = format!(
+                "{} at {:.2}% drawdown, limit now {:?}",
+                self.account_id,
+                drawdown * 100.0,
+                risk.max_drawdown
+            );
+            warn!("[Risk] Drawdown breaker released: {}", detail);
+            audit("drawdown_breaker", None, "released", &detail);
+            return;
+        }
+
+        let Some(limit) = risk.max_drawdown else {
+            return;
+        };
+        if !self.drawdown.trip(drawdown, limit) {
+            return;
+        }
+
+        let detail = format!(
+            "{} drawdown {:.2}% from high-water mark {} breached limit {:.2}%, {}",
+            self.account_id,
+            drawdown * 100.0,
+            self.drawdown.high_water_mark().unwrap_or_default(),
+            limit * 100.0,
+            if risk.drawdown_flatten { "flattening" } else { "holding positions" },
+        );
+        error!("[Risk] Drawdown breaker tripped: {}", detail);
+        audit("drawdown_breaker", None, "tripped", &detail);
+
+        let insts: HashSet<&String> = self
+            .acc_weights
+            .keys()
+            .chain(self.pending_diffs.keys())
+            .collect();
+        let timestamp = get_micros_timestamp();
+        self.pending_rejections.extend(insts.into_iter().map(|inst| TradeRejection {
+            model_id: None,
+            account_id: self.account_id.clone(),
+            inst: inst.clone(),
+            reason: "drawdown_breaker".to_string(),
+            detail: detail.clone(),
+            diff: self.pending_diffs.get(inst).copied().unwrap_or_default(),
+            failures: 0,
+            timestamp,
+        }));
+    }
+
+    /// Books the estimated taker fee of a fill until the next balance refresh settles it.
+    fn book_fill_fee(&mut self, inst: &str, filled_diff: f64) {
+        let rate = self.taker_fees.get(inst).copied().unwrap_or_default();
+        let fee = (filled_diff * self.total_equity).abs() * rate;
+        self.unsettled_fees += fee;
+        self.total_equity -= fee;
+    }
+
+    pub async fn rest_update_acc_pos_weight(
+        &mut self,
+        inst_infos: &HashMap<InstKey, InstrumentInfo>,
+    ) -> InfraResult<()> {
+        // Paper positions only move with simulated fills
+        if self.paper {
+            return Ok(());
+        }
+
+        let positions =
+            with_retry(&self.retry, "Positions", true, || self.fetch_positions()).await?;
+        let mut notional_map: HashMap<String, f64> = HashMap::new();
+
+        for (inst, size, mark_price) in positions {
+            let pos_notional = match &self.client {
+                CexClients::BinanceUm(_) => calc_position_notional(size, mark_price, 1.0, false),
+                CexClients::BinanceCm(_) => {
+                    let inst_key = (inst.clone(), Market::BinanceCmFutures);
+                    if let Some(inst_info) = inst_infos.get(&inst_key) {
+                        let ct_val = inst_info.contract_value.unwrap_or(1.0);
+                        calc_position_notional(size, mark_price, ct_val, true)
+                    } else {
+                        0.0
+                    }
+                },
+                CexClients::Okx(_) => {
+                    let inst_key = (inst.clone(), Market::Okx);
+                    if let Some(inst_info) = inst_infos.get(&inst_key) {
+                        let ct_val = inst_info.contract_value.unwrap_or(1.0);
+                        let inverse = is_inverse_inst(&inst);
+                        calc_position_notional(size, mark_price, ct_val, inverse)
+                    } else {
+                        0.0
+                    }
+                },
+                // Linear perps sized in base quantity
+                _ if self.bybit_rest().is_some() || self.hyperliquid.is_some() => {
+                    calc_position_notional(size, mark_price, 1.0, false)
+                },
+                _ => 0.0,
+            };
+
+            self.inst_mark_price.insert(inst.clone(), mark_price);
+
+            *notional_map.entry(inst).or_insert(0.0) += pos_notional;
+        }
+
+        notional_map.iter().for_each(|(inst, &notional)| {
+            let weight = if self.total_equity > f64::EPSILON {
+                notional / self.total_equity
+            } else {
+                0.0
+            };
+
+            self.acc_weights.insert(inst.clone(), weight);
+        });
+
+        self.acc_weights
+            .retain(|inst, _| notional_map.contains_key(inst));
+        self.in_flight.clear();
+        info!("[WS] Update acc_weights={:?}, total equity: {}", self.acc_weights, self.total_equity);
+        Ok(())
+    }
+
+    /// Open positions as `(inst, signed size, mark price)`.
+    async fn fetch_positions(&self) -> InfraResult<Vec<(String, f64, f64)>> {
+        let positions = match (self.bybit_rest(), &self.hyperliquid, self.testnet_rest()) {
+            (Some(rest), _, _) => rest.bybit_positions(&self.margin_asset).await?,
+            (_, Some(hl), _) => hl.positions().await?,
+            (_, _, Some(rest)) if *rest.venue() == RestVenue::Okx => rest.okx_positions().await?,
+            (_, _, Some(rest)) => rest.binance_positions().await?,
+            _ => {
+                self.acquire_rate("/fapi/v2/positionRisk").await;
+                self.client
+                    .get_positions(None)
+                    .await?
+                    .into_iter()
+                    .map(|pos| (pos.inst, pos.size, pos.mark_price))
+                    .collect()
+            },
+        };
+        Ok(positions)
+    }
+
+    /// Spends the venue-wide and this account's order budget before an order goes out.
+    async fn acquire_order_budget(&self) {
+        let venue = match self.inst_market() {
+            Some(Market::Okx) => RateVenue::Okx,
+            Some(Market::BinanceUmFutures | Market::BinanceCmFutures) => RateVenue::Binance,
+            Some(Market::Bybit) => RateVenue::Bybit,
+            Some(Market::HyperLiquid) => RateVenue::Hyperliquid,
+            _ => return,
+        };
+        self.rate_limiter.acquire_order(venue, &self.account_id).await;
+    }
+
+    /// Spends the shared venue rate budget before a `CexClients` call.
+    async fn acquire_rate(&self, binance_path: &str) {
+        let (venue, cost) = match &self.client {
+            CexClients::Okx(_) => (RateVenue::Okx, 1.0),
+            CexClients::BinanceUm(_) | CexClients::BinanceCm(_) => {
+                (RateVenue::Binance, binance_weight(binance_path))
+            },
+            _ => return,
+        };
+        self.rate_limiter.acquire(venue, cost).await;
+    }
+
+    async fn process_weight(
+        &mut self,
+        target_weights: &DashMap<String, (f64, f64)>,
+        inst_infos: &HashMap<InstKey, InstrumentInfo>,
+    ) -> InfraResult<()> {
+        let breaker_flatten = self.drawdown.is_tripped() && self.risk.get().drawdown_flatten;
+        let flat_targets;
+        let target_weights = if self.migration_role == MigrationRole::Flatten || breaker_flatten {
+            flat_targets = self.flat_targets(target_weights);
+            &flat_targets
+        } else {
+            target_weights
+        };
+
+        let (mut diffs, computed_target_weights) = self.compare_weights(target_weights);
+        self.pending_diffs = diffs.clone();
+        self.remainders
+            .retain(|inst, rest| diffs.get(inst).is_some_and(|diff| diff * *rest > 0.0));
+
+        for inst in self.twap.cancel_changed(&computed_target_weights) {
+            self.cancel_open_orders(&inst).await;
+        }
+
+        let risk = self.risk.get();
+        let cooldown = Duration::from_secs(risk.order_cooldown_sec);
+        for inst in self.in_flight.expire(Duration::from_secs(risk.inflight_timeout_sec)) {
+            warn!(
+                "[Order] {} in-flight order on {} unconfirmed after {}s, releasing",
+                self.account_id, inst, risk.inflight_timeout_sec
+            );
+        }
+        // A resting limit order owns its instrument until it fills, is re-priced or cancelled;
+        // an in-flight order holds it until confirmed, refreshed or timed out
+        diffs.retain(|inst, _| {
+            (self.remainders.contains_key(inst)
+                || self
+                    .last_order_at
+                    .get(inst)
+                    .is_none_or(|at| at.elapsed() >= cooldown))
+                && !self.orders.has_open(inst)
+                && !self.in_flight.contains(inst)
+        });
+
+        if !diffs.is_empty() {
+            info!("\n================ ACCOUNT UPDATE ================");
+            info!("Account ID       : {:?}", self.account_id);
+            info!("Account balance  : {:?}", self.total_equity);
+            info!("Account Weights  : {:?}", self.acc_weights);
+            info!("Target R Weights : {:?}", target_weights);
+            info!("Target C Weights : {:?}", computed_target_weights);
+            info!("Diffs            : {:?}", diffs);
+            info!("================================================\n");
+        }
+
+        if self.control.is_paused() && !diffs.is_empty() {
+            info!(
+                "[Account] {} paused by operator, holding {} diff(s)",
+                self.account_id,
+                diffs.len()
+            );
+            return Ok(());
+        }
+
+        if self.drawdown.is_tripped() && !breaker_flatten && !diffs.is_empty() {
+            warn!(
+                "[Risk] {} drawdown breaker tripped, holding {} diff(s)",
+                self.account_id,
+                diffs.len()
+            );
+            return Ok(());
+        }
+
+        if self.migration_role == MigrationRole::Hold && !diffs.is_empty() {
+            info!(
+                "[Migration] {} held until the source account is flat, {} diff(s) pending",
+                self.account_id,
+                diffs.len()
+            );
+            return Ok(());
+        }
+
+        let insts: Vec<String> = diffs.keys().cloned().collect();
+        for inst in self.apply_leverage(&insts).await {
+            self.skips
+                .record(&inst, SkipReason::LeverageUnset, "leverage not set on the venue");
+            diffs.remove(&inst);
+        }
+
+        diffs.retain(|inst, diff| {
+            let breached = self
+                .asset_breaches
+                .get(underlying(inst))
+                .is_some_and(|side| side * *diff > 0.0);
+            if breached {
+                self.skips.record(
+                    inst,
+                    SkipReason::AssetCap,
+                    format!("{} net weight beyond max_asset_net_weight", underlying(inst)),
+                );
+            }
+            !breached
+        });
+
+        self.notify_advisories(&computed_target_weights, &diffs);
+
+        if let Some(twap_config) = &self.twap_config {
+            self.twap.schedule(
+                twap_config,
+                self.total_equity,
+                &computed_target_weights,
+                &self.pending_diffs,
+                &mut diffs,
+            );
+        }
+
+        let order_ids: HashMap<String, String> = diffs
+            .keys()
+            .map(|inst| {
+                let target = computed_target_weights.get(inst).copied().unwrap_or_default();
+                (inst.clone(), self.client_ids.next(&self.account_id, inst, target))
+            })
+            .collect();
+
+        if let Some(venue) = self.venue_sizing() {
+            for (inst, diff) in diffs.iter() {
+                let order_id = order_ids.get(inst).cloned();
+                self.rebalance_inst(&venue, inst, *diff, target_weights, inst_infos, order_id)
+                    .await;
+            }
+        }
+        self.skips.flush(&self.account_id);
+
+        Ok(())
+    }
+
+    fn venue_sizing(&self) -> Option<VenueSizing> {
+        // Linear perps are sized in base quantity, with caps in notional
+        let linear = |market| VenueSizing {
+            market,
+            size_fn: calc_binance_order_size,
+            units_per_weight: |equity, mark_price, _| equity / mark_price,
+            cap_to_units: |mark_price| 1.0 / mark_price,
+            isolated: false,
+        };
+
+        match &self.client {
+            CexClients::BinanceUm(_) => Some(linear(Market::BinanceUmFutures)),
+            // Sized and capped in contracts
+            CexClients::Okx(_) => Some(VenueSizing {
+                market: Market::Okx,
+                size_fn: calc_okx_order_size,
+                units_per_weight: |equity, mark_price, info| {
+                    equity / (mark_price * info.contract_value.unwrap_or(1.0))
+                },
+                cap_to_units: |_| 1.0,
+                isolated: true,
+            }),
+            _ if self.bybit_rest().is_some() => Some(linear(Market::Bybit)),
+            _ if self.hyperliquid.is_some() => Some(linear(Market::HyperLiquid)),
+            _ => None,
+        }
+    }
+
+    /// Sizes, caps and submits the rebalance order of `diff` on `inst`.
+    async fn rebalance_inst(
+        &mut self,
+        venue: &VenueSizing,
+        inst: &str,
+        diff: f64,
+        target_weights: &DashMap<String, (f64, f64)>,
+        inst_infos: &HashMap<InstKey, InstrumentInfo>,
+        client_order_id: Option<String>,
+    ) {
+        let Some(&mark_price) = self.inst_mark_price.get(inst) else {
+            self.skips.record(inst, SkipReason::NoMarkPrice, "");
+            return;
+        };
+        let Some(inst_info) = inst_infos.get(&(inst.to_string(), venue.market.clone())) else {
+            self.skips
+                .record(inst, SkipReason::NoInstInfo, format!("{:?}", venue.market));
+            return;
+        };
+
+        let side = if diff > 0.0 {
+            OrderSide::BUY
+        } else {
+            OrderSide::SELL
+        };
+        let inst_notional = (diff * self.total_equity).abs();
+        let min_notional = self.min_order_notional(inst, inst_info);
+        if inst_notional < min_notional {
+            self.skips.record(
+                inst,
+                SkipReason::BelowMinNotional,
+                format!("{:.2} < {}", inst_notional, min_notional),
+            );
+            return;
+        }
+
+        let size = match (venue.size_fn)(mark_price, inst_notional, inst_info) {
+            Ok(s) => s,
+            Err(e) => {
+                self.skips.record(inst, SkipReason::OrderSize, e.to_string());
+                return;
+            },
+        };
+
+        let units_per_weight = (venue.units_per_weight)(self.total_equity, mark_price, inst_info);
+        let cap_to_units = (venue.cap_to_units)(mark_price);
+        let Some(size) = self
+            .apply_position_cap(inst, size, diff, units_per_weight, cap_to_units, inst_info)
+            .await
+        else {
+            self.skips.record(inst, SkipReason::PositionCap, "");
+            return;
+        };
+        let submitted = self.submitted_diff(inst, diff, &size, units_per_weight, inst_info);
+
+        let decision_price = decision_price(target_weights, inst, mark_price);
+        let Some(order_kind) =
+            self.rebalance_order(inst, &side, mark_price, decision_price, inst_info)
+        else {
+            return;
+        };
+        let order_info = OrderParams {
+            margin_mode: venue.isolated.then_some(MarginMode::Isolated),
+            ..self.rebalance_params(inst, &size, &side, diff, client_order_id, order_kind)
+        };
+
+        info!("{:?} order info: {:?}", venue.market, order_info);
+        match self
+            .submit_order(order_info, submitted, decision_price, inst_info)
+            .await
+        {
+            Ok(Submission::Sent(filled_weight)) => {
+                info!("{:?} order placed successfully for {}", venue.market, inst);
+                self.exec_failures.remove(inst);
+                self.apply_fill(inst, filled_weight);
+            },
+            // Recorded as a skip; the identical order is still on its way
+            Ok(Submission::Duplicate) => {},
+            Err(e) => {
+                warn!("Failed to place order for {}: {} — skipping", inst, e);
+                self.record_exec_failure(inst, diff, &e.to_string());
+            },
+        };
+    }
+
+    /// True when `diff` only shrinks the current position, so it goes out reduce-only.
+    fn reduces_position(&self, inst: &str, diff: f64) -> bool {
+        let current = self.acc_weights.get(inst).copied().unwrap_or_default();
+        current * diff < 0.0
+            && (diff.abs() <= current.abs()
+                || (current + diff).abs() <= self.risk.get().converged_tolerance)
+    }
+
+    /// Rebalance order of `size` on every venue, reduce-only when it only closes exposure.
+    fn rebalance_params(
+        &self,
+        inst: &str,
+        size: &str,
+        side: &OrderSide,
+        diff: f64,
+        client_order_id: Option<String>,
+        order_kind: OrderParams,
+    ) -> OrderParams {
+        OrderParams {
+            inst: inst.to_string(),
+            size: size.to_string(),
+            side: side.clone(),
+            reduce_only: self.reduces_position(inst, diff).then_some(true),
+            client_order_id,
+            ..order_kind
+        }
+    }
+
+    /// Order type and price for a rebalance order.
+    fn rebalance_order(
+        &mut self,
+        inst: &str,
+        side: &OrderSide,
+        mark_price: f64,
+        decision_price: f64,
+        inst_info: &InstrumentInfo,
+    ) -> Option<OrderParams> {
+        let buy = matches!(side, OrderSide::BUY);
+        if let Some(limit) = self.price_limits.get(inst).map(|l| l.value().clone()) {
+            let level = limit.level(buy, decision_price);
+            if (buy && mark_price > level) || (!buy && mark_price < level) {
+                self.skips.record(
+                    inst,
+                    SkipReason::PriceLimit,
+                    format!("mark {} past {} of model={}", mark_price, level, limit.model_id),
+                );
+                return None;
+            }
+            return Some(OrderParams {
+                order_type: OrderType::Limit,
+                price: Some(normalize_to_string(level, inst_info.tick_size)),
+                time_in_force: Some(TimeInForce::IOC),
+                ..OrderParams::default()
+            });
+        }
+
+        if self.execution_mode == ExecutionMode::Market {
+            return Some(OrderParams {
+                order_type: OrderType::Market,
+                ..OrderParams::default()
+            });
+        }
+
+        Some(OrderParams {
+            order_type: OrderType::Limit,
+            price: Some(self.limit_price(side, mark_price, inst_info)),
+            time_in_force: Some(TimeInForce::GTC),
+            ..OrderParams::default()
+        })
+    }
+
+    fn limit_price(&self, side: &OrderSide, mark_price: f64, inst_info: &InstrumentInfo) -> String {
+        let offset = self.limit_offset_bps / 10_000.0;
+        let price = match side {
+            OrderSide::BUY => mark_price * (1.0 - offset),
+            _ => mark_price * (1.0 + offset),
+        };
+        normalize_to_string(price, inst_info.tick_size)
+    }
+
+    async fn submit_order(
+        &mut self,
+        mut order_info: OrderParams,
+        mut diff: f64,
+        decision_price: f64,
+        inst_info: &InstrumentInfo,
+    ) -> InfraResult<Submission> {
+        active_profile().ensure_orders_allowed("Order")?;
+
+        if self.paper {
+            self.submit_paper_order(&order_info, inst_info)?;
+            self.command_audit.record_order(&self.account_id, &order_info, diff, "simulated");
+            let mark_price = self
+                .inst_mark_price
+                .get(&order_info.inst)
+                .copied()
+                .unwrap_or(decision_price);
+            self.record_execution(&order_info, diff, decision_price, Some((0.0, mark_price)));
+            self.last_order_at.insert(order_info.inst.clone(), Instant::now());
+            self.client_ids.acked(&order_info.inst);
+            return Ok(Submission::Sent(Some(diff)));
+        }
+
+        self.ensure_no_self_match(&order_info)?;
+        let current = self.acc_weights.get(&order_info.inst).copied();
+        let target_weight = current.unwrap_or_default() + diff;
+
+        // Keyed as requested, so a duplicate is caught before the margin lookup resizes it
+        let mut intent = OrderIntent::new(
+            &self.account_id,
+            &order_info.inst,
+            &order_info.side,
+            &order_info.size,
+            diff,
+            order_info.client_order_id.clone(),
+        );
+        let dedup_key = intent.dedup_key(target_weight);
+        if self.intent_log.is_open(dedup_key) {
+            return Ok(self.skip_duplicate(&order_info));
+        }
+
+        self.apply_margin_limit(&mut order_info, &mut diff, decision_price, inst_info)
+            .await?;
+        intent.size = order_info.size.clone();
+        intent.diff = diff;
+        if !self.intent_log.open(&intent, dedup_key)? {
+            return Ok(self.skip_duplicate(&order_info));
+        }
+
+        // IOC limits fill or expire at once, like market orders
+        let resting = !matches!(order_info.order_type, OrderType::Market)
+            && !matches!(order_info.time_in_force, Some(TimeInForce::IOC));
+        let tracked = order_info.client_order_id.clone().map(|cl_id| TrackedOrder {
+            inst: order_info.inst.clone(),
+            order: OrderRef::ClientId(cl_id),
+            side: order_info.side.clone(),
+            size: order_info.size.clone(),
+            price: order_info.price.clone(),
+            submitted_at: Instant::now(),
+            state: OrderState::Open,
+            reprices: 0,
+            diff,
+            decision_price,
+            intent_id: Some(intent.intent_id.clone()),
+            filled: 0.0,
+        });
+
+        self.load_taker_fee(&order_info.inst).await;
+        // Registered before the venue call so nothing else can send on this instrument while
+        // the order is in flight
+        self.in_flight.begin(&order_info.inst);
+        // A client order id makes a retry after a timeout a duplicate instead of a second order
+        let idempotent = order_info.client_order_id.is_some();
+        let result = with_retry(&self.retry, "Order", idempotent, || {
+            self.send_order(&order_info, decision_price)
+        })
+        .await;
+        let report_order = order_info;
+        let status = if result.is_ok() { "acked" } else { "failed" };
+        self.command_audit.record_order(&self.account_id, &report_order, diff, status);
+
+        // Resting orders stay tracked so they can be amended or cancelled on timeout; their
+        // intent stays open until the order stream or a REST sweep sees them terminal
+        match (resting, tracked, result.is_ok()) {
+            (true, Some(tracked), true) => self.orders.track(tracked),
+            _ => self.close_intent(&intent.intent_id, status),
+        }
+
+        let order_id = match result {
+            Ok(order_id) => order_id,
+            Err(e) => {
+                // The id is kept: if the order did reach the venue, its retry is a duplicate
+                self.in_flight.finish(&report_order.inst);
+                return Err(e);
+            },
+        };
+        self.client_ids.acked(&report_order.inst);
+        self.last_order_at.insert(report_order.inst.clone(), Instant::now());
+        if resting {
+            return Ok(Submission::Sent(None));
+        }
+
+        let fill = self.query_fill(&report_order.inst, &order_id).await;
+        // Partial fills move the weight pro rata; the residual diff is re-evaluated next cycle
+        let order_size = report_order.size.parse::<f64>().unwrap_or_default();
+        let filled_diff = fill
+            .filter(|_| order_size > 0.0)
+            .map(|(filled, _)| diff * (filled / order_size).min(1.0));
+        // An acked market order without a readable fill is charged as filled
+        self.book_fill_fee(&report_order.inst, filled_diff.unwrap_or(diff));
+        self.record_execution(&report_order, diff, decision_price, fill);
+
+        Ok(Submission::Sent(filled_diff))
+    }
+
+    fn skip_duplicate(&mut self, order_info: &OrderParams) -> Submission {
+        self.skips.record(
+            &order_info.inst,
+            SkipReason::DuplicateIntent,
+            format!("{:?} {}", order_info.side, order_info.size),
+        );
+        Submission::Duplicate
+    }
+
+    /// Shrinks an order that adds exposure to what the available margin covers.
+    async fn apply_margin_limit(
+        &mut self,
+        order_info: &mut OrderParams,
+        diff: &mut f64,
+        decision_price: f64,
+        inst_info: &InstrumentInfo,
+    ) -> InfraResult<()> {
+        let Some(rest) = self.rest.clone() else {
+            return Ok(());
+        };
+
+        // Closing exposure releases margin, so reducing orders are never limited
+        if self.reduces_position(&order_info.inst, *diff) {
+            return Ok(());
+        }
+
+        let mark_price = self
+            .inst_mark_price
+            .get(&order_info.inst)
+            .copied()
+            .unwrap_or(decision_price);
+        let max_size = match rest
+            .max_order_size(&order_info.inst, &order_info.side, mark_price)
+            .await
+        {
+            Ok(max_size) => max_size,
+            Err(e) => {
+                warn!(
+                    "[Risk] {} {} margin check failed, sending order unchanged: {}",
+                    self.account_id, order_info.inst, e
+                );
+                return Ok(());
+            },
+        };
+
+        let size = order_info.size.parse::<f64>().unwrap_or_default();
+        if size <= max_size {
+            return Ok(());
+        }
+
+        let min_size = inst_info.min_mkt_size.max(inst_info.lot_size);
+        if max_size < min_size {
+            return Err(InfraError::Msg(format!(
+                "Insufficient margin for {}: available size {} below minimum {}",
+                order_info.inst, max_size, min_size
+            )));
+        }
+
+        // Floor to the lot so rounding cannot push the order back over the margin
+        let lots = (max_size / inst_info.lot_size.max(f64::EPSILON)).floor();
+        let downsized = normalize_to_string(lots * inst_info.lot_size, inst_info.lot_size);
+        warn!(
+            "[Risk] {} {} order {} exceeds available margin, downsized to {}",
+            self.account_id, order_info.inst, order_info.size, downsized
+        );
+        *diff *= downsized.parse::<f64>().unwrap_or_default() / size;
+        order_info.size = downsized;
+
+        Ok(())
+    }
+
+    async fn send_order(
+        &self,
+        order_info: &OrderParams,
+        decision_price: f64,
+    ) -> InfraResult<String> {
+        self.acquire_order_budget().await;
+        match (self.bybit_rest(), &self.hyperliquid, self.testnet_rest()) {
+            (Some(rest), _, _) => rest.bybit_place_order(order_info).await,
+            (_, Some(hl), _) => {
+                let mark_price = self
+                    .inst_mark_price
+                    .get(&order_info.inst)
+                    .copied()
+                    .unwrap_or(decision_price);
+                hl.place_order(order_info, mark_price).await
+            },
+            // The exchange clients only reach the live hosts
+            (_, _, Some(rest)) if *rest.venue() == RestVenue::Okx => {
+                rest.okx_place_order(order_info).await
+            },
+            (_, _, Some(rest)) => rest.binance_place_order(order_info).await,
+            _ => {
+                self.acquire_rate("/fapi/v1/order").await;
+                self.client
+                    .place_order(order_info.clone())
+                    .await
+                    .map(|ack| ack.order_id)
+            },
+        }
+    }
+
+    /// Moves the instrument's weight by a confirmed fill, in weight units.
+    fn apply_fill(&mut self, inst: &str, filled_weight: Option<f64>) {
+        match filled_weight {
+            Some(weight) => {
+                *self.acc_weights.entry(inst.to_string()).or_insert(0.0) += weight;
+                self.in_flight.finish(inst);
+            },
+            None => {
+                info!(
+                    "[Account] {} fill of {} unconfirmed, held until the next position update",
+                    self.account_id, inst
+                );
+                self.in_flight.begin(inst);
+            },
+        }
+    }
+
+    /// Actual fill of a market order, if the venue already reports it.
+    async fn query_fill(&self, inst: &str, order_id: &str) -> Option<(f64, f64)> {
+        let rest = self.rest.as_ref()?;
+        match rest.order_fill(inst, &OrderRef::Id(order_id.to_string())).await {
+            Ok((filled, avg_price)) if filled > 0.0 && avg_price > 0.0 => Some((filled, avg_price)),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("[Execution] Fill lookup failed for {} order {}: {}", inst, order_id, e);
+                None
+            },
+        }
+    }
+
+    /// `fill` is `(filled_size, avg_price)`; a zero size means the whole order filled.
+    fn record_execution(
+        &mut self,
+        order_info: &OrderParams,
+        diff: f64,
+        decision_price: f64,
+        fill: Option<(f64, f64)>,
+    ) {
+        let order_size = order_info.size.parse::<f64>().unwrap_or_default();
+        let estimated = fill.is_none();
+        let (filled_size, avg_price) = match fill {
+            Some((size, px)) if size > 0.0 => (size, px),
+            Some((_, px)) => (order_size, px),
+            None => (order_size, decision_price),
+        };
+        let buy = matches!(order_info.side, OrderSide::BUY);
+        let slippage_bps = slippage_bps(buy, avg_price, decision_price);
+
+        info!(
+            "[Execution] account={} inst={} filled={} avg_px={} decision_px={} slip={:.2}bps{}",
+            self.account_id,
+            order_info.inst,
+            filled_size,
+            avg_price,
+            decision_price,
+            slippage_bps,
+            if estimated { " (estimated)" } else { "" },
+        );
+
+        self.pending_executions.push(ExecutionReport {
+            account_id: self.account_id.clone(),
+            inst: order_info.inst.clone(),
+            buy,
+            filled_size,
+            avg_price,
+            decision_price,
+            slippage_bps,
+            diff,
+            estimated,
+            client_order_id: order_info.client_order_id.clone(),
+            timestamp: get_micros_timestamp(),
+        });
+    }
+
+    /// Fills a paper order immediately, rejecting it like the venue would.
+    fn submit_paper_order(
+        &self,
+        order_info: &OrderParams,
+        inst_info: &InstrumentInfo,
+    ) -> InfraResult<()> {
+        let mark_price = self
+            .inst_mark_price
+            .get(&order_info.inst)
+            .copied()
+            .unwrap_or_default();
+        let current_weight = self
+            .acc_weights
+            .get(&order_info.inst)
+            .copied()
+            .unwrap_or_default();
+
+        let price_band = self.risk.get().paper_price_band;
+        simulate_rejection(order_info, inst_info, mark_price, current_weight, price_band)
+            .map_err(|reason| {
+                info!(
+                    "[Paper] {} order rejected for {}: {}",
+                    self.account_id, order_info.inst, reason
+                );
+                InfraError::Msg(format!("Paper order rejected: {}", reason))
+            })?;
+
+        info!(
+            "[Paper] {} filled {:?} {} {} @ {}",
+            self.account_id, order_info.side, order_info.size, order_info.inst, mark_price
+        );
+        Ok(())
+    }
+
+    /// Market whose instrument info sizes this account's orders.
+    fn inst_market(&self) -> Option<Market> {
+        match &self.client {
+            CexClients::BinanceUm(_) => Some(Market::BinanceUmFutures),
+            CexClients::BinanceCm(_) => Some(Market::BinanceCmFutures),
+            CexClients::Okx(_) => Some(Market::Okx),
+            _ if self.bybit_rest().is_some() => Some(Market::Bybit),
+            _ if self.hyperliquid.is_some() => Some(Market::HyperLiquid),
+            _ => None,
+        }
+    }
+
+    /// Bybit accounts run entirely on `PrivateRest`; `CexClients` has no Bybit client.
+    fn bybit_rest(&self) -> Option<&PrivateRest> {
+        self.rest
+            .as_ref()
+            .filter(|rest| matches!(rest.venue(), RestVenue::Bybit))
+    }
+
+    /// `PrivateRest` of an OKX or Binance UM account on a testnet profile.
+    fn testnet_rest(&self) -> Option<&PrivateRest> {
+        self.rest.as_ref().filter(|rest| {
+            rest.is_testnet() && matches!(rest.venue(), RestVenue::Okx | RestVenue::BinanceUm)
+        })
+    }
+
+    fn private_rest(&self) -> InfraResult<&PrivateRest> {
+        self.rest.as_ref().ok_or_else(|| {
+            InfraError::Msg(format!(
+                "Order management not supported for account {}",
+                self.account_id
+            ))
+        })
+    }
+
+    /// Refuses an order that would trade against another of our accounts.
+    fn ensure_no_self_match(&self, order_info: &OrderParams) -> InfraResult<()> {
+        let buy = matches!(order_info.side, OrderSide::BUY);
+        let price = match order_info.order_type {
+            OrderType::Market => None,
+            _ => order_info.price.as_deref().and_then(|p| p.parse::<f64>().ok()),
+        };
+
+        match self.orders.crossing(&order_info.inst, buy, price).first() {
+            Some(resting) => Err(InfraError::Msg(format!(
+                "Self-match: {} would trade against order {} of account {} at {:?}",
+                order_info.inst, resting.order, resting.account_id, resting.price
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn cancel_order(&mut self, inst: &str, order: &OrderRef) -> InfraResult<()> {
+        self.private_rest()?.cancel_order(inst, order).await?;
+        self.close_order(order.key(), OrderState::Cancelled);
+
+        info!(
+            "[Order] Cancelled account={} inst={} order={:?}",
+            self.account_id, inst, order
+        );
+        Ok(())
+    }
+
+    pub async fn amend_order(
+        &mut self,
+        inst: &str,
+        order: &OrderRef,
+        new_size: Option<&str>,
+        new_price: Option<&str>,
+    ) -> InfraResult<()> {
+        let tracked = self.orders.get(order.key()).cloned();
+        let side = tracked.as_ref().map(|o| o.side.clone());
+        let size = new_size
+            .map(|s| s.to_string())
+            .or(tracked.as_ref().map(|o| o.size.clone()));
+        let price = new_price
+            .map(|p| p.to_string())
+            .or(tracked.as_ref().and_then(|o| o.price.clone()));
+
+        let rest = self.private_rest()?;
+        self.acquire_order_budget().await;
+        rest.amend_order(inst, order, side.as_ref(), size.as_deref(), price.as_deref())
+            .await?;
+
+        if let Some(mut tracked) = tracked {
+            tracked.size = size.clone().unwrap_or(tracked.size);
+            tracked.price = price.clone();
+            self.orders.track(tracked);
+        }
+
+        info!(
+            "[Order] Amended account={} inst={} order={:?} size={:?} price={:?}",
+            self.account_id, inst, order, size, price
+        );
+        Ok(())
+    }
+
+    /// Cancels the resting child orders of a TWAP whose target changed.
+    async fn cancel_open_orders(&mut self, inst: &str) {
+        for tracked in self.orders.open_for(inst) {
+            if let Err(e) = self.cancel_order(&tracked.inst, &tracked.order).await {
+                warn!(
+                    "[Twap] Cancel failed account={} order={:?}: {}",
+                    self.account_id, tracked.order, e
+                );
+            }
+        }
+    }
+
+    /// Resting orders past `timeout`.
+    pub async fn cancel_stale_orders(
+        &mut self,
+        timeout: Duration,
+        inst_infos: &HashMap<InstKey, InstrumentInfo>,
+    ) {
+        for tracked in self.orders.stale(timeout) {
+            if !self.orders.cancel_due(tracked.order.key()) {
+                continue;
+            }
+            if self.execution_mode == ExecutionMode::LimitOffset {
+                match self.reprice_order(&tracked, inst_infos).await {
+                    Ok(true) => continue,
+                    Ok(false) => {},
+                    Err(e) => warn!(
+                        "[Order] Re-price failed account={} order={:?}: {}",
+                        self.account_id, tracked.order, e
+                    ),
+                }
+            }
+
+            let Err(e) = self.cancel_order(&tracked.inst, &tracked.order).await else {
+                continue;
+            };
+
+            let failures = self.orders.cancel_failed(tracked.order.key());
+            if failures < MAX_CANCEL_RETRIES {
+                warn!(
+                    "[Order] Cancel-on-timeout failed account={} order={:?} ({}/{}): {}",
+                    self.account_id, tracked.order, failures, MAX_CANCEL_RETRIES, e
+                );
+                continue;
+            }
+
+            let detail = format!(
+                "{} order {} on {} still resting after {} failed cancels: {}",
+                self.account_id,
+                tracked.order.key(),
+                tracked.inst,
+                failures,
+                e
+            );
+            error!("[Order] ALERT cancel retries exhausted, {}", detail);
+            audit("cancel_on_timeout", None, "exhausted", &detail);
+        }
+    }
+
+    /// `Ok(true)` when the order is settled here: filled, or moved to the fresh mark.
+    async fn reprice_order(
+        &mut self,
+        tracked: &TrackedOrder,
+        inst_infos: &HashMap<InstKey, InstrumentInfo>,
+    ) -> InfraResult<bool> {
+        let (filled, avg_price) = self
+            .private_rest()?
+            .order_fill(&tracked.inst, &tracked.order)
+            .await?;
+        let size = tracked.size.parse::<f64>().unwrap_or_default();
+        if filled > 0.0 && filled >= size - f64::EPSILON {
+            self.book_limit_fill(tracked, filled, avg_price);
+            // The position refresh may already include this fill, so it sets the weight
+            self.in_flight.begin(&tracked.inst);
+            return Ok(true);
+        }
+
+        if tracked.reprices >= MAX_LIMIT_REPRICES {
+            return Ok(false);
+        }
 
-    fn add_account(&mut self, account_info: AccountInfo) {
-        self.task_index.insert(
-            account_info.account_orders_task_id,
-            account_info.account_id.clone(),
-        );
+        let Some(&mark_price) = self.inst_mark_price.get(&tracked.inst) else {
+            return Ok(false);
+        };
+        let Some(inst_info) = self
+            .inst_market()
+            .and_then(|market| inst_infos.get(&(tracked.inst.clone(), market)))
+        else {
+            return Ok(false);
+        };
 
-        self.task_index.insert(
-            account_info.account_bal_pos_task_id,
-            account_info.account_id.clone(),
-        );
+        let price = self.limit_price(&tracked.side, mark_price, inst_info);
+        self.amend_order(&tracked.inst, &tracked.order, None, Some(&price))
+            .await?;
 
-        self.account_infos
-            .insert(account_info.account_id.clone(), account_info);
+        let mut repriced = tracked.clone();
+        repriced.price = Some(price);
+        repriced.reprices += 1;
+        repriced.submitted_at = Instant::now();
+        self.orders.track(repriced);
+        Ok(true)
     }
-}
 
-#[derive(Clone, Debug)]
-pub struct AccountInfo {
-    pub account_id: String,
-    pub client: CexClients,
-    pub acc_weights: HashMap<String, f64>,
-    pub inst_mark_price: HashMap<String, f64>,
-    pub total_equity: f64,
-    pub account_orders_task_id: u64,
-    pub account_bal_pos_task_id: u64,
-}
-
-impl AccountInfo {
-    fn ws_update_acc_order(&mut self, acc_order: &WsAccOrder, _inst_info: &InstrumentInfo) {
-        info!("[Account] Update acc_order={:?}", acc_order);
+    /// Stops tracking a terminal order and closes its intent.
+    fn close_order(&mut self, key: &str, state: OrderState) {
+        let Some(intent_id) = self.orders.mark(key, state.clone()).and_then(|o| o.intent_id) else {
+            return;
+        };
+        let status = match state {
+            OrderState::Filled => "filled",
+            _ => "cancelled",
+        };
+        self.close_intent(&intent_id, status);
     }
 
-    fn ws_update_acc_position(&mut self, pos: &WsAccPosition, inst_info: &InstrumentInfo) {
-        let mark_price = self
-            .inst_mark_price
-            .get(&pos.inst)
-            .unwrap_or(&pos.avg_price);
+    fn close_intent(&self, intent_id: &str, status: &str) {
+        if let Err(e) = self.intent_log.complete(intent_id, status) {
+            warn!("[Intent] Failed to close intent {}: {}", intent_id, e);
+        }
+    }
 
-        let pos_notional = match &self.client {
-            CexClients::BinanceUm(_) => pos.size * mark_price,
-            CexClients::Okx(_) => {
-                let multiplier = inst_info.contract_value.unwrap_or(1.0);
-                pos.size * mark_price * multiplier
+    /// Marks a tracked limit order filled and records its execution.
+    fn book_limit_fill(&mut self, tracked: &TrackedOrder, filled: f64, avg_price: f64) {
+        self.close_order(tracked.order.key(), OrderState::Filled);
+        let order_info = OrderParams {
+            inst: tracked.inst.clone(),
+            size: tracked.size.clone(),
+            side: tracked.side.clone(),
+            order_type: OrderType::Limit,
+            price: tracked.price.clone(),
+            client_order_id: match &tracked.order {
+                OrderRef::ClientId(id) => Some(id.clone()),
+                OrderRef::Id(_) => None,
             },
-            _ => 0.0,
-        };
-
-        let weight = if self.total_equity > f64::EPSILON {
-            pos_notional / self.total_equity
-        } else {
-            0.0
+            ..OrderParams::default()
         };
-        self.acc_weights.insert(pos.inst.clone(), weight);
+        let order_size = tracked.size.parse::<f64>().unwrap_or_default();
+        if order_size > 0.0 {
+            self.book_fill_fee(&tracked.inst, tracked.diff * (filled / order_size).min(1.0));
+        }
+        let fill = Some((filled, avg_price)).filter(|(_, px)| *px > 0.0);
+        self.record_execution(&order_info, tracked.diff, tracked.decision_price, fill);
     }
 
-    pub async fn rest_update_acc_balance(&mut self) -> InfraResult<()> {
-        let balances = self.client.get_balance(Some(&["USDT".to_string()])).await?;
+    /// Rebuilds order and position state from REST after a private WS reconnect.
+    async fn reconcile(
+        &mut self,
+        inst_infos: &HashMap<InstKey, InstrumentInfo>,
+    ) -> InfraResult<()> {
+        if !self.paper && self.rest.is_some() {
+            for tracked in self.orders.stale(Duration::ZERO) {
+                let (state, filled, avg_price) = self
+                    .private_rest()?
+                    .order_status(&tracked.inst, &tracked.order)
+                    .await?;
+                match state {
+                    OrderState::Open => {},
+                    OrderState::Filled => {
+                        warn!(
+                            "[Reconcile] Account={} order {} on {} filled while disconnected",
+                            self.account_id,
+                            tracked.order.key(),
+                            tracked.inst
+                        );
+                        self.book_limit_fill(&tracked, filled, avg_price);
+                    },
+                    OrderState::Cancelled => {
+                        warn!(
+                            "[Reconcile] Account={} order {} on {} closed while disconnected, \
+                             filled {} of {}",
+                            self.account_id,
+                            tracked.order.key(),
+                            tracked.inst,
+                            filled,
+                            tracked.size
+                        );
+                        self.close_order(tracked.order.key(), OrderState::Cancelled);
+                    },
+                }
+            }
+        }
 
-        let usdt_balance = balances
-            .iter()
-            .find(|b| b.asset.eq_ignore_ascii_case("USDT"))
-            .ok_or_else(|| {
-                InfraError::Msg("Rest update account bal err: USDT balance missing".into())
-            })?;
+        let before = self.acc_weights.clone();
+        self.rest_update_acc_balance().await?;
+        self.rest_update_acc_pos_weight(inst_infos).await?;
 
-        self.total_equity = usdt_balance.total;
-        info!("[WS] Rest update acc_order={:?}", usdt_balance);
+        let insts: HashSet<&String> = before.keys().chain(self.acc_weights.keys()).collect();
+        for inst in insts {
+            let prev = before.get(inst).copied().unwrap_or_default();
+            let now = self.acc_weights.get(inst).copied().unwrap_or_default();
+            if (now - prev).abs() > RECONCILE_TOLERANCE {
+                warn!(
+                    "[Reconcile] Account={} {} weight drifted {} -> {}",
+                    self.account_id, inst, prev, now
+                );
+            }
+        }
+
+        self.reconcile_pending = false;
+        info!("[Reconcile] Account={} re-synced from REST snapshot", self.account_id);
         Ok(())
     }
 
-    pub async fn rest_update_acc_pos_weight(
+    /// Forgets the per-instrument state of instruments idle for `ttl`.
+    fn collect_idle_insts(
         &mut self,
-        inst_infos: &HashMap<InstKey, InstrumentInfo>,
-    ) -> InfraResult<()> {
-        let positions = self.client.get_positions(None).await?;
-        let mut notional_map: HashMap<String, f64> = HashMap::new();
+        target_weights: &DashMap<String, (f64, f64)>,
+        ttl: Duration,
+    ) {
+        let held = self
+            .acc_weights
+            .iter()
+            .filter(|(_, weight)| weight.abs() > f64::EPSILON)
+            .map(|(inst, _)| inst.clone());
+        let targeted = target_weights.iter().map(|entry| entry.key().clone());
+        for inst in held.chain(targeted).collect::<Vec<_>>() {
+            self.idle_insts.touch(&inst);
+        }
 
-        for pos in positions {
-            let pos_notional = match &self.client {
-                CexClients::BinanceUm(_) => pos.size * pos.mark_price,
-                CexClients::Okx(_) => {
-                    let inst_key = (pos.inst.clone(), Market::Okx);
-                    if let Some(inst_info) = inst_infos.get(&inst_key) {
-                        let ct_val = inst_info.contract_value.unwrap_or(1.0);
-                        pos.size * pos.mark_price * ct_val
-                    } else {
-                        0.0
-                    }
-                },
-                _ => 0.0,
-            };
+        let cached: Vec<String> = self
+            .inst_mark_price
+            .keys()
+            .chain(self.acc_weights.keys())
+            .chain(self.funding.keys())
+            .chain(self.taker_fees.keys())
+            .cloned()
+            .collect();
+        let idle: Vec<String> = self
+            .idle_insts
+            .expire(cached, ttl)
+            .into_iter()
+            .filter(|inst| !self.orders.has_open(inst) && !self.in_flight.contains(inst))
+            .collect();
+        if idle.is_empty() {
+            return;
+        }
 
-            self.inst_mark_price
-                .insert(pos.inst.clone(), pos.mark_price);
+        for inst in idle.iter() {
+            self.inst_mark_price.remove(inst);
+            self.acc_weights.remove(inst);
+            self.funding.remove(inst);
+            self.taker_fees.remove(inst);
+            self.exec_failures.remove(inst);
+            self.last_order_at.remove(inst);
+            self.pending_diffs.remove(inst);
+            self.skips.forget(inst);
+        }
+        info!(
+            "[Account] {} dropped state of {} idle instruments: {:?}",
+            self.account_id,
+            idle.len(),
+            idle
+        );
+    }
 
-            *notional_map.entry(pos.inst.clone()).or_insert(0.0) += pos_notional;
+    fn snapshot(&self) -> AccountSnapshot {
+        let next_update_sec = self
+            .next_update
+            .map(|t| t.saturating_duration_since(Instant::now()).as_secs())
+            .unwrap_or_default();
+
+        AccountSnapshot {
+            total_equity: self.total_equity,
+            acc_weights: self.acc_weights.clone(),
+            pending_diffs: self.pending_diffs.clone(),
+            exec_failures: self.exec_failures.clone(),
+            next_update_sec,
+            paused: self.control.is_paused(),
+            drawdown_tripped: self.drawdown.is_tripped(),
+            skips: self.skips.counts().clone(),
+            timestamp: get_micros_timestamp(),
         }
+    }
 
-        notional_map.iter().for_each(|(inst, &notional)| {
-            let weight = if self.total_equity > f64::EPSILON {
-                notional / self.total_equity
-            } else {
-                0.0
-            };
+    fn record_exec_failure(&mut self, inst: &str, diff: f64, detail: &str) {
+        let failures = self.exec_failures.entry(inst.to_string()).or_insert(0);
+        *failures += 1;
 
-            self.acc_weights.insert(inst.clone(), weight);
-        });
+        if !failures.is_multiple_of(self.risk.get().max_exec_failures) {
+            return;
+        }
 
-        self.acc_weights
-            .retain(|inst, _| notional_map.contains_key(inst));
-        println!("[WS] Update acc_weights={:?}, total equity: {}", self.acc_weights, self.total_equity);
-        Ok(())
+        self.pending_rejections.push(TradeRejection {
+            model_id: None,
+            account_id: self.account_id.clone(),
+            inst: inst.to_string(),
+            reason: "execution_failed".to_string(),
+            detail: detail.to_string(),
+            diff,
+            failures: *failures,
+            timestamp: get_micros_timestamp(),
+        });
     }
 
-    async fn process_weight(
+    /// Weight an order of `size` actually moves; a shortfall goes to `remainders`.
+    fn submitted_diff(
         &mut self,
-        target_weights: &DashMap<String, (f64, f64)>,
-        inst_infos: &HashMap<InstKey, InstrumentInfo>,
-    ) -> InfraResult<()> {
-        let (diffs, computed_target_weights) = self.compare_weights(target_weights);
+        inst: &str,
+        diff: f64,
+        size: &str,
+        units_per_weight: f64,
+        inst_info: &InstrumentInfo,
+    ) -> f64 {
+        let Some(size) = size.parse::<f64>().ok().filter(|_| units_per_weight > f64::EPSILON)
+        else {
+            return diff;
+        };
 
-        if !diffs.is_empty() {
-            info!("\n================ ACCOUNT UPDATE ================");
-            info!("Account ID       : {:?}", self.account_id);
-            info!("Account balance  : {:?}", self.total_equity);
-            info!("Account Weights  : {:?}", self.acc_weights);
-            info!("Target R Weights : {:?}", target_weights);
-            info!("Target C Weights : {:?}", computed_target_weights);
-            info!("Diffs            : {:?}", diffs);
-            info!("================================================\n");
+        let submitted = size / units_per_weight * diff.signum();
+        let rest = diff - submitted;
+        if rest * diff > 0.0 && rest.abs() * units_per_weight > inst_info.lot_size {
+            warn!(
+                "[Order] {} {} order clamped to {}: {:.4} of {:.4} weight submitted, {:.4} queued",
+                self.account_id, inst, size, submitted, diff, rest
+            );
+            self.remainders.insert(inst.to_string(), rest);
+        } else {
+            self.remainders.remove(inst);
         }
 
-        match &self.client {
-            CexClients::BinanceUm(_) => {
-                for (inst, diff) in diffs.iter() {
-                    let mark_price = match self.inst_mark_price.get(inst) {
-                        Some(&price) => price,
-                        None => {
-                            warn!("Mark price not found for {} — skipping", inst);
-                            continue;
-                        },
-                    };
+        submitted
+    }
 
-                    let inst_key = (inst.clone(), Market::BinanceUmFutures);
-                    let Some(binance_info) = inst_infos.get(&inst_key) else {
-                        warn!("Binance info not found for {} — skipping", inst);
-                        continue;
-                    };
+    /// Smallest rebalance order notional on `inst`.
+    fn min_order_notional(&self, inst: &str, inst_info: &InstrumentInfo) -> f64 {
+        let risk = self.risk.get();
+        match risk.min_notional_overrides.get(inst) {
+            Some(min) => *min,
+            None => risk
+                .min_order_notional
+                .max(inst_info.min_notional.unwrap_or_default()),
+        }
+    }
 
-                    let side = if *diff > 0.0 {
-                        OrderSide::BUY
-                    } else {
-                        OrderSide::SELL
-                    };
-                    let inst_notional = (diff * self.total_equity).abs();
-                    if inst_notional < 6.0 {
-                        warn!(
-                            "Inst notional less than 6.0 USDT on Binance Um, inst notional: {}",
-                            inst_notional,
-                        );
+    fn configured_leverage(&self, inst: &str) -> Option<u32> {
+        self.leverage
+            .get(inst)
+            .or_else(|| self.leverage.get("*"))
+            .copied()
+            .filter(|lever| *lever > 0)
+    }
 
-                        continue;
-                    }
+    /// Sets the configured leverage of `insts` and returns those still missing it.
+    async fn apply_leverage(&mut self, insts: &[String]) -> Vec<String> {
+        let mut missing = Vec::new();
+        for inst in insts {
+            if self.leverage_set.contains(inst) {
+                continue;
+            }
+            let Some(lever) = self.configured_leverage(inst) else {
+                continue;
+            };
 
-                    let size =
-                        match calc_binance_order_size(mark_price, inst_notional, binance_info) {
-                            Ok(s) => s,
-                            Err(e) => {
-                                warn!(
-                                    "Failed to calculate Binance order size for {}: {} — skipping",
-                                    inst, e,
-                                );
-
-                                continue;
-                            },
-                        };
-
-                    let order_info = OrderParams {
-                        inst: inst.clone(),
-                        size: size.clone(),
-                        side: side.clone(),
-                        order_type: OrderType::Market,
-                        ..OrderParams::default()
-                    };
+            let result = if self.paper {
+                Ok(())
+            } else if let Some(hyperliquid) = &self.hyperliquid {
+                hyperliquid.set_leverage(inst, lever).await
+            } else {
+                match self.private_rest() {
+                    Ok(rest) => rest.set_leverage(inst, lever).await,
+                    Err(e) => Err(e),
+                }
+            };
 
-                    println!("Binance order info: {:#?}", order_info);
+            match result {
+                Ok(()) => {
+                    info!("[Leverage] {} {} set to {}x", self.account_id, inst, lever);
+                    self.leverage_set.insert(inst.clone());
+                },
+                Err(e) => {
+                    warn!(
+                        "[Leverage] {} failed to set {} to {}x: {}",
+                        self.account_id, inst, lever, e
+                    );
+                    missing.push(inst.clone());
+                },
+            }
+        }
+        missing
+    }
 
-                    match self.client.place_order(order_info).await {
-                        Ok(_) => {
-                            info!("Binance order placed successfully for {}", inst);
+    /// Scales targets down to what the account's leverage covers.
+    fn enforce_leverage(&mut self, targets: &mut HashMap<String, f64>) {
+        let margin: f64 = targets
+            .iter()
+            .filter_map(|(inst, w)| Some(w.abs() / f64::from(self.configured_leverage(inst)?)))
+            .sum();
+        let gross: f64 = targets.values().map(|w| w.abs()).sum();
 
-                            self.acc_weights
-                                .entry(inst.clone())
-                                .and_modify(|weight| *weight += *diff)
-                                .or_insert(*diff);
-                        },
-                        Err(e) => {
-                            warn!("Failed to place order for {}: {} — skipping", inst, e);
-                        },
-                    };
-                }
-            },
-            CexClients::Okx(_) => {
-                for (inst, diff) in diffs.iter() {
-                    let mark_price = match self.inst_mark_price.get(inst) {
-                        Some(&price) => price,
-                        None => {
-                            warn!("Mark price not found for {} — skipping", inst);
-                            continue;
-                        },
-                    };
+        let mut scale: f64 = 1.0;
+        if margin > 1.0 {
+            scale = scale.min(1.0 / margin);
+        }
+        if let Some(max) = self.max_leverage
+            && gross > max
+        {
+            scale = scale.min(max / gross);
+        }
+        if scale >= 1.0 {
+            self.leverage_rejected.clear();
+            return;
+        }
 
-                    let inst_key = (inst.clone(), Market::Okx);
-                    let Some(okx_info) = inst_infos.get(&inst_key) else {
-                        warn!("Okx info not found for {} — skipping", inst);
-                        continue;
-                    };
+        warn!(
+            "[Leverage] {} targets need margin {:.2}x equity at gross {:.4}, scaling by {:.4}",
+            self.account_id, margin, gross, scale
+        );
+        let timestamp = get_micros_timestamp();
+        for (inst, w) in targets.iter_mut() {
+            let requested = *w;
+            *w *= scale;
+            if requested == 0.0 || self.leverage_rejected.get(inst) == Some(&requested) {
+                continue;
+            }
 
-                    let side = if *diff > 0.0 {
-                        OrderSide::BUY
-                    } else {
-                        OrderSide::SELL
-                    };
-                    let inst_notional = (diff * self.total_equity).abs();
-
-                    let size = match calc_okx_order_size(mark_price, inst_notional, okx_info) {
-                        Ok(s) => s,
-                        Err(e) => {
-                            warn!(
-                                "Failed to calculate OKX order size for {}: {} — skipping",
-                                inst, e,
-                            );
-
-                            continue;
-                        },
-                    };
+            self.leverage_rejected.insert(inst.clone(), requested);
+            self.pending_rejections.push(TradeRejection {
+                model_id: None,
+                account_id: self.account_id.clone(),
+                inst: inst.clone(),
+                reason: "leverage_exceeded".to_string(),
+                detail: format!(
+                    "target {:.4} beyond the account's leverage, capped at {:.4}",
+                    requested, *w
+                ),
+                diff: *w - requested,
+                failures: 0,
+                timestamp,
+            });
+        }
+    }
 
-                    let order_info = OrderParams {
-                        inst: inst.clone(),
-                        size: size.clone(),
-                        side: side.clone(),
-                        order_type: OrderType::Market,
-                        margin_mode: Some(MarginMode::Isolated),
-                        ..Default::default()
-                    };
+    /// Advisory mode: reports each large rebalance with its context before the order goes out.
+    fn notify_advisories(&mut self, targets: &HashMap<String, f64>, diffs: &HashMap<String, f64>) {
+        let Some(notifier) = &self.advisory else {
+            return;
+        };
 
-                    println!("okx order info: {:#?}", order_info);
+        let timestamp = get_micros_timestamp();
+        let advisories: Vec<Advisory> = diffs
+            .iter()
+            .filter_map(|(inst, diff)| {
+                let target_weight = targets.get(inst).copied().unwrap_or_default();
+                let severity = notifier.severity(inst, *diff, target_weight)?;
+                let notional = (diff * self.total_equity).abs();
+                let twap = self
+                    .twap_config
+                    .as_ref()
+                    .is_some_and(|twap| notional > twap.notional);
+
+                Some(Advisory {
+                    account_id: self.account_id.clone(),
+                    inst: inst.clone(),
+                    severity,
+                    diff: *diff,
+                    notional,
+                    total_equity: self.total_equity,
+                    current_weight: self.acc_weights.get(inst).copied().unwrap_or_default(),
+                    target_weight,
+                    mark_price: self.inst_mark_price.get(inst).copied(),
+                    reduce_only: self.reduces_position(inst, *diff),
+                    execution: format!(
+                        "{:?}{}",
+                        self.execution_mode,
+                        if twap { " via TWAP" } else { "" }
+                    ),
+                    timestamp,
+                })
+            })
+            .collect();
+
+        if let Some(notifier) = self.advisory.as_mut() {
+            notifier.retain(&self.pending_diffs);
+            advisories
+                .into_iter()
+                .for_each(|advisory| notifier.notify(advisory));
+        }
+    }
 
-                    match self.client.place_order(order_info).await {
-                        Ok(_) => {
-                            info!("Okx order placed successfully for {}", inst);
+    /// Zero targets for every targeted or held instrument, priced like the shared targets.
+    fn flat_targets(
+        &self,
+        target_weights: &DashMap<String, (f64, f64)>,
+    ) -> DashMap<String, (f64, f64)> {
+        let flat: DashMap<String, (f64, f64)> = target_weights
+            .iter()
+            .map(|r| (r.key().clone(), (r.value().0, 0.0)))
+            .collect();
 
-                            self.acc_weights
-                                .entry(inst.clone())
-                                .and_modify(|weight| *weight += *diff)
-                                .or_insert(*diff);
-                        },
-                        Err(e) => {
-                            warn!("Failed to place order for {}: {} — skipping", inst, e);
-                        },
-                    };
-                }
-            },
-            _ => {},
-        };
+        for inst in self.acc_weights.keys() {
+            if flat.contains_key(inst) {
+                continue;
+            }
 
-        Ok(())
+            match self.inst_mark_price.get(inst) {
+                Some(&price) => {
+                    flat.insert(inst.clone(), (price, 0.0));
+                },
+                None => warn!("[Migration] No mark price to flatten {} — skipping", inst),
+            }
+        }
+
+        flat
     }
 
     fn compare_weights(
@@ -751,22 +3604,26 @@ impl AccountInfo {
     ) -> (HashMap<String, f64>, HashMap<String, f64>) {
         let mut diffs = HashMap::new();
         let mut computed_target_weights = HashMap::new();
-
-        let inst_count = target_weights.len().max(1) as f64;
+        let risk = self.risk.get();
 
         for r in target_weights.iter() {
-            let inst = r.key();
-            let (price, raw_weight) = *r.value();
-
-            self.inst_mark_price.insert(inst.clone(), price);
+            let (price, target_w) = *r.value();
+            self.inst_mark_price.insert(r.key().clone(), price);
+            computed_target_weights.insert(r.key().clone(), target_w);
+        }
 
-            let target_w = raw_weight / inst_count;
-            computed_target_weights.insert(inst.clone(), target_w);
+        // Targets edited after the server applied the caps, or caps tightened since, must
+        // not reach the venue unclamped
+        risk.clamp_exposure(&self.account_id, &mut computed_target_weights);
+        self.enforce_leverage(&mut computed_target_weights);
 
+        for (inst, &target_w) in computed_target_weights.iter() {
             let current_w = self.acc_weights.get(inst).cloned().unwrap_or(0.0);
             let diff = target_w - current_w;
 
-            if diff.abs() > 0.01 {
+            // Toxic flow makes small rebalances likely to trade against informed takers
+            let threshold = risk.rebalance_band(self.toxicity.get(inst).map(|vpin| *vpin));
+            if diff.abs() > threshold {
                 diffs.insert(inst.clone(), diff);
             }
         }
@@ -774,7 +3631,46 @@ impl AccountInfo {
         (diffs, computed_target_weights)
     }
 
-    fn from_config(cfg: &AccountFileConfig, shared_client: Arc<Client>) -> InfraResult<Self> {
+    fn from_config(
+        cfg: &AccountFileConfig,
+        shared_client: Arc<Client>,
+        meta_cache: &MetaCache,
+        rate_limiter: &RateLimiter,
+    ) -> InfraResult<Self> {
+        let rest = PrivateRest::from_config(
+            cfg,
+            shared_client.clone(),
+            meta_cache.clone(),
+            rate_limiter.clone(),
+        );
+        let paper = cfg.paper || active_profile().simulate_orders();
+        let mut drawdown = DrawdownBreaker::default();
+        drawdown.restore(&format!("{}_{}.json", DRAWDOWN_STATE_PREFIX, cfg.account_id))?;
+        let advisory = cfg
+            .advisory
+            .clone()
+            .map(|config| AdvisoryNotifier::new(config, shared_client.clone()));
+        // Re-pricing and cancel-on-timeout go through `PrivateRest`
+        let execution_mode = match cfg.execution_mode {
+            ExecutionMode::LimitOffset if rest.is_none() && !paper => {
+                warn!(
+                    "[Account] {} has no order management on {}, using market execution",
+                    cfg.account_id, cfg.exchange
+                );
+                ExecutionMode::Market
+            },
+            mode => mode,
+        };
+        let hyperliquid = if cfg.exchange.eq_ignore_ascii_case("hyperliquid") {
+            Some(HyperliquidClient::from_config(
+                cfg,
+                shared_client.clone(),
+                meta_cache.clone(),
+                rate_limiter.clone(),
+            )?)
+        } else {
+            None
+        };
         let client = match cfg.exchange.to_lowercase().as_str() {
             "okx" => {
                 let mut cli = OkxCli::new(shared_client);
@@ -793,6 +3689,12 @@ impl AccountInfo {
                 });
                 CexClients::BinanceUm(cli)
             },
+            "binance_cm" if active_profile().testnet => {
+                return Err(InfraError::Msg(format!(
+                    "{}: binance_cm has no testnet routing",
+                    cfg.account_id
+                )));
+            },
             "binance_cm" => {
                 let mut cli = BinanceCmCli::new(shared_client);
                 cli.api_key = Some(BinanceKey {
@@ -801,6 +3703,10 @@ impl AccountInfo {
                 });
                 CexClients::BinanceCm(cli)
             },
+            // Served by `PrivateRest`, see `bybit_rest`
+            "bybit" => CexClients::Unknown,
+            // Served by `HyperliquidClient`
+            "hyperliquid" => CexClients::Unknown,
             e => return Err(InfraError::Msg(format!("Unknown exchange: {}", e))),
         };
 
@@ -809,15 +3715,363 @@ impl AccountInfo {
             client,
             acc_weights: HashMap::new(),
             inst_mark_price: HashMap::new(),
+            margin_asset: cfg.margin_asset.clone().unwrap_or_else(|| "USDT".to_string()),
             total_equity: 0.0,
+            reported_equity: 0.0,
+            funding: HashMap::new(),
+            funding_interval_ms: funding_interval_ms(cfg),
+            funding_bills: FundingLedger::default(),
+            taker_fees: HashMap::new(),
+            unsettled_fees: 0.0,
             account_orders_task_id: cfg.account_orders_task_id,
             account_bal_pos_task_id: cfg.account_bal_pos_task_id,
+            next_update: None,
+            exec_failures: HashMap::new(),
+            pending_rejections: Vec::new(),
+            pending_executions: Vec::new(),
+            intent_log: IntentLog::default(),
+            command_audit: CommandAudit::default(),
+            rest,
+            hyperliquid,
+            orders: OrderTracker::default(),
+            position_caps: PositionCaps::default(),
+            control: ControlState::default(),
+            risk: RiskLimits::default(),
+            toxicity: Arc::new(DashMap::new()),
+            price_limits: Arc::new(DashMap::new()),
+            retry: RetryPolicy::default(),
+            rate_limiter: rate_limiter.clone(),
+            last_order_at: HashMap::new(),
+            day_start_equity: None,
+            transfers: TransferLedger::default(),
+            drawdown,
+            pending_diffs: HashMap::new(),
+            remainders: HashMap::new(),
+            skips: SkipLog::default(),
+            asset_breaches: HashMap::new(),
+            in_flight: InFlightOrders::default(),
+            reconcile_pending: false,
+            paper,
+            migration_role: MigrationRole::default(),
+            execution_mode,
+            limit_offset_bps: cfg.limit_offset_bps,
+            twap_config: cfg.twap.clone(),
+            twap: TwapScheduler::default(),
+            advisory,
+            leverage: cfg.leverage.clone(),
+            leverage_set: HashSet::new(),
+            max_leverage: cfg.max_leverage,
+            leverage_rejected: HashMap::new(),
+            client_ids: ClientOrderIds::default(),
+            idle_insts: IdleInstruments::default(),
+            file_config: cfg.clone(),
         })
     }
 
     fn config_changed(&self, other: &Self) -> bool {
-        self.account_id != other.account_id
-            || self.account_orders_task_id != other.account_orders_task_id
-            || self.account_bal_pos_task_id != other.account_bal_pos_task_id
+        self.file_config != other.file_config
+    }
+}
+
+/// Per-venue order sizing of the rebalance loop.
+struct VenueSizing {
+    market: Market,
+    size_fn: fn(f64, f64, &InstrumentInfo) -> InfraResult<String>,
+    /// Order size units per unit of weight, from equity, mark price and instrument.
+    units_per_weight: fn(f64, f64, &InstrumentInfo) -> f64,
+    /// Venue position cap to order size units at the mark price.
+    cap_to_units: fn(f64) -> f64,
+    isolated: bool,
+}
+
+/// Price published with the target, falling back to mark for targets set without one.
+fn decision_price(
+    target_weights: &DashMap<String, (f64, f64)>,
+    inst: &str,
+    mark_price: f64,
+) -> f64 {
+    target_weights
+        .get(inst)
+        .map(|entry| entry.value().0)
+        .filter(|px| *px > 0.0)
+        .unwrap_or(mark_price)
+}
+
+/// Perpetual instrument info of every supported venue, cached through `meta`.
+pub async fn fetch_inst_infos(meta: &MetaCache) -> InfraResult<HashMap<InstKey, InstrumentInfo>> {
+    let client = Client::new();
+    let venues = if active_profile().testnet {
+        vec![
+            (
+                Market::Okx,
+                cached_inst_infos(meta, Market::Okx, okx_testnet_instrument_info(&client)).await,
+            ),
+            (
+                Market::BinanceUmFutures,
+                cached_inst_infos(
+                    meta,
+                    Market::BinanceUmFutures,
+                    binance_um_testnet_instrument_info(&client),
+                )
+                .await,
+            ),
+            (
+                Market::Bybit,
+                cached_inst_infos(meta, Market::Bybit, bybit_instrument_info(&client)).await,
+            ),
+            (
+                Market::HyperLiquid,
+                cached_inst_infos(meta, Market::HyperLiquid, hyperliquid_instrument_info(&client))
+                    .await,
+            ),
+        ]
+    } else {
+        let okx = OkxCli::default();
+        let binance_um = BinanceUmCli::default();
+        let binance_cm = BinanceCmCli::default();
+        vec![
+            (
+                Market::Okx,
+                cached_inst_infos(
+                    meta,
+                    Market::Okx,
+                    okx.get_instrument_info(InstrumentType::Perpetual),
+                )
+                .await,
+            ),
+            (
+                Market::BinanceUmFutures,
+                cached_inst_infos(
+                    meta,
+                    Market::BinanceUmFutures,
+                    binance_um.get_instrument_info(InstrumentType::Perpetual),
+                )
+                .await,
+            ),
+            (
+                Market::BinanceCmFutures,
+                cached_inst_infos(
+                    meta,
+                    Market::BinanceCmFutures,
+                    binance_cm.get_instrument_info(InstrumentType::Perpetual),
+                )
+                .await,
+            ),
+            (
+                Market::Bybit,
+                cached_inst_infos(meta, Market::Bybit, bybit_instrument_info(&client)).await,
+            ),
+            (
+                Market::HyperLiquid,
+                cached_inst_infos(meta, Market::HyperLiquid, hyperliquid_instrument_info(&client))
+                    .await,
+            ),
+        ]
+    };
+
+    let mut infos = HashMap::new();
+    let mut failed = Vec::new();
+    for (market, venue_infos) in venues {
+        match venue_infos {
+            Ok(venue_infos) => {
+                for inst in venue_infos {
+                    infos.insert((inst.inst.clone(), market.clone()), inst);
+                }
+            },
+            Err(e) => {
+                warn!("[Account] Skipping {:?} instrument info: {:?}", market, e);
+                failed.push(market);
+            },
+        }
+    }
+
+    if infos.is_empty() && !failed.is_empty() {
+        return Err(InfraError::Msg(format!(
+            "Instrument info failed on every venue: {:?}",
+            failed
+        )));
+    }
+    Ok(infos)
+}
+
+/// Instrument info of one venue, fetched only when the cached copy is older than `METADATA_TTL`.
+async fn cached_inst_infos(
+    meta: &MetaCache,
+    market: Market,
+    fetch: impl Future<Output = InfraResult<Vec<InstrumentInfo>>>,
+) -> InfraResult<Vec<InstrumentInfo>> {
+    let key = format!("{:?}:{}::inst_info:", market, active_profile().testnet);
+    meta.get_or_fetch(key, METADATA_TTL, fetch).await
+}
+
+/// Application-level ping of an account's private streams.
+fn private_ws_ping(acc: &AccountInfo) -> Option<String> {
+    match &acc.client {
+        CexClients::BinanceUm(_) => {
+            Some(json!({ "method": "LIST_SUBSCRIPTIONS", "id": 1 }).to_string())
+        },
+        CexClients::Okx(_) => Some("ping".to_string()),
+        _ if acc.bybit_rest().is_some() => Some(json!({ "op": "ping" }).to_string()),
+        _ if acc.hyperliquid.is_some() => Some(json!({ "method": "ping" }).to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arch::control_module::ctl_risk::RiskConfig;
+
+    fn account(overrides: Value) -> AccountInfo {
+        let mut raw = json!({
+            "account_id": "acc",
+            "exchange": "okx",
+            "api_key": "key",
+            "api_secret": "secret",
+            "account_orders_task_id": 1100,
+            "account_bal_pos_task_id": 1150,
+            "paper": true,
+            "funding_interval_hours": 8,
+        });
+        if let (Some(raw), Some(overrides)) = (raw.as_object_mut(), overrides.as_object()) {
+            raw.extend(overrides.clone());
+        }
+        let cfg: AccountFileConfig = serde_json::from_value(raw).unwrap();
+
+        AccountInfo::from_config(
+            &cfg,
+            Arc::new(Client::new()),
+            &MetaCache::default(),
+            &RateLimiter::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reloaded_account_keeps_shared_and_runtime_state() {
+        let mut manager = AccountManager::new(AccountInitConfig::default());
+        manager.add_account(account(json!({})));
+
+        let mut old = manager.account_infos["acc"].clone();
+        old.acc_weights.insert("BTC-USDT-SWAP".into(), 0.25);
+        old.in_flight.begin("BTC-USDT-SWAP");
+        let changed = account(json!({ "limit_offset_bps": 5.0 }));
+        assert!(changed.config_changed(&old));
+
+        let reloaded = manager.replace_account(old, changed);
+        assert_eq!(reloaded.limit_offset_bps, 5.0);
+        assert_eq!(reloaded.acc_weights["BTC-USDT-SWAP"], 0.25);
+        assert!(reloaded.in_flight.contains("BTC-USDT-SWAP"));
+        assert_eq!(manager.task_index[&1100], "acc");
+
+        manager.control.kill(&manager.target_weights, "test");
+        let limits = RiskConfig {
+            max_gross_weight: Some(0.5),
+            ..RiskConfig::default()
+        };
+        manager.risk.set(limits.clone());
+
+        let acc = &manager.account_infos["acc"];
+        assert!(acc.control.is_killed());
+        assert_eq!(acc.risk.get(), limits);
+    }
+
+    #[test]
+    fn leverage_and_key_edits_count_as_changed() {
+        let current = account(json!({}));
+        assert!(!account(json!({})).config_changed(&current));
+        assert!(account(json!({ "max_leverage": 3.0 })).config_changed(&current));
+        assert!(account(json!({ "leverage": { "*": 5 } })).config_changed(&current));
+        assert!(account(json!({ "api_secret": "rotated" })).config_changed(&current));
+        assert!(account(json!({ "margin_asset": "USDC" })).config_changed(&current));
+    }
+
+    #[test]
+    fn flatten_outside_a_model_reply_counts_as_retarget() {
+        let mut manager = AccountManager::new(AccountInitConfig::default());
+        manager
+            .target_weights
+            .insert("BTC_USDT_PERP".into(), (60_000.0, 0.5));
+        assert!(manager.targets_changed());
+
+        manager.processed_targets = manager.current_targets();
+        assert!(!manager.targets_changed());
+
+        manager.control.flatten(&manager.target_weights);
+        assert!(manager.targets_changed());
+    }
+
+    #[tokio::test]
+    async fn duplicate_intent_sends_nothing() {
+        let path = std::env::temp_dir().join(format!("dedup_test_{}.jsonl", std::process::id()));
+        let mut acc = account(json!({}));
+        acc.paper = false;
+        acc.intent_log = IntentLog::new(path.to_str().unwrap());
+
+        let pending = OrderIntent::new("acc", "BTC_USDT_PERP", &OrderSide::BUY, "1", 0.1, None);
+        assert!(acc.intent_log.open(&pending, pending.dedup_key(0.1)).unwrap());
+
+        let order = OrderParams {
+            inst: "BTC_USDT_PERP".into(),
+            size: "1".into(),
+            side: OrderSide::BUY,
+            client_order_id: Some("cl1".into()),
+            ..OrderParams::default()
+        };
+        let outcome = acc
+            .submit_order(order, 0.1, 60_000.0, &InstrumentInfo::default())
+            .await;
+        let _ = std::fs::remove_file(&path);
+
+        assert!(matches!(outcome, Ok(Submission::Duplicate)));
+        assert!(!acc.in_flight.contains("BTC_USDT_PERP"));
+        assert!(acc.orders.get("cl1").is_none());
+    }
+
+    #[test]
+    fn terminal_order_closes_its_intent() {
+        let path = std::env::temp_dir().join(format!("intents_test_{}.jsonl", std::process::id()));
+        let mut acc = account(json!({}));
+        acc.intent_log = IntentLog::new(path.to_str().unwrap());
+
+        let open = |cl_id: &str, key: u64| {
+            let mut intent = OrderIntent::new(
+                "acc",
+                "BTC_USDT_PERP",
+                &OrderSide::BUY,
+                "1",
+                0.1,
+                Some(cl_id.to_string()),
+            );
+            intent.intent_id = format!("intent-{}", cl_id);
+            acc.intent_log.open(&intent, key).unwrap();
+            TrackedOrder {
+                inst: "BTC_USDT_PERP".into(),
+                order: OrderRef::ClientId(cl_id.to_string()),
+                side: OrderSide::BUY,
+                size: "1".into(),
+                price: Some("60000".into()),
+                submitted_at: Instant::now(),
+                state: OrderState::Open,
+                reprices: 0,
+                diff: 0.1,
+                decision_price: 60_000.0,
+                intent_id: Some(intent.intent_id),
+                filled: 0.0,
+            }
+        };
+        let cancelled = open("cl1", 1);
+        let resting = open("cl2", 2);
+        acc.orders.track(cancelled);
+        acc.orders.track(resting.clone());
+
+        acc.close_order("cl1", OrderState::Cancelled);
+        assert!(acc.orders.get("cl1").is_none());
+
+        // Only the order still resting is left open in the log
+        let dangling = acc.intent_log.recover().unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(dangling.len(), 1);
+        assert_eq!(Some(&dangling[0].intent_id), resting.intent_id.as_ref());
     }
 }
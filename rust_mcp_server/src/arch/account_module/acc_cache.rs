@@ -0,0 +1,60 @@
+use dashmap::DashMap;
+use std::{
+    any::Any,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use extrema_infra::prelude::*;
+
+/// Funding rates move every few seconds but only feed the accrual estimate.
+pub const FUNDING_TTL: Duration = Duration::from_secs(60);
+/// Leverage only changes when it is set on the venue, which drops the cached value.
+pub const LEVERAGE_TTL: Duration = Duration::from_secs(60);
+/// Venue metadata changes a few times a day at most.
+pub const METADATA_TTL: Duration = Duration::from_secs(3600);
+/// The host clock drifts from the venue's by milliseconds per hour.
+pub const SERVER_TIME_TTL: Duration = Duration::from_secs(300);
+
+type CacheEntry = (Instant, Arc<dyn Any + Send + Sync>);
+
+/// In-process TTL cache for venue metadata calls, shared by every account.
+#[derive(Clone, Default)]
+pub struct MetaCache {
+    entries: Arc<DashMap<String, CacheEntry>>,
+}
+
+impl MetaCache {
+    /// Cached value of `key` if younger than `ttl`, otherwise the result of `fetch`.
+    pub async fn get_or_fetch<T, F>(&self, key: String, ttl: Duration, fetch: F) -> InfraResult<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: Future<Output = InfraResult<T>>,
+    {
+        let cached = self
+            .entries
+            .get(&key)
+            .filter(|entry| entry.0.elapsed() < ttl)
+            .and_then(|entry| entry.1.downcast_ref::<T>().cloned());
+        if let Some(value) = cached {
+            return Ok(value);
+        }
+
+        let value = fetch.await?;
+        self.entries
+            .insert(key, (Instant::now(), Arc::new(value.clone())));
+        Ok(value)
+    }
+
+    pub fn invalidate(&self, key: &str) {
+        self.entries.remove(key);
+    }
+}
+
+impl std::fmt::Debug for MetaCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetaCache")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
@@ -12,6 +12,12 @@ impl Strategy for AccountManager {
             error!("Failed to init account manager: {:?}", e);
         }
 
+        self.init_leverage().await;
+
+        if let Err(e) = self.recover_intents() {
+            error!("Recover order intents failed: {:?}", e);
+        }
+
         if let Err(e) = self.init_inst_info().await {
             error!("Init instrument info failed: {:?}", e);
         }
@@ -38,14 +44,47 @@ impl EventHandler for AccountManager {
     async fn on_schedule(&mut self, msg: InfraMsg<AltScheduleEvent>) {
         match msg.task_id {
             id if id == self.config.reload_task_id => {
+                let stall_after = self.config.reload_interval_sec * 2;
+                self.heartbeats.beat(&format!("scheduler:{}", id), stall_after);
+
                 if let Err(e) = self.reload_accounts().await {
                     error!("Reload accounts failed: {:?}", e);
                 }
+                if let Err(e) = self.init_inst_info().await {
+                    error!("Refresh instrument info failed: {:?}", e);
+                }
+
+                if let Err(e) = self.export_journals().await {
+                    error!("Export journals failed: {:?}", e);
+                }
+
+                if let Err(e) = self.tracking.flush() {
+                    error!("Tracking report failed: {:?}", e);
+                }
+
+                self.collect_idle_insts();
             },
             id if id == self.config.update_task_id => {
-                if let Err(e) = self.update_accounts().await {
+                let stall_after = self.watchdog.scheduler_stall_sec;
+                self.heartbeats.beat(&format!("scheduler:{}", id), stall_after);
+
+                if let Err(e) = self.update_due_accounts().await {
                     error!("Update accounts failed: {:?}", e);
                 }
+                self.enforce_loss_limit();
+                self.cancel_stale_orders().await;
+                self.ping_private_ws().await;
+                self.restart_stalled_ws().await;
+                self.advance_migration();
+
+                self.handle_account_requests().await;
+                // Due accounts already processed their weights above; an alert that changed
+                // the risk state or targets moved outside a model reply, e.g. a kill or a
+                // control socket flatten, reprocess all of them at once.
+                let alerted = self.handle_risk_alerts().await;
+                if !alerted && !self.targets_changed() {
+                    return;
+                }
 
                 if let Err(e) = self.process_weights().await {
                     warn!(
@@ -59,7 +98,23 @@ impl EventHandler for AccountManager {
     }
 
     async fn on_preds(&mut self, msg: InfraMsg<AltTensor>) {
-        if let Err(e) = self.process_weights().await {
+        let ordered = self
+            .handle_order_command(&msg.data)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to handle order command: {:?}, task: {:?}", e, msg.task_id);
+                false
+            });
+        let alerted = self.handle_risk_alerts().await;
+
+        // McpServer applies the same reply concurrently, so its targets are only compared once
+        // published. Heartbeats, handshakes and noops leave them as they are.
+        self.settle_targets().await;
+        if !ordered && !alerted && !self.targets_changed() {
+            return;
+        }
+
+        if let Err(e) = self.process_settled_weights().await {
             warn!(
                 "Failed to process weights: {:?}, task: {:?}",
                 e, msg.task_id
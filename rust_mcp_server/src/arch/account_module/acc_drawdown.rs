@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, env::current_dir, fs, path::PathBuf};
+use tracing::{error, warn};
+
+use extrema_infra::prelude::*;
+
+/// Prefix of the per-account latch files, `drawdown_state_<account_id>.json`.
+pub const DRAWDOWN_STATE_PREFIX: &str = "drawdown_state";
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PersistedDrawdown {
+    tripped_at: Option<f64>,
+}
+
+/// Per-account circuit breaker on the drawdown from a rolling equity high-water mark.
+#[derive(Clone, Debug, Default)]
+pub struct DrawdownBreaker {
+    /// Equity samples with strictly decreasing equity; the front is the window peak.
+    peaks: VecDeque<(u64, f64)>,
+    /// `max_drawdown` the breaker tripped at.
+    tripped_at: Option<f64>,
+    path: Option<PathBuf>,
+}
+
+impl DrawdownBreaker {
+    /// Reads the latch of the last run from `file_name` and persists every later change there.
+    pub fn restore(&mut self, file_name: &str) -> InfraResult<()> {
+        let mut path = current_dir()?;
+        path.push(file_name);
+
+        if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| InfraError::Msg(format!("Failed to read {:?}: {}", path, e)))?;
+            let saved: PersistedDrawdown = serde_json::from_str(&content)
+                .map_err(|e| InfraError::Msg(format!("Failed to parse {:?}: {}", path, e)))?;
+            if let Some(limit) = saved.tripped_at {
+                warn!("[Risk] Restored drawdown breaker tripped at {} from {:?}", limit, path);
+            }
+            self.tripped_at = saved.tripped_at;
+        }
+
+        self.path = Some(path);
+        Ok(())
+    }
+
+    /// Writes the latch through a temporary file.
+    fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let saved = PersistedDrawdown {
+            tripped_at: self.tripped_at,
+        };
+        let tmp = path.with_extension("json.tmp");
+        let written = serde_json::to_string(&saved)
+            .map_err(std::io::Error::other)
+            .and_then(|content| fs::write(&tmp, content))
+            .and_then(|_| fs::rename(&tmp, path));
+        if let Err(e) = written {
+            error!("[Risk] Failed to persist drawdown breaker to {:?}: {}", path, e);
+        }
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped_at.is_some()
+    }
+
+    /// Highest equity recorded within the window.
+    pub fn high_water_mark(&self) -> Option<f64> {
+        self.peaks.front().map(|(_, equity)| *equity)
+    }
+
+    /// Records `equity` and returns the drawdown from the window's high-water mark.
+    pub fn record(&mut self, now_ms: u64, equity: f64, window_ms: u64) -> f64 {
+        while self
+            .peaks
+            .front()
+            .is_some_and(|(ts, _)| now_ms.saturating_sub(*ts) > window_ms)
+        {
+            self.peaks.pop_front();
+        }
+        while self.peaks.back().is_some_and(|(_, peak)| *peak <= equity) {
+            self.peaks.pop_back();
+        }
+        self.peaks.push_back((now_ms, equity));
+
+        match self.high_water_mark() {
+            Some(peak) if peak > f64::EPSILON => (1.0 - equity / peak).max(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Trips when `drawdown` reached `limit`. Returns true on the transition.
+    pub fn trip(&mut self, drawdown: f64, limit: f64) -> bool {
+        if self.is_tripped() || drawdown < limit {
+            return false;
+        }
+        self.tripped_at = Some(limit);
+        self.persist();
+        true
+    }
+
+    /// Releases a tripped breaker once its limit was changed and `drawdown` is within the new one.
+    pub fn release_if_relaxed(&mut self, drawdown: f64, limit: Option<f64>) -> bool {
+        let Some(tripped_at) = self.tripped_at else {
+            return false;
+        };
+        if limit == Some(tripped_at) || limit.is_some_and(|l| drawdown >= l) {
+            return false;
+        }
+        self.reset();
+        true
+    }
+
+    /// Releases the breaker and restarts the high-water mark from the next sample.
+    pub fn reset(&mut self) {
+        self.peaks.clear();
+        if self.tripped_at.take().is_some() {
+            self.persist();
+        }
+    }
+}
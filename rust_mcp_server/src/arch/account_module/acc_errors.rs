@@ -0,0 +1,79 @@
+use serde::Deserialize;
+use std::{collections::HashMap, time::Duration};
+
+/// Strict mode for account failures, which are otherwise logged and skipped per account.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ErrorBudgetConfig {
+    pub max_failed_accounts: usize,
+    pub window_sec: u64,
+    pub pause_trading: bool,
+}
+
+impl Default for ErrorBudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_failed_accounts: 2,
+            window_sec: 300,
+            pause_trading: false,
+        }
+    }
+}
+
+impl ErrorBudgetConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window_sec == 0 {
+            return Err("window_sec must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Update failures per account within the budget window.
+#[derive(Clone, Debug, Default)]
+pub struct ErrorBudget {
+    /// Last failed update per account, in milliseconds.
+    failures: HashMap<String, u64>,
+    exhausted: bool,
+}
+
+/// Change of the budget state after a failure was recorded or the window moved.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BudgetChange {
+    /// Accounts failing within the window, sorted, now over the budget.
+    Exhausted(Vec<String>),
+    Recovered,
+}
+
+impl ErrorBudget {
+    pub fn record_failure(&mut self, account_id: &str, now_ms: u64) {
+        self.failures.insert(account_id.to_string(), now_ms);
+    }
+
+    /// Reports when the error budget is exhausted or recovered, once per transition.
+    pub fn evaluate(&mut self, config: &ErrorBudgetConfig, now_ms: u64) -> Option<BudgetChange> {
+        let window_ms = Duration::from_secs(config.window_sec).as_millis() as u64;
+        self.failures
+            .retain(|_, at| now_ms.saturating_sub(*at) <= window_ms);
+
+        let over = self.failures.len() > config.max_failed_accounts;
+        match (over, self.exhausted) {
+            (true, false) => {
+                self.exhausted = true;
+                let mut accounts: Vec<String> = self.failures.keys().cloned().collect();
+                accounts.sort();
+                Some(BudgetChange::Exhausted(accounts))
+            },
+            (false, true) => {
+                self.exhausted = false;
+                Some(BudgetChange::Recovered)
+            },
+            _ => None,
+        }
+    }
+
+    /// Forgets an account that was removed by a reload.
+    pub fn forget(&mut self, account_id: &str) {
+        self.failures.remove(account_id);
+    }
+}
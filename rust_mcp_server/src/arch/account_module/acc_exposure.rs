@@ -0,0 +1,69 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::acc_utils::AccountSnapshot;
+
+/// Underlying asset of an instrument, e.g. `DOGE` for `DOGE_USDT_PERP` and `DOGE_USD_PERP`.
+pub fn underlying(inst: &str) -> &str {
+    inst.split(['_', '-']).next().unwrap_or(inst)
+}
+
+/// Position notional in one underlying, summed over its instruments on every account.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct AssetExposure {
+    /// Long minus short notional, in quote currency.
+    pub net_notional: f64,
+    pub gross_notional: f64,
+    /// Net notional as a fraction of the combined equity of all accounts.
+    pub net_weight: f64,
+    /// Net notional per account.
+    pub accounts: HashMap<String, f64>,
+    /// Net notional per instrument.
+    pub insts: HashMap<String, f64>,
+}
+
+/// Exposure by underlying asset across accounts.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ExposureReport {
+    pub total_equity: f64,
+    pub assets: HashMap<String, AssetExposure>,
+}
+
+impl ExposureReport {
+    pub fn from_accounts<'a>(
+        accounts: impl IntoIterator<Item = (&'a String, &'a AccountSnapshot)>,
+    ) -> Self {
+        let mut report = Self::default();
+        for (account_id, snapshot) in accounts {
+            report.total_equity += snapshot.total_equity;
+            for (inst, weight) in snapshot.acc_weights.iter() {
+                let notional = weight * snapshot.total_equity;
+                let asset = report
+                    .assets
+                    .entry(underlying(inst).to_string())
+                    .or_default();
+                asset.net_notional += notional;
+                asset.gross_notional += notional.abs();
+                *asset.accounts.entry(account_id.clone()).or_insert(0.0) += notional;
+                *asset.insts.entry(inst.clone()).or_insert(0.0) += notional;
+            }
+        }
+
+        let total_equity = report.total_equity;
+        for asset in report.assets.values_mut() {
+            if total_equity > f64::EPSILON {
+                asset.net_weight = asset.net_notional / total_equity;
+            }
+        }
+        report
+    }
+
+    /// Assets whose net weight is beyond `cap`, with the sign of the side that is blocked.
+    pub fn breaches(&self, cap: f64) -> HashMap<String, f64> {
+        self.assets
+            .iter()
+            .filter(|(_, asset)| asset.net_weight.abs() > cap)
+            .map(|(name, asset)| (name.clone(), asset.net_weight.signum()))
+            .collect()
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Spacing of the funding bill reads.
+pub const FUNDING_BILL_REFRESH: Duration = Duration::from_secs(60);
+/// Bills may be stamped slightly before the scheduled settlement time.
+const SETTLE_SLACK_MS: u64 = 60_000;
+/// Relative gap between a settlement's estimate and its bill logged as drift.
+pub const FUNDING_DRIFT_TOLERANCE: f64 = 0.1;
+
+/// One settlement reconciled against the venue's funding bills.
+#[derive(Clone, Debug)]
+pub struct FundingSettlement {
+    pub inst: String,
+    pub billed: f64,
+    pub estimated: f64,
+}
+
+impl FundingSettlement {
+    pub fn drifted(&self) -> bool {
+        let scale = self.billed.abs().max(self.estimated.abs());
+        scale > f64::EPSILON
+            && (self.billed - self.estimated).abs() > FUNDING_DRIFT_TOLERANCE * scale
+    }
+}
+
+/// Funding payments read back from the venue's bills.
+#[derive(Clone, Debug, Default)]
+pub struct FundingLedger {
+    /// Estimated payment per instrument of the settlement it is waiting for.
+    expected: HashMap<String, (u64, f64)>,
+    /// Time of the latest bill read per instrument.
+    billed_ms: HashMap<String, u64>,
+    /// End of the last window read from the venue.
+    checked_ms: Option<u64>,
+    refreshed_at: Option<Instant>,
+}
+
+impl FundingLedger {
+    /// Records the estimated payment of the settlement at `settle_ms`.
+    pub fn expect(&mut self, inst: &str, settle_ms: u64, amount: f64) {
+        self.expected.insert(inst.to_string(), (settle_ms, amount));
+    }
+
+    /// True once the bill of the settlement at `settle_ms` was read.
+    pub fn is_billed(&self, inst: &str, settle_ms: u64) -> bool {
+        self.billed_ms
+            .get(inst)
+            .is_some_and(|at| *at + SETTLE_SLACK_MS >= settle_ms)
+    }
+
+    /// Window still to be read once `FUNDING_BILL_REFRESH` has passed.
+    pub fn due(&mut self, now_ms: u64) -> Option<(u64, u64)> {
+        let Some(checked_ms) = self.checked_ms else {
+            self.checked_ms = Some(now_ms);
+            self.refreshed_at = Some(Instant::now());
+            return None;
+        };
+        if self
+            .refreshed_at
+            .is_some_and(|at| at.elapsed() < FUNDING_BILL_REFRESH)
+        {
+            return None;
+        }
+        Some((checked_ms + 1, now_ms))
+    }
+
+    /// Books the bills read up to `until_ms` and returns the settlements they reconcile.
+    pub fn settle(
+        &mut self,
+        bills: &[(String, u64, f64)],
+        until_ms: u64,
+    ) -> Vec<FundingSettlement> {
+        let mut paid: HashMap<&str, (u64, f64)> = HashMap::new();
+        for (inst, ts, amount) in bills.iter() {
+            let entry = paid.entry(inst.as_str()).or_default();
+            entry.0 = entry.0.max(*ts);
+            entry.1 -= amount;
+        }
+
+        let mut settled = Vec::new();
+        for (inst, (ts, billed)) in paid {
+            self.billed_ms.insert(inst.to_string(), ts);
+            let estimated = match self.expected.get(inst) {
+                Some((settle_ms, amount)) if ts + SETTLE_SLACK_MS >= *settle_ms => *amount,
+                _ => 0.0,
+            };
+            settled.push(FundingSettlement {
+                inst: inst.to_string(),
+                billed,
+                estimated,
+            });
+        }
+
+        self.checked_ms = Some(until_ms);
+        self.refreshed_at = Some(Instant::now());
+        settled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bill_settles_the_expected_payment() {
+        let mut ledger = FundingLedger::default();
+        ledger.expect("BTC_USDT_PERP", 8_000_000, 1.0);
+        assert!(!ledger.is_billed("BTC_USDT_PERP", 8_000_000));
+
+        let bills = vec![("BTC_USDT_PERP".to_string(), 8_000_010, -1.5)];
+        let settled = ledger.settle(&bills, 8_100_000);
+
+        assert!(ledger.is_billed("BTC_USDT_PERP", 8_000_000));
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].billed, 1.5);
+        assert_eq!(settled[0].estimated, 1.0);
+        assert!(settled[0].drifted());
+    }
+}
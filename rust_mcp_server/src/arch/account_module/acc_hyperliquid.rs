@@ -0,0 +1,558 @@
+use k256::ecdsa::SigningKey;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{Value, json};
+use sha3::{Digest, Keccak256};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+use extrema_infra::{
+    arch::market_assets::{api_data::utils_data::InstrumentInfo, api_general::OrderParams},
+    prelude::*,
+};
+
+use super::{
+    acc_cache::{METADATA_TTL, MetaCache},
+    acc_ratelimit::{RateLimiter, RateVenue},
+    acc_rest::{now_millis, transport_error},
+    acc_retry::FailureKind,
+    acc_utils::AccountFileConfig,
+};
+use crate::arch::profile::active_profile;
+
+const HL_API_URL: &str = "https://api.hyperliquid.xyz";
+const HL_TESTNET_URL: &str = "https://api.hyperliquid-testnet.xyz";
+const HL_WS_URL: &str = "wss://api.hyperliquid.xyz/ws";
+const HL_WS_TESTNET_URL: &str = "wss://api.hyperliquid-testnet.xyz/ws";
+/// Smallest order value the venue accepts, in USDC.
+pub const HL_MIN_ORDER_VALUE: f64 = 10.0;
+/// Market orders are sent as IOC limits this far through the mark price.
+pub const HL_MARKET_SLIPPAGE: f64 = 0.01;
+/// Perp prices carry at most this many decimals minus the asset's size decimals.
+const HL_MAX_PRICE_DECIMALS: i32 = 6;
+const HL_PRICE_SIG_FIGS: i32 = 5;
+/// Info request weights: account state is a light query, universe metadata a standard one.
+const HL_STATE_WEIGHT: f64 = 2.0;
+const HL_INFO_WEIGHT: f64 = 20.0;
+
+/// `DOGE_USDT_PERP` -> `DOGE`.
+pub fn hl_coin(inst: &str) -> String {
+    inst.split('_').next().unwrap_or(inst).to_string()
+}
+
+/// `DOGE` -> `DOGE_USDT_PERP`
+pub fn hl_inst(coin: &str) -> String {
+    format!("{}_USDT_PERP", coin)
+}
+
+#[derive(Serialize)]
+struct LimitWire {
+    tif: String,
+}
+
+#[derive(Serialize)]
+struct OrderTypeWire {
+    limit: LimitWire,
+}
+
+/// Field names and order are part of the signed msgpack payload.
+#[derive(Serialize)]
+struct OrderWire {
+    a: u32,
+    b: bool,
+    p: String,
+    s: String,
+    r: bool,
+    t: OrderTypeWire,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    c: Option<String>,
+}
+
+#[derive(Serialize)]
+struct OrderAction {
+    #[serde(rename = "type")]
+    kind: String,
+    orders: Vec<OrderWire>,
+    grouping: String,
+}
+
+/// Field names and order are part of the signed msgpack payload.
+#[derive(Serialize)]
+struct UpdateLeverageAction {
+    #[serde(rename = "type")]
+    kind: String,
+    asset: u32,
+    #[serde(rename = "isCross")]
+    is_cross: bool,
+    leverage: u32,
+}
+
+/// Hyperliquid perps account.
+#[derive(Clone)]
+pub struct HyperliquidClient {
+    client: Arc<Client>,
+    /// Account address positions and balances are read from, `api_key` in the config.
+    address: String,
+    signing_key: SigningKey,
+    testnet: bool,
+    /// Coin -> asset index, size decimals and max leverage in the perp universe.
+    meta: MetaCache,
+    limiter: RateLimiter,
+}
+
+impl fmt::Debug for HyperliquidClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HyperliquidClient")
+            .field("address", &self.address)
+            .field("testnet", &self.testnet)
+            .field("private_key", &"***")
+            .finish()
+    }
+}
+
+impl HyperliquidClient {
+    pub fn from_config(
+        cfg: &AccountFileConfig,
+        client: Arc<Client>,
+        meta: MetaCache,
+        limiter: RateLimiter,
+    ) -> InfraResult<Self> {
+        let key = cfg.private_key.as_deref().ok_or_else(|| {
+            InfraError::Msg(format!("Hyperliquid account {} needs private_key", cfg.account_id))
+        })?;
+        let key_bytes = hex::decode(key.trim_start_matches("0x"))
+            .map_err(|e| InfraError::Msg(format!("Invalid Hyperliquid private key: {}", e)))?;
+        let signing_key = SigningKey::from_slice(&key_bytes)
+            .map_err(|e| InfraError::Msg(format!("Invalid Hyperliquid private key: {}", e)))?;
+
+        Ok(Self {
+            client,
+            address: cfg.api_key.to_lowercase(),
+            signing_key,
+            testnet: active_profile().testnet,
+            meta,
+            limiter,
+        })
+    }
+
+    /// Account value in USDC, unrealized PnL included.
+    pub async fn equity(&self) -> InfraResult<f64> {
+        let state = self.clearinghouse_state().await?;
+        Ok(value_f64(&state["marginSummary"]["accountValue"]))
+    }
+
+    /// Open positions as `(inst, signed size, mark price)`.
+    pub async fn positions(&self) -> InfraResult<Vec<(String, f64, f64)>> {
+        let state = self.clearinghouse_state().await?;
+        let positions = state["assetPositions"]
+            .as_array()
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| {
+                        let pos = &row["position"];
+                        let size = value_f64(&pos["szi"]);
+                        if size == 0.0 {
+                            return None;
+                        }
+                        let mark = value_f64(&pos["positionValue"]) / size.abs();
+                        Some((hl_inst(pos["coin"].as_str()?), size, mark))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(positions)
+    }
+
+    /// Sends the order as an L1 action and returns the venue order id.
+    pub async fn place_order(&self, order: &OrderParams, mark_price: f64) -> InfraResult<String> {
+        active_profile().ensure_orders_allowed("Hyperliquid order")?;
+
+        let (asset, sz_decimals, _) = self.asset(&hl_coin(&order.inst)).await?;
+        let action = OrderAction {
+            kind: "order".to_string(),
+            orders: vec![order_wire(order, asset, sz_decimals, mark_price)],
+            grouping: "na".to_string(),
+        };
+
+        let resp = self.exchange(&action).await?;
+        let status = &resp["response"]["data"]["statuses"][0];
+        if let Some(err) = status["error"].as_str() {
+            return Err(InfraError::Msg(format!("Hyperliquid order rejected: {}", err)));
+        }
+
+        let oid = status["filled"]["oid"]
+            .as_u64()
+            .or_else(|| status["resting"]["oid"].as_u64())
+            .ok_or_else(|| InfraError::Msg(format!("Hyperliquid order status: {}", status)))?;
+        Ok(oid.to_string())
+    }
+
+    /// Sets the cross-margin leverage of `inst`.
+    pub async fn set_leverage(&self, inst: &str, leverage: u32) -> InfraResult<()> {
+        active_profile().ensure_orders_allowed("Hyperliquid leverage")?;
+
+        let (asset, _, _) = self.asset(&hl_coin(inst)).await?;
+        let action = UpdateLeverageAction {
+            kind: "updateLeverage".to_string(),
+            asset,
+            is_cross: true,
+            leverage,
+        };
+        self.exchange(&action).await?;
+        Ok(())
+    }
+
+    /// Highest leverage the venue allows on `inst`.
+    pub async fn max_leverage(&self, inst: &str) -> InfraResult<f64> {
+        let (_, _, max_leverage) = self.asset(&hl_coin(inst)).await?;
+        Ok(max_leverage)
+    }
+
+    pub fn ws_url(&self) -> String {
+        let url = if self.testnet { HL_WS_TESTNET_URL } else { HL_WS_URL };
+        url.to_string()
+    }
+
+    /// User streams are public by address, so there is no login step.
+    pub fn ws_sub_msg(&self, channel: &WsChannel) -> InfraResult<String> {
+        let kind = match channel {
+            WsChannel::AccountOrders => "orderUpdates",
+            WsChannel::AccountBalAndPos => "webData2",
+            _ => {
+                return Err(InfraError::Msg(format!(
+                    "Unsupported Hyperliquid private channel: {:?}",
+                    channel
+                )));
+            },
+        };
+        Ok(json!({
+            "method": "subscribe",
+            "subscription": { "type": kind, "user": self.address },
+        })
+        .to_string())
+    }
+
+    async fn clearinghouse_state(&self) -> InfraResult<Value> {
+        self.limiter
+            .acquire(RateVenue::Hyperliquid, HL_STATE_WEIGHT)
+            .await;
+        hl_info(
+            &self.client,
+            self.testnet,
+            json!({ "type": "clearinghouseState", "user": self.address }),
+        )
+        .await
+    }
+
+    async fn asset(&self, coin: &str) -> InfraResult<(u32, u32, f64)> {
+        let fetch = async {
+            self.limiter
+                .acquire(RateVenue::Hyperliquid, HL_INFO_WEIGHT)
+                .await;
+            let meta = hl_info(&self.client, self.testnet, json!({ "type": "meta" })).await?;
+            let universe: HashMap<String, (u32, u32, f64)> = meta["universe"]
+                .as_array()
+                .map(|rows| {
+                    rows.iter()
+                        .enumerate()
+                        .filter_map(|(idx, row)| {
+                            let sz_decimals = row["szDecimals"].as_u64()? as u32;
+                            let max_leverage = value_f64(&row["maxLeverage"]);
+                            Some((
+                                row["name"].as_str()?.to_string(),
+                                (idx as u32, sz_decimals, max_leverage),
+                            ))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Ok(universe)
+        };
+        let key = format!("Hyperliquid:{}:universe", self.testnet);
+        let universe = self.meta.get_or_fetch(key, METADATA_TTL, fetch).await?;
+
+        universe
+            .get(coin)
+            .copied()
+            .ok_or_else(|| InfraError::Msg(format!("Unknown Hyperliquid coin: {}", coin)))
+    }
+
+    async fn exchange<A: Serialize>(&self, action: &A) -> InfraResult<Value> {
+        self.limiter.acquire(RateVenue::Hyperliquid, 1.0).await;
+        let nonce = now_millis();
+        let signature = self.sign_l1_action(action, nonce)?;
+        let action_json = serde_json::to_value(action)
+            .map_err(|e| InfraError::Msg(format!("Hyperliquid action encode failed: {}", e)))?;
+
+        let resp = self
+            .client
+            .post(format!("{}/exchange", hl_base_url(self.testnet)))
+            .json(&json!({
+                "action": action_json,
+                "nonce": nonce,
+                "signature": signature,
+                "vaultAddress": Value::Null,
+            }))
+            .send()
+            .await
+            .map_err(|e| transport_error("Hyperliquid", "/exchange", e))?;
+
+        let status = resp.status();
+        let value: Value = resp.json().await.map_err(|e| {
+            FailureKind::unreadable(status.as_u16())
+                .error(format!("Hyperliquid response parse failed: {}", e))
+        })?;
+        if value["status"].as_str() != Some("ok") {
+            return Err(FailureKind::classify(Some(status.as_u16()), None)
+                .error(format!("Hyperliquid exchange error: {}", value)));
+        }
+
+        Ok(value)
+    }
+
+    fn sign_l1_action<A: Serialize>(&self, action: &A, nonce: u64) -> InfraResult<Value> {
+        let digest = l1_digest(action, nonce, self.testnet)?;
+        let (sig, recovery_id) = self
+            .signing_key
+            .sign_prehash_recoverable(&digest)
+            .map_err(|e| InfraError::Msg(format!("Hyperliquid signing failed: {}", e)))?;
+        let bytes = sig.to_bytes();
+
+        Ok(json!({
+            "r": format!("0x{}", hex::encode(&bytes[..32])),
+            "s": format!("0x{}", hex::encode(&bytes[32..])),
+            "v": 27 + recovery_id.to_byte(),
+        }))
+    }
+}
+
+/// EIP-712 digest of the phantom agent signing `action`.
+fn l1_digest<A: Serialize>(action: &A, nonce: u64, testnet: bool) -> InfraResult<[u8; 32]> {
+    let mut payload = rmp_serde::to_vec_named(action)
+        .map_err(|e| InfraError::Msg(format!("Hyperliquid msgpack failed: {}", e)))?;
+    payload.extend_from_slice(&nonce.to_be_bytes());
+    payload.push(0);
+    let connection_id = keccak(&payload);
+
+    let domain = keccak(
+        &[
+            keccak(
+                b"EIP712Domain(string name,string version,uint256 chainId,\
+                  address verifyingContract)",
+            ),
+            keccak(b"Exchange"),
+            keccak(b"1"),
+            u256_word(1337),
+            [0u8; 32],
+        ]
+        .concat(),
+    );
+    let source: &[u8] = if testnet { b"b" } else { b"a" };
+    let agent = keccak(
+        &[
+            keccak(b"Agent(string source,bytes32 connectionId)"),
+            keccak(source),
+            connection_id,
+        ]
+        .concat(),
+    );
+    Ok(keccak(&[&[0x19, 0x01][..], &domain, &agent].concat()))
+}
+
+/// Wire form of `order`.
+fn order_wire(order: &OrderParams, asset: u32, sz_decimals: u32, mark_price: f64) -> OrderWire {
+    let is_buy = matches!(order.side, OrderSide::BUY);
+    let (price, tif) = match (&order.order_type, &order.price) {
+        (OrderType::Market, _) | (_, None) => {
+            let slip = if is_buy { 1.0 + HL_MARKET_SLIPPAGE } else { 1.0 - HL_MARKET_SLIPPAGE };
+            (mark_price * slip, "Ioc")
+        },
+        (OrderType::PostOnly, Some(px)) => (value_f64(&json!(px)), "Alo"),
+        (_, Some(px)) if matches!(order.time_in_force, Some(TimeInForce::IOC)) => {
+            (value_f64(&json!(px)), "Ioc")
+        },
+        (_, Some(px)) => (value_f64(&json!(px)), "Gtc"),
+    };
+
+    OrderWire {
+        a: asset,
+        b: is_buy,
+        p: hl_price(price, sz_decimals),
+        s: trim_wire(&order.size),
+        r: order.reduce_only.unwrap_or(false),
+        t: OrderTypeWire {
+            limit: LimitWire { tif: tif.to_string() },
+        },
+        c: order.client_order_id.as_ref().map(|id| hl_cloid(id)),
+    }
+}
+
+/// Largest position notional on an asset at the account's leverage.
+pub fn hl_position_cap(equity: f64, max_leverage: f64, leverage: Option<f64>) -> f64 {
+    equity * leverage.map_or(max_leverage, |l| l.min(max_leverage))
+}
+
+/// Listed perps as `InstrumentInfo`, sized in coins with the asset's size decimals.
+pub async fn hyperliquid_instrument_info(client: &Client) -> InfraResult<Vec<InstrumentInfo>> {
+    let meta = hl_info(client, active_profile().testnet, json!({ "type": "meta" })).await?;
+
+    let infos = meta["universe"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter(|row| !row["isDelisted"].as_bool().unwrap_or(false))
+                .filter_map(|row| {
+                    let lot = 10f64.powi(-(row["szDecimals"].as_u64()? as i32));
+                    Some(InstrumentInfo {
+                        inst: hl_inst(row["name"].as_str()?),
+                        contract_value: Some(1.0),
+                        min_lmt_size: lot,
+                        min_mkt_size: lot,
+                        max_lmt_size: f64::MAX,
+                        max_mkt_size: f64::MAX,
+                        lot_size: lot,
+                        tick_size: 0.0,
+                        min_notional: Some(HL_MIN_ORDER_VALUE),
+                        ..Default::default()
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(infos)
+}
+
+/// Hyperliquid cloids are 16 bytes in `0x` hex; rebalance ids come without the prefix.
+fn hl_cloid(id: &str) -> String {
+    if id.starts_with("0x") {
+        id.to_string()
+    } else {
+        format!("0x{}", id)
+    }
+}
+
+async fn hl_info(client: &Client, testnet: bool, body: Value) -> InfraResult<Value> {
+    client
+        .post(format!("{}/info", hl_base_url(testnet)))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| InfraError::Msg(format!("Hyperliquid info request failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| InfraError::Msg(format!("Hyperliquid info parse failed: {}", e)))
+}
+
+fn hl_base_url(testnet: bool) -> &'static str {
+    if testnet { HL_TESTNET_URL } else { HL_API_URL }
+}
+
+/// Five significant figures and at most `6 - sz_decimals` decimals; integers always pass.
+fn hl_price(price: f64, sz_decimals: u32) -> String {
+    if price <= 0.0 || !price.is_finite() {
+        return "0".to_string();
+    }
+
+    let sig_decimals = HL_PRICE_SIG_FIGS - 1 - price.log10().floor() as i32;
+    let decimals = sig_decimals
+        .min(HL_MAX_PRICE_DECIMALS - sz_decimals as i32)
+        .max(0);
+    trim_wire(&format!("{:.*}", decimals as usize, price))
+}
+
+/// The venue hashes numbers as strings without trailing zeros.
+fn trim_wire(value: &str) -> String {
+    if !value.contains('.') {
+        return value.to_string();
+    }
+    value.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+fn u256_word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn value_f64(v: &Value) -> f64 {
+    v.as_str()
+        .and_then(|s| s.parse::<f64>().ok())
+        .or_else(|| v.as_f64())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+    #[test]
+    fn market_order_payload() {
+        let order = OrderParams {
+            inst: "DOGE_USDT_PERP".to_string(),
+            size: "1250.00".to_string(),
+            side: OrderSide::SELL,
+            order_type: OrderType::Market,
+            reduce_only: Some(true),
+            client_order_id: Some("00000000000000000000000000000001".to_string()),
+            ..OrderParams::default()
+        };
+        let wire = serde_json::to_value(order_wire(&order, 7, 0, 0.19321)).unwrap();
+
+        assert_eq!(
+            wire,
+            json!({
+                "a": 7,
+                "b": false,
+                "p": "0.19128",
+                "s": "1250",
+                "r": true,
+                "t": { "limit": { "tif": "Ioc" } },
+                "c": "0x00000000000000000000000000000001",
+            })
+        );
+    }
+
+    #[test]
+    fn signature_recovers_the_signing_key() {
+        let client = HyperliquidClient {
+            client: Arc::new(Client::new()),
+            address: String::new(),
+            signing_key: SigningKey::from_slice(&[7u8; 32]).unwrap(),
+            testnet: true,
+            meta: MetaCache::default(),
+            limiter: RateLimiter::default(),
+        };
+        let action = OrderAction {
+            kind: "order".to_string(),
+            orders: Vec::new(),
+            grouping: "na".to_string(),
+        };
+        let sig = client.sign_l1_action(&action, 1_700_000_000_000).unwrap();
+
+        let word = |key: &str| hex::decode(sig[key].as_str().unwrap().trim_start_matches("0x"));
+        let bytes = [word("r").unwrap(), word("s").unwrap()].concat();
+        let signature = Signature::from_slice(&bytes).unwrap();
+        let recovery_id = RecoveryId::from_byte(sig["v"].as_u64().unwrap() as u8 - 27).unwrap();
+        let digest = l1_digest(&action, 1_700_000_000_000, true).unwrap();
+        let recovered = VerifyingKey::recover_from_prehash(&digest, &signature, recovery_id);
+
+        assert_eq!(recovered.unwrap(), *client.signing_key.verifying_key());
+    }
+
+    #[test]
+    fn position_cap_follows_the_leverage() {
+        assert_eq!(hl_position_cap(1000.0, 20.0, None), 20_000.0);
+        assert_eq!(hl_position_cap(1000.0, 20.0, Some(5.0)), 5_000.0);
+        assert_eq!(hl_position_cap(1000.0, 3.0, Some(5.0)), 3_000.0);
+    }
+}
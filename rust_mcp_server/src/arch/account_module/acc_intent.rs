@@ -0,0 +1,253 @@
+use dashmap::{DashMap, mapref::entry::Entry};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    fs::{self, OpenOptions},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+use extrema_infra::{arch::market_assets::api_general::get_micros_timestamp, prelude::*};
+
+/// Longest an open intent blocks identical ones, in case its order never completes.
+const INTENT_DEDUP_TTL: Duration = Duration::from_secs(60);
+/// Records appended before the log is rewritten with only the unresolved intents.
+const COMPACT_AFTER_RECORDS: usize = 1000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderIntent {
+    pub intent_id: String,
+    pub account_id: String,
+    pub inst: String,
+    pub side: String,
+    pub size: String,
+    pub diff: f64,
+    pub timestamp: u64,
+    /// Client order id sent with the order, to correlate the intent with its fills.
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+}
+
+impl OrderIntent {
+    pub fn new(
+        account_id: &str,
+        inst: &str,
+        side: &OrderSide,
+        size: &str,
+        diff: f64,
+        client_order_id: Option<String>,
+    ) -> Self {
+        let timestamp = get_micros_timestamp();
+        Self {
+            intent_id: format!("{}-{}-{}", account_id, inst, timestamp),
+            account_id: account_id.to_string(),
+            inst: inst.to_string(),
+            side: format!("{:?}", side),
+            size: size.to_string(),
+            diff,
+            timestamp,
+            client_order_id,
+        }
+    }
+
+    /// Hash of what the intent does, shared by passes off the same target.
+    pub fn dedup_key(&self, target_weight: f64) -> u64 {
+        let size = self.size.parse::<f64>().unwrap_or_default();
+        let size_bucket = if size > 0.0 {
+            (size.ln() / 0.01f64.ln_1p()).round() as i64
+        } else {
+            i64::MIN
+        };
+        let target_bucket = (target_weight * 10_000.0).round() as i64;
+
+        let mut hasher = DefaultHasher::new();
+        (&self.account_id, &self.inst, &self.side, size_bucket, target_bucket).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IntentRecord {
+    Open(OrderIntent),
+    Done {
+        intent_id: String,
+        status: String,
+        timestamp: u64,
+    },
+}
+
+/// Intents not closed yet and the records in the file, guarded together with the file.
+#[derive(Debug, Default)]
+struct IntentFile {
+    unresolved: HashMap<String, OrderIntent>,
+    records: usize,
+}
+
+/// Write-ahead log of order intents.
+#[derive(Clone, Debug)]
+pub struct IntentLog {
+    path: PathBuf,
+    /// Open intent id and its opening time per dedup key.
+    open_keys: Arc<DashMap<u64, (String, Instant)>>,
+    file: Arc<Mutex<IntentFile>>,
+}
+
+impl Default for IntentLog {
+    fn default() -> Self {
+        Self::new("order_intents.jsonl")
+    }
+}
+
+impl IntentLog {
+    pub fn new(file_name: &str) -> Self {
+        let mut path = current_dir().unwrap_or_default();
+        path.push(file_name);
+        Self {
+            path,
+            open_keys: Arc::new(DashMap::new()),
+            file: Arc::new(Mutex::new(IntentFile::default())),
+        }
+    }
+
+    /// Writes the intent unless an identical one, by `dedup_key`, is still open.
+    pub fn open(&self, intent: &OrderIntent, dedup_key: u64) -> InfraResult<bool> {
+        let claim = (intent.intent_id.clone(), Instant::now());
+        match self.open_keys.entry(dedup_key) {
+            Entry::Occupied(entry) if entry.get().1.elapsed() < INTENT_DEDUP_TTL => {
+                return Ok(false);
+            },
+            Entry::Occupied(mut entry) => {
+                entry.insert(claim);
+            },
+            Entry::Vacant(entry) => {
+                entry.insert(claim);
+            },
+        }
+
+        if let Err(e) = self.append(&IntentRecord::Open(intent.clone())) {
+            self.open_keys.remove(&dedup_key);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// True while an intent with `dedup_key` is open and still blocking identical ones.
+    pub fn is_open(&self, dedup_key: u64) -> bool {
+        self.open_keys
+            .get(&dedup_key)
+            .is_some_and(|entry| entry.1.elapsed() < INTENT_DEDUP_TTL)
+    }
+
+    pub fn complete(&self, intent_id: &str, status: &str) -> InfraResult<()> {
+        self.open_keys.retain(|_, (id, _)| id != intent_id);
+        self.append(&IntentRecord::Done {
+            intent_id: intent_id.to_string(),
+            status: status.to_string(),
+            timestamp: get_micros_timestamp(),
+        })
+    }
+
+    /// Returns intents that were opened but never closed, voids them and compacts the log.
+    pub fn recover(&self) -> InfraResult<Vec<OrderIntent>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| InfraError::Msg(format!("Failed to read intent log: {}", e)))?;
+
+        let mut open: HashMap<String, OrderIntent> = HashMap::new();
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<IntentRecord>(line) {
+                Ok(IntentRecord::Open(intent)) => {
+                    open.insert(intent.intent_id.clone(), intent);
+                },
+                Ok(IntentRecord::Done { intent_id, .. }) => {
+                    open.remove(&intent_id);
+                },
+                Err(e) => warn!("[Intent] Skipping corrupt intent record: {}", e),
+            }
+        }
+
+        let mut dangling: Vec<OrderIntent> = open.into_values().collect();
+        dangling.sort_by_key(|i| i.timestamp);
+
+        let mut file = self.lock()?;
+        fs::write(&self.path, "")
+            .map_err(|e| InfraError::Msg(format!("Failed to compact intent log: {}", e)))?;
+        *file = IntentFile::default();
+
+        info!(
+            "[Intent] Recovered {} in-flight intent(s) from {:?}",
+            dangling.len(),
+            self.path
+        );
+
+        Ok(dangling)
+    }
+
+    fn lock(&self) -> InfraResult<std::sync::MutexGuard<'_, IntentFile>> {
+        self.file
+            .lock()
+            .map_err(|_| InfraError::Msg("Intent log lock poisoned".into()))
+    }
+
+    fn append(&self, record: &IntentRecord) -> InfraResult<()> {
+        let line = serde_json::to_string(record)?;
+        let mut state = self.lock()?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| InfraError::Msg(format!("Failed to open intent log: {}", e)))?;
+
+        writeln!(file, "{}", line)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| InfraError::Msg(format!("Failed to write intent log: {}", e)))?;
+
+        state.records += 1;
+        match record {
+            IntentRecord::Open(intent) => {
+                state.unresolved.insert(intent.intent_id.clone(), intent.clone());
+            },
+            IntentRecord::Done { intent_id, .. } => {
+                state.unresolved.remove(intent_id);
+                if state.records >= COMPACT_AFTER_RECORDS {
+                    self.compact(&mut state)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Rewrites the log with the unresolved intents only.
+    fn compact(&self, state: &mut IntentFile) -> InfraResult<()> {
+        let mut content = String::new();
+        for intent in state.unresolved.values() {
+            content.push_str(&serde_json::to_string(&IntentRecord::Open(intent.clone()))?);
+            content.push('\n');
+        }
+
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::File::create(&tmp)
+            .and_then(|mut f| f.write_all(content.as_bytes()).and_then(|_| f.sync_data()))
+            .and_then(|_| fs::rename(&tmp, &self.path))
+            .map_err(|e| InfraError::Msg(format!("Failed to compact intent log: {}", e)))?;
+
+        info!(
+            "[Intent] Compacted {} records to {} unresolved intent(s)",
+            state.records,
+            state.unresolved.len()
+        );
+        state.records = state.unresolved.len();
+        Ok(())
+    }
+}
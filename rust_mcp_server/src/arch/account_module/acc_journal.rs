@@ -0,0 +1,510 @@
+use reqwest::Method;
+use serde_json::Value;
+use std::{
+    env::current_dir,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+use tracing::info;
+
+use extrema_infra::prelude::*;
+
+use super::acc_rest::{
+    PrivateRest, RestVenue, binance_symbol, bybit_inst, iso8601_millis, now_millis, okx_inst,
+    okx_path,
+};
+
+const DAY_MS: u64 = 86_400_000;
+const OKX_BILLS_LIMIT: usize = 100;
+const BINANCE_PAGE_LIMIT: usize = 1000;
+const BYBIT_LOG_LIMIT: usize = 50;
+
+/// Column order of the exported CSV. Append-only: back office imports rely on positions.
+pub const JOURNAL_HEADER: [&str; 12] = [
+    "date",
+    "timestamp_ms",
+    "account_id",
+    "exchange",
+    "entry_type",
+    "inst",
+    "side",
+    "quantity",
+    "price",
+    "amount",
+    "asset",
+    "ref_id",
+];
+
+/// One journal line.
+#[derive(Clone, Debug)]
+pub struct JournalEntry {
+    pub timestamp: u64,
+    pub entry_type: &'static str,
+    pub inst: String,
+    pub side: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub amount: f64,
+    pub asset: String,
+    pub ref_id: String,
+}
+
+/// Daily CSV journals of fills, fees, funding, transfers and realized PnL per account.
+#[derive(Clone, Debug)]
+pub struct JournalExporter {
+    dir: PathBuf,
+    last_exported_day: Option<u64>,
+}
+
+impl JournalExporter {
+    pub fn new(dir_name: &str) -> Self {
+        let mut dir = current_dir().unwrap_or_default();
+        dir.push(dir_name);
+        Self {
+            dir,
+            last_exported_day: None,
+        }
+    }
+
+    /// Returns the closed UTC day (days since epoch) that still needs exporting.
+    pub fn pending_day(&self) -> Option<u64> {
+        let yesterday = (now_millis() / DAY_MS).checked_sub(1)?;
+        (self.last_exported_day != Some(yesterday)).then_some(yesterday)
+    }
+
+    pub fn mark_exported(&mut self, day: u64) {
+        self.last_exported_day = Some(day);
+    }
+
+    pub async fn export_account(
+        &self,
+        account_id: &str,
+        rest: &PrivateRest,
+        insts: &[String],
+        day: u64,
+    ) -> InfraResult<()> {
+        let start = day * DAY_MS;
+        let end = start + DAY_MS - 1;
+
+        let (exchange, mut entries) = match rest.venue() {
+            RestVenue::Okx => ("okx", fetch_okx_entries(rest, start, end).await?),
+            RestVenue::BinanceUm => (
+                "binance_um",
+                fetch_binance_entries(rest, insts, start, end).await?,
+            ),
+            RestVenue::Bybit => ("bybit", fetch_bybit_entries(rest, start, end).await?),
+        };
+        entries.sort_by_key(|e| e.timestamp);
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| InfraError::Msg(format!("Failed to create journal dir: {}", e)))?;
+
+        let date = &iso8601_millis(start)[..10];
+        let path = self.dir.join(format!("{}_{}.csv", date, account_id));
+        write_journal(&path, date, account_id, exchange, &entries)
+            .map_err(|e| InfraError::Msg(format!("Failed to write journal {:?}: {}", path, e)))?;
+
+        info!(
+            "[Journal] Exported {} entries for account={} to {:?}",
+            entries.len(),
+            account_id,
+            path
+        );
+
+        Ok(())
+    }
+}
+
+/// Net transfers of `asset` into the account between `start` and `end`, in milliseconds.
+pub async fn fetch_net_transfers(
+    rest: &PrivateRest,
+    asset: &str,
+    start: u64,
+    end: u64,
+) -> InfraResult<f64> {
+    let entries = match rest.venue() {
+        RestVenue::Okx => fetch_okx_entries(rest, start, end).await?,
+        RestVenue::BinanceUm => fetch_binance_entries(rest, &[], start, end).await?,
+        RestVenue::Bybit => fetch_bybit_entries(rest, start, end).await?,
+    };
+
+    Ok(entries
+        .iter()
+        .filter(|e| e.entry_type == "transfer" && e.asset == asset)
+        .map(|e| e.amount)
+        .sum())
+}
+
+/// Funding bills of `asset` between `start` and `end` as `(inst, timestamp, amount)`.
+pub async fn fetch_funding_bills(
+    rest: &PrivateRest,
+    asset: &str,
+    start: u64,
+    end: u64,
+) -> InfraResult<Vec<(String, u64, f64)>> {
+    let entries = match rest.venue() {
+        RestVenue::Okx => fetch_okx_entries(rest, start, end).await?,
+        RestVenue::BinanceUm => fetch_binance_entries(rest, &[], start, end).await?,
+        RestVenue::Bybit => fetch_bybit_entries(rest, start, end).await?,
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter(|e| e.entry_type == "funding" && e.asset == asset)
+        .map(|e| {
+            let inst = match rest.venue() {
+                RestVenue::Okx => okx_inst(&e.inst),
+                // Binance UM symbols are the same as Bybit's
+                RestVenue::BinanceUm => bybit_inst(&e.inst),
+                RestVenue::Bybit => e.inst,
+            };
+            (inst, e.timestamp, e.amount)
+        })
+        .collect())
+}
+
+fn write_journal(
+    path: &PathBuf,
+    date: &str,
+    account_id: &str,
+    exchange: &str,
+    entries: &[JournalEntry],
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "{}", JOURNAL_HEADER.join(","))?;
+
+    for e in entries {
+        let row = [
+            date.to_string(),
+            e.timestamp.to_string(),
+            account_id.to_string(),
+            exchange.to_string(),
+            e.entry_type.to_string(),
+            e.inst.clone(),
+            e.side.clone(),
+            e.quantity.to_string(),
+            e.price.to_string(),
+            e.amount.to_string(),
+            e.asset.clone(),
+            e.ref_id.clone(),
+        ];
+        let line = row.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",");
+        writeln!(writer, "{}", line)?;
+    }
+
+    writer.flush()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// OKX account bills: type 1 transfer, 2 trade, 8 funding fee.
+async fn fetch_okx_entries(
+    rest: &PrivateRest,
+    start: u64,
+    end: u64,
+) -> InfraResult<Vec<JournalEntry>> {
+    let mut entries = Vec::new();
+    let mut after: Option<String> = None;
+
+    loop {
+        let (begin_ms, end_ms) = (start.to_string(), end.to_string());
+        let limit = OKX_BILLS_LIMIT.to_string();
+        let mut params = vec![("begin", begin_ms.as_str()), ("end", &end_ms), ("limit", &limit)];
+        if let Some(bill_id) = &after {
+            params.push(("after", bill_id));
+        }
+        let path = okx_path("/api/v5/account/bills-archive", &params)?;
+
+        let resp = rest.okx_request(Method::GET, &path, None).await?;
+        let bills = resp["data"].as_array().cloned().unwrap_or_default();
+
+        for bill in bills.iter() {
+            entries.extend(okx_bill_entries(bill));
+        }
+
+        match bills.last().and_then(|b| b["billId"].as_str()) {
+            Some(bill_id) if bills.len() == OKX_BILLS_LIMIT => after = Some(bill_id.to_string()),
+            _ => break,
+        }
+    }
+
+    Ok(entries)
+}
+
+fn okx_bill_entries(bill: &Value) -> Vec<JournalEntry> {
+    let base = JournalEntry {
+        timestamp: str_u64(&bill["ts"]),
+        entry_type: "",
+        inst: bill["instId"].as_str().unwrap_or_default().to_string(),
+        side: String::new(),
+        quantity: 0.0,
+        price: 0.0,
+        amount: 0.0,
+        asset: bill["ccy"].as_str().unwrap_or_default().to_string(),
+        ref_id: bill["billId"].as_str().unwrap_or_default().to_string(),
+    };
+
+    match bill["type"].as_str().unwrap_or_default() {
+        "1" => vec![JournalEntry {
+            entry_type: "transfer",
+            amount: str_f64(&bill["balChg"]),
+            ..base
+        }],
+        "8" => vec![JournalEntry {
+            entry_type: "funding",
+            amount: str_f64(&bill["balChg"]),
+            ..base
+        }],
+        "2" => {
+            let side = match bill["subType"].as_str() {
+                Some("1") => "buy",
+                Some("2") => "sell",
+                _ => "",
+            };
+
+            let mut rows = vec![JournalEntry {
+                entry_type: "fill",
+                side: side.to_string(),
+                quantity: str_f64(&bill["sz"]),
+                price: str_f64(&bill["px"]),
+                ..base.clone()
+            }];
+
+            let fee = str_f64(&bill["fee"]);
+            if fee != 0.0 {
+                rows.push(JournalEntry {
+                    entry_type: "fee",
+                    amount: fee,
+                    ..base.clone()
+                });
+            }
+
+            let pnl = str_f64(&bill["pnl"]);
+            if pnl != 0.0 {
+                rows.push(JournalEntry {
+                    entry_type: "pnl",
+                    amount: pnl,
+                    ..base
+                });
+            }
+
+            rows
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Binance UM: fills from `userTrades` per traded symbol, everything else from `income`.
+async fn fetch_binance_entries(
+    rest: &PrivateRest,
+    insts: &[String],
+    start: u64,
+    end: u64,
+) -> InfraResult<Vec<JournalEntry>> {
+    let mut entries = Vec::new();
+
+    for inst in insts.iter() {
+        let symbol = binance_symbol(inst);
+        let mut params = vec![
+            ("startTime".to_string(), start.to_string()),
+            ("endTime".to_string(), end.to_string()),
+        ];
+        // The first page is selected by time; `fromId` cannot be combined with a time range,
+        // so later pages continue from the last trade id until one comes back short
+        loop {
+            params.push(("symbol".to_string(), symbol.clone()));
+            params.push(("limit".to_string(), BINANCE_PAGE_LIMIT.to_string()));
+            let resp = rest
+                .binance_request(Method::GET, "/fapi/v1/userTrades", params)
+                .await?;
+
+            let trades = resp.as_array().cloned().unwrap_or_default();
+            let last_id = trades.iter().filter_map(|t| t["id"].as_u64()).max();
+            entries.extend(
+                trades
+                    .iter()
+                    .filter(|t| t["time"].as_u64().is_some_and(|ts| ts <= end))
+                    .map(|t| JournalEntry {
+                        timestamp: t["time"].as_u64().unwrap_or_default(),
+                        entry_type: "fill",
+                        inst: symbol.clone(),
+                        side: t["side"].as_str().unwrap_or_default().to_lowercase(),
+                        quantity: str_f64(&t["qty"]),
+                        price: str_f64(&t["price"]),
+                        amount: 0.0,
+                        asset: t["marginAsset"].as_str().unwrap_or_default().to_string(),
+                        ref_id: t["id"].to_string(),
+                    }),
+            );
+
+            let past_end = trades
+                .iter()
+                .any(|t| t["time"].as_u64().is_some_and(|ts| ts > end));
+            if trades.len() < BINANCE_PAGE_LIMIT || past_end {
+                break;
+            }
+            let Some(last_id) = last_id else {
+                break;
+            };
+            params = vec![("fromId".to_string(), (last_id + 1).to_string())];
+        }
+    }
+
+    let mut page_start = start;
+    loop {
+        let params = vec![
+            ("startTime".to_string(), page_start.to_string()),
+            ("endTime".to_string(), end.to_string()),
+            ("limit".to_string(), BINANCE_PAGE_LIMIT.to_string()),
+        ];
+
+        let resp = rest
+            .binance_request(Method::GET, "/fapi/v1/income", params)
+            .await?;
+        let incomes = resp.as_array().cloned().unwrap_or_default();
+
+        for income in incomes.iter() {
+            let entry_type = match income["incomeType"].as_str().unwrap_or_default() {
+                "COMMISSION" => "fee",
+                "FUNDING_FEE" => "funding",
+                "TRANSFER" => "transfer",
+                "REALIZED_PNL" => "pnl",
+                _ => continue,
+            };
+
+            entries.push(JournalEntry {
+                timestamp: income["time"].as_u64().unwrap_or_default(),
+                entry_type,
+                inst: income["symbol"].as_str().unwrap_or_default().to_string(),
+                side: String::new(),
+                quantity: 0.0,
+                price: 0.0,
+                amount: str_f64(&income["income"]),
+                asset: income["asset"].as_str().unwrap_or_default().to_string(),
+                ref_id: income["tranId"].to_string(),
+            });
+        }
+
+        match incomes.last().and_then(|i| i["time"].as_u64()) {
+            Some(ts) if incomes.len() == BINANCE_PAGE_LIMIT => page_start = ts + 1,
+            _ => break,
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Bybit unified account transaction log entries.
+async fn fetch_bybit_entries(
+    rest: &PrivateRest,
+    start: u64,
+    end: u64,
+) -> InfraResult<Vec<JournalEntry>> {
+    let mut entries = Vec::new();
+    let mut cursor = String::new();
+
+    loop {
+        let mut params = vec![
+            ("accountType".to_string(), "UNIFIED".to_string()),
+            ("startTime".to_string(), start.to_string()),
+            ("endTime".to_string(), end.to_string()),
+            ("limit".to_string(), BYBIT_LOG_LIMIT.to_string()),
+        ];
+        if !cursor.is_empty() {
+            params.push(("cursor".to_string(), cursor.clone()));
+        }
+
+        let resp = rest
+            .bybit_request(Method::GET, "/v5/account/transaction-log", params)
+            .await?;
+        for row in resp["result"]["list"].as_array().cloned().unwrap_or_default() {
+            entries.extend(bybit_log_entries(&row));
+        }
+
+        match resp["result"]["nextPageCursor"].as_str() {
+            Some(next) if !next.is_empty() && next != cursor => cursor = next.to_string(),
+            _ => break,
+        }
+    }
+
+    Ok(entries)
+}
+
+fn bybit_log_entries(row: &Value) -> Vec<JournalEntry> {
+    let base = JournalEntry {
+        timestamp: str_u64(&row["transactionTime"]),
+        entry_type: "",
+        inst: bybit_inst(row["symbol"].as_str().unwrap_or_default()),
+        side: String::new(),
+        quantity: 0.0,
+        price: 0.0,
+        amount: 0.0,
+        asset: row["currency"].as_str().unwrap_or_default().to_string(),
+        ref_id: row["id"].as_str().unwrap_or_default().to_string(),
+    };
+
+    match row["type"].as_str().unwrap_or_default() {
+        "TRANSFER_IN" | "TRANSFER_OUT" => vec![JournalEntry {
+            entry_type: "transfer",
+            amount: str_f64(&row["change"]),
+            ..base
+        }],
+        "SETTLEMENT" => vec![JournalEntry {
+            entry_type: "funding",
+            amount: str_f64(&row["change"]),
+            ..base
+        }],
+        "TRADE" => {
+            let mut rows = vec![JournalEntry {
+                entry_type: "fill",
+                side: row["side"].as_str().unwrap_or_default().to_lowercase(),
+                quantity: str_f64(&row["qty"]),
+                price: str_f64(&row["tradePrice"]),
+                ..base.clone()
+            }];
+
+            // Bybit reports fees paid as positive
+            let fee = str_f64(&row["fee"]);
+            if fee != 0.0 {
+                rows.push(JournalEntry {
+                    entry_type: "fee",
+                    amount: -fee,
+                    ..base.clone()
+                });
+            }
+
+            let pnl = str_f64(&row["cashFlow"]);
+            if pnl != 0.0 {
+                rows.push(JournalEntry {
+                    entry_type: "pnl",
+                    amount: pnl,
+                    ..base
+                });
+            }
+
+            rows
+        },
+        _ => Vec::new(),
+    }
+}
+
+fn str_f64(v: &Value) -> f64 {
+    v.as_str()
+        .and_then(|s| s.parse::<f64>().ok())
+        .or_else(|| v.as_f64())
+        .unwrap_or(0.0)
+}
+
+fn str_u64(v: &Value) -> u64 {
+    v.as_str()
+        .and_then(|s| s.parse::<u64>().ok())
+        .or_else(|| v.as_u64())
+        .unwrap_or_default()
+}
@@ -0,0 +1,58 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Tiers and account leverage are refetched after this long.
+pub const POSITION_CAP_TTL: Duration = Duration::from_secs(3600);
+/// A failed lookup blocks the instrument for this long before it is retried.
+pub const POSITION_CAP_RETRY: Duration = Duration::from_secs(60);
+
+/// One venue risk tier: positions up to `cap` may use at most `max_leverage`.
+#[derive(Clone, Debug)]
+pub struct PositionTier {
+    pub cap: f64,
+    pub max_leverage: f64,
+}
+
+/// Largest position the account's leverage setting allows.
+pub fn tier_cap(tiers: &[PositionTier], leverage: f64) -> Option<f64> {
+    tiers
+        .iter()
+        .filter(|t| t.max_leverage + f64::EPSILON >= leverage)
+        .map(|t| t.cap)
+        .reduce(f64::max)
+}
+
+/// Signed order size that keeps `current + order` within `±cap`.
+pub fn cap_order(current: f64, order: f64, cap: f64) -> f64 {
+    let capped = (current + order).clamp(-cap, cap) - current;
+    if capped * order > 0.0 { capped } else { 0.0 }
+}
+
+/// Per-instrument position caps of one account.
+#[derive(Clone, Debug, Default)]
+pub struct PositionCaps {
+    caps: HashMap<String, (Option<f64>, Instant, Duration)>,
+}
+
+impl PositionCaps {
+    /// `Some(cap)` when cached and fresh; an inner `None` blocks the instrument.
+    pub fn get(&self, inst: &str) -> Option<Option<f64>> {
+        self.caps
+            .get(inst)
+            .filter(|(_, at, ttl)| at.elapsed() < *ttl)
+            .map(|(cap, _, _)| *cap)
+    }
+
+    pub fn insert(&mut self, inst: &str, cap: Option<f64>) {
+        self.caps
+            .insert(inst.to_string(), (cap, Instant::now(), POSITION_CAP_TTL));
+    }
+
+    /// Blocks `inst` until the lookup is retried.
+    pub fn insert_failed(&mut self, inst: &str) {
+        self.caps
+            .insert(inst.to_string(), (None, Instant::now(), POSITION_CAP_RETRY));
+    }
+}
@@ -0,0 +1,324 @@
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use extrema_infra::prelude::*;
+
+use super::{
+    acc_own_orders::{OwnOrder, OwnOrderBook},
+    acc_rest::{OrderRef, now_millis},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OrderState {
+    Open,
+    Filled,
+    Cancelled,
+}
+
+/// Order stream statuses; a status extrema_infra adds or renames fails to build here.
+impl From<&OrderStatus> for OrderState {
+    fn from(status: &OrderStatus) -> Self {
+        match status {
+            OrderStatus::Live | OrderStatus::PartiallyFilled => OrderState::Open,
+            OrderStatus::Filled => OrderState::Filled,
+            OrderStatus::Canceled | OrderStatus::Expired | OrderStatus::Rejected => {
+                OrderState::Cancelled
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TrackedOrder {
+    pub inst: String,
+    pub order: OrderRef,
+    pub side: OrderSide,
+    pub size: String,
+    pub price: Option<String>,
+    pub submitted_at: Instant,
+    pub state: OrderState,
+    /// Times a `limit_offset` rebalance order was moved to a fresh mark.
+    pub reprices: u32,
+    /// Weight change and decision price, for the execution report once it fills.
+    pub diff: f64,
+    pub decision_price: f64,
+    /// Intent of the order, closed in the write-ahead log once the order is terminal.
+    pub intent_id: Option<String>,
+    /// Size filled so far, as last reported by the order stream.
+    pub filled: f64,
+}
+
+/// Wait after the first failed cancel-on-timeout, doubled per further failure.
+const CANCEL_RETRY_BASE: Duration = Duration::from_secs(5);
+/// Failed cancels of one order before the automatic retries stop and an alert is raised.
+pub const MAX_CANCEL_RETRIES: u32 = 5;
+
+/// Lifecycle of resting (non-market) orders per account.
+#[derive(Clone, Debug, Default)]
+pub struct OrderTracker {
+    orders: HashMap<String, TrackedOrder>,
+    /// Failed cancels per order and when the next may be tried.
+    cancel_failures: HashMap<String, (u32, Instant)>,
+    book: Option<(OwnOrderBook, String, String)>,
+}
+
+impl OrderTracker {
+    /// Mirrors this account's orders into `book` under `venue`.
+    pub fn attach(&mut self, book: OwnOrderBook, venue: &str, account_id: &str) {
+        self.book = Some((book, venue.to_string(), account_id.to_string()));
+        self.publish();
+    }
+
+    pub fn venue(&self) -> Option<&str> {
+        self.book.as_ref().map(|(_, venue, _)| venue.as_str())
+    }
+
+    /// Resting orders of other accounts that an order on `inst` would trade against.
+    pub fn crossing(&self, inst: &str, buy: bool, price: Option<f64>) -> Vec<OwnOrder> {
+        self.book
+            .as_ref()
+            .map(|(book, venue, account_id)| book.crossing(venue, account_id, inst, buy, price))
+            .unwrap_or_default()
+    }
+
+    pub fn track(&mut self, order: TrackedOrder) {
+        self.orders.insert(order.order.key().to_string(), order);
+        self.publish();
+    }
+
+    pub fn get(&self, key: &str) -> Option<&TrackedOrder> {
+        self.orders.get(key)
+    }
+
+    /// Records a partial fill of `key` so the order book shows what is left.
+    pub fn update_filled(&mut self, key: &str, filled: f64) {
+        let Some(order) = self.orders.get_mut(key) else {
+            return;
+        };
+        if (order.filled - filled).abs() <= f64::EPSILON {
+            return;
+        }
+
+        order.filled = filled;
+        self.publish();
+    }
+
+    /// Drops `key` once it is terminal and returns it.
+    pub fn mark(&mut self, key: &str, state: OrderState) -> Option<TrackedOrder> {
+        if state == OrderState::Open {
+            return None;
+        }
+
+        let order = self.orders.remove(key);
+        self.cancel_failures.remove(key);
+        self.publish();
+        order
+    }
+
+    /// True unless an earlier cancel of `key` is backing off or out of retries.
+    pub fn cancel_due(&self, key: &str) -> bool {
+        self.cancel_failures
+            .get(key)
+            .is_none_or(|(failures, retry_at)| {
+                *failures < MAX_CANCEL_RETRIES && Instant::now() >= *retry_at
+            })
+    }
+
+    /// Records a failed cancel of `key` and returns the failures so far.
+    pub fn cancel_failed(&mut self, key: &str) -> u32 {
+        let entry = self
+            .cancel_failures
+            .entry(key.to_string())
+            .or_insert((0, Instant::now()));
+        entry.0 += 1;
+        entry.1 = Instant::now() + CANCEL_RETRY_BASE.saturating_mul(1 << (entry.0 - 1).min(16));
+        entry.0
+    }
+
+    pub fn has_open(&self, inst: &str) -> bool {
+        self.orders
+            .values()
+            .any(|o| o.state == OrderState::Open && o.inst == inst)
+    }
+
+    pub fn open_for(&self, inst: &str) -> Vec<TrackedOrder> {
+        self.orders
+            .values()
+            .filter(|o| o.state == OrderState::Open && o.inst == inst)
+            .cloned()
+            .collect()
+    }
+
+    pub fn stale(&self, timeout: Duration) -> Vec<TrackedOrder> {
+        self.orders
+            .values()
+            .filter(|o| o.state == OrderState::Open && o.submitted_at.elapsed() >= timeout)
+            .cloned()
+            .collect()
+    }
+
+    fn publish(&self) {
+        let Some((book, venue, account_id)) = &self.book else {
+            return;
+        };
+
+        let now = now_millis();
+        let orders = self
+            .orders
+            .values()
+            .filter(|o| o.state == OrderState::Open)
+            .map(|o| OwnOrder {
+                account_id: account_id.clone(),
+                inst: o.inst.clone(),
+                order: o.order.key().to_string(),
+                buy: matches!(o.side, OrderSide::BUY),
+                size: o.size.clone(),
+                filled: o.filled,
+                price: o.price.clone(),
+                submitted_ms: now.saturating_sub(o.submitted_at.elapsed().as_millis() as u64),
+            })
+            .collect();
+        book.replace(venue, account_id, orders);
+    }
+}
+
+/// Rebalance orders sent but not yet known to be terminal, per instrument.
+#[derive(Clone, Debug, Default)]
+pub struct InFlightOrders {
+    orders: HashMap<String, Instant>,
+}
+
+impl InFlightOrders {
+    pub fn begin(&mut self, inst: &str) {
+        self.orders.entry(inst.to_string()).or_insert_with(Instant::now);
+    }
+
+    pub fn finish(&mut self, inst: &str) {
+        self.orders.remove(inst);
+    }
+
+    pub fn clear(&mut self) {
+        self.orders.clear();
+    }
+
+    pub fn contains(&self, inst: &str) -> bool {
+        self.orders.contains_key(inst)
+    }
+
+    /// Drops entries older than `timeout` and returns their instruments.
+    pub fn expire(&mut self, timeout: Duration) -> Vec<String> {
+        let expired: Vec<String> = self
+            .orders
+            .iter()
+            .filter(|(_, at)| at.elapsed() >= timeout)
+            .map(|(inst, _)| inst.clone())
+            .collect();
+        for inst in expired.iter() {
+            self.orders.remove(inst);
+        }
+        expired
+    }
+}
+
+/// Target moves smaller than this belong to the same rebalance decision.
+const EPOCH_TARGET_TOLERANCE: f64 = 1e-9;
+
+/// Rebalance decision an instrument's orders belong to.
+#[derive(Clone, Debug)]
+struct RebalanceEpoch {
+    target: f64,
+    /// Time (ms) the target was first acted on.
+    epoch: u64,
+    /// Orders of this epoch acked so far.
+    seq: u32,
+}
+
+/// Deterministic client order ids from account, instrument and rebalance epoch.
+#[derive(Clone, Debug, Default)]
+pub struct ClientOrderIds {
+    epochs: HashMap<String, RebalanceEpoch>,
+}
+
+impl ClientOrderIds {
+    /// Id of the next order of `inst` towards `target`.
+    pub fn next(&mut self, account_id: &str, inst: &str, target: f64) -> String {
+        let epoch = self
+            .epochs
+            .entry(inst.to_string())
+            .and_modify(|e| {
+                if (e.target - target).abs() > EPOCH_TARGET_TOLERANCE {
+                    *e = RebalanceEpoch {
+                        target,
+                        epoch: now_millis(),
+                        seq: 0,
+                    };
+                }
+            })
+            .or_insert_with(|| RebalanceEpoch {
+                target,
+                epoch: now_millis(),
+                seq: 0,
+            });
+
+        let seed = format!("{}|{}|{}|{}", account_id, inst, epoch.epoch, epoch.seq);
+        let digest = Sha256::digest(seed.as_bytes());
+        hex::encode(&digest[..16])
+    }
+
+    /// The venue accepted the current id of `inst`; the next order gets a fresh one.
+    pub fn acked(&mut self, inst: &str) {
+        if let Some(epoch) = self.epochs.get_mut(inst) {
+            epoch.seq += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_statuses_map_to_order_states() {
+        let cases = [
+            (OrderStatus::Live, OrderState::Open),
+            (OrderStatus::PartiallyFilled, OrderState::Open),
+            (OrderStatus::Filled, OrderState::Filled),
+            (OrderStatus::Canceled, OrderState::Cancelled),
+            (OrderStatus::Expired, OrderState::Cancelled),
+            (OrderStatus::Rejected, OrderState::Cancelled),
+        ];
+        for (status, state) in cases {
+            assert_eq!(OrderState::from(&status), state, "{:?}", status);
+        }
+    }
+
+    #[test]
+    fn book_follows_partial_fills_and_cancels() {
+        let book = OwnOrderBook::default();
+        let mut tracker = OrderTracker::default();
+        tracker.attach(book.clone(), "Okx", "acc");
+        tracker.track(TrackedOrder {
+            inst: "BTC_USDT_PERP".into(),
+            order: OrderRef::ClientId("cl1".into()),
+            side: OrderSide::BUY,
+            size: "2".into(),
+            price: Some("60000".into()),
+            submitted_at: Instant::now(),
+            state: OrderState::Open,
+            reprices: 0,
+            diff: 0.1,
+            decision_price: 60_000.0,
+            intent_id: None,
+            filled: 0.0,
+        });
+
+        tracker.update_filled("cl1", 0.5);
+        assert_eq!(book.snapshot()["Okx"][0].filled, 0.5);
+
+        assert!(tracker.mark("cl1", OrderState::Cancelled).is_some());
+        assert!(book.snapshot()["Okx"].is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc};
+
+/// One of our resting orders, as listed across accounts.
+#[derive(Clone, Debug, Serialize)]
+pub struct OwnOrder {
+    pub account_id: String,
+    pub inst: String,
+    /// Venue order id or client order id.
+    pub order: String,
+    pub buy: bool,
+    pub size: String,
+    /// Part of `size` already filled.
+    pub filled: f64,
+    pub price: Option<String>,
+    pub submitted_ms: u64,
+}
+
+impl OwnOrder {
+    /// True when an order on the other side at `price` would trade against this one.
+    fn crossed_by(&self, buy: bool, price: Option<f64>) -> bool {
+        if self.buy == buy {
+            return false;
+        }
+
+        let resting = self.price.as_deref().and_then(|p| p.parse::<f64>().ok());
+        match (price, resting) {
+            (Some(price), Some(resting)) if buy => price >= resting,
+            (Some(price), Some(resting)) => price <= resting,
+            _ => true,
+        }
+    }
+}
+
+/// Resting orders of every account per venue, kept current by each account's `OrderTracker`.
+#[derive(Clone, Debug, Default)]
+pub struct OwnOrderBook {
+    venues: Arc<DashMap<String, HashMap<String, Vec<OwnOrder>>>>,
+}
+
+impl OwnOrderBook {
+    pub fn replace(&self, venue: &str, account_id: &str, orders: Vec<OwnOrder>) {
+        self.venues
+            .entry(venue.to_string())
+            .or_default()
+            .insert(account_id.to_string(), orders);
+    }
+
+    /// Drops the orders of accounts that are no longer loaded.
+    pub fn retain_accounts(&self, keep: impl Fn(&str) -> bool) {
+        for mut accounts in self.venues.iter_mut() {
+            accounts.retain(|account_id, _| keep(account_id));
+        }
+    }
+
+    /// Resting orders per venue.
+    pub fn snapshot(&self) -> HashMap<String, Vec<OwnOrder>> {
+        self.venues
+            .iter()
+            .map(|entry| {
+                let orders = entry.value().values().flatten().cloned().collect();
+                (entry.key().clone(), orders)
+            })
+            .collect()
+    }
+
+    /// Resting orders of other accounts on `venue` an order would trade against.
+    pub fn crossing(
+        &self,
+        venue: &str,
+        account_id: &str,
+        inst: &str,
+        buy: bool,
+        price: Option<f64>,
+    ) -> Vec<OwnOrder> {
+        let Some(accounts) = self.venues.get(venue) else {
+            return Vec::new();
+        };
+
+        accounts
+            .iter()
+            .filter(|(id, _)| id.as_str() != account_id)
+            .flat_map(|(_, orders)| orders)
+            .filter(|order| order.inst == inst && order.crossed_by(buy, price))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,90 @@
+use extrema_infra::{
+    arch::market_assets::{api_data::utils_data::InstrumentInfo, api_general::OrderParams},
+    prelude::*,
+};
+
+/// Relative slack when checking sizes and prices against lot and tick steps.
+const STEP_TOLERANCE: f64 = 1e-6;
+
+/// Checks a paper order against the venue rules and returns the rejection reason.
+pub fn simulate_rejection(
+    order: &OrderParams,
+    info: &InstrumentInfo,
+    mark_price: f64,
+    current_weight: f64,
+    price_band: f64,
+) -> Result<(), String> {
+    let size = order
+        .size
+        .parse::<f64>()
+        .map_err(|_| format!("invalid order size {}", order.size))?;
+
+    let (min_size, max_size) = match order.order_type {
+        OrderType::Market => (info.min_mkt_size, info.max_mkt_size),
+        _ => (info.min_lmt_size, info.max_lmt_size),
+    };
+    if size < min_size {
+        return Err(format!("size {} below minimum {}", size, min_size));
+    }
+    if max_size > 0.0 && size > max_size {
+        return Err(format!("size {} above maximum {}", size, max_size));
+    }
+    if !is_step_multiple(size, info.lot_size) {
+        return Err(format!("size {} not a multiple of lot size {}", size, info.lot_size));
+    }
+
+    let limit_price = match &order.price {
+        Some(px) => Some(
+            px.parse::<f64>()
+                .map_err(|_| format!("invalid order price {}", px))?,
+        ),
+        None => None,
+    };
+
+    if let Some(px) = limit_price {
+        if mark_price > 0.0 && (px / mark_price - 1.0).abs() > price_band {
+            return Err(format!(
+                "price {} outside {}% band around mark {}",
+                px,
+                price_band * 100.0,
+                mark_price
+            ));
+        }
+        if !is_step_multiple(px, info.tick_size) {
+            return Err(format!("price {} not a multiple of tick size {}", px, info.tick_size));
+        }
+    }
+
+    let price = limit_price.unwrap_or(mark_price);
+    let notional = size * info.contract_value.unwrap_or(1.0) * price;
+    if let Some(min_notional) = info.min_notional
+        && notional < min_notional
+    {
+        return Err(format!("notional {} below minimum {}", notional, min_notional));
+    }
+
+    if order.reduce_only == Some(true) {
+        let reduces = if matches!(order.side, OrderSide::BUY) {
+            current_weight < 0.0
+        } else {
+            current_weight > 0.0
+        };
+        if !reduces {
+            return Err(format!(
+                "reduce-only {:?} conflicts with position weight {}",
+                order.side, current_weight
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_step_multiple(value: f64, step: f64) -> bool {
+    if step <= 0.0 {
+        return true;
+    }
+
+    let steps = value / step;
+    (steps - steps.round()).abs() <= STEP_TOLERANCE * steps.abs().max(1.0)
+}
@@ -0,0 +1,318 @@
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+use tracing::info;
+
+/// Venues whose REST limits are shared by every account trading on them from this host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RateVenue {
+    Okx,
+    Binance,
+    Bybit,
+    Hyperliquid,
+}
+
+/// Token bucket of one venue: bursts up to `capacity`, sustained `refill_per_sec`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BucketConfig {
+    pub capacity: f64,
+    pub refill_per_sec: f64,
+}
+
+/// Per-venue limits, at about half of each venue's IP limit. Venues left out keep the default.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub okx: BucketConfig,
+    /// Weight units; the IP limit is 2400 per minute.
+    pub binance: BucketConfig,
+    pub bybit: BucketConfig,
+    /// Weight units; the IP limit is 1200 per minute.
+    pub hyperliquid: BucketConfig,
+    /// Order placements and amendments, spent on top of the request budget.
+    pub orders: OrderRateConfig,
+}
+
+/// Order budget of one venue, shared and per account.
+#[derive(Clone, Debug, Deserialize)]
+pub struct OrderBudgetConfig {
+    pub venue: BucketConfig,
+    pub per_account: BucketConfig,
+}
+
+/// Per-venue order budgets, at about half of each venue's order limits.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OrderRateConfig {
+    /// 60 orders per 2s per instrument and key.
+    pub okx: OrderBudgetConfig,
+    /// 1200 orders per minute and 300 per 10s per account.
+    pub binance: OrderBudgetConfig,
+    /// 10 orders per second per account.
+    pub bybit: OrderBudgetConfig,
+    /// Address-based; bursts are allowed, sustained rates scale with traded volume.
+    pub hyperliquid: OrderBudgetConfig,
+}
+
+impl Default for OrderRateConfig {
+    fn default() -> Self {
+        Self {
+            okx: OrderBudgetConfig {
+                venue: BucketConfig {
+                    capacity: 60.0,
+                    refill_per_sec: 30.0,
+                },
+                per_account: BucketConfig {
+                    capacity: 30.0,
+                    refill_per_sec: 15.0,
+                },
+            },
+            binance: OrderBudgetConfig {
+                venue: BucketConfig {
+                    capacity: 100.0,
+                    refill_per_sec: 20.0,
+                },
+                per_account: BucketConfig {
+                    capacity: 50.0,
+                    refill_per_sec: 10.0,
+                },
+            },
+            bybit: OrderBudgetConfig {
+                venue: BucketConfig {
+                    capacity: 20.0,
+                    refill_per_sec: 20.0,
+                },
+                per_account: BucketConfig {
+                    capacity: 5.0,
+                    refill_per_sec: 5.0,
+                },
+            },
+            hyperliquid: OrderBudgetConfig {
+                venue: BucketConfig {
+                    capacity: 20.0,
+                    refill_per_sec: 5.0,
+                },
+                per_account: BucketConfig {
+                    capacity: 10.0,
+                    refill_per_sec: 2.0,
+                },
+            },
+        }
+    }
+}
+
+impl OrderRateConfig {
+    fn budget(&self, venue: RateVenue) -> &OrderBudgetConfig {
+        match venue {
+            RateVenue::Okx => &self.okx,
+            RateVenue::Binance => &self.binance,
+            RateVenue::Bybit => &self.bybit,
+            RateVenue::Hyperliquid => &self.hyperliquid,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            okx: BucketConfig {
+                capacity: 20.0,
+                refill_per_sec: 10.0,
+            },
+            binance: BucketConfig {
+                capacity: 600.0,
+                refill_per_sec: 20.0,
+            },
+            bybit: BucketConfig {
+                capacity: 100.0,
+                refill_per_sec: 50.0,
+            },
+            hyperliquid: BucketConfig {
+                capacity: 300.0,
+                refill_per_sec: 10.0,
+            },
+            orders: OrderRateConfig::default(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn bucket(&self, venue: RateVenue) -> &BucketConfig {
+        match venue {
+            RateVenue::Okx => &self.okx,
+            RateVenue::Binance => &self.binance,
+            RateVenue::Bybit => &self.bybit,
+            RateVenue::Hyperliquid => &self.hyperliquid,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum BucketKey {
+    Requests(RateVenue),
+    VenueOrders(RateVenue),
+    AccountOrders(RateVenue, String),
+}
+
+/// Takes `cost` tokens from the bucket under `key` and returns the wait.
+fn reserve(
+    buckets: &mut HashMap<BucketKey, TokenBucket>,
+    key: BucketKey,
+    cfg: &BucketConfig,
+    cost: f64,
+) -> Duration {
+    let bucket = buckets.entry(key).or_insert_with(|| TokenBucket {
+        tokens: cfg.capacity,
+        refilled_at: Instant::now(),
+    });
+
+    let now = Instant::now();
+    let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * cfg.refill_per_sec).min(cfg.capacity);
+    bucket.refilled_at = now;
+    bucket.tokens -= cost;
+
+    if bucket.tokens >= 0.0 || cfg.refill_per_sec <= 0.0 {
+        Duration::ZERO
+    } else {
+        Duration::from_secs_f64(-bucket.tokens / cfg.refill_per_sec)
+    }
+}
+
+/// Token buckets per venue, shared by every account of the manager.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Arc<Mutex<HashMap<BucketKey, TokenBucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Takes `cost` tokens from the venue's bucket, waiting until they have refilled.
+    pub async fn acquire(&self, venue: RateVenue, cost: f64) {
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            reserve(&mut buckets, BucketKey::Requests(venue), self.config.bucket(venue), cost)
+        };
+
+        if wait >= Duration::from_secs(1) {
+            info!("[RateLimit] {:?} budget spent, waiting {:?}", venue, wait);
+        }
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+    }
+
+    /// Takes one order from the venue and account budgets and waits for both.
+    pub async fn acquire_order(&self, venue: RateVenue, account_id: &str) {
+        let budget = self.config.orders.budget(venue);
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            let shared = reserve(&mut buckets, BucketKey::VenueOrders(venue), &budget.venue, 1.0);
+            let own = reserve(
+                &mut buckets,
+                BucketKey::AccountOrders(venue, account_id.to_string()),
+                &budget.per_account,
+                1.0,
+            );
+            shared.max(own)
+        };
+
+        if wait >= Duration::from_secs(1) {
+            info!(
+                "[RateLimit] {:?} order budget spent, account={} waiting {:?}",
+                venue, account_id, wait
+            );
+        }
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+    }
+}
+
+/// Request weight of a Binance UM futures endpoint; account and position snapshots cost 5.
+pub fn binance_weight(path: &str) -> f64 {
+    match path {
+        "/fapi/v2/account" | "/fapi/v3/account" | "/fapi/v2/positionRisk" | "/fapi/v2/balance" => {
+            5.0
+        },
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUCKET: BucketConfig = BucketConfig {
+        capacity: 10.0,
+        refill_per_sec: 5.0,
+    };
+
+    #[test]
+    fn weighted_cost_waits_for_its_deficit() {
+        let mut buckets = HashMap::new();
+        let key = BucketKey::Requests(RateVenue::Binance);
+
+        assert!(reserve(&mut buckets, key.clone(), &BUCKET, 8.0).is_zero());
+        let wait = reserve(&mut buckets, key, &BUCKET, 4.0);
+
+        // 2 tokens short at 5 per second
+        assert!(wait > Duration::from_millis(350) && wait <= Duration::from_millis(400));
+    }
+
+    #[test]
+    fn refill_is_capped_at_capacity() {
+        let mut buckets = HashMap::new();
+        let key = BucketKey::Requests(RateVenue::Okx);
+        buckets.insert(
+            key.clone(),
+            TokenBucket {
+                tokens: -5.0,
+                refilled_at: Instant::now() - Duration::from_secs(10),
+            },
+        );
+
+        assert!(reserve(&mut buckets, key.clone(), &BUCKET, 10.0).is_zero());
+        assert!(buckets[&key].tokens.abs() < 1e-6);
+        assert!(!reserve(&mut buckets, key, &BUCKET, 1.0).is_zero());
+    }
+
+    #[test]
+    fn account_order_budgets_are_separate() {
+        let mut buckets = HashMap::new();
+        let acc = |id: &str| BucketKey::AccountOrders(RateVenue::Bybit, id.to_string());
+
+        assert!(reserve(&mut buckets, acc("a"), &BUCKET, 10.0).is_zero());
+        assert!(!reserve(&mut buckets, acc("a"), &BUCKET, 1.0).is_zero());
+        assert!(reserve(&mut buckets, acc("b"), &BUCKET, 1.0).is_zero());
+    }
+
+    #[test]
+    fn partial_config_keeps_default_venues() {
+        let cfg: RateLimitConfig = serde_json::from_value(serde_json::json!({
+            "okx": { "capacity": 5.0, "refill_per_sec": 1.0 },
+        }))
+        .unwrap();
+
+        assert_eq!(cfg.okx.capacity, 5.0);
+        assert_eq!(cfg.binance.capacity, RateLimitConfig::default().binance.capacity);
+        assert_eq!(cfg.orders.bybit.per_account.capacity, 5.0);
+    }
+}
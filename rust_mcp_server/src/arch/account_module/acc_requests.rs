@@ -0,0 +1,62 @@
+use serde_json::Value;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{sync::oneshot, time::timeout};
+
+use extrema_infra::prelude::*;
+
+/// Order management request from an MCP client.
+#[derive(Clone, Debug)]
+pub enum AccountRequest {
+    ListOpenOrders {
+        account_id: String,
+    },
+    /// Every resting order of the account, or only those on `inst`.
+    CancelAll {
+        account_id: String,
+        inst: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+pub struct PendingRequest {
+    pub request: AccountRequest,
+    pub reply: oneshot::Sender<InfraResult<Value>>,
+}
+
+/// Request/response channel into `AccountManager`.
+#[derive(Clone, Debug, Default)]
+pub struct AccountRequests {
+    queue: Arc<Mutex<VecDeque<PendingRequest>>>,
+}
+
+impl AccountRequests {
+    /// Queues `request` and waits up to `wait` for the reply.
+    pub async fn call(&self, request: AccountRequest, wait: Duration) -> InfraResult<Value> {
+        let (reply, answer) = oneshot::channel();
+        self.queue
+            .lock()
+            .map_err(|_| InfraError::Msg("account request queue poisoned".into()))?
+            .push_back(PendingRequest { request, reply });
+
+        match timeout(wait, answer).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(InfraError::Msg("account request dropped unanswered".into())),
+            Err(_) => Err(InfraError::Msg(format!(
+                "no reply from the accounts within {}s",
+                wait.as_secs()
+            ))),
+        }
+    }
+
+    /// Requests queued since the last call.
+    pub fn take(&self) -> Vec<PendingRequest> {
+        self.queue
+            .lock()
+            .map(|mut queue| queue.drain(..).collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,1483 @@
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method};
+use serde::Serialize;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use std::{
+    fmt,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+use extrema_infra::{
+    arch::market_assets::{api_data::utils_data::InstrumentInfo, api_general::OrderParams},
+    prelude::*,
+};
+
+use super::{
+    acc_cache::{FUNDING_TTL, LEVERAGE_TTL, METADATA_TTL, MetaCache, SERVER_TIME_TTL},
+    acc_limits::PositionTier,
+    acc_orders::OrderState,
+    acc_ratelimit::{RateLimiter, RateVenue, binance_weight},
+    acc_retry::FailureKind,
+    acc_utils::AccountFileConfig,
+};
+use crate::arch::profile::active_profile;
+
+const OKX_BASE_URL: &str = "https://www.okx.com";
+const BINANCE_UM_BASE_URL: &str = "https://fapi.binance.com";
+const BINANCE_UM_TESTNET_URL: &str = "https://testnet.binancefuture.com";
+const BINANCE_RECV_WINDOW_MS: u64 = 5000;
+const BYBIT_BASE_URL: &str = "https://api.bybit.com";
+const BYBIT_TESTNET_URL: &str = "https://api-testnet.bybit.com";
+const BYBIT_WS_PRIVATE_URL: &str = "wss://stream.bybit.com/v5/private";
+const BYBIT_WS_TESTNET_URL: &str = "wss://stream-testnet.bybit.com/v5/private";
+const BYBIT_RECV_WINDOW_MS: u64 = 5000;
+const BINANCE_UM_WS_URL: &str = "wss://fstream.binance.com";
+const BINANCE_UM_WS_TESTNET_URL: &str = "wss://stream.binancefuture.com";
+/// Stream hosts the exchange clients connect to, with their testnet counterparts.
+const TESTNET_WS_HOSTS: [(&str, &str); 3] = [
+    (BINANCE_UM_WS_URL, BINANCE_UM_WS_TESTNET_URL),
+    ("wss://dstream.binance.com", "wss://dstream.binancefuture.com"),
+    ("wss://ws.okx.com:8443", "wss://wspap.okx.com:8443"),
+];
+/// Rows per Bybit list request, the venue maximum for instruments and positions.
+const BYBIT_INST_PAGE_LIMIT: u32 = 1000;
+const BYBIT_POSITION_PAGE_LIMIT: u32 = 200;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RestVenue {
+    Okx,
+    BinanceUm,
+    Bybit,
+}
+
+#[derive(Clone, Debug)]
+pub enum OrderRef {
+    Id(String),
+    ClientId(String),
+}
+
+impl OrderRef {
+    pub fn key(&self) -> &str {
+        match self {
+            OrderRef::Id(id) => id,
+            OrderRef::ClientId(id) => id,
+        }
+    }
+}
+
+/// Signed private REST calls for order management endpoints not covered by `CexClients`.
+#[derive(Clone)]
+pub struct PrivateRest {
+    client: Arc<Client>,
+    venue: RestVenue,
+    api_key: String,
+    api_secret: String,
+    passphrase: String,
+    /// OKX demo trading header / Binance futures and Bybit testnet hosts.
+    testnet: bool,
+    meta: MetaCache,
+    limiter: RateLimiter,
+}
+
+impl fmt::Debug for PrivateRest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateRest")
+            .field("venue", &self.venue)
+            .field("testnet", &self.testnet)
+            .field("api_key", &"***")
+            .finish()
+    }
+}
+
+impl PrivateRest {
+    pub fn from_config(
+        cfg: &AccountFileConfig,
+        client: Arc<Client>,
+        meta: MetaCache,
+        limiter: RateLimiter,
+    ) -> Option<Self> {
+        let venue = match cfg.exchange.to_lowercase().as_str() {
+            "okx" => RestVenue::Okx,
+            "binance_um" => RestVenue::BinanceUm,
+            "bybit" => RestVenue::Bybit,
+            _ => return None,
+        };
+
+        Some(Self {
+            client,
+            venue,
+            api_key: cfg.api_key.clone(),
+            api_secret: cfg.api_secret.clone(),
+            passphrase: cfg.passphrase.clone().unwrap_or_default(),
+            testnet: active_profile().testnet,
+            meta,
+            limiter,
+        })
+    }
+
+    pub fn venue(&self) -> &RestVenue {
+        &self.venue
+    }
+
+    pub fn is_testnet(&self) -> bool {
+        self.testnet
+    }
+
+    /// Cache key of a metadata call; account-specific calls are keyed by the API key too.
+    fn meta_key(&self, call: &str, inst: &str, per_account: bool) -> String {
+        let account = if per_account { self.api_key.as_str() } else { "" };
+        format!("{:?}:{}:{}:{}:{}", self.venue, self.testnet, account, call, inst)
+    }
+
+    /// Local time shifted onto the venue clock, for request signatures.
+    async fn signed_now(&self) -> u64 {
+        let key = self.meta_key("server_time", "", false);
+        match self
+            .meta
+            .get_or_fetch(key, SERVER_TIME_TTL, self.fetch_clock_offset())
+            .await
+        {
+            Ok(offset) => now_millis().saturating_add_signed(offset),
+            Err(e) => {
+                warn!(
+                    "[Rest] {:?} server time unavailable, signing with local time: {}",
+                    self.venue, e
+                );
+                now_millis()
+            },
+        }
+    }
+
+    /// Venue clock minus the local one in ms, measured against the middle of the round trip.
+    async fn fetch_clock_offset(&self) -> InfraResult<i64> {
+        let (venue, url, path, rate) = match self.venue {
+            RestVenue::Okx => ("OKX", OKX_BASE_URL, "/api/v5/public/time", RateVenue::Okx),
+            RestVenue::BinanceUm => (
+                "Binance",
+                binance_base_url(self.testnet),
+                "/fapi/v1/time",
+                RateVenue::Binance,
+            ),
+            RestVenue::Bybit => {
+                ("Bybit", bybit_base_url(self.testnet), "/v5/market/time", RateVenue::Bybit)
+            },
+        };
+        self.limiter.acquire(rate, 1.0).await;
+
+        let sent = now_millis();
+        let value: Value = self
+            .client
+            .get(format!("{}{}", url, path))
+            .send()
+            .await
+            .map_err(|e| transport_error(venue, path, e))?
+            .json()
+            .await
+            .map_err(|e| {
+                InfraError::Msg(format!("{} response {} parse failed: {}", venue, path, e))
+            })?;
+        let received = now_millis();
+
+        let server = match self.venue {
+            RestVenue::Okx => value_f64(&value["data"][0]["ts"]),
+            RestVenue::BinanceUm => value_f64(&value["serverTime"]),
+            RestVenue::Bybit => value_f64(&value["time"]),
+        } as u64;
+        if server == 0 {
+            return Err(InfraError::Msg(format!("{} {} returned no time: {}", venue, path, value)));
+        }
+
+        Ok(server as i64 - (sent + received).div_ceil(2) as i64)
+    }
+
+    /// Current funding rate and next settlement time (ms) of a perpetual.
+    pub async fn funding_rate(&self, inst: &str) -> InfraResult<(f64, u64)> {
+        let key = self.meta_key("funding", inst, false);
+        self.meta
+            .get_or_fetch(key, FUNDING_TTL, self.fetch_funding_rate(inst))
+            .await
+    }
+
+    /// Risk tiers of `inst` on the account's venue.
+    pub async fn position_tiers(&self, inst: &str) -> InfraResult<Vec<PositionTier>> {
+        // Binance brackets can be negotiated per account
+        let per_account = matches!(self.venue, RestVenue::BinanceUm);
+        let key = self.meta_key("tiers", inst, per_account);
+        self.meta
+            .get_or_fetch(key, METADATA_TTL, self.fetch_position_tiers(inst))
+            .await
+    }
+
+    /// Leverage currently set on the account for `inst`.
+    pub async fn leverage(&self, inst: &str) -> InfraResult<f64> {
+        let key = self.meta_key("leverage", inst, true);
+        self.meta
+            .get_or_fetch(key, LEVERAGE_TTL, self.fetch_leverage(inst))
+            .await
+    }
+
+    /// Sets the leverage of `inst`, for isolated margin on OKX and both sides on Bybit.
+    pub async fn set_leverage(&self, inst: &str, leverage: u32) -> InfraResult<()> {
+        match self.venue {
+            RestVenue::Okx => {
+                let body = json!({
+                    "instId": okx_inst_id(inst),
+                    "lever": leverage.to_string(),
+                    "mgnMode": "isolated",
+                });
+                self.okx_request(Method::POST, "/api/v5/account/set-leverage", Some(body))
+                    .await?;
+            },
+            RestVenue::BinanceUm => {
+                let params = vec![
+                    ("symbol".to_string(), binance_symbol(inst)),
+                    ("leverage".to_string(), leverage.to_string()),
+                ];
+                self.binance_request(Method::POST, "/fapi/v1/leverage", params)
+                    .await?;
+            },
+            RestVenue::Bybit => {
+                let mut params = bybit_params(inst);
+                params.push(("buyLeverage".to_string(), leverage.to_string()));
+                params.push(("sellLeverage".to_string(), leverage.to_string()));
+                // 110043: leverage not modified, i.e. already set
+                if let Err(e) = self
+                    .bybit_request(Method::POST, "/v5/position/set-leverage", params)
+                    .await
+                    && !e.to_string().contains("110043")
+                {
+                    return Err(e);
+                }
+            },
+        };
+
+        self.meta
+            .invalidate(&self.meta_key("leverage", inst, true));
+        Ok(())
+    }
+
+    /// Taker commission rate of this account for `inst`, positive for a cost.
+    pub async fn taker_fee_rate(&self, inst: &str) -> InfraResult<f64> {
+        let key = self.meta_key("taker_fee", inst, true);
+        self.meta
+            .get_or_fetch(key, METADATA_TTL, self.fetch_taker_fee_rate(inst))
+            .await
+    }
+
+    pub async fn cancel_order(&self, inst: &str, order: &OrderRef) -> InfraResult<()> {
+        match self.venue {
+            RestVenue::Okx => {
+                let mut body = json!({ "instId": okx_inst_id(inst) });
+                match order {
+                    OrderRef::Id(id) => body["ordId"] = json!(id),
+                    OrderRef::ClientId(id) => body["clOrdId"] = json!(id),
+                };
+                self.okx_request(Method::POST, "/api/v5/trade/cancel-order", Some(body))
+                    .await?;
+            },
+            RestVenue::BinanceUm => {
+                let mut params = vec![("symbol".to_string(), binance_symbol(inst))];
+                params.push(binance_order_param(order));
+                self.binance_request(Method::DELETE, "/fapi/v1/order", params)
+                    .await?;
+            },
+            RestVenue::Bybit => {
+                let mut params = bybit_params(inst);
+                params.push(bybit_order_param(order));
+                self.bybit_request(Method::POST, "/v5/order/cancel", params)
+                    .await?;
+            },
+        };
+
+        Ok(())
+    }
+
+    /// Amends size and/or price. Binance requires side, quantity and price on every modify.
+    pub async fn amend_order(
+        &self,
+        inst: &str,
+        order: &OrderRef,
+        side: Option<&OrderSide>,
+        new_size: Option<&str>,
+        new_price: Option<&str>,
+    ) -> InfraResult<()> {
+        match self.venue {
+            RestVenue::Okx => {
+                let mut body = json!({ "instId": okx_inst_id(inst) });
+                match order {
+                    OrderRef::Id(id) => body["ordId"] = json!(id),
+                    OrderRef::ClientId(id) => body["clOrdId"] = json!(id),
+                };
+                if let Some(sz) = new_size {
+                    body["newSz"] = json!(sz);
+                }
+                if let Some(px) = new_price {
+                    body["newPx"] = json!(px);
+                }
+                self.okx_request(Method::POST, "/api/v5/trade/amend-order", Some(body))
+                    .await?;
+            },
+            RestVenue::BinanceUm => {
+                let (Some(side), Some(size), Some(price)) = (side, new_size, new_price) else {
+                    return Err(InfraError::Msg(
+                        "Binance amend requires side, size and price".into(),
+                    ));
+                };
+
+                let side = if matches!(side, OrderSide::BUY) {
+                    "BUY"
+                } else {
+                    "SELL"
+                };
+
+                let params = vec![
+                    ("symbol".to_string(), binance_symbol(inst)),
+                    binance_order_param(order),
+                    ("side".to_string(), side.to_string()),
+                    ("quantity".to_string(), size.to_string()),
+                    ("price".to_string(), price.to_string()),
+                ];
+                self.binance_request(Method::PUT, "/fapi/v1/order", params)
+                    .await?;
+            },
+            RestVenue::Bybit => {
+                let mut params = bybit_params(inst);
+                params.push(bybit_order_param(order));
+                if let Some(sz) = new_size {
+                    params.push(("qty".to_string(), sz.to_string()));
+                }
+                if let Some(px) = new_price {
+                    params.push(("price".to_string(), px.to_string()));
+                }
+                self.bybit_request(Method::POST, "/v5/order/amend", params)
+                    .await?;
+            },
+        };
+
+        Ok(())
+    }
+
+    /// Filled size (venue units) and average fill price of an order.
+    pub async fn order_fill(&self, inst: &str, order: &OrderRef) -> InfraResult<(f64, f64)> {
+        let (_, filled, avg_price) = self.order_status(inst, order).await?;
+        Ok((filled, avg_price))
+    }
+
+    /// Venue state of an order as `(state, filled size, average fill price)`.
+    pub async fn order_status(
+        &self,
+        inst: &str,
+        order: &OrderRef,
+    ) -> InfraResult<(OrderState, f64, f64)> {
+        let (state, filled, avg_price) = match self.venue {
+            RestVenue::Okx => {
+                let key = match order {
+                    OrderRef::Id(id) => ("ordId", id.as_str()),
+                    OrderRef::ClientId(id) => ("clOrdId", id.as_str()),
+                };
+                let path = okx_path("/api/v5/trade/order", &[("instId", &okx_inst_id(inst)), key])?;
+                let resp = self.okx_request(Method::GET, &path, None).await?;
+                let row = &resp["data"][0];
+                let state = match row["state"].as_str() {
+                    Some("live" | "partially_filled") => OrderState::Open,
+                    Some("filled") => OrderState::Filled,
+                    _ => OrderState::Cancelled,
+                };
+                (state, row["accFillSz"].clone(), row["avgPx"].clone())
+            },
+            RestVenue::BinanceUm => {
+                let params = vec![
+                    ("symbol".to_string(), binance_symbol(inst)),
+                    binance_order_param(order),
+                ];
+                let resp = self
+                    .binance_request(Method::GET, "/fapi/v1/order", params)
+                    .await?;
+                let state = match resp["status"].as_str() {
+                    Some("NEW" | "PARTIALLY_FILLED") => OrderState::Open,
+                    Some("FILLED") => OrderState::Filled,
+                    _ => OrderState::Cancelled,
+                };
+                (state, resp["executedQty"].clone(), resp["avgPrice"].clone())
+            },
+            RestVenue::Bybit => {
+                let mut params = bybit_params(inst);
+                params.push(bybit_order_param(order));
+                let resp = self
+                    .bybit_request(Method::GET, "/v5/order/realtime", params)
+                    .await?;
+                let row = &resp["result"]["list"][0];
+                if row.is_null() {
+                    return Err(InfraError::Msg(format!(
+                        "Bybit order {} not found for {}",
+                        order.key(),
+                        inst
+                    )));
+                }
+                let state = match row["orderStatus"].as_str() {
+                    Some("New" | "PartiallyFilled" | "Untriggered") => OrderState::Open,
+                    Some("Filled") => OrderState::Filled,
+                    _ => OrderState::Cancelled,
+                };
+                (state, row["cumExecQty"].clone(), row["avgPrice"].clone())
+            },
+        };
+
+        Ok((state, value_f64(&filled), value_f64(&avg_price)))
+    }
+
+    async fn fetch_funding_rate(&self, inst: &str) -> InfraResult<(f64, u64)> {
+        match self.venue {
+            RestVenue::Okx => {
+                let path =
+                    okx_path("/api/v5/public/funding-rate", &[("instId", &okx_inst_id(inst))])?;
+                let resp = self.okx_request(Method::GET, &path, None).await?;
+                let row = &resp["data"][0];
+                Ok((value_f64(&row["fundingRate"]), value_f64(&row["fundingTime"]) as u64))
+            },
+            RestVenue::BinanceUm => {
+                let params = vec![("symbol".to_string(), binance_symbol(inst))];
+                let resp = self
+                    .binance_public_request("/fapi/v1/premiumIndex", params)
+                    .await?;
+                Ok((
+                    value_f64(&resp["lastFundingRate"]),
+                    value_f64(&resp["nextFundingTime"]) as u64,
+                ))
+            },
+            RestVenue::Bybit => {
+                let resp = self
+                    .bybit_request(Method::GET, "/v5/market/tickers", bybit_params(inst))
+                    .await?;
+                let row = &resp["result"]["list"][0];
+                Ok((value_f64(&row["fundingRate"]), value_f64(&row["nextFundingTime"]) as u64))
+            },
+        }
+    }
+
+    async fn fetch_position_tiers(&self, inst: &str) -> InfraResult<Vec<PositionTier>> {
+        match self.venue {
+            RestVenue::Okx => {
+                let family = okx_inst_id(inst).trim_end_matches("-SWAP").to_string();
+                let path = okx_path(
+                    "/api/v5/public/position-tiers",
+                    &[("instType", "SWAP"), ("tdMode", "isolated"), ("instFamily", &family)],
+                )?;
+                let resp = self.okx_request(Method::GET, &path, None).await?;
+                let tiers = resp["data"]
+                    .as_array()
+                    .map(|rows| {
+                        rows.iter()
+                            .map(|row| PositionTier {
+                                cap: value_f64(&row["maxSz"]),
+                                max_leverage: value_f64(&row["maxLever"]),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Ok(tiers)
+            },
+            RestVenue::BinanceUm => {
+                let params = vec![("symbol".to_string(), binance_symbol(inst))];
+                let resp = self
+                    .binance_request(Method::GET, "/fapi/v1/leverageBracket", params)
+                    .await?;
+                let tiers = resp[0]["brackets"]
+                    .as_array()
+                    .map(|rows| {
+                        rows.iter()
+                            .map(|row| PositionTier {
+                                cap: value_f64(&row["notionalCap"]),
+                                max_leverage: value_f64(&row["initialLeverage"]),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Ok(tiers)
+            },
+            RestVenue::Bybit => {
+                let resp = self
+                    .bybit_request(Method::GET, "/v5/market/risk-limit", bybit_params(inst))
+                    .await?;
+                let tiers = resp["result"]["list"]
+                    .as_array()
+                    .map(|rows| {
+                        rows.iter()
+                            .map(|row| PositionTier {
+                                cap: value_f64(&row["riskLimitValue"]),
+                                max_leverage: value_f64(&row["maxLeverage"]),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Ok(tiers)
+            },
+        }
+    }
+
+    async fn fetch_leverage(&self, inst: &str) -> InfraResult<f64> {
+        match self.venue {
+            RestVenue::Okx => {
+                let path = okx_path(
+                    "/api/v5/account/leverage-info",
+                    &[("instId", &okx_inst_id(inst)), ("mgnMode", "isolated")],
+                )?;
+                let resp = self.okx_request(Method::GET, &path, None).await?;
+                // Long/short mode reports one row per side; the larger one binds
+                let lever = resp["data"]
+                    .as_array()
+                    .map(|rows| rows.iter().map(|r| value_f64(&r["lever"])).fold(0.0, f64::max))
+                    .unwrap_or_default();
+                Ok(lever)
+            },
+            RestVenue::BinanceUm => {
+                let params = vec![("symbol".to_string(), binance_symbol(inst))];
+                let resp = self
+                    .binance_request(Method::GET, "/fapi/v2/positionRisk", params)
+                    .await?;
+                let lever = resp
+                    .as_array()
+                    .map(|rows| rows.iter().map(|r| value_f64(&r["leverage"])).fold(0.0, f64::max))
+                    .unwrap_or_default();
+                Ok(lever)
+            },
+            RestVenue::Bybit => {
+                let resp = self
+                    .bybit_request(Method::GET, "/v5/position/list", bybit_params(inst))
+                    .await?;
+                let lever = resp["result"]["list"]
+                    .as_array()
+                    .map(|rows| rows.iter().map(|r| value_f64(&r["leverage"])).fold(0.0, f64::max))
+                    .unwrap_or_default();
+                Ok(lever)
+            },
+        }
+    }
+
+    /// Largest order on `side` the account's available margin covers.
+    pub async fn max_order_size(
+        &self,
+        inst: &str,
+        side: &OrderSide,
+        mark_price: f64,
+    ) -> InfraResult<f64> {
+        let available = match self.venue {
+            RestVenue::Okx => {
+                let path = okx_path(
+                    "/api/v5/account/max-avail-size",
+                    &[("instId", &okx_inst_id(inst)), ("tdMode", "isolated")],
+                )?;
+                let resp = self.okx_request(Method::GET, &path, None).await?;
+                let row = &resp["data"][0];
+                return Ok(match side {
+                    OrderSide::BUY => value_f64(&row["availBuy"]),
+                    _ => value_f64(&row["availSell"]),
+                });
+            },
+            RestVenue::BinanceUm => {
+                let resp = self
+                    .binance_request(Method::GET, "/fapi/v2/account", Vec::new())
+                    .await?;
+                value_f64(&resp["availableBalance"])
+            },
+            RestVenue::Bybit => {
+                let params = vec![("accountType".to_string(), "UNIFIED".to_string())];
+                let resp = self
+                    .bybit_request(Method::GET, "/v5/account/wallet-balance", params)
+                    .await?;
+                value_f64(&resp["result"]["list"][0]["totalAvailableBalance"])
+            },
+        };
+
+        if mark_price <= 0.0 {
+            return Err(InfraError::Msg(format!("No mark price to size {} margin", inst)));
+        }
+        let leverage = self.leverage(inst).await?.max(1.0);
+        Ok(available * leverage / mark_price)
+    }
+
+    async fn fetch_taker_fee_rate(&self, inst: &str) -> InfraResult<f64> {
+        match self.venue {
+            RestVenue::Okx => {
+                let family = okx_inst_id(inst).trim_end_matches("-SWAP").to_string();
+                let path = okx_path(
+                    "/api/v5/account/trade-fee",
+                    &[("instType", "SWAP"), ("instFamily", &family)],
+                )?;
+                let resp = self.okx_request(Method::GET, &path, None).await?;
+                let row = &resp["data"][0];
+                // OKX reports fees as negative rebates; `takerU` covers USDT-margined swaps
+                let taker = match row["takerU"].as_str() {
+                    Some(v) if !v.is_empty() => value_f64(&row["takerU"]),
+                    _ => value_f64(&row["taker"]),
+                };
+                Ok(-taker)
+            },
+            RestVenue::BinanceUm => {
+                let params = vec![("symbol".to_string(), binance_symbol(inst))];
+                let resp = self
+                    .binance_request(Method::GET, "/fapi/v1/commissionRate", params)
+                    .await?;
+                Ok(value_f64(&resp["takerCommissionRate"]))
+            },
+            RestVenue::Bybit => {
+                let resp = self
+                    .bybit_request(Method::GET, "/v5/account/fee-rate", bybit_params(inst))
+                    .await?;
+                Ok(value_f64(&resp["result"]["list"][0]["takerFeeRate"]))
+            },
+        }
+    }
+
+    /// Equity of `coin` in the unified trading account, unrealized PnL included.
+    pub async fn bybit_equity(&self, coin: &str) -> InfraResult<f64> {
+        let params = vec![
+            ("accountType".to_string(), "UNIFIED".to_string()),
+            ("coin".to_string(), coin.to_string()),
+        ];
+        let resp = self
+            .bybit_request(Method::GET, "/v5/account/wallet-balance", params)
+            .await?;
+
+        resp["result"]["list"][0]["coin"]
+            .as_array()
+            .and_then(|coins| {
+                coins
+                    .iter()
+                    .find(|c| c["coin"].as_str().is_some_and(|c| c.eq_ignore_ascii_case(coin)))
+            })
+            .map(|c| value_f64(&c["equity"]))
+            .ok_or_else(|| InfraError::Msg(format!("Bybit {} balance missing", coin)))
+    }
+
+    /// Open linear positions settled in `settle_coin` as `(inst, signed size, mark price)`.
+    pub async fn bybit_positions(&self, settle_coin: &str) -> InfraResult<Vec<(String, f64, f64)>> {
+        let mut positions = Vec::new();
+        let mut cursor = String::new();
+
+        loop {
+            let mut params = vec![
+                ("category".to_string(), "linear".to_string()),
+                ("settleCoin".to_string(), settle_coin.to_string()),
+                ("limit".to_string(), BYBIT_POSITION_PAGE_LIMIT.to_string()),
+            ];
+            if !cursor.is_empty() {
+                params.push(("cursor".to_string(), cursor.clone()));
+            }
+
+            let resp = self
+                .bybit_request(Method::GET, "/v5/position/list", params)
+                .await?;
+            for row in resp["result"]["list"].as_array().cloned().unwrap_or_default() {
+                let size = value_f64(&row["size"]);
+                if size <= 0.0 {
+                    continue;
+                }
+                let sign = if row["side"].as_str() == Some("Sell") { -1.0 } else { 1.0 };
+                positions.push((
+                    bybit_inst(row["symbol"].as_str().unwrap_or_default()),
+                    size * sign,
+                    value_f64(&row["markPrice"]),
+                ));
+            }
+
+            match resp["result"]["nextPageCursor"].as_str() {
+                Some(next) if !next.is_empty() && next != cursor => cursor = next.to_string(),
+                _ => break,
+            }
+        }
+
+        Ok(positions)
+    }
+
+    /// Equity of `coin` in the trading account, unrealized PnL included.
+    pub async fn okx_equity(&self, coin: &str) -> InfraResult<f64> {
+        let path = okx_path("/api/v5/account/balance", &[("ccy", coin)])?;
+        let resp = self.okx_request(Method::GET, &path, None).await?;
+
+        resp["data"][0]["details"]
+            .as_array()
+            .and_then(|details| {
+                details
+                    .iter()
+                    .find(|d| d["ccy"].as_str().is_some_and(|c| c.eq_ignore_ascii_case(coin)))
+            })
+            .map(|d| value_f64(&d["eq"]))
+            .ok_or_else(|| InfraError::Msg(format!("OKX {} balance missing", coin)))
+    }
+
+    /// Open swap positions as `(inst, signed contracts, mark price)`, like the exchange client.
+    pub async fn okx_positions(&self) -> InfraResult<Vec<(String, f64, f64)>> {
+        let resp = self
+            .okx_request(Method::GET, "/api/v5/account/positions?instType=SWAP", None)
+            .await?;
+
+        let mut positions = Vec::new();
+        for row in resp["data"].as_array().cloned().unwrap_or_default() {
+            let size = value_f64(&row["pos"]);
+            if size == 0.0 {
+                continue;
+            }
+            // Net mode signs `pos`; long/short mode reports both sides positive
+            let size = if row["posSide"].as_str() == Some("short") {
+                -size.abs()
+            } else {
+                size
+            };
+            positions.push((
+                okx_inst(row["instId"].as_str().unwrap_or_default()),
+                size,
+                value_f64(&row["markPx"]),
+            ));
+        }
+
+        Ok(positions)
+    }
+
+    /// Margin balance of `coin`, unrealized PnL included.
+    pub async fn binance_equity(&self, coin: &str) -> InfraResult<f64> {
+        let resp = self
+            .binance_request(Method::GET, "/fapi/v2/balance", Vec::new())
+            .await?;
+
+        resp.as_array()
+            .and_then(|assets| {
+                assets
+                    .iter()
+                    .find(|a| a["asset"].as_str().is_some_and(|c| c.eq_ignore_ascii_case(coin)))
+            })
+            .map(|a| value_f64(&a["balance"]) + value_f64(&a["crossUnPnl"]))
+            .ok_or_else(|| InfraError::Msg(format!("Binance {} balance missing", coin)))
+    }
+
+    /// Open positions as `(inst, signed size, mark price)`.
+    pub async fn binance_positions(&self) -> InfraResult<Vec<(String, f64, f64)>> {
+        let resp = self
+            .binance_request(Method::GET, "/fapi/v2/positionRisk", Vec::new())
+            .await?;
+
+        Ok(resp
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .filter(|row| value_f64(&row["positionAmt"]) != 0.0)
+            .map(|row| {
+                (
+                    bybit_inst(row["symbol"].as_str().unwrap_or_default()),
+                    value_f64(&row["positionAmt"]),
+                    value_f64(&row["markPrice"]),
+                )
+            })
+            .collect())
+    }
+
+    /// URL of the account's user data stream.
+    pub async fn binance_user_stream_url(&self) -> InfraResult<String> {
+        let path = "/fapi/v1/listenKey";
+        self.limiter
+            .acquire(RateVenue::Binance, binance_weight(path))
+            .await;
+
+        let resp = self
+            .client
+            .post(format!("{}{}", binance_base_url(self.testnet), path))
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| transport_error("Binance", path, e))?;
+
+        let status = resp.status();
+        let value: Value = resp.json().await.map_err(|e| {
+            FailureKind::unreadable(status.as_u16())
+                .error(format!("Binance response {} parse failed: {}", path, e))
+        })?;
+        if !status.is_success() {
+            return Err(binance_error(path, status.as_u16(), &value));
+        }
+
+        let listen_key = value["listenKey"]
+            .as_str()
+            .ok_or_else(|| InfraError::Msg(format!("Binance listen key missing: {}", value)))?;
+        let ws_url = if self.testnet {
+            BINANCE_UM_WS_TESTNET_URL
+        } else {
+            BINANCE_UM_WS_URL
+        };
+        Ok(format!("{}/ws/{}", ws_url, listen_key))
+    }
+
+    /// Places a swap order and returns the venue order id. Rebalance orders are isolated.
+    pub async fn okx_place_order(&self, order: &OrderParams) -> InfraResult<String> {
+        let side = if matches!(order.side, OrderSide::BUY) {
+            "buy"
+        } else {
+            "sell"
+        };
+        let ord_type = if matches!(order.order_type, OrderType::Market) {
+            "market"
+        } else if matches!(order.order_type, OrderType::PostOnly) {
+            "post_only"
+        } else if matches!(order.time_in_force, Some(TimeInForce::IOC)) {
+            "ioc"
+        } else {
+            "limit"
+        };
+        let td_mode = if order.margin_mode.is_some() {
+            "isolated"
+        } else {
+            "cross"
+        };
+
+        let mut body = json!({
+            "instId": okx_inst_id(&order.inst),
+            "tdMode": td_mode,
+            "side": side,
+            "ordType": ord_type,
+            "sz": order.size,
+        });
+        if let Some(price) = &order.price {
+            body["px"] = json!(price);
+        }
+        if order.reduce_only == Some(true) {
+            body["reduceOnly"] = json!(true);
+        }
+        if let Some(cl_id) = &order.client_order_id {
+            body["clOrdId"] = json!(cl_id);
+        }
+
+        let resp = self
+            .okx_request(Method::POST, "/api/v5/trade/order", Some(body))
+            .await?;
+        Ok(resp["data"][0]["ordId"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// Places a UM perpetual order and returns the venue order id.
+    pub async fn binance_place_order(&self, order: &OrderParams) -> InfraResult<String> {
+        let side = if matches!(order.side, OrderSide::BUY) {
+            "BUY"
+        } else {
+            "SELL"
+        };
+        let order_type = if matches!(order.order_type, OrderType::Market) {
+            "MARKET"
+        } else {
+            "LIMIT"
+        };
+
+        let mut params = vec![
+            ("symbol".to_string(), binance_symbol(&order.inst)),
+            ("side".to_string(), side.to_string()),
+            ("type".to_string(), order_type.to_string()),
+            ("quantity".to_string(), order.size.clone()),
+        ];
+        if let Some(price) = &order.price {
+            params.push(("price".to_string(), price.clone()));
+        }
+        if matches!(order.order_type, OrderType::PostOnly) {
+            params.push(("timeInForce".to_string(), "GTX".to_string()));
+        } else if matches!(order.time_in_force, Some(TimeInForce::IOC)) {
+            params.push(("timeInForce".to_string(), "IOC".to_string()));
+        } else if order_type == "LIMIT" {
+            params.push(("timeInForce".to_string(), "GTC".to_string()));
+        }
+        if order.reduce_only == Some(true) {
+            params.push(("reduceOnly".to_string(), "true".to_string()));
+        }
+        if let Some(cl_id) = &order.client_order_id {
+            params.push(("newClientOrderId".to_string(), cl_id.clone()));
+        }
+
+        let resp = self
+            .binance_request(Method::POST, "/fapi/v1/order", params)
+            .await?;
+        Ok(resp["orderId"].to_string())
+    }
+
+    /// Places a linear perpetual order and returns the venue order id.
+    pub async fn bybit_place_order(&self, order: &OrderParams) -> InfraResult<String> {
+        let side = if matches!(order.side, OrderSide::BUY) {
+            "Buy"
+        } else {
+            "Sell"
+        };
+        let order_type = if matches!(order.order_type, OrderType::Market) {
+            "Market"
+        } else {
+            "Limit"
+        };
+
+        let mut params = bybit_params(&order.inst);
+        params.push(("side".to_string(), side.to_string()));
+        params.push(("orderType".to_string(), order_type.to_string()));
+        params.push(("qty".to_string(), order.size.clone()));
+        if let Some(price) = &order.price {
+            params.push(("price".to_string(), price.clone()));
+        }
+        if matches!(order.order_type, OrderType::PostOnly) {
+            params.push(("timeInForce".to_string(), "PostOnly".to_string()));
+        } else if matches!(order.time_in_force, Some(TimeInForce::IOC)) {
+            params.push(("timeInForce".to_string(), "IOC".to_string()));
+        }
+        if order.reduce_only == Some(true) {
+            params.push(("reduceOnly".to_string(), "true".to_string()));
+        }
+        if let Some(cl_id) = &order.client_order_id {
+            params.push(("orderLinkId".to_string(), cl_id.clone()));
+        }
+
+        let resp = self
+            .bybit_request(Method::POST, "/v5/order/create", params)
+            .await?;
+        Ok(resp["result"]["orderId"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    pub fn bybit_ws_url(&self) -> String {
+        let url = if self.testnet {
+            BYBIT_WS_TESTNET_URL
+        } else {
+            BYBIT_WS_PRIVATE_URL
+        };
+        url.to_string()
+    }
+
+    /// Private stream `auth` op, signed as `GET/realtime<expires>`.
+    pub async fn bybit_ws_login_msg(&self) -> InfraResult<String> {
+        let expires = self.signed_now().await + BYBIT_RECV_WINDOW_MS;
+        let sign = hex::encode(hmac_sha256(
+            &self.api_secret,
+            &format!("GET/realtime{}", expires),
+        )?);
+        Ok(json!({ "op": "auth", "args": [self.api_key, expires, sign] }).to_string())
+    }
+
+    pub async fn okx_request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> InfraResult<Value> {
+        if method != Method::GET {
+            active_profile().ensure_orders_allowed(path)?;
+        }
+        self.limiter.acquire(RateVenue::Okx, 1.0).await;
+
+        let body_str = body.map(|b| b.to_string()).unwrap_or_default();
+        let timestamp = iso8601_millis(self.signed_now().await);
+        let prehash = format!("{}{}{}{}", timestamp, method.as_str(), path, body_str);
+        let sign = BASE64.encode(hmac_sha256(&self.api_secret, &prehash)?);
+
+        let mut request = self
+            .client
+            .request(method, format!("{}{}", OKX_BASE_URL, path))
+            .header("OK-ACCESS-KEY", &self.api_key)
+            .header("OK-ACCESS-SIGN", sign)
+            .header("OK-ACCESS-TIMESTAMP", timestamp)
+            .header("OK-ACCESS-PASSPHRASE", &self.passphrase)
+            .header("Content-Type", "application/json");
+        if self.testnet {
+            request = request.header("x-simulated-trading", "1");
+        }
+
+        let resp = request
+            .body(body_str)
+            .send()
+            .await
+            .map_err(|e| transport_error("OKX", path, e))?;
+
+        let status = resp.status();
+        let value: Value = resp.json().await.map_err(|e| {
+            FailureKind::unreadable(status.as_u16())
+                .error(format!("OKX response {} parse failed: {}", path, e))
+        })?;
+
+        if value["code"].as_str() != Some("0") {
+            // Order endpoints report the per-order code in `sCode`
+            let code = value["data"][0]["sCode"]
+                .as_str()
+                .filter(|c| *c != "0")
+                .or(value["code"].as_str());
+            return Err(FailureKind::classify(Some(status.as_u16()), code)
+                .error(format!("OKX {} error: {}", path, value)));
+        }
+
+        Ok(value)
+    }
+
+    pub async fn binance_request(
+        &self,
+        method: Method,
+        path: &str,
+        mut params: Vec<(String, String)>,
+    ) -> InfraResult<Value> {
+        if method != Method::GET {
+            active_profile().ensure_orders_allowed(path)?;
+        }
+        self.limiter
+            .acquire(RateVenue::Binance, binance_weight(path))
+            .await;
+
+        params.push(("recvWindow".to_string(), BINANCE_RECV_WINDOW_MS.to_string()));
+        params.push(("timestamp".to_string(), self.signed_now().await.to_string()));
+
+        let query = encode_query(&params)?;
+        let sign = hex::encode(hmac_sha256(&self.api_secret, &query)?);
+
+        let base_url = binance_base_url(self.testnet);
+        let resp = self
+            .client
+            .request(
+                method,
+                format!("{}{}?{}&signature={}", base_url, path, query, sign),
+            )
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| transport_error("Binance", path, e))?;
+
+        let status = resp.status();
+        let value: Value = resp.json().await.map_err(|e| {
+            FailureKind::unreadable(status.as_u16())
+                .error(format!("Binance response {} parse failed: {}", path, e))
+        })?;
+
+        if !status.is_success() {
+            return Err(binance_error(path, status.as_u16(), &value));
+        }
+
+        Ok(value)
+    }
+
+    /// Bybit v5: GET parameters go in the query, others in a JSON body.
+    pub async fn bybit_request(
+        &self,
+        method: Method,
+        path: &str,
+        params: Vec<(String, String)>,
+    ) -> InfraResult<Value> {
+        if method != Method::GET {
+            active_profile().ensure_orders_allowed(path)?;
+        }
+        self.limiter.acquire(RateVenue::Bybit, 1.0).await;
+
+        let is_get = method == Method::GET;
+        let payload = if is_get {
+            encode_query(&params)?
+        } else {
+            // Flags such as `reduceOnly` are JSON booleans, every other field a string
+            let field = |v: String| match v.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => Value::String(v),
+            };
+            Value::Object(params.into_iter().map(|(k, v)| (k, field(v))).collect()).to_string()
+        };
+
+        let timestamp = self.signed_now().await.to_string();
+        let prehash = format!("{}{}{}{}", timestamp, self.api_key, BYBIT_RECV_WINDOW_MS, payload);
+        let sign = hex::encode(hmac_sha256(&self.api_secret, &prehash)?);
+
+        let base_url = bybit_base_url(self.testnet);
+        let request = if is_get {
+            self.client
+                .request(method, format!("{}{}?{}", base_url, path, payload))
+        } else {
+            self.client
+                .request(method, format!("{}{}", base_url, path))
+                .header("Content-Type", "application/json")
+                .body(payload)
+        };
+
+        let resp = request
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-SIGN", sign)
+            .header("X-BAPI-TIMESTAMP", timestamp)
+            .header("X-BAPI-RECV-WINDOW", BYBIT_RECV_WINDOW_MS.to_string())
+            .send()
+            .await
+            .map_err(|e| transport_error("Bybit", path, e))?;
+
+        let status = resp.status();
+        let value: Value = resp.json().await.map_err(|e| {
+            FailureKind::unreadable(status.as_u16())
+                .error(format!("Bybit response {} parse failed: {}", path, e))
+        })?;
+
+        if value["retCode"].as_i64() != Some(0) {
+            let code = value["retCode"].as_i64().map(|c| c.to_string());
+            return Err(FailureKind::classify(Some(status.as_u16()), code.as_deref())
+                .error(format!("Bybit {} error: {}", path, value)));
+        }
+
+        Ok(value)
+    }
+
+    /// Unsigned market data call on the same host as the signed endpoints.
+    pub async fn binance_public_request(
+        &self,
+        path: &str,
+        params: Vec<(String, String)>,
+    ) -> InfraResult<Value> {
+        self.limiter
+            .acquire(RateVenue::Binance, binance_weight(path))
+            .await;
+        let base_url = binance_base_url(self.testnet);
+
+        let resp = self
+            .client
+            .get(format!("{}{}", base_url, path))
+            .query(&params)
+            .send()
+            .await
+            .map_err(|e| transport_error("Binance", path, e))?;
+
+        let status = resp.status();
+        let value: Value = resp.json().await.map_err(|e| {
+            FailureKind::unreadable(status.as_u16())
+                .error(format!("Binance response {} parse failed: {}", path, e))
+        })?;
+
+        if !status.is_success() {
+            return Err(binance_error(path, status.as_u16(), &value));
+        }
+
+        Ok(value)
+    }
+}
+
+/// A request that could not be built never reached the venue; any other transport failure may have.
+pub fn transport_error(venue: &str, path: &str, e: reqwest::Error) -> InfraError {
+    let kind = if e.is_builder() {
+        FailureKind::Rejected
+    } else {
+        FailureKind::Transient
+    };
+    kind.error(format!("{} request {} failed: {}", venue, path, e))
+}
+
+fn binance_error(path: &str, status: u16, value: &Value) -> InfraError {
+    let code = value["code"].as_i64().map(|c| c.to_string());
+    FailureKind::classify(Some(status), code.as_deref())
+        .error(format!("Binance {} error ({}): {}", path, status, value))
+}
+
+fn value_f64(v: &Value) -> f64 {
+    v.as_str()
+        .and_then(|s| s.parse::<f64>().ok())
+        .or_else(|| v.as_f64())
+        .unwrap_or_default()
+}
+
+fn binance_order_param(order: &OrderRef) -> (String, String) {
+    match order {
+        OrderRef::Id(id) => ("orderId".to_string(), id.clone()),
+        OrderRef::ClientId(id) => ("origClientOrderId".to_string(), id.clone()),
+    }
+}
+
+fn bybit_order_param(order: &OrderRef) -> (String, String) {
+    match order {
+        OrderRef::Id(id) => ("orderId".to_string(), id.clone()),
+        OrderRef::ClientId(id) => ("orderLinkId".to_string(), id.clone()),
+    }
+}
+
+fn bybit_params(inst: &str) -> Vec<(String, String)> {
+    vec![
+        ("category".to_string(), "linear".to_string()),
+        ("symbol".to_string(), bybit_symbol(inst)),
+    ]
+}
+
+pub fn binance_base_url(testnet: bool) -> &'static str {
+    if testnet {
+        BINANCE_UM_TESTNET_URL
+    } else {
+        BINANCE_UM_BASE_URL
+    }
+}
+
+fn bybit_base_url(testnet: bool) -> &'static str {
+    if testnet {
+        BYBIT_TESTNET_URL
+    } else {
+        BYBIT_BASE_URL
+    }
+}
+
+/// Subscription for the private stream behind an account channel.
+pub fn bybit_ws_sub_msg(channel: &WsChannel) -> InfraResult<String> {
+    let topics = match channel {
+        WsChannel::AccountOrders => vec!["order"],
+        WsChannel::AccountBalAndPos => vec!["position", "wallet"],
+        _ => {
+            return Err(InfraError::Msg(format!(
+                "Unsupported Bybit private channel: {:?}",
+                channel
+            )));
+        },
+    };
+    Ok(json!({ "op": "subscribe", "args": topics }).to_string())
+}
+
+/// Linear perpetuals in trading, paged through `instruments-info`. Sizes are base quantity.
+pub async fn bybit_instrument_info(client: &Client) -> InfraResult<Vec<InstrumentInfo>> {
+    let url = format!(
+        "{}/v5/market/instruments-info",
+        bybit_base_url(active_profile().testnet)
+    );
+    let mut infos = Vec::new();
+    let mut cursor = String::new();
+
+    loop {
+        let mut params = vec![
+            ("category".to_string(), "linear".to_string()),
+            ("limit".to_string(), BYBIT_INST_PAGE_LIMIT.to_string()),
+        ];
+        if !cursor.is_empty() {
+            params.push(("cursor".to_string(), cursor.clone()));
+        }
+
+        let value: Value = client
+            .get(&url)
+            .query(&params)
+            .send()
+            .await
+            .map_err(|e| InfraError::Msg(format!("Bybit instruments request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| InfraError::Msg(format!("Bybit instruments parse failed: {}", e)))?;
+        if value["retCode"].as_i64() != Some(0) {
+            return Err(InfraError::Msg(format!("Bybit instruments error: {}", value)));
+        }
+
+        let rows = value["result"]["list"].as_array().cloned().unwrap_or_default();
+        for row in rows.iter().filter(|r| {
+            r["contractType"].as_str() == Some("LinearPerpetual")
+                && r["status"].as_str() == Some("Trading")
+        }) {
+            let lot = &row["lotSizeFilter"];
+            infos.push(InstrumentInfo {
+                inst: bybit_inst(row["symbol"].as_str().unwrap_or_default()),
+                contract_value: Some(1.0),
+                min_lmt_size: value_f64(&lot["minOrderQty"]),
+                min_mkt_size: value_f64(&lot["minOrderQty"]),
+                max_lmt_size: value_f64(&lot["maxOrderQty"]),
+                max_mkt_size: value_f64(&lot["maxMktOrderQty"]),
+                lot_size: value_f64(&lot["qtyStep"]),
+                tick_size: value_f64(&row["priceFilter"]["tickSize"]),
+                min_notional: Some(value_f64(&lot["minNotionalValue"])),
+                ..Default::default()
+            });
+        }
+
+        match value["result"]["nextPageCursor"].as_str() {
+            Some(next) if !next.is_empty() && next != cursor => cursor = next.to_string(),
+            _ => break,
+        }
+    }
+
+    Ok(infos)
+}
+
+/// Live swaps of the OKX demo environment, which the exchange client cannot reach.
+pub async fn okx_testnet_instrument_info(client: &Client) -> InfraResult<Vec<InstrumentInfo>> {
+    let value: Value = client
+        .get(format!("{}/api/v5/public/instruments", OKX_BASE_URL))
+        .query(&[("instType", "SWAP")])
+        .header("x-simulated-trading", "1")
+        .send()
+        .await
+        .map_err(|e| InfraError::Msg(format!("OKX instruments request failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| InfraError::Msg(format!("OKX instruments parse failed: {}", e)))?;
+    if value["code"].as_str() != Some("0") {
+        return Err(InfraError::Msg(format!("OKX instruments error: {}", value)));
+    }
+
+    let rows = value["data"].as_array().cloned().unwrap_or_default();
+    Ok(rows
+        .iter()
+        .filter(|r| r["state"].as_str() == Some("live"))
+        .map(|row| InstrumentInfo {
+            inst: okx_inst(row["instId"].as_str().unwrap_or_default()),
+            contract_value: Some(value_f64(&row["ctVal"])),
+            min_lmt_size: value_f64(&row["minSz"]),
+            min_mkt_size: value_f64(&row["minSz"]),
+            max_lmt_size: value_f64(&row["maxLmtSz"]),
+            max_mkt_size: value_f64(&row["maxMktSz"]),
+            lot_size: value_f64(&row["lotSz"]),
+            tick_size: value_f64(&row["tickSz"]),
+            ..Default::default()
+        })
+        .collect())
+}
+
+/// Perpetuals in trading on the Binance UM testnet, which the exchange client cannot reach.
+pub async fn binance_um_testnet_instrument_info(
+    client: &Client,
+) -> InfraResult<Vec<InstrumentInfo>> {
+    let value: Value = client
+        .get(format!("{}/fapi/v1/exchangeInfo", BINANCE_UM_TESTNET_URL))
+        .send()
+        .await
+        .map_err(|e| InfraError::Msg(format!("Binance instruments request failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| InfraError::Msg(format!("Binance instruments parse failed: {}", e)))?;
+
+    let rows = value["symbols"].as_array().cloned().ok_or_else(|| {
+        InfraError::Msg(format!("Binance instruments error: {}", value))
+    })?;
+    Ok(rows
+        .iter()
+        .filter(|r| {
+            r["contractType"].as_str() == Some("PERPETUAL")
+                && r["status"].as_str() == Some("TRADING")
+        })
+        .map(|row| {
+            let filter = |kind: &str| {
+                row["filters"]
+                    .as_array()
+                    .and_then(|filters| {
+                        filters
+                            .iter()
+                            .find(|f| f["filterType"].as_str() == Some(kind))
+                            .cloned()
+                    })
+                    .unwrap_or_default()
+            };
+            let (lot, market_lot) = (filter("LOT_SIZE"), filter("MARKET_LOT_SIZE"));
+            InstrumentInfo {
+                inst: bybit_inst(row["symbol"].as_str().unwrap_or_default()),
+                contract_value: Some(1.0),
+                min_lmt_size: value_f64(&lot["minQty"]),
+                min_mkt_size: value_f64(&market_lot["minQty"]),
+                max_lmt_size: value_f64(&lot["maxQty"]),
+                max_mkt_size: value_f64(&market_lot["maxQty"]),
+                lot_size: value_f64(&lot["stepSize"]),
+                tick_size: value_f64(&filter("PRICE_FILTER")["tickSize"]),
+                min_notional: Some(value_f64(&filter("MIN_NOTIONAL")["notional"])),
+                ..Default::default()
+            }
+        })
+        .collect())
+}
+
+/// `DOGE_USDT_PERP` -> `DOGE-USDT-SWAP`
+pub fn okx_inst_id(inst: &str) -> String {
+    match inst.strip_suffix("_PERP") {
+        Some(pair) => format!("{}-SWAP", pair.replace('_', "-")),
+        None => inst.replace('_', "-"),
+    }
+}
+
+/// `DOGE-USDT-SWAP` -> `DOGE_USDT_PERP`
+pub fn okx_inst(inst_id: &str) -> String {
+    match inst_id.strip_suffix("-SWAP") {
+        Some(pair) => format!("{}_PERP", pair.replace('-', "_")),
+        None => inst_id.replace('-', "_"),
+    }
+}
+
+/// Stream URL of an exchange client, moved to the venue's testnet host on a testnet profile.
+pub fn profile_ws_url(url: String) -> InfraResult<String> {
+    if !active_profile().testnet {
+        return Ok(url);
+    }
+
+    TESTNET_WS_HOSTS
+        .iter()
+        .find_map(|(live, testnet)| {
+            url.strip_prefix(live)
+                .map(|path| format!("{}{}", testnet, path))
+        })
+        .ok_or_else(|| InfraError::Msg(format!("No testnet host for stream {}", url)))
+}
+
+/// `DOGE_USDT_PERP` -> `DOGEUSDT`
+pub fn binance_symbol(inst: &str) -> String {
+    inst.trim_end_matches("_PERP").replace('_', "")
+}
+
+/// `DOGE_USDT_PERP` -> `DOGEUSDT`, the same symbols as Binance UM.
+pub fn bybit_symbol(inst: &str) -> String {
+    binance_symbol(inst)
+}
+
+/// `DOGEUSDT` -> `DOGE_USDT_PERP`
+pub fn bybit_inst(symbol: &str) -> String {
+    ["USDT", "USDC"]
+        .iter()
+        .find_map(|quote| {
+            symbol
+                .strip_suffix(quote)
+                .map(|base| format!("{}_{}_PERP", base, quote))
+        })
+        .unwrap_or_else(|| symbol.to_string())
+}
+
+/// Percent-encodes `params` as a query string, in order.
+pub fn encode_query<T: Serialize + ?Sized>(params: &T) -> InfraResult<String> {
+    serde_urlencoded::to_string(params)
+        .map_err(|e| InfraError::Msg(format!("Failed to encode query: {}", e)))
+}
+
+/// `path?query` of a signed OKX GET, whose query is part of the signed path.
+pub fn okx_path(path: &str, params: &[(&str, &str)]) -> InfraResult<String> {
+    Ok(format!("{}?{}", path, encode_query(params)?))
+}
+
+fn hmac_sha256(secret: &str, payload: &str) -> InfraResult<Vec<u8>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| InfraError::Msg(format!("Invalid api secret: {}", e)))?;
+    mac.update(payload.as_bytes());
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Formats unix millis as `2020-12-08T09:08:57.715Z` (OKX signature timestamp).
+pub fn iso8601_millis(ms: u64) -> String {
+    let secs = ms / 1000;
+    let days = (secs / 86_400) as i64;
+    let sod = secs % 86_400;
+
+    // civil_from_days (H. Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        sod / 3600,
+        (sod % 3600) / 60,
+        sod % 60,
+        ms % 1000,
+    )
+}
@@ -0,0 +1,164 @@
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::warn;
+
+use extrema_infra::prelude::*;
+
+use super::acc_utils::jitter;
+
+/// Venue codes of requests rejected by a rate limit before execution.
+const RATE_LIMIT_CODES: &[&str] = &["50011", "50061", "10006", "10018", "-1003", "-1015"];
+/// Venue codes where the request may or may not have executed.
+const TRANSIENT_CODES: &[&str] = &["50001", "50004", "50013", "10000", "10016", "-1001", "-1007"];
+
+/// Why a venue REST call failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureKind {
+    /// Rejected before execution by a rate limit.
+    RateLimited,
+    /// May or may not have reached the venue.
+    Transient,
+    /// Anything else, including errors of the library clients, which carry no status.
+    Rejected,
+}
+
+impl FailureKind {
+    const TAGS: [(FailureKind, &'static str); 2] = [
+        (FailureKind::RateLimited, "[rate_limited] "),
+        (FailureKind::Transient, "[transient] "),
+    ];
+
+    /// From the HTTP status and the venue's own error code, either may be absent.
+    pub fn classify(status: Option<u16>, code: Option<&str>) -> Self {
+        if status == Some(429) || code.is_some_and(|c| RATE_LIMIT_CODES.contains(&c)) {
+            FailureKind::RateLimited
+        } else if status.is_some_and(|s| (500..600).contains(&s))
+            || code.is_some_and(|c| TRANSIENT_CODES.contains(&c))
+        {
+            FailureKind::Transient
+        } else {
+            FailureKind::Rejected
+        }
+    }
+
+    /// Response without a readable body, e.g. a gateway page.
+    pub fn unreadable(status: u16) -> Self {
+        match status {
+            429 => FailureKind::RateLimited,
+            _ => FailureKind::Transient,
+        }
+    }
+
+    /// Builds the error of a failed call, tagged with its kind.
+    pub fn error(self, msg: String) -> InfraError {
+        match Self::TAGS.iter().find(|(kind, _)| *kind == self) {
+            Some((_, tag)) => InfraError::Msg(format!("{}{}", tag, msg)),
+            None => InfraError::Msg(msg),
+        }
+    }
+
+    /// The kind an error was tagged with by `error`; untagged errors are `Rejected`.
+    pub fn of(err: &InfraError) -> Self {
+        let InfraError::Msg(msg) = err else {
+            return FailureKind::Rejected;
+        };
+        Self::TAGS
+            .iter()
+            .find(|(_, tag)| msg.starts_with(tag))
+            .map_or(FailureKind::Rejected, |(kind, _)| *kind)
+    }
+}
+
+/// Retry policy for REST order placement and balance/position reads.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Attempts including the first one.
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 250,
+            max_delay_ms: 4000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential delay before retry `attempt` (1-based), plus up to the same again as jitter.
+    fn delay(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay_ms
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(self.max_delay_ms);
+        Duration::from_millis(exp) + jitter(exp)
+    }
+}
+
+/// Whether a failed venue call is safe and worth retrying.
+pub fn is_retryable(err: &InfraError, idempotent: bool) -> bool {
+    idempotent
+        && matches!(
+            FailureKind::of(err),
+            FailureKind::RateLimited | FailureKind::Transient
+        )
+}
+
+/// Runs `op` until it succeeds, fails with a fatal error or runs out of attempts.
+pub async fn with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    label: &str,
+    idempotent: bool,
+    mut op: F,
+) -> InfraResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = InfraResult<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts && is_retryable(&e, idempotent) => {
+                let delay = policy.delay(attempt);
+                warn!(
+                    "[Retry] {} attempt {}/{} failed, retrying in {:?}: {}",
+                    label, attempt, policy.max_attempts, delay, e
+                );
+                sleep(delay).await;
+                attempt += 1;
+            },
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_on_status_and_code_only() {
+        assert_eq!(FailureKind::classify(Some(429), None), FailureKind::RateLimited);
+        assert_eq!(FailureKind::classify(Some(200), Some("50011")), FailureKind::RateLimited);
+        assert_eq!(FailureKind::classify(Some(503), None), FailureKind::Transient);
+        assert_eq!(FailureKind::classify(Some(400), Some("-1007")), FailureKind::Transient);
+        // An order id or price that happens to contain "429" is not a rate limit
+        let err = InfraError::Msg("OKX /api/v5/trade/order error: ordId 4290001".into());
+        assert_eq!(FailureKind::of(&err), FailureKind::Rejected);
+    }
+
+    #[test]
+    fn placements_without_client_id_are_never_retried() {
+        let limited = FailureKind::RateLimited.error("Bybit /v5/order/create error".into());
+        assert!(is_retryable(&limited, true));
+        assert!(!is_retryable(&limited, false));
+
+        let rejected = FailureKind::classify(Some(400), Some("51008")).error("rejected".into());
+        assert!(!is_retryable(&rejected, true));
+    }
+}
@@ -0,0 +1,79 @@
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Why the rebalancer left an instrument's diff unsent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    NoMarkPrice,
+    NoInstInfo,
+    BelowMinNotional,
+    OrderSize,
+    PositionCap,
+    LeverageUnset,
+    AssetCap,
+    /// The model's price limit is already crossed by the mark.
+    PriceLimit,
+    /// An identical order intent of another rebalance pass is still open.
+    DuplicateIntent,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Skip {
+    inst: String,
+    reason: SkipReason,
+    detail: String,
+}
+
+/// Instruments skipped by one account's rebalancer.
+#[derive(Clone, Debug, Default)]
+pub struct SkipLog {
+    tick: Vec<Skip>,
+    counts: HashMap<String, HashMap<SkipReason, u64>>,
+}
+
+impl SkipLog {
+    pub fn record(&mut self, inst: &str, reason: SkipReason, detail: impl Into<String>) {
+        *self
+            .counts
+            .entry(inst.to_string())
+            .or_default()
+            .entry(reason)
+            .or_insert(0) += 1;
+        self.tick.push(Skip {
+            inst: inst.to_string(),
+            reason,
+            detail: detail.into(),
+        });
+    }
+
+    /// Logs the skips of the pass that just ran and starts the next one.
+    pub fn flush(&mut self, account_id: &str) {
+        if self.tick.is_empty() {
+            return;
+        }
+
+        let mut by_reason: HashMap<SkipReason, Vec<&str>> = HashMap::new();
+        for skip in self.tick.iter() {
+            by_reason.entry(skip.reason).or_default().push(&skip.inst);
+        }
+        warn!(
+            "[Skip] {} skipped {} instrument(s): {}",
+            account_id,
+            self.tick.len(),
+            json!({ "by_reason": by_reason, "skips": self.tick })
+        );
+        self.tick.clear();
+    }
+
+    /// Skips per instrument and reason since the instrument was last dropped as idle.
+    pub fn counts(&self) -> &HashMap<String, HashMap<SkipReason, u64>> {
+        &self.counts
+    }
+
+    pub fn forget(&mut self, inst: &str) {
+        self.counts.remove(inst);
+    }
+}
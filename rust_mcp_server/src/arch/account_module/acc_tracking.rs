@@ -0,0 +1,164 @@
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    time::Instant,
+};
+use tracing::info;
+
+use extrema_infra::prelude::*;
+
+use super::acc_rest::now_millis;
+
+#[derive(Clone, Debug, Default)]
+struct InstTracking {
+    samples: u64,
+    abs_error_sum: f64,
+    max_abs_error: f64,
+    last_target: Option<f64>,
+    changed_at: Option<Instant>,
+    target_changes: u64,
+    converge_secs: Vec<f64>,
+}
+
+/// Execution quality of one account/instrument over a report window.
+#[derive(Clone, Debug, Serialize)]
+pub struct TrackingRow {
+    pub account_id: String,
+    pub inst: String,
+    pub samples: u64,
+    /// Mean |target − actual| weight over the rebalancer's evaluations.
+    pub mean_abs_error: f64,
+    pub max_abs_error: f64,
+    pub target_changes: u64,
+    /// Time from a target change until the gap first fell under `converged_tolerance`.
+    pub mean_converge_sec: Option<f64>,
+    pub max_converge_sec: Option<f64>,
+    /// Age of a target change that has not converged yet.
+    pub unconverged_sec: Option<f64>,
+}
+
+/// Tracking error of achieved versus target weights.
+#[derive(Clone, Debug)]
+pub struct TrackingReport {
+    path: PathBuf,
+    stats: HashMap<(String, String), InstTracking>,
+}
+
+impl TrackingReport {
+    pub fn new(file_name: &str) -> Self {
+        let mut path = current_dir().unwrap_or_default();
+        path.push(file_name);
+        Self {
+            path,
+            stats: HashMap::new(),
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        account_id: &str,
+        targets: &HashMap<String, f64>,
+        actual: &HashMap<String, f64>,
+        converged_tolerance: f64,
+    ) {
+        for (inst, target) in targets.iter() {
+            let current = actual.get(inst).copied().unwrap_or(0.0);
+            let error = (target - current).abs();
+
+            let s = self
+                .stats
+                .entry((account_id.to_string(), inst.clone()))
+                .or_default();
+            s.samples += 1;
+            s.abs_error_sum += error;
+            s.max_abs_error = s.max_abs_error.max(error);
+
+            if s
+                .last_target
+                .is_some_and(|last| (last - target).abs() > f64::EPSILON)
+            {
+                s.target_changes += 1;
+                s.changed_at = Some(Instant::now());
+            }
+            s.last_target = Some(*target);
+
+            if error < converged_tolerance
+                && let Some(changed_at) = s.changed_at.take()
+            {
+                s.converge_secs.push(changed_at.elapsed().as_secs_f64());
+            }
+        }
+    }
+
+    pub fn rows(&self) -> Vec<TrackingRow> {
+        let mut rows: Vec<TrackingRow> = self
+            .stats
+            .iter()
+            .filter(|(_, s)| s.samples > 0)
+            .map(|((account_id, inst), s)| {
+                let converged = s.converge_secs.len();
+                TrackingRow {
+                    account_id: account_id.clone(),
+                    inst: inst.clone(),
+                    samples: s.samples,
+                    mean_abs_error: s.abs_error_sum / s.samples as f64,
+                    max_abs_error: s.max_abs_error,
+                    target_changes: s.target_changes,
+                    mean_converge_sec: (converged > 0)
+                        .then(|| s.converge_secs.iter().sum::<f64>() / converged as f64),
+                    max_converge_sec: s.converge_secs.iter().copied().reduce(f64::max),
+                    unconverged_sec: s.changed_at.map(|t| t.elapsed().as_secs_f64()),
+                }
+            })
+            .collect();
+
+        rows.sort_by(|a, b| (&a.account_id, &a.inst).cmp(&(&b.account_id, &b.inst)));
+        rows
+    }
+
+    /// Logs and appends the window's rows, then starts a new window.
+    pub fn flush(&mut self) -> InfraResult<()> {
+        let rows = self.rows();
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        for r in rows.iter() {
+            info!(
+                "[Tracking] account={} inst={} mean_err={:.4} max_err={:.4} changes={} \
+                 converge_mean={:?}s converge_max={:?}s unconverged={:?}s",
+                r.account_id,
+                r.inst,
+                r.mean_abs_error,
+                r.max_abs_error,
+                r.target_changes,
+                r.mean_converge_sec,
+                r.max_converge_sec,
+                r.unconverged_sec,
+            );
+        }
+
+        let line = serde_json::json!({ "timestamp": now_millis(), "rows": rows });
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| InfraError::Msg(format!("Failed to open tracking report: {}", e)))?;
+        writeln!(file, "{}", line)
+            .map_err(|e| InfraError::Msg(format!("Failed to write tracking report: {}", e)))?;
+
+        for s in self.stats.values_mut() {
+            *s = InstTracking {
+                last_target: s.last_target,
+                changed_at: s.changed_at,
+                ..Default::default()
+            };
+        }
+
+        Ok(())
+    }
+}
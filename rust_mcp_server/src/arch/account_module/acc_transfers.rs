@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant};
+
+/// Spacing of the transfer history reads.
+pub const TRANSFER_REFRESH: Duration = Duration::from_secs(60);
+
+/// Net deposits and withdrawals of an account since the process started.
+#[derive(Clone, Debug, Default)]
+pub struct TransferLedger {
+    net: f64,
+    /// End of the last window read from the venue.
+    checked_ms: Option<u64>,
+    refreshed_at: Option<Instant>,
+}
+
+impl TransferLedger {
+    /// Transfers in minus transfers out, in the margin asset.
+    pub fn net(&self) -> f64 {
+        self.net
+    }
+
+    /// Window still to be read once `TRANSFER_REFRESH` has passed.
+    pub fn due(&mut self, now_ms: u64) -> Option<(u64, u64)> {
+        let Some(checked_ms) = self.checked_ms else {
+            self.checked_ms = Some(now_ms);
+            self.refreshed_at = Some(Instant::now());
+            return None;
+        };
+        if self
+            .refreshed_at
+            .is_some_and(|at| at.elapsed() < TRANSFER_REFRESH)
+        {
+            return None;
+        }
+        Some((checked_ms + 1, now_ms))
+    }
+
+    pub fn add(&mut self, amount: f64, until_ms: u64) {
+        self.net += amount;
+        self.checked_ms = Some(until_ms);
+        self.refreshed_at = Some(Instant::now());
+    }
+}
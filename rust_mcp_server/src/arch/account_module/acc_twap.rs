@@ -0,0 +1,115 @@
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// Target moves smaller than this keep a running TWAP alive.
+const TWAP_TARGET_TOLERANCE: f64 = 1e-9;
+
+/// Per-account TWAP execution.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct TwapConfig {
+    pub notional: f64,
+    pub slices: u32,
+    pub window_sec: u64,
+}
+
+/// One instrument's running TWAP.
+#[derive(Clone, Debug)]
+pub struct TwapPlan {
+    /// Target weight the plan executes towards; any other target cancels it.
+    pub target: f64,
+    pub slices_left: u32,
+    pub next_at: Instant,
+    pub interval: Duration,
+}
+
+/// Execution scheduler for large weight changes.
+#[derive(Clone, Debug, Default)]
+pub struct TwapScheduler {
+    plans: HashMap<String, TwapPlan>,
+}
+
+impl TwapScheduler {
+    /// Drops plans whose target changed and returns their instruments.
+    pub fn cancel_changed(&mut self, targets: &HashMap<String, f64>) -> Vec<String> {
+        let changed: Vec<String> = self
+            .plans
+            .iter()
+            .filter(|(inst, plan)| {
+                targets
+                    .get(*inst)
+                    .is_none_or(|t| (t - plan.target).abs() > TWAP_TARGET_TOLERANCE)
+            })
+            .map(|(inst, _)| inst.clone())
+            .collect();
+
+        for inst in changed.iter() {
+            if let Some(plan) = self.plans.remove(inst) {
+                warn!(
+                    "[Twap] Target of {} changed mid-execution, cancelled {} remaining slices",
+                    inst, plan.slices_left
+                );
+            }
+        }
+
+        changed
+    }
+
+    /// Rewrites `diffs` into this tick's TWAP child orders.
+    pub fn schedule(
+        &mut self,
+        config: &TwapConfig,
+        total_equity: f64,
+        targets: &HashMap<String, f64>,
+        pending: &HashMap<String, f64>,
+        diffs: &mut HashMap<String, f64>,
+    ) {
+        let now = Instant::now();
+        self.plans.retain(|inst, _| pending.contains_key(inst));
+
+        diffs.retain(|inst, diff| {
+            if let Some(plan) = self.plans.get_mut(inst) {
+                if now < plan.next_at {
+                    return false;
+                }
+
+                *diff /= plan.slices_left as f64;
+                plan.slices_left -= 1;
+                plan.next_at = now + plan.interval;
+                info!("[Twap] {} slice {:.6}, {} left", inst, diff, plan.slices_left);
+                if plan.slices_left == 0 {
+                    self.plans.remove(inst);
+                }
+                return true;
+            }
+
+            let notional = diff.abs() * total_equity;
+            if config.slices < 2 || notional <= config.notional {
+                return true;
+            }
+            let Some(&target) = targets.get(inst) else {
+                return true;
+            };
+
+            let interval = Duration::from_secs(config.window_sec / config.slices as u64);
+            *diff /= config.slices as f64;
+            self.plans.insert(
+                inst.clone(),
+                TwapPlan {
+                    target,
+                    slices_left: config.slices - 1,
+                    next_at: now + interval,
+                    interval,
+                },
+            );
+            info!(
+                "[Twap] {} notional {:.2} split into {} slices over {}s",
+                inst, notional, config.slices, config.window_sec
+            );
+            true
+        });
+    }
+}
@@ -1,32 +1,103 @@
 use extrema_infra::{
     arch::market_assets::{api_data::utils_data::InstrumentInfo, api_general::normalize_to_string},
     errors::{InfraError, InfraResult},
+    prelude::Market,
 };
-use serde::Deserialize;
-use std::{env::current_dir, fs};
-use tracing::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, hash_map::DefaultHasher},
+    fs,
+    hash::{Hash, Hasher},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, error, info};
+
+use super::{
+    acc_advisory::AdvisoryConfig, acc_ratelimit::RateLimitConfig, acc_retry::RetryPolicy,
+    acc_skips::SkipReason, acc_twap::TwapConfig,
+};
+use crate::arch::{feats::feat_config::load_feature_config, profile::config_path};
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct AccountFileConfig {
     pub account_id: String,
     pub exchange: String,
     pub api_key: String,
     pub api_secret: String,
     pub passphrase: Option<String>,
+    /// Hex wallet key for venues that sign with it (Hyperliquid).
+    #[serde(default)]
+    pub private_key: Option<String>,
+    /// Settlement asset used as equity, e.g. the base coin for coin-margined accounts.
+    #[serde(default)]
+    pub margin_asset: Option<String>,
     pub account_orders_task_id: u64,
     pub account_bal_pos_task_id: u64,
+    /// Simulate fills and exchange-side rejections instead of sending orders.
+    #[serde(default)]
+    pub paper: bool,
+    #[serde(default)]
+    pub execution_mode: ExecutionMode,
+    /// Distance of `limit_offset` orders from the mark, in basis points.
+    #[serde(default)]
+    pub limit_offset_bps: f64,
+    /// Split large rebalances into timed child orders; unset sends each gap at once.
+    #[serde(default)]
+    pub twap: Option<TwapConfig>,
+    /// Notify a human of large rebalances before their orders go out; execution stays automatic.
+    #[serde(default)]
+    pub advisory: Option<AdvisoryConfig>,
+    /// Leverage set per instrument before its first order, `*` for the rest.
+    #[serde(default)]
+    pub leverage: HashMap<String, u32>,
+    /// Cap on the gross target weight of the account, i.e. its total leverage.
+    #[serde(default)]
+    pub max_leverage: Option<f64>,
+    /// Funding settlement period of the venue, e.g. 1 on Hyperliquid.
+    #[serde(default)]
+    pub funding_interval_hours: Option<u64>,
+}
+
+/// Market whose instruments an `exchange` of `account_config.json` trades.
+pub fn exchange_market(exchange: &str) -> Option<Market> {
+    match exchange.to_lowercase().as_str() {
+        "okx" => Some(Market::Okx),
+        "binance_um" => Some(Market::BinanceUmFutures),
+        "binance_cm" => Some(Market::BinanceCmFutures),
+        "bybit" => Some(Market::Bybit),
+        "hyperliquid" => Some(Market::HyperLiquid),
+        _ => None,
+    }
 }
 
+/// How rebalance orders are sent.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionMode {
+    #[default]
+    Market,
+    /// Tracked limit orders at mark ± `limit_offset_bps`, re-priced when unfilled.
+    LimitOffset,
+}
+
+/// Re-prices of one `limit_offset` order before it is cancelled and left to the next update.
+pub const MAX_LIMIT_REPRICES: u32 = 3;
+
+/// Weight change a post-reconnect REST snapshot logs as drift from the WS-maintained state.
+pub const RECONCILE_TOLERANCE: f64 = 1e-4;
+
 pub fn load_account_config() -> InfraResult<Vec<AccountFileConfig>> {
-    let mut path = current_dir().map_err(|e| {
+    let path = config_path("account_config.json").map_err(|e| {
         InfraError::Msg(format!(
             "Failed to get current directory for account config: {}",
             e,
         ))
     })?;
 
-    path.push("account_config.json");
-
     info!("account_config path: {:?}", path);
 
     if !path.exists() {
@@ -45,12 +116,161 @@ pub fn load_account_config() -> InfraResult<Vec<AccountFileConfig>> {
     Ok(configs)
 }
 
+pub const MAX_PENDING_REJECTIONS: usize = 256;
+
+/// A target that could not be executed, reported back to the models trading the account.
+#[derive(Clone, Debug)]
+pub struct TradeRejection {
+    /// Model whose command was refused; `None` reaches every model of the account.
+    pub model_id: Option<String>,
+    pub account_id: String,
+    pub inst: String,
+    pub reason: String,
+    pub detail: String,
+    pub diff: f64,
+    pub failures: u32,
+    pub timestamp: u64,
+}
+
+pub const MAX_PENDING_EXECUTIONS: usize = 256;
+
+/// Realized execution of one rebalance order, sent back to the models trading the account.
+#[derive(Clone, Debug)]
+pub struct ExecutionReport {
+    pub account_id: String,
+    pub inst: String,
+    pub buy: bool,
+    /// Filled size in venue units (contracts on OKX, base quantity on Binance).
+    pub filled_size: f64,
+    pub avg_price: f64,
+    /// Mark price published with the target the order was sized from.
+    pub decision_price: f64,
+    /// Adverse move of the fill against the decision price; negative is price improvement.
+    pub slippage_bps: f64,
+    pub diff: f64,
+    /// The venue did not report the fill in time; size and price are the order's own.
+    pub estimated: bool,
+    /// Client order id of the rebalance order, when it had one.
+    pub client_order_id: Option<String>,
+    pub timestamp: u64,
+}
+
+/// Execution price bound a model attached to its last `adjust_position` of an instrument.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceLimit {
+    pub model_id: String,
+    /// Worst price the model accepts: the most a buy pays, the least a sell receives.
+    pub limit_price: Option<f64>,
+    /// Worst price as an adverse distance from the decision price.
+    pub max_slippage_bps: Option<f64>,
+}
+
+impl PriceLimit {
+    /// Reads `limit_price` and `max_slippage_bps` from command metadata.
+    pub fn from_metadata(
+        model_id: &str,
+        meta: &HashMap<String, String>,
+    ) -> InfraResult<Option<Self>> {
+        let field = |key: &str| -> InfraResult<Option<f64>> {
+            meta.get(key)
+                .map(|v| {
+                    v.parse::<f64>()
+                        .ok()
+                        .filter(|x| x.is_finite() && *x >= 0.0)
+                        .ok_or_else(|| InfraError::Msg(format!("{} {:?} invalid", key, v)))
+                })
+                .transpose()
+        };
+        let limit_price = field("limit_price")?.filter(|px| *px > 0.0);
+        let max_slippage_bps = field("max_slippage_bps")?;
+
+        if limit_price.is_none() && max_slippage_bps.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            model_id: model_id.to_string(),
+            limit_price,
+            max_slippage_bps,
+        }))
+    }
+
+    /// Worst acceptable price at `decision_price`, the tighter bound when both are set.
+    pub fn level(&self, buy: bool, decision_price: f64) -> f64 {
+        let slip = self.max_slippage_bps.map(|bps| {
+            let offset = bps / 10_000.0;
+            if buy {
+                decision_price * (1.0 + offset)
+            } else {
+                decision_price * (1.0 - offset)
+            }
+        });
+        match (self.limit_price, slip, buy) {
+            (Some(px), Some(slip), true) => px.min(slip),
+            (Some(px), Some(slip), false) => px.max(slip),
+            (Some(px), None, _) | (None, Some(px), _) => px,
+            (None, None, _) => decision_price,
+        }
+    }
+}
+
+pub fn slippage_bps(buy: bool, avg_price: f64, decision_price: f64) -> f64 {
+    if decision_price <= f64::EPSILON || avg_price <= f64::EPSILON {
+        return 0.0;
+    }
+
+    let rel = avg_price / decision_price - 1.0;
+    if buy { rel * 1e4 } else { -rel * 1e4 }
+}
+
+/// Part an account plays in an operator migration, see `AccountMigration`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MigrationRole {
+    #[default]
+    Normal,
+    /// Trade every position down to zero regardless of the shared targets
+    Flatten,
+    /// Keep positions as they are and place no orders
+    Hold,
+}
+
+/// Execution state of one account as last seen by the account manager.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct AccountSnapshot {
+    pub total_equity: f64,
+    pub acc_weights: HashMap<String, f64>,
+    pub pending_diffs: HashMap<String, f64>,
+    /// Consecutive failed executions per instrument; feedback fires every `max_exec_failures`.
+    pub exec_failures: HashMap<String, u32>,
+    /// Seconds until the account's next staggered refresh.
+    pub next_update_sec: u64,
+    pub paused: bool,
+    /// Drawdown breaker tripped: the account holds or flattens instead of following targets.
+    pub drawdown_tripped: bool,
+    /// Rebalancer skips per instrument and reason.
+    pub skips: HashMap<String, HashMap<SkipReason, u64>>,
+    pub timestamp: u64,
+}
+
 #[derive(Clone, Debug)]
 pub struct AccountInitConfig {
     pub reload_task_id: u64,
     pub update_task_id: u64,
     pub reload_interval_sec: u64,
     pub update_interval_sec: u64,
+    /// Initial period of the shared market data sent to the models.
+    pub model_data_interval_sec: u64,
+    pub tick_interval_sec: u64,
+    pub update_jitter_ms: u64,
+    pub intent_log_path: String,
+    pub limit_order_timeout_sec: u64,
+    pub journal_dir: String,
+    pub tracking_report_path: String,
+    /// Kill, re-arm and breaker events are appended here, one JSON object per line.
+    pub admin_audit_path: String,
+    pub retry: RetryPolicy,
+    pub rate_limits: RateLimitConfig,
+    /// Instruments neither targeted nor held for this long have their cached state dropped.
+    pub idle_inst_ttl_sec: u64,
 }
 
 impl Default for AccountInitConfig {
@@ -60,10 +280,186 @@ impl Default for AccountInitConfig {
             update_task_id: 20,
             reload_interval_sec: 3600,
             update_interval_sec: 30,
+            model_data_interval_sec: 30,
+            tick_interval_sec: 1,
+            update_jitter_ms: 2000,
+            intent_log_path: "order_intents.jsonl".to_string(),
+            limit_order_timeout_sec: 30,
+            journal_dir: "journals".to_string(),
+            tracking_report_path: "tracking_report.jsonl".to_string(),
+            admin_audit_path: "admin_audit.jsonl".to_string(),
+            retry: RetryPolicy::default(),
+            rate_limits: RateLimitConfig::default(),
+            idle_inst_ttl_sec: 86_400,
         }
     }
 }
 
+/// Intervals shared between modules and adjustable at runtime.
+#[derive(Clone, Debug)]
+pub struct SchedulerIntervals {
+    account_update_sec: Arc<AtomicU64>,
+    model_data_sec: Arc<AtomicU64>,
+}
+
+impl Default for SchedulerIntervals {
+    fn default() -> Self {
+        Self::new(30, 30)
+    }
+}
+
+impl SchedulerIntervals {
+    pub fn new(account_update_sec: u64, model_data_sec: u64) -> Self {
+        Self {
+            account_update_sec: Arc::new(AtomicU64::new(account_update_sec)),
+            model_data_sec: Arc::new(AtomicU64::new(model_data_sec)),
+        }
+    }
+
+    pub fn account_update(&self) -> Duration {
+        Duration::from_secs(self.account_update_sec.load(Ordering::Relaxed))
+    }
+
+    pub fn model_data(&self) -> Duration {
+        Duration::from_secs(self.model_data_sec.load(Ordering::Relaxed))
+    }
+
+    pub fn set_interval(&self, scheduler: &str, interval_sec: u64) -> InfraResult<()> {
+        if interval_sec == 0 {
+            return Err(InfraError::Msg(format!(
+                "Scheduler interval must be positive: {}",
+                scheduler,
+            )));
+        }
+
+        let slot = match scheduler {
+            "account_update" => &self.account_update_sec,
+            "model_data" => &self.model_data_sec,
+            e => return Err(InfraError::Msg(format!("Unknown scheduler: {}", e))),
+        };
+        slot.store(interval_sec, Ordering::Relaxed);
+
+        Ok(())
+    }
+}
+
+pub fn interval_elapsed(last_run: &mut Option<Instant>, interval: Duration) -> bool {
+    let now = Instant::now();
+    match last_run {
+        Some(t) if now.duration_since(*t) < interval => false,
+        _ => {
+            *last_run = Some(now);
+            true
+        },
+    }
+}
+
+/// When each instrument was last targeted or held.
+#[derive(Clone, Debug, Default)]
+pub struct IdleInstruments {
+    last_active: HashMap<String, Instant>,
+}
+
+impl IdleInstruments {
+    pub fn touch(&mut self, inst: &str) {
+        self.last_active.insert(inst.to_string(), Instant::now());
+    }
+
+    /// Instruments of `cached` not touched within `ttl`, which are forgotten here.
+    pub fn expire(
+        &mut self,
+        cached: impl IntoIterator<Item = String>,
+        ttl: Duration,
+    ) -> Vec<String> {
+        let now = Instant::now();
+        let mut idle = Vec::new();
+        for inst in cached {
+            let last = *self.last_active.entry(inst.clone()).or_insert(now);
+            if now.duration_since(last) >= ttl {
+                idle.push(inst);
+            }
+        }
+
+        idle.sort();
+        idle.dedup();
+        for inst in idle.iter() {
+            self.last_active.remove(inst);
+        }
+        self.last_active
+            .retain(|_, last| now.duration_since(*last) < ttl);
+        idle
+    }
+}
+
+/// Deterministic offset within `interval` derived from `key`, used to stagger accounts.
+pub fn stagger_offset(key: &str, interval: Duration) -> Duration {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+
+    let interval_ms = (interval.as_millis() as u64).max(1);
+    Duration::from_millis(hasher.finish() % interval_ms)
+}
+
+pub fn jitter(max_ms: u64) -> Duration {
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or_default();
+
+    Duration::from_millis(nanos % (max_ms + 1))
+}
+
+/// Funding settlement period of an account in milliseconds.
+pub fn funding_interval_ms(cfg: &AccountFileConfig) -> u64 {
+    let hours = cfg.funding_interval_hours.unwrap_or_else(|| {
+        load_feature_config()
+            .unwrap_or_default()
+            .funding_interval_hours
+    });
+    hours.max(1) * 3_600_000
+}
+
+/// Funding accrued on a position since the last settlement, positive when it is owed.
+pub fn accrued_funding(
+    notional: f64,
+    rate: f64,
+    next_funding_ms: u64,
+    interval_ms: u64,
+    now_ms: u64,
+) -> f64 {
+    if next_funding_ms == 0 || interval_ms == 0 {
+        return 0.0;
+    }
+
+    let remaining = next_funding_ms.saturating_sub(now_ms).min(interval_ms);
+    let elapsed = 1.0 - remaining as f64 / interval_ms as f64;
+    notional * rate * elapsed
+}
+
+/// Inverse instruments are quoted in USD and margined in the base coin, e.g. `BTC_USD_PERP`.
+pub fn is_inverse_inst(inst: &str) -> bool {
+    inst.split('_')
+        .nth(1)
+        .is_some_and(|quote| quote.eq_ignore_ascii_case("USD"))
+}
+
+/// Position notional in margin currency.
+pub fn calc_position_notional(size: f64, price: f64, ct_val: f64, inverse: bool) -> f64 {
+    if !inverse {
+        return size * ct_val * price;
+    }
+
+    if price > f64::EPSILON {
+        size * ct_val / price
+    } else {
+        0.0
+    }
+}
+
 pub fn calc_okx_order_size(
     price: f64,
     notional: f64,
@@ -90,11 +486,9 @@ pub fn calc_binance_order_size(
     let min_sz = info.min_lmt_size.max(info.min_mkt_size);
     let max_sz = info.max_lmt_size.min(info.max_mkt_size);
     size = size.clamp(min_sz, max_sz);
-    println!("size: {}", size);
-    println!("price: {}", price);
-    println!("notional: {}", notional);
-    println!("min_sz: {}", min_sz);
-    println!("max_sz: {}", max_sz);
-    println!("lot_size: {}", info.lot_size);
+    debug!(
+        "size: {}, price: {}, notional: {}, min_sz: {}, max_sz: {}, lot_size: {}",
+        size, price, notional, min_sz, max_sz, info.lot_size
+    );
     Ok(normalize_to_string(size, info.lot_size))
 }
@@ -0,0 +1,11 @@
+pub mod ctl_admin;
+pub mod ctl_attribution;
+pub mod ctl_audit;
+pub mod ctl_auth;
+pub mod ctl_base;
+pub mod ctl_lint;
+pub mod ctl_portfolio;
+pub mod ctl_risk;
+pub mod ctl_socket;
+pub mod ctl_systemd;
+pub mod ctl_watchdog;
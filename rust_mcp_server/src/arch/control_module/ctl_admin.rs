@@ -0,0 +1,240 @@
+use serde_json::json;
+use std::{
+    env,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+use crate::arch::account_module::acc_rest::now_millis;
+
+/// Comma-separated admin tokens allowed to re-arm trading after the kill switch.
+pub const ADMIN_TOKENS_ENV: &str = "MCP_ADMIN_TOKENS";
+/// Shorter tokens are ignored at startup.
+pub const MIN_ADMIN_TOKEN_LEN: usize = 16;
+/// A single admin may re-arm alone by confirming again after this delay.
+pub const REARM_DELAY: Duration = Duration::from_secs(900);
+/// A pending approval expires after this long and the re-arm starts over.
+pub const REARM_APPROVAL_TTL: Duration = Duration::from_secs(3600);
+
+static AUDIT_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Admin tokens from `MCP_ADMIN_TOKENS`.
+#[derive(Clone, Debug, Default)]
+pub struct AdminTokens {
+    tokens: Arc<Vec<String>>,
+}
+
+impl AdminTokens {
+    pub fn from_env() -> Self {
+        let mut tokens: Vec<String> = Vec::new();
+        for token in env::var(ADMIN_TOKENS_ENV).unwrap_or_default().split(',') {
+            let token = token.trim();
+            if token.is_empty() || tokens.iter().any(|t| t == token) {
+                continue;
+            }
+            if token.len() < MIN_ADMIN_TOKEN_LEN {
+                warn!(
+                    "[Control] Ignoring admin token #{}: shorter than {} chars",
+                    tokens.len() + 1,
+                    MIN_ADMIN_TOKEN_LEN
+                );
+                continue;
+            }
+            tokens.push(token.to_string());
+        }
+
+        if tokens.is_empty() {
+            warn!("[Control] {} not set, the kill switch cannot be re-armed", ADMIN_TOKENS_ENV);
+        } else {
+            info!("[Control] {} admin tokens loaded", tokens.len());
+        }
+
+        Self {
+            tokens: Arc::new(tokens),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn from_tokens(tokens: &[&str]) -> Self {
+        Self {
+            tokens: Arc::new(tokens.iter().map(|t| t.to_string()).collect()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Index of the admin owning `token`, compared without early exit.
+    pub fn identify(&self, token: &str) -> Option<usize> {
+        self.tokens.iter().position(|t| tokens_match(t, token))
+    }
+}
+
+/// Token comparison without early exit on the first differing byte.
+pub fn tokens_match(expected: &str, token: &str) -> bool {
+    expected.len() == token.len()
+        && expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+pub fn admin_label(admin: usize) -> String {
+    format!("admin#{}", admin + 1)
+}
+
+pub enum RearmDecision {
+    Pending(String),
+    Granted(String),
+}
+
+/// First approval of an in-progress re-arm.
+#[derive(Clone, Debug, Default)]
+pub struct RearmApprovals {
+    first: Option<(usize, Instant)>,
+}
+
+impl RearmApprovals {
+    pub fn clear(&mut self) {
+        self.first = None;
+    }
+
+    pub fn pending_admin(&self) -> Option<String> {
+        self.first
+            .filter(|(_, at)| at.elapsed() < REARM_APPROVAL_TTL)
+            .map(|(admin, _)| admin_label(admin))
+    }
+
+    pub fn approve(&mut self, admin: usize) -> RearmDecision {
+        self.approve_at(admin, Instant::now())
+    }
+
+    /// `approve` as of `now`.
+    fn approve_at(&mut self, admin: usize, now: Instant) -> RearmDecision {
+        let Some((first, at)) = self
+            .first
+            .filter(|(_, at)| now.duration_since(*at) < REARM_APPROVAL_TTL)
+        else {
+            self.first = Some((admin, now));
+            return RearmDecision::Pending(format!(
+                "approved by {}, needs a second admin or a confirmation after {}s",
+                admin_label(admin),
+                REARM_DELAY.as_secs()
+            ));
+        };
+
+        let waited = now.duration_since(at);
+        if first != admin {
+            self.first = None;
+            RearmDecision::Granted(format!(
+                "two-admin approval by {} and {}",
+                admin_label(first),
+                admin_label(admin)
+            ))
+        } else if waited >= REARM_DELAY {
+            self.first = None;
+            RearmDecision::Granted(format!(
+                "delayed confirmation by {} after {}s",
+                admin_label(admin),
+                waited.as_secs()
+            ))
+        } else {
+            RearmDecision::Pending(format!(
+                "{} already approved, confirm again in {}s or ask a second admin",
+                admin_label(admin),
+                (REARM_DELAY - waited).as_secs()
+            ))
+        }
+    }
+}
+
+/// Names the admin audit log; until set, records are only logged.
+pub fn set_audit_path(path: &str) {
+    if AUDIT_PATH.set(PathBuf::from(path)).is_err() {
+        warn!("[Audit] Audit path already set, ignoring {}", path);
+    }
+}
+
+/// Appends one record to the admin audit log. Failures are logged, never fatal.
+pub fn audit(action: &str, admin: Option<usize>, outcome: &str, detail: &str) {
+    let record = json!({
+        "timestamp": now_millis(),
+        "action": action,
+        "admin": admin.map(admin_label),
+        "outcome": outcome,
+        "detail": detail,
+    });
+    info!("[Audit] {}", record);
+
+    let Some(path) = AUDIT_PATH.get() else {
+        return;
+    };
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut f| writeln!(f, "{}", record));
+    if let Err(e) = written {
+        warn!("[Audit] Failed to write {:?}: {}", path, e);
+    }
+}
+
+/// Control line safe to log: arguments of token-bearing commands are masked.
+pub fn redact_command(line: &str) -> String {
+    let mut parts = line.split_whitespace();
+    match parts.next() {
+        Some(cmd) if cmd.eq_ignore_ascii_case("rearm") => format!("{} ***", cmd),
+        _ => line.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn granted(decision: RearmDecision) -> bool {
+        matches!(decision, RearmDecision::Granted(_))
+    }
+
+    #[test]
+    fn second_admin_grants_at_once() {
+        let start = Instant::now();
+        let mut approvals = RearmApprovals::default();
+
+        assert!(!granted(approvals.approve_at(0, start)));
+        assert!(granted(approvals.approve_at(1, start + Duration::from_secs(1))));
+        assert!(approvals.pending_admin().is_none());
+    }
+
+    #[test]
+    fn same_admin_waits_for_the_delay() {
+        let start = Instant::now();
+        let early = start + REARM_DELAY - Duration::from_secs(1);
+        let mut approvals = RearmApprovals::default();
+
+        assert!(!granted(approvals.approve_at(0, start)));
+        assert!(!granted(approvals.approve_at(0, early)));
+        // The early confirmation does not restart the delay
+        assert!(granted(approvals.approve_at(0, start + REARM_DELAY)));
+    }
+
+    #[test]
+    fn approval_expires_after_ttl() {
+        let start = Instant::now();
+        let expired = start + REARM_APPROVAL_TTL;
+        let later = expired + Duration::from_secs(1);
+        let mut approvals = RearmApprovals::default();
+
+        assert!(!granted(approvals.approve_at(0, start)));
+        // admin#1's approval expired, so admin#2 only starts a new one
+        assert!(!granted(approvals.approve_at(1, expired)));
+        assert!(!granted(approvals.approve_at(1, later)));
+        assert!(granted(approvals.approve_at(0, later)));
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    env::current_dir,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::warn;
+
+use extrema_infra::prelude::*;
+
+use crate::arch::account_module::acc_rest::now_millis;
+
+/// Target and executed weights over time, one JSON row per line.
+pub const ATTRIBUTION_FILE: &str = "attribution_history.jsonl";
+/// Spacing of the samples `McpServer` takes.
+pub const ATTRIBUTION_INTERVAL: Duration = Duration::from_secs(60);
+/// Source of the rows holding the shared target, after the ensemble and the risk limits.
+pub const CONSOLIDATED_SOURCE: &str = "consolidated";
+/// Rows older than this are dropped from memory; the file keeps them.
+const RETENTION_MS: u64 = 7 * 24 * 3_600_000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttributionRow {
+    pub timestamp: u64,
+    pub inst: String,
+    /// Model id, or `consolidated`.
+    pub source: String,
+    /// Scaled contribution of the model, or the consolidated target weight.
+    pub target: f64,
+    /// Actual weight of each account at the same time.
+    pub executed: BTreeMap<String, f64>,
+}
+
+/// History of how each model's signal turned into targets and positions.
+#[derive(Clone, Debug)]
+pub struct AttributionHistory {
+    path: PathBuf,
+    rows: Arc<Mutex<VecDeque<AttributionRow>>>,
+}
+
+impl Default for AttributionHistory {
+    fn default() -> Self {
+        Self::new(ATTRIBUTION_FILE)
+    }
+}
+
+impl AttributionHistory {
+    pub fn new(file_name: &str) -> Self {
+        let mut path = current_dir().unwrap_or_default();
+        path.push(file_name);
+        Self {
+            path,
+            rows: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Reads the rows still within retention back from the file, e.g. after a restart.
+    pub fn load(&self) -> InfraResult<usize> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| InfraError::Msg(format!("Failed to read attribution history: {}", e)))?;
+        let cutoff = now_millis().saturating_sub(RETENTION_MS);
+        let loaded: VecDeque<AttributionRow> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<AttributionRow>(line).ok())
+            .filter(|row| row.timestamp >= cutoff)
+            .collect();
+
+        let count = loaded.len();
+        if let Ok(mut rows) = self.rows.lock() {
+            *rows = loaded;
+        }
+        Ok(count)
+    }
+
+    /// Appends one sample's rows to the file and the in-memory history.
+    pub fn record(&self, sample: Vec<AttributionRow>) {
+        if sample.is_empty() {
+            return;
+        }
+
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut f| {
+                sample.iter().try_for_each(|row| {
+                    let line = serde_json::to_string(row).map_err(std::io::Error::other)?;
+                    writeln!(f, "{}", line)
+                })
+            });
+        if let Err(e) = written {
+            warn!("[Attribution] Failed to write {:?}: {}", self.path, e);
+        }
+
+        let Ok(mut rows) = self.rows.lock() else {
+            return;
+        };
+        rows.extend(sample);
+        let cutoff = now_millis().saturating_sub(RETENTION_MS);
+        while rows.front().is_some_and(|row| row.timestamp < cutoff) {
+            rows.pop_front();
+        }
+    }
+
+    /// Rows since `since_ms`, oldest first, of one instrument and source when given.
+    pub fn query(
+        &self,
+        inst: Option<&str>,
+        source: Option<&str>,
+        since_ms: u64,
+        limit: usize,
+    ) -> Vec<AttributionRow> {
+        let Ok(rows) = self.rows.lock() else {
+            return Vec::new();
+        };
+        let mut matched: Vec<AttributionRow> = rows
+            .iter()
+            .rev()
+            .filter(|row| row.timestamp >= since_ms)
+            .filter(|row| inst.is_none_or(|inst| row.inst == inst))
+            .filter(|row| source.is_none_or(|source| row.source == source))
+            .take(limit)
+            .cloned()
+            .collect();
+        matched.reverse();
+        matched
+    }
+}
@@ -0,0 +1,167 @@
+use dashmap::DashMap;
+use serde_json::{Map, Value, json};
+use std::{
+    collections::{BTreeSet, HashMap},
+    env::current_dir,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    sync::Arc,
+};
+use tracing::warn;
+
+use extrema_infra::{arch::market_assets::api_general::OrderParams, prelude::*};
+
+use super::ctl_auth::{SIGNATURE_KEY, TOKEN_KEY};
+use crate::arch::account_module::acc_rest::now_millis;
+
+/// Every MCP command, the weights it moved and the orders that followed, one JSON object per line.
+pub const COMMAND_AUDIT_FILE: &str = "command_audit.jsonl";
+
+/// Append-only audit trail of commands, actions and orders.
+#[derive(Clone, Debug)]
+pub struct CommandAudit {
+    path: PathBuf,
+    causes: Arc<DashMap<String, String>>,
+}
+
+impl Default for CommandAudit {
+    fn default() -> Self {
+        Self::new(COMMAND_AUDIT_FILE)
+    }
+}
+
+impl CommandAudit {
+    pub fn new(file_name: &str) -> Self {
+        let mut path = current_dir().unwrap_or_default();
+        path.push(file_name);
+        Self {
+            path,
+            causes: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Records an MCP command with the target weights it changed.
+    pub fn record_command<T>(
+        &self,
+        meta: &HashMap<String, String>,
+        timestamp: u64,
+        result: &InfraResult<T>,
+        before: &HashMap<String, f64>,
+        after: &HashMap<String, f64>,
+    ) {
+        let cmd = meta.get("cmd").map(|x| x.as_str()).unwrap_or("noop");
+        if cmd == "noop" {
+            return;
+        }
+
+        let command_id = command_id(meta, timestamp);
+        let insts: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        let mut weights = Map::new();
+        for inst in insts {
+            let old = before.get(inst).copied().unwrap_or(0.0);
+            let new = after.get(inst).copied().unwrap_or(0.0);
+            if (old - new).abs() > f64::EPSILON {
+                weights.insert(inst.clone(), json!({ "before": old, "after": new }));
+                self.causes.insert(inst.clone(), command_id.clone());
+            }
+        }
+
+        let params: Map<String, Value> = meta
+            .iter()
+            .filter(|(key, _)| !matches!(key.as_str(), "cmd" | "model_id"))
+            .map(|(key, value)| match key.as_str() {
+                SIGNATURE_KEY | TOKEN_KEY => (key.clone(), json!("***")),
+                _ => (key.clone(), json!(value)),
+            })
+            .collect();
+
+        self.append(json!({
+            "event": "command",
+            "timestamp": now_millis(),
+            "command_id": command_id,
+            "cmd": cmd,
+            "model_id": meta.get("model_id"),
+            "params": params,
+            "outcome": outcome(result),
+            "weights": weights,
+        }));
+    }
+
+    /// Records the result of an order management command.
+    pub fn record_action(
+        &self,
+        command_id: Option<String>,
+        cmd: &str,
+        account_id: Option<&str>,
+        result: &InfraResult<Value>,
+    ) {
+        self.append(json!({
+            "event": "action",
+            "timestamp": now_millis(),
+            "command_id": command_id,
+            "cmd": cmd,
+            "account_id": account_id,
+            "outcome": outcome(result),
+            "result": result.as_ref().ok(),
+        }));
+    }
+
+    /// Rebalance order and how its placement ended, e.g. `acked`, `failed` or `simulated`.
+    pub fn record_order(&self, account_id: &str, order: &OrderParams, diff: f64, status: &str) {
+        let cause = self.causes.get(&order.inst).map(|id| id.value().clone());
+        self.append(json!({
+            "event": "order",
+            "timestamp": now_millis(),
+            "cause": cause,
+            "account_id": account_id,
+            "inst": order.inst,
+            "side": format!("{:?}", order.side),
+            "size": order.size,
+            "price": order.price,
+            "client_order_id": order.client_order_id,
+            "diff": diff,
+            "status": status,
+        }));
+    }
+
+    fn append(&self, record: Value) {
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut f| writeln!(f, "{}", record));
+        if let Err(e) = written {
+            warn!("[Audit] Failed to write {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Id shared by every record of one command tensor.
+pub fn command_id(meta: &HashMap<String, String>, timestamp: u64) -> String {
+    format!(
+        "{}-{}-{}",
+        meta.get("model_id")
+            .map(|x| x.as_str())
+            .unwrap_or("unknown"),
+        meta.get("cmd").map(|x| x.as_str()).unwrap_or("noop"),
+        meta.get("ts")
+            .cloned()
+            .unwrap_or_else(|| timestamp.to_string()),
+    )
+}
+
+/// Target weight per instrument, to diff around a command.
+pub fn weight_snapshot(weights: &DashMap<String, (f64, f64)>) -> HashMap<String, f64> {
+    weights
+        .iter()
+        .map(|entry| (entry.key().clone(), entry.value().1))
+        .collect()
+}
+
+fn outcome<T>(result: &InfraResult<T>) -> String {
+    match result {
+        Ok(_) => "ok".to_string(),
+        Err(e) => format!("error: {:?}", e),
+    }
+}
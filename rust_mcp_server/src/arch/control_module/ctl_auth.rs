@@ -0,0 +1,332 @@
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, Mutex, RwLock},
+};
+use tracing::warn;
+
+use extrema_infra::prelude::*;
+
+use super::ctl_admin::tokens_match;
+use crate::arch::{
+    account_module::acc_rest::now_millis,
+    server_module::server_utils::{ModelConfig, ModelMode},
+};
+
+/// Hex HMAC-SHA256 of the canonical metadata, keyed with the model's `auth_secret`.
+pub const SIGNATURE_KEY: &str = "signature";
+/// The model's `auth_secret` itself, for hosts that cannot sign.
+pub const TOKEN_KEY: &str = "token";
+/// Signed commands whose `ts` is further than this from now are refused as replays, as is a
+/// signature already accepted within it.
+pub const MAX_SIGNATURE_SKEW_MS: u64 = 60_000;
+
+/// What a model may ask of the server through MCP commands.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelPermission {
+    /// `noop` and `query` only.
+    ReadOnly,
+    #[default]
+    Trading,
+}
+
+impl ModelPermission {
+    fn allows(&self, cmd: &str) -> bool {
+        match self {
+            ModelPermission::ReadOnly => matches!(cmd, "noop" | "query"),
+            ModelPermission::Trading => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Credential {
+    secret: Option<String>,
+    permission: ModelPermission,
+    mode: ModelMode,
+}
+
+impl Credential {
+    fn permit(&self, model_id: &str, cmd: &str) -> InfraResult<()> {
+        if !self.permission.allows(cmd) {
+            return Err(InfraError::Msg(format!(
+                "{} refused: model={} is {:?}",
+                cmd, model_id, self.permission
+            )));
+        }
+        // Shadow targets are diverted by `McpServer`; every other command would act live
+        if self.mode == ModelMode::Shadow && !matches!(cmd, "noop" | "query" | "adjust_position")
+        {
+            return Err(InfraError::Msg(format!(
+                "{} refused: model={} is in shadow mode",
+                cmd, model_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Credentials and permissions per model from `model_config.json`.
+#[derive(Clone, Debug, Default)]
+pub struct CommandAuth {
+    models: Arc<RwLock<HashMap<String, Credential>>>,
+    /// Signatures accepted per model with their `ts`, kept for `MAX_SIGNATURE_SKEW_MS`.
+    seen: Arc<Mutex<HashMap<String, HashMap<String, u64>>>>,
+}
+
+impl CommandAuth {
+    /// Same credentials with a replay window of its own, for one more module reading the
+    /// command stream; each module sees every command once.
+    pub fn scoped(&self) -> Self {
+        Self {
+            models: self.models.clone(),
+            seen: Arc::default(),
+        }
+    }
+
+    /// Replaces the credentials with those of `configs`.
+    pub fn load<'a>(&self, configs: impl IntoIterator<Item = &'a ModelConfig>) {
+        let mut models = HashMap::new();
+        for cfg in configs {
+            let secret = match cfg.auth_secret.as_deref() {
+                Some(secret) => match secret.strip_prefix("env:") {
+                    Some(name) => {
+                        let value = env::var(name).ok().filter(|v| !v.is_empty());
+                        if value.is_none() {
+                            warn!(
+                                "[Auth] {} not set, every command of model={} will be refused",
+                                name, cfg.model_id
+                            );
+                        }
+                        Some(value.unwrap_or_default())
+                    },
+                    None => Some(secret.to_string()),
+                },
+                None => {
+                    warn!(
+                        "[Auth] Model {} has no auth_secret, its commands are unauthenticated",
+                        cfg.model_id
+                    );
+                    None
+                },
+            };
+
+            models.insert(
+                cfg.model_id.clone(),
+                Credential {
+                    secret,
+                    permission: cfg.permission,
+                    mode: cfg.mode,
+                },
+            );
+        }
+
+        if let Ok(mut current) = self.models.write() {
+            *current = models;
+        }
+    }
+
+    /// Checks a command tensor's credentials and permission. `noop` passes untouched.
+    pub fn authorize(&self, meta: &HashMap<String, String>) -> InfraResult<()> {
+        let cmd = meta.get("cmd").map(|x| x.as_str()).unwrap_or("noop");
+        if cmd == "noop" {
+            return Ok(());
+        }
+
+        let model_id = meta.get("model_id").map(|x| x.as_str()).unwrap_or_default();
+        let credential = self.credential(model_id, cmd)?;
+        if let Some(secret) = credential.secret.as_deref() {
+            authenticate(secret, meta, now_millis())
+                .and_then(|signed| match signed {
+                    Some((signature, ts)) => self.check_replay(model_id, signature, ts),
+                    None => Ok(()),
+                })
+                .map_err(|e| {
+                    InfraError::Msg(format!("{} from model={} refused: {}", cmd, model_id, e))
+                })?;
+        }
+
+        credential.permit(model_id, cmd)
+    }
+
+    /// Records an accepted signature, refusing one already seen within the skew window.
+    fn check_replay(&self, model_id: &str, signature: String, ts: u64) -> Result<(), String> {
+        let mut seen = self
+            .seen
+            .lock()
+            .map_err(|_| "replay window poisoned".to_string())?;
+        let now = now_millis();
+        let model = seen.entry(model_id.to_string()).or_default();
+        model.retain(|_, seen_ts| now.abs_diff(*seen_ts) <= MAX_SIGNATURE_SKEW_MS);
+
+        match model.insert(signature, ts) {
+            Some(_) => Err("replayed signature".to_string()),
+            None => Ok(()),
+        }
+    }
+
+    /// Permission check alone, for already authenticated MCP clients.
+    pub fn permit(&self, model_id: &str, cmd: &str) -> InfraResult<()> {
+        self.credential(model_id, cmd)?.permit(model_id, cmd)
+    }
+
+    fn credential(&self, model_id: &str, cmd: &str) -> InfraResult<Credential> {
+        self.models
+            .read()
+            .ok()
+            .and_then(|models| models.get(model_id).cloned())
+            .ok_or_else(|| {
+                InfraError::Msg(format!("{} refused: unknown model {:?}", cmd, model_id))
+            })
+    }
+}
+
+/// Checks the token or signature of `meta`; a valid signature is returned with its `ts`.
+fn authenticate(
+    secret: &str,
+    meta: &HashMap<String, String>,
+    now_ms: u64,
+) -> Result<Option<(String, u64)>, String> {
+    if secret.is_empty() {
+        return Err("auth_secret unavailable".to_string());
+    }
+    if let Some(token) = meta.get(TOKEN_KEY) {
+        return match tokens_match(secret, token) {
+            true => Ok(None),
+            false => Err("bad token".to_string()),
+        };
+    }
+
+    let signature = meta
+        .get(SIGNATURE_KEY)
+        .ok_or_else(|| "no signature or token".to_string())?;
+    let ts = meta
+        .get("ts")
+        .and_then(|ts| ts.parse::<u64>().ok())
+        .ok_or_else(|| "signed command without ts".to_string())?;
+    if now_ms.abs_diff(ts) > MAX_SIGNATURE_SKEW_MS {
+        return Err(format!(
+            "ts {} outside {}ms of now",
+            ts, MAX_SIGNATURE_SKEW_MS
+        ));
+    }
+
+    let expected = sign_metadata(secret, meta).map_err(|e| e.to_string())?;
+    match tokens_match(&expected, &signature.to_ascii_lowercase()) {
+        true => Ok(Some((expected, ts))),
+        false => Err("bad signature".to_string()),
+    }
+}
+
+/// Hex signature of `meta` as the Python host computes it.
+fn sign_metadata(secret: &str, meta: &HashMap<String, String>) -> InfraResult<String> {
+    let mut fields: Vec<(&String, &String)> = meta
+        .iter()
+        .filter(|(key, _)| *key != SIGNATURE_KEY && *key != TOKEN_KEY)
+        .collect();
+    fields.sort();
+    let payload = fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| InfraError::Msg(format!("Invalid auth secret: {}", e)))?;
+    mac.update(payload.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth(permission: &str) -> CommandAuth {
+        let cfg: ModelConfig = serde_json::from_value(serde_json::json!({
+            "port": 5555,
+            "model_id": "m1",
+            "account_id": "acc",
+            "mode": "live",
+            "auth_secret": "secret",
+            "permission": permission,
+        }))
+        .unwrap();
+
+        let auth = CommandAuth::default();
+        auth.load([&cfg]);
+        auth
+    }
+
+    fn command(ts: u64) -> HashMap<String, String> {
+        [
+            ("cmd", "adjust_position"),
+            ("model_id", "m1"),
+            ("inst", "BTC_USDT_PERP"),
+            ("weight", "0.25"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .chain([("ts".to_string(), ts.to_string())])
+        .collect()
+    }
+
+    fn signed(ts: u64) -> HashMap<String, String> {
+        let mut meta = command(ts);
+        let signature = sign_metadata("secret", &meta).unwrap();
+        meta.insert(SIGNATURE_KEY.to_string(), signature);
+        meta
+    }
+
+    #[test]
+    fn signature_matches_the_python_host() {
+        // hmac.new(b"secret", "\n".join(sorted key=value), sha256).hexdigest()
+        assert_eq!(
+            sign_metadata("secret", &command(1_700_000_000_000)).unwrap(),
+            "e626d9d00c29c5e5fdcc7e388d2f5fd3c35ad71ad932577d7f9fdddb0c4c7da4"
+        );
+    }
+
+    #[test]
+    fn bad_token_is_refused() {
+        let auth = auth("trading");
+        let mut meta = command(now_millis());
+        meta.insert(TOKEN_KEY.to_string(), "wrong".to_string());
+        assert!(auth.authorize(&meta).is_err());
+
+        meta.insert(TOKEN_KEY.to_string(), "secret".to_string());
+        assert!(auth.authorize(&meta).is_ok());
+    }
+
+    #[test]
+    fn skewed_ts_is_refused() {
+        let meta = signed(1_700_000_000_000);
+        let now = 1_700_000_000_000 + MAX_SIGNATURE_SKEW_MS + 1;
+        assert!(authenticate("secret", &meta, now).is_err());
+        assert!(authenticate("secret", &meta, now - 2).is_ok());
+    }
+
+    #[test]
+    fn replayed_signature_is_refused_per_reader() {
+        let auth = auth("trading");
+        let other_module = auth.scoped();
+        let meta = signed(now_millis());
+
+        assert!(auth.authorize(&meta).is_ok());
+        assert!(auth.authorize(&meta).is_err());
+        // Another module reading the same command stream accepts it once too
+        assert!(other_module.authorize(&meta).is_ok());
+        assert!(other_module.authorize(&meta).is_err());
+        assert!(auth.authorize(&signed(now_millis() + 1)).is_ok());
+    }
+
+    #[test]
+    fn read_only_model_cannot_trade() {
+        let auth = auth("read_only");
+        let err = auth.authorize(&signed(now_millis())).unwrap_err();
+        assert!(format!("{:?}", err).contains("ReadOnly"));
+    }
+}
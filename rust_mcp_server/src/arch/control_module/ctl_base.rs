@@ -0,0 +1,384 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    env::current_dir,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tracing::{error, info, warn};
+
+use extrema_infra::prelude::*;
+
+use super::{
+    ctl_admin::{AdminTokens, RearmApprovals, RearmDecision, audit, redact_command},
+    ctl_portfolio::PortfolioView,
+};
+use crate::arch::{
+    account_module::{
+        acc_base::TargetWeights, acc_exposure::ExposureReport, acc_own_orders::OwnOrderBook,
+    },
+    profile::active_profile,
+};
+
+/// Kill switch and flatten state, restored on startup.
+pub const CONTROL_STATE_FILE: &str = "control_state.json";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct PersistedControl {
+    killed: bool,
+    flattened: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStage {
+    /// Waiting for the account manager's risk checks
+    Requested,
+    /// Source account trades down to zero while the destination is held
+    Flattening,
+    /// Destination follows the targets, source stays pinned flat
+    Done,
+    Failed,
+}
+
+/// Operator-driven move of the shared targets from one account to another.
+#[derive(Clone, Debug, Serialize)]
+pub struct AccountMigration {
+    pub from: String,
+    pub to: String,
+    pub stage: MigrationStage,
+    pub detail: String,
+}
+
+impl AccountMigration {
+    pub fn is_active(&self) -> bool {
+        matches!(self.stage, MigrationStage::Requested | MigrationStage::Flattening)
+    }
+}
+
+/// Operator switches shared by the control socket, `AccountManager` and `McpServer`.
+#[derive(Clone, Debug, Default)]
+pub struct ControlState {
+    paused: Arc<AtomicBool>,
+    flattened: Arc<AtomicBool>,
+    killed: Arc<AtomicBool>,
+    migration: Arc<Mutex<Option<AccountMigration>>>,
+    admins: AdminTokens,
+    rearm: Arc<Mutex<RearmApprovals>>,
+    path: Option<PathBuf>,
+}
+
+impl ControlState {
+    pub fn with_admin_tokens(&mut self, admins: AdminTokens) -> &mut Self {
+        self.admins = admins;
+        self
+    }
+
+    /// Restores the kill and flatten state from `path` and persists changes there.
+    pub fn restore(&mut self, path: &Path) -> InfraResult<()> {
+        let path = current_dir()?.join(path);
+
+        if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| InfraError::Msg(format!("Failed to read {:?}: {}", path, e)))?;
+            let saved: PersistedControl = serde_json::from_str(&content)
+                .map_err(|e| InfraError::Msg(format!("Failed to parse {:?}: {}", path, e)))?;
+            self.killed.store(saved.killed, Ordering::Relaxed);
+            self.flattened.store(saved.flattened, Ordering::Relaxed);
+            if saved.killed || saved.flattened {
+                warn!(
+                    "[Control] Restored killed={} flattened={} from {:?}",
+                    saved.killed, saved.flattened, path
+                );
+            }
+        }
+
+        self.path = Some(path);
+        Ok(())
+    }
+
+    /// Writes the current state through a temporary file.
+    fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let saved = PersistedControl {
+            killed: self.is_killed(),
+            flattened: self.is_flattened(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        let written = serde_json::to_string(&saved)
+            .map_err(std::io::Error::other)
+            .and_then(|content| fs::write(&tmp, content))
+            .and_then(|_| fs::rename(&tmp, path));
+        if let Err(e) = written {
+            error!("[Control] Failed to persist control state to {:?}: {}", path, e);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn is_flattened(&self) -> bool {
+        self.flattened.load(Ordering::Relaxed)
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::Relaxed)
+    }
+
+    pub fn resume(&self) -> InfraResult<()> {
+        if self.is_killed() {
+            audit("resume", None, "rejected", "kill switch engaged");
+            return Err(InfraError::Msg("kill switch engaged, use rearm <admin_token>".into()));
+        }
+
+        self.paused.store(false, Ordering::Relaxed);
+        self.flattened.store(false, Ordering::Relaxed);
+        self.persist();
+        Ok(())
+    }
+
+    /// Kill switch: flattens every target and keeps them flat until the admins re-arm.
+    pub fn kill(&self, target_weights: &TargetWeights, reason: &str) {
+        if let Ok(mut approvals) = self.rearm.lock() {
+            approvals.clear();
+        }
+        self.killed.store(true, Ordering::Relaxed);
+        self.flatten(target_weights);
+        audit("kill", None, "engaged", reason);
+    }
+
+    /// One admin approval towards re-arming after the kill switch.
+    pub fn rearm(&self, token: &str) -> InfraResult<String> {
+        if !self.is_killed() {
+            audit("rearm", None, "rejected", "kill switch not engaged");
+            return Err(InfraError::Msg("kill switch is not engaged".into()));
+        }
+        if self.admins.is_empty() {
+            audit("rearm", None, "rejected", "no admin tokens configured");
+            return Err(InfraError::Msg("no admin tokens configured".into()));
+        }
+        let Some(admin) = self.admins.identify(token) else {
+            audit("rearm", None, "rejected", "invalid admin token");
+            return Err(InfraError::Msg("invalid admin token".into()));
+        };
+
+        let mut approvals = self.rearm.lock().map_err(|_| {
+            audit("rearm", Some(admin), "rejected", "approval state poisoned");
+            InfraError::Msg("re-arm state poisoned".into())
+        })?;
+
+        match approvals.approve(admin) {
+            RearmDecision::Pending(detail) => {
+                audit("rearm", Some(admin), "pending", &detail);
+                Ok(format!("pending: {}", detail))
+            },
+            RearmDecision::Granted(detail) => {
+                self.killed.store(false, Ordering::Relaxed);
+                self.paused.store(false, Ordering::Relaxed);
+                self.flattened.store(false, Ordering::Relaxed);
+                self.persist();
+                audit("rearm", Some(admin), "rearmed", &detail);
+                warn!("[Control] Kill switch re-armed: {}", detail);
+                Ok(format!("rearmed: {}", detail))
+            },
+        }
+    }
+
+    fn pending_rearm(&self) -> Option<String> {
+        self.rearm.lock().ok().and_then(|a| a.pending_admin())
+    }
+
+    pub fn flatten(&self, target_weights: &TargetWeights) {
+        self.flattened.store(true, Ordering::Relaxed);
+        self.paused.store(false, Ordering::Relaxed);
+        self.persist();
+        target_weights
+            .iter_mut()
+            .for_each(|mut entry| entry.value_mut().1 = 0.0);
+    }
+
+    pub fn migration(&self) -> Option<AccountMigration> {
+        self.migration.lock().ok().and_then(|m| m.clone())
+    }
+
+    pub fn set_migration(&self, migration: Option<AccountMigration>) {
+        if let Ok(mut m) = self.migration.lock() {
+            *m = migration;
+        }
+    }
+
+    pub fn start_migration(&self, from: &str, to: &str) -> InfraResult<()> {
+        if from == to {
+            return Err(InfraError::Msg("source and destination are the same".into()));
+        }
+
+        let mut m = self
+            .migration
+            .lock()
+            .map_err(|_| InfraError::Msg("migration state poisoned".into()))?;
+        if m.as_ref().is_some_and(|m| m.is_active()) {
+            return Err(InfraError::Msg("a migration is already running".into()));
+        }
+
+        *m = Some(AccountMigration {
+            from: from.to_string(),
+            to: to.to_string(),
+            stage: MigrationStage::Requested,
+            detail: String::new(),
+        });
+        Ok(())
+    }
+}
+
+/// Executes one line of the control protocol and returns the reply payload.
+pub fn handle_control_command(
+    line: &str,
+    state: &ControlState,
+    target_weights: &TargetWeights,
+    portfolio: &PortfolioView,
+    own_orders: &OwnOrderBook,
+) -> InfraResult<String> {
+    let mut parts = line.split_whitespace();
+    let cmd = parts.next().unwrap_or_default().to_lowercase();
+
+    match cmd.as_str() {
+        "status" => {
+            let snapshot = portfolio.read();
+            let targets: serde_json::Map<String, serde_json::Value> = snapshot
+                .targets
+                .iter()
+                .map(|(inst, (_, weight))| (inst.clone(), json!(weight)))
+                .collect();
+
+            return Ok(json!({
+                "paused": state.is_paused(),
+                "flattened": state.is_flattened(),
+                "killed": state.is_killed(),
+                "observe": active_profile().observe,
+                "rearm_pending": state.pending_rearm(),
+                "targets": targets,
+                "migration": state.migration(),
+                "accounts": snapshot.accounts,
+                "exposure": ExposureReport::from_accounts(&snapshot.accounts),
+                "snapshot_version": snapshot.version,
+                "snapshot_ts": snapshot.timestamp,
+            })
+            .to_string());
+        },
+        // Resting orders of all accounts per venue
+        "orders" => {
+            let mut book = own_orders.snapshot();
+            if let Some(venue) = parts.next() {
+                book.retain(|v, _| v.eq_ignore_ascii_case(venue));
+            }
+            return Ok(json!(book).to_string());
+        },
+        "pause" => state.pause(),
+        "resume" => state.resume()?,
+        "rearm" => {
+            let Some(token) = parts.next() else {
+                return Err(InfraError::Msg("usage: rearm <admin_token>".into()));
+            };
+            return state.rearm(token);
+        },
+        "flatten" => state.flatten(target_weights),
+        "set-target" => {
+            let (Some(inst), Some(weight)) = (parts.next(), parts.next()) else {
+                return Err(InfraError::Msg("usage: set-target <inst> <weight>".into()));
+            };
+
+            let weight = weight
+                .parse::<f64>()
+                .ok()
+                .filter(|w| w.is_finite())
+                .ok_or_else(|| InfraError::Msg(format!("invalid weight: {}", weight)))?;
+
+            if state.is_flattened() && weight != 0.0 {
+                return Err(InfraError::Msg("targets are flattened, resume first".into()));
+            }
+
+            // Sizing needs the mark price published alongside the model target
+            let Some(mut entry) = target_weights.get_mut(inst) else {
+                return Err(InfraError::Msg(format!("no priced target for {}", inst)));
+            };
+            entry.1 = weight;
+        },
+        "migrate" => {
+            let (Some(from), Some(to)) = (parts.next(), parts.next()) else {
+                return Err(InfraError::Msg("usage: migrate <from_account> <to_account>".into()));
+            };
+            state.start_migration(from, to)?;
+        },
+        // Aborts a running migration or releases the pinned source account after one
+        "migrate-clear" => state.set_migration(None),
+        "" => return Err(InfraError::Msg("empty command".into())),
+        other => return Err(InfraError::Msg(format!("unknown command: {}", other))),
+    };
+
+    portfolio.publish_targets(target_weights, None);
+    info!("[Control] Executed: {}", redact_command(line));
+    Ok("done".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dashmap::DashMap;
+
+    struct TempFile(PathBuf);
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn kill_survives_restart() {
+        let file = TempFile(
+            std::env::temp_dir().join(format!("control_state_test_{}.json", std::process::id())),
+        );
+        let targets: TargetWeights = Arc::new(DashMap::new());
+
+        let mut before = ControlState::default();
+        before.restore(&file.0).unwrap();
+        before.kill(&targets, "test");
+
+        let mut after = ControlState::default();
+        after.restore(&file.0).unwrap();
+
+        assert!(after.is_killed());
+        assert!(after.is_flattened());
+        assert!(after.resume().is_err());
+    }
+
+    #[test]
+    fn kill_clears_pending_rearm() {
+        let admins = ["admin-token-0000001", "admin-token-0000002"];
+        let targets: TargetWeights = Arc::new(DashMap::new());
+        let mut state = ControlState::default();
+        state.with_admin_tokens(AdminTokens::from_tokens(&admins));
+
+        state.kill(&targets, "test");
+        assert!(state.rearm(admins[0]).unwrap().starts_with("pending"));
+        assert!(state.pending_rearm().is_some());
+
+        state.kill(&targets, "again");
+        assert!(state.pending_rearm().is_none());
+        assert!(state.rearm(admins[1]).unwrap().starts_with("pending"));
+        assert!(state.is_killed());
+    }
+}
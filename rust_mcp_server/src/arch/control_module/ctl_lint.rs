@@ -0,0 +1,430 @@
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+};
+
+use extrema_infra::prelude::*;
+
+use super::ctl_risk::RiskLimits;
+use crate::arch::{
+    account_module::{
+        acc_base::fetch_inst_infos,
+        acc_cache::MetaCache,
+        acc_utils::{AccountFileConfig, exchange_market, load_account_config},
+    },
+    feats::feat_config::load_feature_config,
+    server_module::{
+        server_ensemble::{ENSEMBLE_FILE, load_ensemble_config},
+        server_utils::{ModelConfig, UNIVERSE_FILE, load_model_config, load_universe},
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The process would fail or misbehave with this config.
+    Error,
+    /// Suspicious but runnable, e.g. a venue check that could not be made.
+    Warning,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    /// File the issue was found in, e.g. `model_config.json`.
+    pub config: &'static str,
+    /// Entry at fault, e.g. a model or account id.
+    pub subject: String,
+    pub message: String,
+}
+
+/// Instrument named by a config entry, with the account whose venue must list it.
+struct InstRef {
+    config: &'static str,
+    subject: String,
+    inst: String,
+    /// Unset for entries that apply to every account, e.g. risk overrides.
+    account_id: Option<String>,
+}
+
+/// Findings of `lint_configs`, printed as JSON by `--validate-config`.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    fn push(&mut self, severity: Severity, config: &'static str, subject: &str, message: String) {
+        self.issues.push(ConfigIssue {
+            severity,
+            config,
+            subject: subject.to_string(),
+            message,
+        });
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == severity)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "ok": !self.has_errors(),
+            "errors": self.count(Severity::Error),
+            "warnings": self.count(Severity::Warning),
+            "issues": self.issues,
+        })
+    }
+}
+
+/// Loads and cross-checks the configs of the active profile.
+pub async fn lint_configs(reserved_task_ids: &[u64]) -> ConfigReport {
+    let mut report = ConfigReport::default();
+
+    let accounts = match load_account_config() {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            report.push(
+                Severity::Error,
+                "account_config.json",
+                "",
+                format!("{:?}", e),
+            );
+            Vec::new()
+        },
+    };
+    let models = match load_model_config() {
+        Ok(models) => models,
+        Err(e) => {
+            report.push(Severity::Error, "model_config.json", "", format!("{:?}", e));
+            Vec::new()
+        },
+    };
+    let universe = match load_universe() {
+        Ok(universe) => universe,
+        Err(e) => {
+            report.push(Severity::Error, UNIVERSE_FILE, "", format!("{:?}", e));
+            Vec::new()
+        },
+    };
+    if let Err(e) = load_feature_config() {
+        report.push(
+            Severity::Error,
+            "feature_config.json",
+            "",
+            format!("{:?}", e),
+        );
+    }
+    if let Err(e) = load_ensemble_config() {
+        report.push(Severity::Error, ENSEMBLE_FILE, "", format!("{:?}", e));
+    }
+    let risk = match RiskLimits::load() {
+        Ok(limits) => Some(limits.get()),
+        Err(e) => {
+            report.push(Severity::Error, "risk_config.json", "", format!("{:?}", e));
+            None
+        },
+    };
+
+    lint_accounts(&accounts, &mut report);
+    lint_models(&models, &accounts, reserved_task_ids, &mut report);
+
+    let mut inst_refs: Vec<InstRef> = Vec::new();
+    for cfg in accounts.iter() {
+        for inst in cfg.leverage.keys().filter(|inst| *inst != "*") {
+            inst_refs.push(InstRef {
+                config: "account_config.json",
+                subject: cfg.account_id.clone(),
+                inst: inst.clone(),
+                account_id: Some(cfg.account_id.clone()),
+            });
+        }
+    }
+    for cfg in models.iter() {
+        let mut insts: Vec<String> = cfg.insts.clone();
+        if insts.is_empty() {
+            insts.extend(universe.iter().cloned());
+        }
+        insts.extend(cfg.fallback_weights.keys().cloned());
+        for inst in insts {
+            inst_refs.push(InstRef {
+                config: "model_config.json",
+                subject: cfg.model_id.clone(),
+                inst,
+                account_id: Some(cfg.account_id.clone()),
+            });
+        }
+    }
+    for inst in universe.iter() {
+        inst_refs.push(InstRef {
+            config: UNIVERSE_FILE,
+            subject: inst.clone(),
+            inst: inst.clone(),
+            account_id: None,
+        });
+    }
+    if let Some(risk) = risk.as_ref() {
+        for inst in risk
+            .min_notional_overrides
+            .keys()
+            .chain(risk.risk_off_windows.keys())
+        {
+            inst_refs.push(InstRef {
+                config: "risk_config.json",
+                subject: inst.clone(),
+                inst: inst.clone(),
+                account_id: None,
+            });
+        }
+    }
+    if !inst_refs.is_empty() {
+        lint_insts(&inst_refs, &accounts, &mut report).await;
+    }
+
+    report
+}
+
+fn lint_accounts(accounts: &[AccountFileConfig], report: &mut ConfigReport) {
+    const FILE: &str = "account_config.json";
+    let mut ids = HashSet::new();
+    let mut task_ids: HashMap<u64, &str> = HashMap::new();
+
+    for cfg in accounts.iter() {
+        let id = cfg.account_id.as_str();
+        if !ids.insert(id) {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                "duplicate account_id".to_string(),
+            );
+        }
+        if exchange_market(&cfg.exchange).is_none() {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                format!("unknown exchange {}", cfg.exchange),
+            );
+        }
+        if !cfg.paper && (cfg.api_key.is_empty() || cfg.api_secret.is_empty()) {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                "live account without API key".to_string(),
+            );
+        }
+        if cfg.account_orders_task_id == cfg.account_bal_pos_task_id {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                format!(
+                    "orders and bal/pos share task id {}",
+                    cfg.account_orders_task_id
+                ),
+            );
+        }
+        for task_id in [cfg.account_orders_task_id, cfg.account_bal_pos_task_id] {
+            match task_ids.get(&task_id) {
+                Some(other) if *other != id => report.push(
+                    Severity::Error,
+                    FILE,
+                    id,
+                    format!("task id {} already used by account {}", task_id, other),
+                ),
+                _ => {
+                    task_ids.insert(task_id, id);
+                },
+            }
+        }
+        for (inst, leverage) in cfg.leverage.iter() {
+            if *leverage == 0 {
+                report.push(
+                    Severity::Error,
+                    FILE,
+                    id,
+                    format!("leverage of {} is zero", inst),
+                );
+            }
+        }
+    }
+}
+
+fn lint_models(
+    models: &[ModelConfig],
+    accounts: &[AccountFileConfig],
+    reserved_task_ids: &[u64],
+    report: &mut ConfigReport,
+) {
+    const FILE: &str = "model_config.json";
+    let mut ids = HashSet::new();
+
+    for cfg in models.iter() {
+        let id = cfg.model_id.as_str();
+        if id.is_empty() {
+            report.push(Severity::Error, FILE, id, "empty model_id".to_string());
+        }
+        if !ids.insert(id) {
+            report.push(Severity::Error, FILE, id, "duplicate model_id".to_string());
+        }
+        if !accounts.iter().any(|acc| acc.account_id == cfg.account_id) {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                format!("account_id {} not in account_config.json", cfg.account_id),
+            );
+        }
+        if cfg.port == 0 || cfg.port > u16::MAX as u64 {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                format!("invalid port {}", cfg.port),
+            );
+        } else if reserved_task_ids.contains(&cfg.port) {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                format!("port {} collides with a task id", cfg.port),
+            );
+        }
+        if let Some(leverage) = cfg.target_leverage
+            && !(leverage > 0.0 && leverage.is_finite())
+        {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                format!("target_leverage {} must be positive", leverage),
+            );
+        }
+        if let Some(windows) = cfg.oi_change_windows.as_ref()
+            && windows.iter().any(|w| *w <= 0)
+        {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                format!("oi_change_windows {:?} must be positive", windows),
+            );
+        }
+        if cfg.interval_sec == Some(0) {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                "interval_sec must be positive".to_string(),
+            );
+        }
+        if cfg.max_prediction_age_ms == Some(0) {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                "max_prediction_age_ms must be positive".to_string(),
+            );
+        }
+        for (name, bound) in [
+            ("max_abs_target", cfg.max_abs_target),
+            ("max_price_deviation_bps", cfg.max_price_deviation_bps),
+        ] {
+            if let Some(bound) = bound
+                && !(bound > 0.0 && bound.is_finite())
+            {
+                report.push(
+                    Severity::Error,
+                    FILE,
+                    id,
+                    format!("{} {} must be positive", name, bound),
+                );
+            }
+        }
+        if let Some(weight) = cfg.ensemble_weight
+            && !(weight >= 0.0 && weight.is_finite())
+        {
+            report.push(
+                Severity::Error,
+                FILE,
+                id,
+                format!("ensemble_weight {} must not be negative", weight),
+            );
+        }
+        if let Some(name) = cfg
+            .auth_secret
+            .as_deref()
+            .and_then(|secret| secret.strip_prefix("env:"))
+            && env::var(name).map_or(true, |v| v.is_empty())
+        {
+            report.push(
+                Severity::Warning,
+                FILE,
+                id,
+                format!(
+                    "{} not set here, the model's commands would be refused",
+                    name
+                ),
+            );
+        }
+    }
+}
+
+/// Each instrument must be listed on the venue it trades on.
+async fn lint_insts(
+    inst_refs: &[InstRef],
+    accounts: &[AccountFileConfig],
+    report: &mut ConfigReport,
+) {
+    let infos = match fetch_inst_infos(&MetaCache::default()).await {
+        Ok(infos) => infos,
+        Err(e) => {
+            report.push(
+                Severity::Warning,
+                "instruments",
+                "",
+                format!("venue instrument lists unavailable, skipped: {:?}", e),
+            );
+            return;
+        },
+    };
+    let markets: HashMap<&str, Market> = accounts
+        .iter()
+        .filter_map(|cfg| Some((cfg.account_id.as_str(), exchange_market(&cfg.exchange)?)))
+        .collect();
+
+    for inst_ref in inst_refs.iter() {
+        let candidates: Vec<&Market> = match inst_ref.account_id.as_deref() {
+            Some(id) => markets.get(id).into_iter().collect(),
+            None => markets.values().collect(),
+        };
+        if candidates.is_empty() {
+            continue;
+        }
+        if !candidates
+            .iter()
+            .any(|market| infos.contains_key(&(inst_ref.inst.clone(), (*market).clone())))
+        {
+            report.push(
+                Severity::Error,
+                inst_ref.config,
+                &inst_ref.subject,
+                format!(
+                    "instrument {} not listed on {:?}",
+                    inst_ref.inst, candidates
+                ),
+            );
+        }
+    }
+}
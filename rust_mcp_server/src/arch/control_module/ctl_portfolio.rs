@@ -0,0 +1,71 @@
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use extrema_infra::arch::market_assets::api_general::get_micros_timestamp;
+
+use crate::arch::account_module::{acc_base::TargetWeights, acc_utils::AccountSnapshot};
+
+/// Targets, prices and account state as of one publish.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PortfolioSnapshot {
+    /// Bumped on every publish.
+    pub version: u64,
+    pub timestamp: u64,
+    /// Decision price and consolidated weight per instrument.
+    pub targets: HashMap<String, (f64, f64)>,
+    /// Last price per instrument on the configured price market.
+    pub prices: HashMap<String, f64>,
+    pub accounts: HashMap<String, AccountSnapshot>,
+}
+
+/// Latest `PortfolioSnapshot`, shared by `McpServer`, `AccountManager` and the control socket.
+#[derive(Clone, Debug, Default)]
+pub struct PortfolioView {
+    current: Arc<RwLock<Arc<PortfolioSnapshot>>>,
+}
+
+impl PortfolioView {
+    pub fn read(&self) -> Arc<PortfolioSnapshot> {
+        self.current
+            .read()
+            .map(|snapshot| snapshot.clone())
+            .unwrap_or_default()
+    }
+
+    /// Copies the consolidated targets, together with `prices` when given.
+    pub fn publish_targets(
+        &self,
+        target_weights: &TargetWeights,
+        prices: Option<HashMap<String, f64>>,
+    ) {
+        let targets = target_weights
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        self.publish(|snapshot| {
+            snapshot.targets = targets;
+            if let Some(prices) = prices {
+                snapshot.prices = prices;
+            }
+        });
+    }
+
+    pub fn publish_accounts(&self, accounts: HashMap<String, AccountSnapshot>) {
+        self.publish(|snapshot| snapshot.accounts = accounts);
+    }
+
+    fn publish(&self, update: impl FnOnce(&mut PortfolioSnapshot)) {
+        let Ok(mut current) = self.current.write() else {
+            return;
+        };
+
+        let mut next = (**current).clone();
+        update(&mut next);
+        next.version += 1;
+        next.timestamp = get_micros_timestamp();
+        *current = Arc::new(next);
+    }
+}
@@ -0,0 +1,394 @@
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+use tracing::{error, info, warn};
+
+use extrema_infra::prelude::*;
+
+use crate::arch::{
+    account_module::acc_errors::ErrorBudgetConfig,
+    control_module::ctl_watchdog::WatchdogConfig,
+    profile::config_path,
+    risk_module::risk_schedule::RiskOffWindow,
+};
+
+pub const RISK_CONFIG_FILE: &str = "risk_config.json";
+
+/// Risk limits from `risk_config.json`, re-read whenever the file changes.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskConfig {
+    /// Weight gap below which an instrument is not rebalanced.
+    pub rebalance_threshold: f64,
+    /// Floor on rebalance order notional on every venue, in quote currency.
+    pub min_order_notional: f64,
+    /// Minimum order notional per instrument, replacing both the floor and the venue minimum.
+    pub min_notional_overrides: HashMap<String, f64>,
+    /// Cap on the gross consolidated target weight.
+    pub max_gross_weight: Option<f64>,
+    /// Cap on the absolute consolidated target weight of any single instrument.
+    pub max_inst_weight: Option<f64>,
+    /// Cap on the absolute net (long minus short) consolidated target weight.
+    pub max_net_weight: Option<f64>,
+    /// Cap on the net position of one asset across accounts, as a fraction of equity.
+    pub max_asset_net_weight: Option<f64>,
+    /// Windows per instrument in which its target weight is held at zero.
+    pub risk_off_windows: HashMap<String, Vec<RiskOffWindow>>,
+    /// Equity drawdown from the start of the UTC day, as a fraction, that flattens all targets.
+    pub daily_loss_limit: Option<f64>,
+    /// Drawdown over `drawdown_window_sec` that trips the account's breaker.
+    pub max_drawdown: Option<f64>,
+    pub drawdown_window_sec: u64,
+    /// Tripped accounts trade their positions flat; off, they hold them instead.
+    pub drawdown_flatten: bool,
+    /// Minimum seconds between two rebalance orders on the same instrument.
+    pub order_cooldown_sec: u64,
+    /// Seconds an unconfirmed order keeps its instrument blocked before it is presumed terminal.
+    pub inflight_timeout_sec: u64,
+    /// VPIN at or above which an instrument's flow counts as toxic.
+    pub toxicity_threshold: Option<f64>,
+    /// Factor `rebalance_threshold` is widened by while an instrument's flow is toxic.
+    pub toxicity_band_mult: f64,
+    /// Engages the kill switch when set. Clearing it does not re-arm; the admins do.
+    pub kill_switch: bool,
+    /// Escalates account update failures past a budget instead of only logging them.
+    pub account_error_budget: Option<ErrorBudgetConfig>,
+    /// Consecutive placement failures per instrument before the models are notified.
+    pub max_exec_failures: u32,
+    /// Weight gap under which an instrument counts as converged.
+    pub converged_tolerance: f64,
+    /// Paper limit prices further than this fraction from mark are refused.
+    pub paper_price_band: f64,
+    /// Stall thresholds and restart limits of the task watchdog; changes apply on restart.
+    pub watchdog: WatchdogConfig,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            rebalance_threshold: 0.01,
+            min_order_notional: 6.0,
+            min_notional_overrides: HashMap::new(),
+            max_gross_weight: None,
+            max_inst_weight: None,
+            max_net_weight: None,
+            max_asset_net_weight: None,
+            risk_off_windows: HashMap::new(),
+            daily_loss_limit: None,
+            max_drawdown: None,
+            drawdown_window_sec: 30 * 86_400,
+            drawdown_flatten: true,
+            order_cooldown_sec: 0,
+            inflight_timeout_sec: 30,
+            toxicity_threshold: None,
+            toxicity_band_mult: 2.0,
+            kill_switch: false,
+            account_error_budget: None,
+            max_exec_failures: 3,
+            converged_tolerance: 0.01,
+            paper_price_band: 0.05,
+            watchdog: WatchdogConfig::default(),
+        }
+    }
+}
+
+impl RiskConfig {
+    /// Served while the shared limits cannot be read.
+    fn fail_closed() -> Self {
+        Self {
+            max_gross_weight: Some(0.0),
+            kill_switch: true,
+            ..Self::default()
+        }
+    }
+
+    /// Rebalance threshold of an instrument at `vpin`, widened while its flow is toxic.
+    pub fn rebalance_band(&self, vpin: Option<f64>) -> f64 {
+        let toxic = self
+            .toxicity_threshold
+            .is_some_and(|limit| vpin.is_some_and(|v| v >= limit));
+        if toxic {
+            self.rebalance_threshold * self.toxicity_band_mult
+        } else {
+            self.rebalance_threshold
+        }
+    }
+
+    pub fn validate(&self) -> InfraResult<()> {
+        let mut errors = Vec::new();
+        if !(self.rebalance_threshold > 0.0 && self.rebalance_threshold < 1.0) {
+            errors.push(format!("rebalance_threshold {} not in (0, 1)", self.rebalance_threshold));
+        }
+        if !(self.min_order_notional >= 0.0 && self.min_order_notional.is_finite()) {
+            errors.push(format!("min_order_notional {} negative", self.min_order_notional));
+        }
+        for (inst, min) in self.min_notional_overrides.iter() {
+            if !(*min >= 0.0 && min.is_finite()) {
+                errors.push(format!("min_notional_overrides {} {} negative", inst, min));
+            }
+        }
+        for (name, cap) in [
+            ("max_gross_weight", self.max_gross_weight),
+            ("max_inst_weight", self.max_inst_weight),
+        ] {
+            if let Some(cap) = cap
+                && !(cap > 0.0 && cap.is_finite())
+            {
+                errors.push(format!("{} {} must be positive", name, cap));
+            }
+        }
+        if let Some(cap) = self.max_net_weight
+            && !(cap >= 0.0 && cap.is_finite())
+        {
+            errors.push(format!("max_net_weight {} negative", cap));
+        }
+        if let Some(cap) = self.max_asset_net_weight
+            && !(cap >= 0.0 && cap.is_finite())
+        {
+            errors.push(format!("max_asset_net_weight {} negative", cap));
+        }
+        for (inst, windows) in self.risk_off_windows.iter() {
+            for window in windows.iter() {
+                if let Err(e) = window.validate() {
+                    errors.push(format!("risk_off_windows {}: {}", inst, e));
+                }
+            }
+        }
+        if self.inflight_timeout_sec == 0 {
+            errors.push("inflight_timeout_sec must be positive".to_string());
+        }
+        if let Some(threshold) = self.toxicity_threshold
+            && !(threshold > 0.0 && threshold < 1.0)
+        {
+            errors.push(format!("toxicity_threshold {} not in (0, 1)", threshold));
+        }
+        if !(self.toxicity_band_mult >= 1.0 && self.toxicity_band_mult.is_finite()) {
+            errors.push(format!("toxicity_band_mult {} below 1", self.toxicity_band_mult));
+        }
+        if let Some(limit) = self.daily_loss_limit
+            && !(limit > 0.0 && limit < 1.0)
+        {
+            errors.push(format!("daily_loss_limit {} not in (0, 1)", limit));
+        }
+        if let Some(limit) = self.max_drawdown
+            && !(limit > 0.0 && limit < 1.0)
+        {
+            errors.push(format!("max_drawdown {} not in (0, 1)", limit));
+        }
+        if self.drawdown_window_sec == 0 {
+            errors.push("drawdown_window_sec must be positive".to_string());
+        }
+        if let Some(budget) = &self.account_error_budget
+            && let Err(e) = budget.validate()
+        {
+            errors.push(format!("account_error_budget: {}", e));
+        }
+        if self.max_exec_failures == 0 {
+            errors.push("max_exec_failures must be positive".to_string());
+        }
+        for (name, fraction) in [
+            ("converged_tolerance", self.converged_tolerance),
+            ("paper_price_band", self.paper_price_band),
+        ] {
+            if !(fraction > 0.0 && fraction < 1.0) {
+                errors.push(format!("{} {} not in (0, 1)", name, fraction));
+            }
+        }
+        if let Err(e) = self.watchdog.validate() {
+            errors.push(format!("watchdog: {}", e));
+        }
+
+        if !errors.is_empty() {
+            return Err(InfraError::Msg(format!("Invalid risk config: {}", errors.join("; "))));
+        }
+        Ok(())
+    }
+
+    /// Instruments inside one of their `risk_off_windows` at `now_ms`.
+    pub fn risk_off_insts(&self, now_ms: u64) -> HashSet<String> {
+        self.risk_off_windows
+            .iter()
+            .filter(|(_, windows)| windows.iter().any(|w| w.is_active(now_ms)))
+            .map(|(inst, _)| inst.clone())
+            .collect()
+    }
+
+    /// Clamps a target book to the instrument, gross and net exposure caps.
+    pub fn clamp_exposure(&self, book: &str, weights: &mut HashMap<String, f64>) {
+        if let Some(cap) = self.max_inst_weight {
+            for (inst, w) in weights.iter_mut() {
+                if w.abs() > cap {
+                    warn!(
+                        "[Risk] {} target weight {:.4} of {} above cap {}, clamping",
+                        book, w, inst, cap
+                    );
+                    *w = w.clamp(-cap, cap);
+                }
+            }
+        }
+
+        let gross: f64 = weights.values().map(|w| w.abs()).sum();
+        if let Some(cap) = self.max_gross_weight
+            && gross > cap
+        {
+            warn!(
+                "[Risk] {} gross target weight {:.4} above cap {}, scaling down",
+                book, gross, cap
+            );
+            weights.values_mut().for_each(|w| *w *= cap / gross);
+        }
+
+        let net: f64 = weights.values().sum();
+        if let Some(cap) = self.max_net_weight
+            && net.abs() > cap
+        {
+            warn!("[Risk] {} net target weight {:.4} above cap {}, scaling down", book, net, cap);
+            weights.values_mut().for_each(|w| *w *= cap / net.abs());
+        }
+    }
+}
+
+/// Current risk limits shared by `AccountManager` and `McpServer`.
+#[derive(Clone, Debug)]
+pub struct RiskLimits {
+    config: Arc<RwLock<RiskConfig>>,
+    modified: Arc<RwLock<Option<SystemTime>>>,
+    path: PathBuf,
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            config: Arc::new(RwLock::new(RiskConfig::default())),
+            modified: Arc::new(RwLock::new(None)),
+            path: PathBuf::from(RISK_CONFIG_FILE),
+        }
+    }
+}
+
+impl RiskLimits {
+    /// Loads `risk_config.json` from the profile's config directory.
+    pub fn load() -> InfraResult<Self> {
+        let limits = Self {
+            path: config_path(RISK_CONFIG_FILE)?,
+            ..Self::default()
+        };
+
+        if limits.path.exists() {
+            let cfg = read_risk_config(&limits.path)?;
+            info!("[Risk] Loaded {:?}: {:?}", limits.path, cfg);
+            limits.store(cfg);
+        } else {
+            info!("[Risk] {:?} not found, using defaults", limits.path);
+        }
+
+        Ok(limits)
+    }
+
+    /// Current limits. A poisoned lock fails closed, see `RiskConfig::fail_closed`.
+    pub fn get(&self) -> RiskConfig {
+        match self.config.read() {
+            Ok(cfg) => cfg.clone(),
+            Err(_) => {
+                error!("[Risk] Limits lock poisoned, failing closed");
+                RiskConfig::fail_closed()
+            },
+        }
+    }
+
+    /// Re-reads the file when its modification time moved.
+    pub fn reload_if_changed(&self) -> bool {
+        let Ok(modified) = fs::metadata(&self.path).and_then(|m| m.modified()) else {
+            return false;
+        };
+        if self.modified.read().is_ok_and(|m| *m == Some(modified)) {
+            return false;
+        }
+        if let Ok(mut m) = self.modified.write() {
+            *m = Some(modified);
+        }
+
+        match read_risk_config(&self.path) {
+            Ok(cfg) if cfg == self.get() => false,
+            Ok(cfg) => {
+                info!("[Risk] Limits reloaded: {:?}", cfg);
+                self.store(cfg);
+                true
+            },
+            Err(e) => {
+                error!("[Risk] Keeping previous limits: {:?}", e);
+                false
+            },
+        }
+    }
+
+    /// Replaces the limits in place, as a reload of the file would.
+    #[cfg(test)]
+    pub fn set(&self, cfg: RiskConfig) {
+        self.store(cfg);
+    }
+
+    fn store(&self, cfg: RiskConfig) {
+        if let Ok(mut current) = self.config.write() {
+            *current = cfg;
+        }
+        if let Ok(mut m) = self.modified.write() {
+            *m = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        }
+    }
+}
+
+fn read_risk_config(path: &Path) -> InfraResult<RiskConfig> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| InfraError::Msg(format!("Failed to read risk config: {}", e)))?;
+    let cfg: RiskConfig = serde_json::from_str(&content)
+        .map_err(|e| InfraError::Msg(format!("Failed to parse risk config: {}", e)))?;
+    cfg.validate()?;
+    Ok(cfg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_config_is_valid() {
+        let cfg: RiskConfig =
+            serde_json::from_str(include_str!("../../../risk_config.example.json")).unwrap();
+        assert!(cfg.validate().is_ok());
+        assert!(RiskConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn every_invalid_field_is_reported() {
+        let cfg = RiskConfig {
+            rebalance_threshold: 0.0,
+            max_gross_weight: Some(-1.0),
+            daily_loss_limit: Some(1.5),
+            paper_price_band: f64::NAN,
+            ..RiskConfig::default()
+        };
+
+        let err = format!("{:?}", cfg.validate().unwrap_err());
+        for field in [
+            "rebalance_threshold",
+            "max_gross_weight",
+            "daily_loss_limit",
+            "paper_price_band",
+        ] {
+            assert!(err.contains(field), "{} missing from {}", field, err);
+        }
+        assert!(!err.contains("max_inst_weight"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_field_is_refused() {
+        let parsed = serde_json::from_str::<RiskConfig>(r#"{ "rebalance_treshold": 0.02 }"#);
+        assert!(parsed.is_err());
+    }
+}
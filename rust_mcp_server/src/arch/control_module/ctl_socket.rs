@@ -0,0 +1,172 @@
+use std::{
+    env, fs,
+    os::unix::{
+        fs::{FileTypeExt, PermissionsExt},
+        io::FromRawFd,
+        net::{UnixListener as StdUnixListener, UnixStream as StdUnixStream},
+    },
+    path::PathBuf,
+    process,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    task::JoinHandle,
+};
+use tracing::{error, info, warn};
+
+use extrema_infra::prelude::*;
+
+use super::{
+    ctl_admin::redact_command,
+    ctl_base::{ControlState, handle_control_command},
+    ctl_portfolio::PortfolioView,
+};
+use crate::arch::account_module::{acc_base::TargetWeights, acc_own_orders::OwnOrderBook};
+
+/// First descriptor passed by systemd socket activation (`SD_LISTEN_FDS_START`).
+const LISTEN_FDS_START: i32 = 3;
+/// Owner read/write only: the socket accepts admin commands.
+const SOCKET_MODE: u32 = 0o600;
+
+/// Line-based operator socket, e.g. `echo status | socat - UNIX-CONNECT:mcp_control.sock`.
+pub struct ControlSocket {
+    path: PathBuf,
+    state: ControlState,
+    target_weights: TargetWeights,
+    portfolio: PortfolioView,
+    own_orders: OwnOrderBook,
+}
+
+impl ControlSocket {
+    pub fn new(path: &str, state: ControlState, target_weights: TargetWeights) -> Self {
+        Self {
+            path: PathBuf::from(path),
+            state,
+            target_weights,
+            portfolio: PortfolioView::default(),
+            own_orders: OwnOrderBook::default(),
+        }
+    }
+
+    pub fn with_portfolio_view(&mut self, portfolio: PortfolioView) -> &mut Self {
+        self.portfolio = portfolio;
+        self
+    }
+
+    pub fn with_own_orders(&mut self, own_orders: OwnOrderBook) -> &mut Self {
+        self.own_orders = own_orders;
+        self
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            if let Err(e) = self.run().await {
+                error!("[Control] Socket stopped: {}", e);
+            }
+        })
+    }
+
+    async fn run(self) -> InfraResult<()> {
+        let listener = self.listener()?;
+
+        loop {
+            let (stream, _) = listener
+                .accept()
+                .await
+                .map_err(|e| InfraError::Msg(format!("Control socket accept failed: {}", e)))?;
+
+            let state = self.state.clone();
+            let target_weights = self.target_weights.clone();
+            let portfolio = self.portfolio.clone();
+            let own_orders = self.own_orders.clone();
+            tokio::spawn(async move {
+                let served =
+                    serve_connection(stream, state, target_weights, portfolio, own_orders).await;
+                if let Err(e) = served {
+                    warn!("[Control] Connection error: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Uses the socket handed over by systemd when `LISTEN_PID` matches, otherwise binds `path`.
+    fn listener(&self) -> InfraResult<UnixListener> {
+        let activated = env::var("LISTEN_PID").ok().and_then(|p| p.parse::<u32>().ok())
+            == Some(process::id())
+            && env::var("LISTEN_FDS")
+                .ok()
+                .and_then(|n| n.parse::<u32>().ok())
+                .is_some_and(|n| n >= 1);
+
+        let std_listener = if activated {
+            info!("[Control] Using socket-activated fd {}", LISTEN_FDS_START);
+            // SAFETY: systemd guarantees fd 3 is an open listening socket owned by this process
+            unsafe { StdUnixListener::from_raw_fd(LISTEN_FDS_START) }
+        } else {
+            self.remove_stale_socket()?;
+            let listener = StdUnixListener::bind(&self.path).map_err(|e| {
+                InfraError::Msg(format!("Failed to bind control socket {:?}: {}", self.path, e))
+            })?;
+            fs::set_permissions(&self.path, fs::Permissions::from_mode(SOCKET_MODE)).map_err(
+                |e| InfraError::Msg(format!("Failed to restrict socket {:?}: {}", self.path, e)),
+            )?;
+            info!("[Control] Listening on {:?}", self.path);
+            listener
+        };
+
+        std_listener
+            .set_nonblocking(true)
+            .and_then(|_| UnixListener::from_std(std_listener))
+            .map_err(|e| InfraError::Msg(format!("Failed to register control socket: {}", e)))
+    }
+
+    /// Removes a socket left by a previous run.
+    fn remove_stale_socket(&self) -> InfraResult<()> {
+        let Ok(meta) = fs::symlink_metadata(&self.path) else {
+            return Ok(());
+        };
+        if !meta.file_type().is_socket() {
+            return Err(InfraError::Msg(format!(
+                "{:?} exists and is not a socket, not removing it",
+                self.path
+            )));
+        }
+        if StdUnixStream::connect(&self.path).is_ok() {
+            return Err(InfraError::Msg(format!(
+                "{:?} is in use by another process",
+                self.path
+            )));
+        }
+
+        fs::remove_file(&self.path).map_err(|e| {
+            InfraError::Msg(format!("Failed to remove stale socket {:?}: {}", self.path, e))
+        })
+    }
+}
+
+async fn serve_connection(
+    stream: UnixStream,
+    state: ControlState,
+    target_weights: TargetWeights,
+    portfolio: PortfolioView,
+    own_orders: OwnOrderBook,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        let result =
+            handle_control_command(&line, &state, &target_weights, &portfolio, &own_orders);
+        let reply = match result {
+            Ok(payload) => format!("ok {}\n", payload),
+            Err(e) => {
+                warn!("[Control] Rejected '{}': {}", redact_command(&line), e);
+                format!("err {}\n", e)
+            },
+        };
+        writer.write_all(reply.as_bytes()).await?;
+    }
+
+    Ok(())
+}
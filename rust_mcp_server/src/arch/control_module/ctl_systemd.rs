@@ -0,0 +1,80 @@
+#[cfg(target_os = "linux")]
+use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+use std::{env, io, os::unix::net::UnixDatagram, process, time::Duration};
+use tracing::{info, warn};
+
+/// `sd_notify` client for a `Type=notify` unit, e.g. with `WatchdogSec=60`.
+#[derive(Clone, Debug, Default)]
+pub struct SystemdNotify {
+    socket: Option<String>,
+    watchdog: Option<Duration>,
+}
+
+impl SystemdNotify {
+    /// Reads `NOTIFY_SOCKET`, and `WATCHDOG_USEC` when `WATCHDOG_PID` is unset or this process.
+    pub fn from_env() -> Self {
+        let socket = env::var("NOTIFY_SOCKET").ok().filter(|s| !s.is_empty());
+        let watchdog_pid = env::var("WATCHDOG_PID")
+            .ok()
+            .and_then(|p| p.parse::<u32>().ok());
+        let watchdog = env::var("WATCHDOG_USEC")
+            .ok()
+            .and_then(|usec| usec.parse::<u64>().ok())
+            .filter(|usec| *usec > 0 && watchdog_pid.is_none_or(|pid| pid == process::id()))
+            .map(Duration::from_micros);
+
+        if let Some(socket) = &socket {
+            info!("[Systemd] Notifying {}, watchdog {:?}", socket, watchdog);
+        }
+        Self { socket, watchdog }
+    }
+
+    /// `WatchdogSec` of the unit, when systemd expects pings.
+    pub fn watchdog(&self) -> Option<Duration> {
+        self.watchdog.filter(|_| self.socket.is_some())
+    }
+
+    pub fn ready(&self, status: &str) {
+        self.send(&format!("READY=1\nSTATUS={}", status));
+    }
+
+    pub fn ping(&self) {
+        if self.watchdog().is_some() {
+            self.send("WATCHDOG=1");
+        }
+    }
+
+    pub fn status(&self, status: &str) {
+        self.send(&format!("STATUS={}", status));
+    }
+
+    fn send(&self, state: &str) {
+        let Some(socket) = &self.socket else {
+            return;
+        };
+
+        // A leading `@` names a socket in the abstract namespace
+        let sent = UnixDatagram::unbound().and_then(|datagram| match socket.strip_prefix('@') {
+            Some(name) => send_abstract(&datagram, name, state),
+            None => datagram.send_to(state.as_bytes(), socket),
+        });
+        if let Err(e) = sent {
+            warn!("[Systemd] Failed to notify {:?}: {}", state, e);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn send_abstract(datagram: &UnixDatagram, name: &str, state: &str) -> io::Result<usize> {
+    let addr = SocketAddr::from_abstract_name(name.as_bytes())?;
+    datagram.send_to_addr(state.as_bytes(), &addr)
+}
+
+/// Abstract sockets only exist on Linux.
+#[cfg(not(target_os = "linux"))]
+fn send_abstract(_datagram: &UnixDatagram, name: &str, _state: &str) -> io::Result<usize> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("abstract socket @{} needs Linux", name),
+    ))
+}
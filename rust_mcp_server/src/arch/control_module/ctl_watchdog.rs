@@ -0,0 +1,302 @@
+use dashmap::DashMap;
+use serde::Deserialize;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use super::ctl_systemd::SystemdNotify;
+
+/// Heartbeats of the strategies' event loops; systemd watchdog pings stop once one stalls.
+const EVENT_LOOP_PREFIXES: [&str; 2] = ["scheduler:", "loop:"];
+
+/// The `watchdog` section of `risk_config.json`, read once at startup.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchdogConfig {
+    pub check_interval_sec: u64,
+    /// Restarts allowed per task before the watchdog only alerts.
+    pub max_restarts: u32,
+    /// Seconds a restarted task must stay healthy before its restart count is reset.
+    pub stable_sec: u64,
+    pub candle_stall_sec: u64,
+    /// Account WS channels are judged by their pings, not their traffic.
+    pub account_ws_stall_sec: u64,
+    pub account_ws_ping_sec: u64,
+    pub model_stall_sec: u64,
+    /// Event loops (`scheduler:`, `loop:`) cannot be restarted in-process: a stall stops the
+    /// systemd watchdog pings so systemd restarts the service, without systemd it is alerted.
+    pub scheduler_stall_sec: u64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_sec: 10,
+            max_restarts: 5,
+            stable_sec: 600,
+            candle_stall_sec: 180,
+            account_ws_stall_sec: 90,
+            account_ws_ping_sec: 20,
+            model_stall_sec: 300,
+            scheduler_stall_sec: 60,
+        }
+    }
+}
+
+impl WatchdogConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for (name, sec) in [
+            ("check_interval_sec", self.check_interval_sec),
+            ("candle_stall_sec", self.candle_stall_sec),
+            ("account_ws_ping_sec", self.account_ws_ping_sec),
+            ("model_stall_sec", self.model_stall_sec),
+            ("scheduler_stall_sec", self.scheduler_stall_sec),
+        ] {
+            if sec == 0 {
+                errors.push(format!("{} must be positive", name));
+            }
+        }
+        if self.account_ws_stall_sec <= self.account_ws_ping_sec {
+            errors.push(format!(
+                "account_ws_stall_sec {} must exceed account_ws_ping_sec {}",
+                self.account_ws_stall_sec, self.account_ws_ping_sec
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Heartbeat {
+    last_beat: Instant,
+    stall_after: Duration,
+    restarts: u32,
+    /// Time of the last restart, until the task has been stable for `stable_sec`.
+    restarted_at: Option<Instant>,
+    alerted: bool,
+}
+
+/// Last event time per task.
+#[derive(Clone, Debug, Default)]
+pub struct TaskHeartbeats {
+    beats: Arc<DashMap<String, Heartbeat>>,
+}
+
+impl TaskHeartbeats {
+    pub fn beat(&self, task: &str, stall_after_sec: u64) {
+        let mut entry = self.beats.entry(task.to_string()).or_insert_with(|| Heartbeat {
+            last_beat: Instant::now(),
+            stall_after: Duration::from_secs(stall_after_sec),
+            restarts: 0,
+            restarted_at: None,
+            alerted: false,
+        });
+
+        if entry.alerted {
+            info!("[Watchdog] Task {} recovered after {:?}", task, entry.last_beat.elapsed());
+        }
+        entry.last_beat = Instant::now();
+        entry.stall_after = Duration::from_secs(stall_after_sec);
+        entry.alerted = false;
+    }
+
+    pub fn forget(&self, task: &str) {
+        self.beats.remove(task);
+    }
+
+    /// Stalled tasks under `prefix` that are still within the restart limit.
+    pub fn due_restarts(&self, prefix: &str, config: &WatchdogConfig) -> Vec<String> {
+        let mut due = Vec::new();
+        let stable = Duration::from_secs(config.stable_sec);
+
+        for mut entry in self.beats.iter_mut() {
+            if !entry.key().starts_with(prefix) {
+                continue;
+            }
+
+            let stalled = entry.last_beat.elapsed() >= entry.stall_after;
+            if !stalled {
+                if entry.restarted_at.is_some_and(|at| at.elapsed() >= stable) {
+                    info!(
+                        "[Watchdog] Task {} stable after {} restart(s), resetting",
+                        entry.key(),
+                        entry.restarts
+                    );
+                    entry.restarts = 0;
+                    entry.restarted_at = None;
+                }
+                continue;
+            }
+            if entry.restarts >= config.max_restarts {
+                continue;
+            }
+
+            entry.restarts += 1;
+            entry.restarted_at = Some(Instant::now());
+            entry.last_beat = Instant::now();
+            entry.alerted = false;
+            warn!(
+                "[Watchdog] Restarting stalled task {} ({}/{})",
+                entry.key(),
+                entry.restarts,
+                config.max_restarts
+            );
+            due.push(entry.key().clone());
+        }
+
+        due
+    }
+}
+
+/// Background alerting over all heartbeats, independent of the strategies' own schedulers.
+pub struct Watchdog {
+    heartbeats: TaskHeartbeats,
+    config: WatchdogConfig,
+    systemd: SystemdNotify,
+    ready: bool,
+}
+
+impl Watchdog {
+    pub fn new(heartbeats: TaskHeartbeats, config: WatchdogConfig) -> Self {
+        Self {
+            heartbeats,
+            config,
+            systemd: SystemdNotify::default(),
+            ready: false,
+        }
+    }
+
+    pub fn with_systemd(&mut self, systemd: SystemdNotify) -> &mut Self {
+        self.systemd = systemd;
+        self
+    }
+
+    pub fn spawn(mut self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            // systemd wants pings at least twice per watchdog period
+            let mut period = Duration::from_secs(self.config.check_interval_sec.max(1));
+            if let Some(watchdog) = self.systemd.watchdog() {
+                period = period.min(watchdog / 2);
+            }
+            let mut ticker = tokio::time::interval(period);
+
+            loop {
+                ticker.tick().await;
+                self.check();
+                self.supervise();
+            }
+        })
+    }
+
+    fn supervise(&mut self) {
+        let mut loops = 0;
+        let mut stalled = Vec::new();
+        for entry in self.heartbeats.beats.iter() {
+            if !EVENT_LOOP_PREFIXES.iter().any(|p| entry.key().starts_with(p)) {
+                continue;
+            }
+            loops += 1;
+            if entry.last_beat.elapsed() >= entry.stall_after {
+                stalled.push(entry.key().clone());
+            }
+        }
+
+        // Strategies are initialized before their first event
+        if loops == 0 {
+            return;
+        }
+        if !self.ready {
+            self.ready = true;
+            self.systemd.ready("running");
+            info!("[Watchdog] Event loops running, {} heartbeat(s)", loops);
+        }
+
+        if stalled.is_empty() {
+            self.systemd.ping();
+        } else {
+            self.systemd
+                .status(&format!("event loop stalled: {}", stalled.join(",")));
+        }
+    }
+
+    fn check(&self) {
+        for mut entry in self.heartbeats.beats.iter_mut() {
+            let silent = entry.last_beat.elapsed();
+            if silent < entry.stall_after || entry.alerted {
+                continue;
+            }
+
+            entry.alerted = true;
+            error!("[Watchdog] ALERT {}", self.alert(entry.key(), silent, entry.restarts));
+        }
+    }
+
+    fn alert(&self, task: &str, silent: Duration, restarts: u32) -> String {
+        if EVENT_LOOP_PREFIXES.iter().any(|p| task.starts_with(p)) {
+            return match self.systemd.watchdog() {
+                Some(_) => format!(
+                    "event loop {} silent for {:?}, systemd will restart the service",
+                    task, silent
+                ),
+                None => format!(
+                    "event loop {} silent for {:?}, no systemd watchdog, restart the service",
+                    task, silent
+                ),
+            };
+        }
+
+        if restarts >= self.config.max_restarts {
+            format!(
+                "task {} silent for {:?}, restart limit {} reached",
+                task, silent, self.config.max_restarts
+            )
+        } else {
+            format!("task {} silent for {:?} (restarts={})", task, silent, restarts)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restart_budget_resets_after_stable_window() {
+        let heartbeats = TaskHeartbeats::default();
+        let config = WatchdogConfig {
+            max_restarts: 1,
+            stable_sec: 0,
+            ..WatchdogConfig::default()
+        };
+
+        heartbeats.beat("ws:candles", 0);
+        assert_eq!(heartbeats.due_restarts("ws:candles", &config), vec!["ws:candles"]);
+        assert!(heartbeats.due_restarts("ws:candles", &config).is_empty());
+
+        // Healthy again: the next check resets the budget, the one after restarts
+        heartbeats.beat("ws:candles", 60);
+        assert!(heartbeats.due_restarts("ws:candles", &config).is_empty());
+        heartbeats.beat("ws:candles", 0);
+        assert_eq!(heartbeats.due_restarts("ws:candles", &config), vec!["ws:candles"]);
+    }
+
+    #[test]
+    fn stalled_event_loop_asks_for_a_service_restart() {
+        let watchdog = Watchdog::new(TaskHeartbeats::default(), WatchdogConfig::default());
+        let silent = Duration::from_secs(120);
+
+        let alert = watchdog.alert("scheduler:1200", silent, 0);
+        assert!(alert.contains("event loop"), "{}", alert);
+        assert!(alert.contains("restart the service"), "{}", alert);
+        assert!(!watchdog.alert("ws:candles", silent, 0).contains("event loop"));
+    }
+}
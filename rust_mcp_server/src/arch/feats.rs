@@ -1,2 +1,10 @@
 pub mod alt_df_build;
-pub mod expr_operators;
\ No newline at end of file
+pub mod data_gaps;
+pub mod expr_operators;
+pub mod feat_config;
+pub mod feat_store;
+pub mod frame_pool;
+pub mod order_flow;
+pub mod rolling_stats;
+pub mod stream_health;
+pub mod term_structure;
\ No newline at end of file
@@ -1,12 +1,17 @@
 use polars::prelude::*;
+use std::{collections::VecDeque, f64::consts::TAU};
 
 use extrema_infra::{
     prelude::*,
     arch::market_assets::api_data::utils_data::*,
 };
 
-pub fn oi_to_lf(oi: Vec<OpenInterest>) -> InfraResult<LazyFrame> {
+use super::data_gaps::{GAP_COL, OI_PERIOD_MS, gap_bars};
+
+/// Open interest rows with a `gap_bars` column counting the bars missing before each row.
+pub fn oi_to_lf(oi: Vec<OpenInterest>, prefix: &str) -> InfraResult<LazyFrame> {
     let ts: Vec<u64> = oi.iter().map(|x| x.timestamp).collect();
+    let gaps = gap_bars(&ts, OI_PERIOD_MS);
     let sum_oi: Vec<f64> = oi.iter().map(|x| x.sum_open_interest).collect();
     let sum_oi_val: Vec<f64> = oi
         .iter()
@@ -17,10 +22,62 @@ pub fn oi_to_lf(oi: Vec<OpenInterest>) -> InfraResult<LazyFrame> {
         "timestamp" => ts,
         "sum_open_interest" => sum_oi,
         "sum_open_interest_value" => sum_oi_val,
+        GAP_COL => gaps,
     ]?;
 
-    df.rename("sum_open_interest", "oi_sum_open_interest".into())?;
-    df.rename("sum_open_interest_value", "oi_sum_open_interest_value".into())?;
+    prefix_columns(&mut df, prefix)?;
 
     Ok(df.lazy())
+}
+
+/// Candle closes keyed by candle open time, as `[timestamp, <col_name>]`.
+pub fn closes_to_lf(closes: &VecDeque<(u64, f64)>, col_name: &str) -> InfraResult<LazyFrame> {
+    let ts: Vec<u64> = closes.iter().map(|x| x.0).collect();
+    let px: Vec<f64> = closes.iter().map(|x| x.1).collect();
+
+    let df = DataFrame::new(vec![
+        Column::new("timestamp".into(), ts),
+        Column::new(col_name.into(), px),
+    ])?;
+
+    Ok(df.lazy())
+}
+
+/// Time-of-day, day-of-week and time-to-funding features.
+pub fn with_calendar_feats(lf: LazyFrame, prefix: &str, funding_interval_hours: u64) -> LazyFrame {
+    const DAY_MS: i64 = 86_400_000;
+    const HOUR_MS: f64 = 3_600_000.0;
+    let funding_ms = funding_interval_hours.max(1) as i64 * 3_600_000;
+
+    let ts = col("timestamp").cast(DataType::Int64);
+    let day_frac = (ts.clone() % lit(DAY_MS)).cast(DataType::Float64) / lit(DAY_MS as f64);
+    // 1970-01-01 was a Thursday, shift so Monday is 0
+    let week_frac =
+        ((ts.clone() / lit(DAY_MS) + lit(3)) % lit(7)).cast(DataType::Float64) / lit(7.0);
+    let to_funding =
+        (lit(funding_ms) - ts % lit(funding_ms)).cast(DataType::Float64) / lit(HOUR_MS);
+
+    lf.with_columns([
+        (day_frac.clone() * lit(TAU)).sin().alias(format!("{}tod_sin", prefix)),
+        (day_frac * lit(TAU)).cos().alias(format!("{}tod_cos", prefix)),
+        (week_frac.clone() * lit(TAU)).sin().alias(format!("{}dow_sin", prefix)),
+        (week_frac * lit(TAU)).cos().alias(format!("{}dow_cos", prefix)),
+        to_funding.alias(format!("{}hours_to_funding", prefix)),
+    ])
+}
+
+/// Tags every column except `timestamp` with its source prefix, e.g. `oi_`.
+pub fn prefix_columns(df: &mut DataFrame, prefix: &str) -> InfraResult<()> {
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|n| n.to_string())
+        .filter(|n| n != "timestamp" && !n.starts_with(prefix))
+        .collect();
+
+    for name in names {
+        df.rename(&name, format!("{}{}", prefix, name).into())?;
+    }
+
+    Ok(())
 }
\ No newline at end of file
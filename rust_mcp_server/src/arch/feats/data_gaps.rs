@@ -0,0 +1,57 @@
+use extrema_infra::arch::market_assets::api_data::utils_data::OpenInterest;
+
+/// Open interest history period and its bar length.
+pub const OI_PERIOD: &str = "5m";
+pub const OI_PERIOD_MS: u64 = 300_000;
+/// Rows per open interest history request, the venue maximum.
+pub const OI_PAGE_LIMIT: u32 = 500;
+/// Candle stream bar length, see the candle task in `main.rs`.
+pub const KLINE_PERIOD_MS: u64 = 60_000;
+/// Bars missing right before a row. Kept out of the z-score so models see the raw count.
+pub const GAP_COL: &str = "gap_bars";
+
+/// Bars missing between `prev` and `next`; jitter under half a bar is not a gap.
+pub fn missing_bars(prev: u64, next: u64, step_ms: u64) -> u64 {
+    if step_ms == 0 || next <= prev {
+        return 0;
+    }
+
+    let bars = (next - prev + step_ms / 2) / step_ms;
+    bars.saturating_sub(1)
+}
+
+/// Missing ranges as `(first_missing, last_missing)` bar timestamps, in time order.
+pub fn find_gaps(timestamps: &[u64], step_ms: u64) -> Vec<(u64, u64)> {
+    timestamps
+        .windows(2)
+        .filter_map(|w| {
+            let missing = missing_bars(w[0], w[1], step_ms);
+            (missing > 0).then(|| (w[0] + step_ms, w[0] + missing * step_ms))
+        })
+        .collect()
+}
+
+/// Gap indicator per row: bars missing right before it, zero for contiguous rows.
+pub fn gap_bars(timestamps: &[u64], step_ms: u64) -> Vec<u64> {
+    let mut gaps = Vec::with_capacity(timestamps.len());
+    let mut prev: Option<u64> = None;
+    for ts in timestamps.iter() {
+        gaps.push(prev.map_or(0, |p| missing_bars(p, *ts, step_ms)));
+        prev = Some(*ts);
+    }
+    gaps
+}
+
+/// Bars missing within the last `window` rows, for series a rolling window reads whole.
+pub fn window_gap_bars(timestamps: &[u64], window: usize, step_ms: u64) -> u64 {
+    let start = timestamps.len().saturating_sub(window);
+    gap_bars(&timestamps[start..], step_ms).iter().sum()
+}
+
+/// Sorted, one row per timestamp; rows from `extra` replace rows of `base`.
+pub fn merge_oi(mut base: Vec<OpenInterest>, extra: Vec<OpenInterest>) -> Vec<OpenInterest> {
+    base.retain(|row| !extra.iter().any(|e| e.timestamp == row.timestamp));
+    base.extend(extra);
+    base.sort_by_key(|row| row.timestamp);
+    base
+}
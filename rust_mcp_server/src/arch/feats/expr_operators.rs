@@ -3,6 +3,8 @@ use polars::prelude::*;
 use extrema_infra::prelude::*;
 
 pub const EPSILON: f64 = 1e-8_f64;
+/// Makes MAD a consistent estimator of the standard deviation for normal data.
+pub const MAD_NORMAL_SCALE: f64 = 1.4826;
 
 pub fn collect_schema_safe(lf: &LazyFrame) -> InfraResult<Arc<Schema>> {
     Ok(lf.clone().collect_schema()?)
@@ -27,6 +29,8 @@ pub fn convert_all_to_float64_except_timestamp(lf: LazyFrame) -> InfraResult<Laz
     Ok(lf.with_columns(exprs))
 }
 
+/// Rolling z-score over the whole frame.
+#[cfg(test)]
 pub fn z_score_expr(col_name: &str, window: usize) -> Expr {
     let (mean_expr, std_expr) = rolling_mean_std_expr(col_name, window);
     normalize_clip_expr(col_name, mean_expr, std_expr)
@@ -34,6 +38,69 @@ pub fn z_score_expr(col_name: &str, window: usize) -> Expr {
 }
 
 
+/// Z-score from rolling median and MAD, less distorted by the outliers being clipped.
+pub fn robust_z_score_expr(col_name: &str, window: usize) -> Expr {
+    let (median_expr, mad_expr) = rolling_median_mad_expr(col_name, window);
+    normalize_clip_expr(col_name, median_expr, mad_expr * lit(MAD_NORMAL_SCALE))
+        .alias(format!("z_{}", col_name))
+}
+
+/// Rolling median and MAD.
+pub fn rolling_median_mad_expr(col_name: &str, window: usize) -> (Expr, Expr) {
+    let opts = RollingOptionsFixedWindow {
+        window_size: window,
+        min_periods: 1,
+        center: false,
+        ..Default::default()
+    };
+
+    let median_expr = col(col_name).rolling_median(opts);
+    let mad_expr = col(col_name)
+        .map(
+            move |c| rolling_mad(c, window),
+            |_, field| Ok(Field::new(field.name().clone(), DataType::Float64)),
+        )
+        .fill_nan(lit(0.0))
+        .fill_null(lit(0.0));
+
+    (median_expr, mad_expr)
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
+
+/// MAD of the trailing `window` values at each row; nulls and NaNs are skipped.
+fn rolling_mad(c: Column, window: usize) -> PolarsResult<Column> {
+    let values: Vec<Option<f64>> = c.cast(&DataType::Float64)?.f64()?.into_iter().collect();
+    let window = window.max(1);
+
+    let mad: Vec<Option<f64>> = (0..values.len())
+        .map(|idx| {
+            let mut win: Vec<f64> = values[(idx + 1).saturating_sub(window)..=idx]
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|v| !v.is_nan())
+                .collect();
+            let center = median(&mut win)?;
+            let mut deviations: Vec<f64> = win.iter().map(|v| (v - center).abs()).collect();
+            median(&mut deviations)
+        })
+        .collect();
+
+    Ok(Column::new(c.name().clone(), mad))
+}
+
 pub fn rolling_mean_std_expr(col_name: &str, window: usize) -> (Expr, Expr) {
     let mean_expr = col(col_name).rolling_mean(RollingOptionsFixedWindow {
         window_size: window,
@@ -50,9 +117,131 @@ pub fn rolling_mean_std_expr(col_name: &str, window: usize) -> (Expr, Expr) {
     (mean_expr, std_expr)
 }
 
+/// Raw value clipped to rolling mean ± `sigma` std, named `<col>_wins`.
+pub fn winsorize_expr(col_name: &str, window: usize, sigma: f64) -> Expr {
+    let (mean_expr, std_expr) = rolling_mean_std_expr(col_name, window);
+    let lower = mean_expr.clone() - std_expr.clone() * lit(sigma);
+    let upper = mean_expr + std_expr * lit(sigma);
+
+    when(col(col_name).lt(lower.clone()))
+        .then(lower)
+        .when(col(col_name).gt(upper.clone()))
+        .then(upper)
+        .otherwise(col(col_name))
+        .alias(format!("{}_wins", col_name))
+}
+
+/// 1.0 when the unclipped rolling z-score exceeds ±`sigma`, else 0.0, named `<col>_outlier`.
+pub fn outlier_flag_expr(col_name: &str, window: usize, sigma: f64) -> Expr {
+    let (mean_expr, std_expr) = rolling_mean_std_expr(col_name, window);
+    let z = (col(col_name) - mean_expr) / (std_expr + lit(EPSILON));
+
+    when(z.clone().gt(lit(sigma)).or(z.lt(lit(-sigma))))
+        .then(lit(1.0))
+        .otherwise(lit(0.0))
+        .alias(format!("{}_outlier", col_name))
+}
+
 pub fn normalize_clip_expr(col_name: &str, mean_expr: Expr, std_expr: Expr) -> Expr {
     ((col(col_name) - mean_expr) / (std_expr + lit(EPSILON)))
         .fill_nan(lit(0.0))
         .fill_null(lit(0.0))
         .clip(lit(-3.0), lit(3.0))
-}
\ No newline at end of file
+}
+
+pub fn simple_return_expr(col_name: &str) -> Expr {
+    (col(col_name) / col(col_name).shift(lit(1)) - lit(1.0))
+        .alias(format!("ret_{}", col_name))
+}
+
+/// Rolling correlation and beta of `x` on benchmark `y`.
+pub fn rolling_corr_beta_expr(x: &str, y: &str, window: usize) -> (Expr, Expr) {
+    let opts = RollingOptionsFixedWindow {
+        window_size: window,
+        min_periods: 2,
+        center: false,
+        ..Default::default()
+    };
+
+    let mean_x = col(x).rolling_mean(opts.clone());
+    let mean_y = col(y).rolling_mean(opts.clone());
+    let mean_xy = (col(x) * col(y)).rolling_mean(opts.clone());
+    let mean_xx = (col(x) * col(x)).rolling_mean(opts.clone());
+    let mean_yy = (col(y) * col(y)).rolling_mean(opts);
+
+    let cov = mean_xy - mean_x.clone() * mean_y.clone();
+    let var_x = mean_xx - mean_x.clone() * mean_x;
+    let var_y = mean_yy - mean_y.clone() * mean_y;
+
+    let corr = (cov.clone() / ((var_x * var_y.clone()).sqrt() + lit(EPSILON)))
+        .fill_nan(lit(0.0))
+        .fill_null(lit(0.0))
+        .clip(lit(-1.0), lit(1.0));
+    let beta = (cov / (var_y + lit(EPSILON)))
+        .fill_nan(lit(0.0))
+        .fill_null(lit(0.0));
+
+    (corr, beta)
+}
+
+/// `x[t] - x[t - periods]`, named `<col>_d<periods>`.
+pub fn diff_expr(col_name: &str, periods: i64) -> Expr {
+    (col(col_name) - col(col_name).shift(lit(periods)))
+        .alias(format!("{}_d{}", col_name, periods))
+}
+
+/// Discrete acceleration `x[t] - 2x[t-1] + x[t-2]`, named `<col>_dd`.
+pub fn second_diff_expr(col_name: &str) -> Expr {
+    (col(col_name) - lit(2.0) * col(col_name).shift(lit(1)) + col(col_name).shift(lit(2)))
+        .alias(format!("{}_dd", col_name))
+}
+
+/// Assumes a positive series (OI, prices).
+pub fn pct_change_of(expr: Expr, periods: i64) -> Expr {
+    let prev = expr.clone().shift(lit(periods));
+    ((expr - prev.clone()) / (prev + lit(EPSILON))).fill_nan(lit(0.0))
+}
+
+/// Percentage change over `periods` rows, named `<col>_pct<periods>`.
+pub fn pct_change_expr(col_name: &str, periods: i64) -> Expr {
+    pct_change_of(col(col_name), periods).alias(format!("{}_pct{}", col_name, periods))
+}
+
+/// Positive when the first series grows faster than the second.
+pub fn divergence_expr(lhs: Expr, rhs: Expr, periods: i64) -> Expr {
+    pct_change_of(lhs, periods) - pct_change_of(rhs, periods)
+}
+
+/// OI velocity, acceleration and OI-vs-price divergence. Price is implied by OI value / OI.
+pub fn oi_change_exprs(prefix: &str, windows: &[i64]) -> Vec<Expr> {
+    let oi = format!("{}sum_open_interest", prefix);
+    let implied_px =
+        col(format!("{}sum_open_interest_value", prefix)) / (col(oi.as_str()) + lit(EPSILON));
+
+    let mut exprs = vec![diff_expr(&oi, 1), second_diff_expr(&oi)];
+    for w in windows.iter() {
+        exprs.push(pct_change_expr(&oi, *w));
+        exprs.push(
+            divergence_expr(col(oi.as_str()), implied_px.clone(), *w)
+                .alias(format!("{}px_div{}", prefix, w)),
+        );
+    }
+
+    exprs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mad_uses_each_window_median() {
+        let df = df!("x" => [1.0, 2.0, 3.0, 100.0, 101.0, 102.0]).unwrap();
+        let (_, mad) = rolling_median_mad_expr("x", 3);
+        let out = df.lazy().select([mad.alias("mad")]).collect().unwrap();
+        let mad: Vec<f64> = out.column("mad").unwrap().f64().unwrap().into_no_null_iter().collect();
+
+        // [3, 100, 101] has median 100 and deviations [97, 0, 1]
+        assert_eq!(mad, vec![0.0, 0.5, 1.0, 1.0, 1.0, 1.0]);
+    }
+}
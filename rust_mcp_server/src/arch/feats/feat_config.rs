@@ -0,0 +1,213 @@
+use polars::prelude::*;
+use serde::Deserialize;
+use std::{collections::HashMap, fs};
+use tracing::{info, warn};
+
+use extrema_infra::prelude::*;
+
+use crate::arch::profile::config_path;
+
+pub const SOURCE_OPEN_INTEREST: &str = "open_interest";
+pub const SOURCE_FUNDING: &str = "funding";
+pub const SOURCE_KLINE: &str = "kline";
+pub const SOURCE_CALENDAR: &str = "calendar";
+pub const SOURCE_TRADES: &str = "trades";
+pub const SOURCE_TERM: &str = "term";
+pub const SOURCE_HEALTH: &str = "health";
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingMethod {
+    /// Rolling mean / std
+    #[default]
+    Standard,
+    /// Rolling median / MAD
+    Robust,
+}
+
+/// Feature pipeline settings from `feature_config.json`. Falls back to defaults when absent.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct FeatureConfig {
+    /// Columns passed through without a z-score, by their prefixed name.
+    pub exclude_cols: Vec<String>,
+    /// Column prefix per data source, applied by the frame builders.
+    pub prefixes: HashMap<String, String>,
+    pub zscore_window: usize,
+    /// Per-column normalization, `standard` unless listed.
+    pub column_scaling: HashMap<String, ScalingMethod>,
+    /// Columns that also get a winsorized copy and an outlier flag next to their z-score.
+    pub winsorize_cols: Vec<String>,
+    /// Winsorization bound in rolling standard deviations.
+    pub winsor_sigma: f64,
+    /// Outlier flag threshold in rolling standard deviations.
+    pub outlier_sigma: f64,
+    /// Instrument the correlation and beta features are measured against.
+    pub benchmark_inst: String,
+    /// Window in candles for the rolling correlation and beta.
+    pub corr_window: usize,
+    /// Lookbacks in OI rows for percentage change and OI/price divergence features.
+    pub oi_change_windows: Vec<i64>,
+    /// Funding settlement period used for the distance-to-funding feature.
+    pub funding_interval_hours: u64,
+    /// Venue whose prices go into feature tensors and published targets.
+    pub price_market: Market,
+    /// OI rows fetched per tick, paged by the venue row limit. Raised to `required_bars`.
+    pub lookback_bars: usize,
+    /// Rows beyond the longest rolling window so every transform is warm on the last row.
+    pub warmup_bars: usize,
+    /// Adds the VPIN order-flow toxicity column.
+    pub vpin_feature: bool,
+    /// Adds basis and calendar-spread columns from the quarterly futures.
+    pub term_structure: bool,
+    /// Adds market-data health columns.
+    pub stream_health: bool,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            exclude_cols: [
+                "timestamp",
+                "fund_funding_interval_hours",
+                "fund_last_funding_rate",
+                "fund_premium_funding_spread",
+                "fund_adjusted_funding_rate",
+                "fund_premium",
+                "fund_premium_open",
+            ]
+            .map(String::from)
+            .to_vec(),
+            prefixes: HashMap::from([
+                (SOURCE_OPEN_INTEREST.to_string(), "oi_".to_string()),
+                (SOURCE_FUNDING.to_string(), "fund_".to_string()),
+                (SOURCE_KLINE.to_string(), "kl_".to_string()),
+                (SOURCE_CALENDAR.to_string(), "cal_".to_string()),
+                (SOURCE_TRADES.to_string(), "flow_".to_string()),
+                (SOURCE_TERM.to_string(), "term_".to_string()),
+                (SOURCE_HEALTH.to_string(), "health_".to_string()),
+            ]),
+            zscore_window: 20,
+            column_scaling: HashMap::new(),
+            winsorize_cols: Vec::new(),
+            winsor_sigma: 3.0,
+            outlier_sigma: 3.0,
+            benchmark_inst: "BTC_USDT_PERP".to_string(),
+            corr_window: 60,
+            oi_change_windows: vec![1, 3, 12],
+            funding_interval_hours: 8,
+            price_market: Market::BinanceUmFutures,
+            lookback_bars: 200,
+            warmup_bars: 10,
+            vpin_feature: false,
+            term_structure: false,
+            stream_health: false,
+        }
+    }
+}
+
+impl FeatureConfig {
+    pub fn prefix(&self, source: &str) -> InfraResult<&str> {
+        self.prefixes
+            .get(source)
+            .map(|p| p.as_str())
+            .ok_or_else(|| InfraError::Msg(format!("No feature prefix for source {}", source)))
+    }
+
+    pub fn scaling(&self, col_name: &str) -> ScalingMethod {
+        self.column_scaling
+            .get(col_name)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Rows the longest rolling transform needs, plus the warm-up margin.
+    pub fn required_bars(&self, oi_windows: &[i64]) -> usize {
+        let longest_change = oi_windows.iter().copied().max().unwrap_or(0);
+        let longest = self.zscore_window.max(longest_change.max(0) as usize + 1);
+        longest + self.warmup_bars
+    }
+
+    pub fn lookback(&self, oi_windows: &[i64]) -> usize {
+        self.lookback_bars.max(self.required_bars(oi_windows))
+    }
+
+    /// Names of the OI change columns of `window` before any transform.
+    pub fn oi_window_cols(&self, window: i64) -> InfraResult<[String; 2]> {
+        let prefix = self.prefix(SOURCE_OPEN_INTEREST)?;
+        Ok([
+            format!("{}sum_open_interest_pct{}", prefix, window),
+            format!("{}px_div{}", prefix, window),
+        ])
+    }
+
+    pub fn is_excluded(&self, col_name: &str) -> bool {
+        self.exclude_cols.iter().any(|c| c == col_name)
+    }
+
+    /// Excluded names that no column can have, as they carry no source prefix.
+    pub fn unprefixed_exclusions(&self) -> Vec<&str> {
+        self.exclude_cols
+            .iter()
+            .map(|c| c.as_str())
+            .filter(|c| *c != "timestamp")
+            .filter(|c| !self.prefixes.values().any(|p| c.starts_with(p.as_str())))
+            .collect()
+    }
+
+    /// Every column other than `timestamp` must carry a source prefix.
+    pub fn check_provenance(&self, schema: &Schema) -> InfraResult<()> {
+        let unknown: Vec<&str> = schema
+            .iter_names()
+            .map(|n| n.as_str())
+            .filter(|n| *n != "timestamp")
+            .filter(|n| {
+                let base = n.strip_prefix("z_").unwrap_or(n);
+                !self.prefixes.values().any(|p| base.starts_with(p.as_str()))
+            })
+            .collect();
+
+        if !unknown.is_empty() {
+            return Err(InfraError::Msg(format!(
+                "Feature columns without source prefix: {:?}",
+                unknown
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn load_feature_config() -> InfraResult<FeatureConfig> {
+    let path = config_path("feature_config.json")?;
+
+    if !path.exists() {
+        info!("feature_config.json not found at {:?}, using defaults", path);
+        return Ok(FeatureConfig::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| InfraError::Msg(format!("Failed to read feature config file: {}", e)))?;
+
+    let config: FeatureConfig = serde_json::from_str(&content)
+        .map_err(|e| InfraError::Msg(format!("Failed to parse feature config: {}", e)))?;
+
+    let unprefixed = config.unprefixed_exclusions();
+    if !unprefixed.is_empty() {
+        warn!(
+            "exclude_cols {:?} carry no source prefix and match no column",
+            unprefixed
+        );
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_exclusions_use_source_prefixes() {
+        assert!(FeatureConfig::default().unprefixed_exclusions().is_empty());
+    }
+}
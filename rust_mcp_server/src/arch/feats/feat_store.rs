@@ -0,0 +1,337 @@
+use polars::prelude::*;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    env::current_dir,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+
+use extrema_infra::prelude::*;
+
+use crate::arch::account_module::acc_rest::iso8601_millis;
+
+/// Rows kept per instrument, in memory and on disk.
+pub const MAX_STORE_ROWS: usize = 100_000;
+/// Span of one Parquet partition; row timestamps are in milliseconds.
+const PARTITION_MS: u64 = 86_400_000;
+
+/// Store key of the raw `source` series of `inst`, e.g. `BTC_USDT_PERP.kline`.
+pub fn history_key(source: &str, inst: &str) -> String {
+    format!("{}.{}", inst, source)
+}
+
+#[derive(Clone, Debug, Default)]
+struct InstFeatures {
+    col_names: Vec<String>,
+    rows: BTreeMap<u64, Vec<f64>>,
+    /// Partitions changed since the last `persist`.
+    dirty_days: BTreeSet<u64>,
+    /// Set when the column layout changed, so every partition on disk is stale.
+    relayout: bool,
+}
+
+/// UTC date of partition `day`, its file stem. Dates sort like the days they name.
+fn partition_name(day: u64) -> String {
+    iso8601_millis(day * PARTITION_MS)[..10].to_string()
+}
+
+/// Feature rows per instrument, one Parquet file per instrument and UTC day.
+#[derive(Clone, Debug)]
+pub struct FeatureStore {
+    dir: PathBuf,
+    insts: HashMap<String, InstFeatures>,
+}
+
+impl FeatureStore {
+    pub fn new(dir_name: &str) -> Self {
+        let mut dir = current_dir().unwrap_or_default();
+        dir.push(dir_name);
+        Self {
+            dir,
+            insts: HashMap::new(),
+        }
+    }
+
+    /// Loads the partitions of every instrument directory in the store.
+    pub fn load(&mut self) -> InfraResult<()> {
+        if !self.dir.exists() {
+            return Ok(());
+        }
+
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| InfraError::Msg(format!("Failed to read feature store dir: {}", e)))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let Some(inst) = path.file_name().and_then(|s| s.to_str()).map(String::from) else {
+                continue;
+            };
+
+            let mut loaded = 0;
+            for partition in partition_files(&path)? {
+                let file = File::open(&partition).map_err(|e| {
+                    InfraError::Msg(format!("Failed to open {:?}: {}", partition, e))
+                })?;
+                match ParquetReader::new(file).finish() {
+                    Ok(df) => {
+                        self.upsert_frame(&inst, &df)?;
+                        loaded += df.height();
+                    },
+                    Err(e) => warn!("[FeatureStore] Skipping unreadable {:?}: {}", partition, e),
+                }
+            }
+
+            // What was just read is already on disk
+            if let Some(store) = self.insts.get_mut(&inst) {
+                store.dirty_days.clear();
+                store.relayout = false;
+            }
+            info!("[FeatureStore] Loaded {} rows for {}", loaded, inst);
+        }
+
+        Ok(())
+    }
+
+    /// Merges a feature frame with a `timestamp` column.
+    pub fn upsert_frame(&mut self, inst: &str, df: &DataFrame) -> InfraResult<()> {
+        let ts = df.column("timestamp")?.cast(&DataType::UInt64)?;
+        let ts = ts.u64()?;
+
+        let col_names: Vec<String> = df
+            .get_column_names()
+            .iter()
+            .map(|n| n.to_string())
+            .filter(|n| n != "timestamp")
+            .collect();
+
+        let mut cols = Vec::with_capacity(col_names.len());
+        for name in col_names.iter() {
+            cols.push(df.column(name)?.cast(&DataType::Float64)?);
+        }
+
+        let store = self.insts.entry(inst.to_string()).or_default();
+        if store.col_names != col_names {
+            if !store.rows.is_empty() {
+                warn!("[FeatureStore] Column layout changed for {}, resetting history", inst);
+            }
+            store.col_names = col_names;
+            store.rows.clear();
+            store.relayout = true;
+        }
+
+        for idx in 0..df.height() {
+            let Some(timestamp) = ts.get(idx) else {
+                continue;
+            };
+
+            let mut values = Vec::with_capacity(cols.len());
+            for c in cols.iter() {
+                values.push(c.f64()?.get(idx).unwrap_or(f64::NAN));
+            }
+            let unchanged = store.rows.get(&timestamp).is_some_and(|old| {
+                old.iter().zip(values.iter()).all(|(a, b)| a.to_bits() == b.to_bits())
+            });
+            if !unchanged {
+                store.dirty_days.insert(timestamp / PARTITION_MS);
+                store.rows.insert(timestamp, values);
+            }
+        }
+
+        while store.rows.len() > MAX_STORE_ROWS {
+            store.rows.pop_first();
+        }
+
+        Ok(())
+    }
+
+    /// Drops all but the newest `keep` rows of `key`.
+    pub fn truncate(&mut self, key: &str, keep: usize) {
+        if let Some(store) = self.insts.get_mut(key) {
+            while store.rows.len() > keep {
+                store.rows.pop_first();
+            }
+        }
+    }
+
+    /// Instruments with a stored raw `source` series.
+    pub fn history_insts(&self, source: &str) -> Vec<String> {
+        let suffix = format!(".{}", source);
+        self.insts
+            .keys()
+            .filter_map(|key| key.strip_suffix(&suffix).map(String::from))
+            .collect()
+    }
+
+    /// Instruments with feature rows or raw series in memory.
+    pub fn cached_insts(&self) -> Vec<String> {
+        self.insts
+            .keys()
+            .map(|key| key.split('.').next().unwrap_or(key).to_string())
+            .collect()
+    }
+
+    /// Drops the feature rows and raw series of `inst` from memory.
+    pub fn evict(&mut self, inst: &str) {
+        let prefix = format!("{}.", inst);
+        self.insts
+            .retain(|key, _| key != inst && !key.starts_with(&prefix));
+    }
+
+    /// Timestamp of the newest row of `key`.
+    pub fn last_timestamp(&self, key: &str) -> Option<u64> {
+        self.insts.get(key)?.rows.keys().next_back().copied()
+    }
+
+    /// Values of column `col` of `key` from `since` on, oldest first.
+    pub fn column_since(&self, key: &str, col: &str, since: u64) -> Vec<(u64, f64)> {
+        let Some(store) = self.insts.get(key) else {
+            return Vec::new();
+        };
+        let Some(idx) = store.col_names.iter().position(|c| c == col) else {
+            return Vec::new();
+        };
+
+        store
+            .rows
+            .range(since..)
+            .map(|(ts, values)| (*ts, values[idx]))
+            .collect()
+    }
+
+    /// Latest row at or before `timestamp`.
+    pub fn point_in_time(&self, inst: &str, timestamp: u64) -> Option<(u64, &[f64])> {
+        self.insts
+            .get(inst)?
+            .rows
+            .range(..=timestamp)
+            .next_back()
+            .map(|(ts, values)| (*ts, values.as_slice()))
+    }
+
+    /// The point-in-time row as a one-row frame in the original column order.
+    pub fn as_of_frame(&self, inst: &str, timestamp: u64) -> InfraResult<DataFrame> {
+        let store = self
+            .insts
+            .get(inst)
+            .ok_or_else(|| InfraError::Msg(format!("No features stored for {}", inst)))?;
+        let (ts, values) = self.point_in_time(inst, timestamp).ok_or_else(|| {
+            InfraError::Msg(format!("No features for {} at or before {}", inst, timestamp))
+        })?;
+
+        let mut columns = vec![Column::new("timestamp".into(), [ts])];
+        for (name, value) in store.col_names.iter().zip(values.iter()) {
+            columns.push(Column::new(name.as_str().into(), [*value]));
+        }
+
+        Ok(DataFrame::new(columns)?)
+    }
+
+    /// Rewrites the changed partitions of `inst` and deletes expired ones.
+    pub fn persist(&mut self, inst: &str) -> InfraResult<()> {
+        let Some(store) = self.insts.get_mut(inst) else {
+            return Ok(());
+        };
+        if store.dirty_days.is_empty() && !store.relayout {
+            return Ok(());
+        }
+
+        let dir = self.dir.join(inst);
+        fs::create_dir_all(&dir)
+            .map_err(|e| InfraError::Msg(format!("Failed to create feature store dir: {}", e)))?;
+
+        let oldest = store.rows.keys().next().map(|ts| partition_name(ts / PARTITION_MS));
+        for path in partition_files(&dir)? {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            if store.relayout || oldest.as_deref().is_none_or(|oldest| stem < oldest) {
+                fs::remove_file(&path)
+                    .map_err(|e| InfraError::Msg(format!("Failed to remove {:?}: {}", path, e)))?;
+            }
+        }
+
+        let days: BTreeSet<u64> = if store.relayout {
+            store.rows.keys().map(|ts| ts / PARTITION_MS).collect()
+        } else {
+            store.dirty_days.clone()
+        };
+        for day in days {
+            let rows = store.rows.range(day * PARTITION_MS..(day + 1) * PARTITION_MS);
+            let mut columns = vec![Column::new(
+                "timestamp".into(),
+                rows.clone().map(|(ts, _)| *ts).collect::<Vec<u64>>(),
+            )];
+            for (idx, name) in store.col_names.iter().enumerate() {
+                let values: Vec<f64> = rows.clone().map(|(_, row)| row[idx]).collect();
+                columns.push(Column::new(name.as_str().into(), values));
+            }
+            let mut df = DataFrame::new(columns)?;
+            if df.height() == 0 {
+                continue;
+            }
+
+            let path = dir.join(format!("{}.parquet", partition_name(day)));
+            let file = File::create(&path)
+                .map_err(|e| InfraError::Msg(format!("Failed to create {:?}: {}", path, e)))?;
+            ParquetWriter::new(file).finish(&mut df)?;
+        }
+
+        store.dirty_days.clear();
+        store.relayout = false;
+        Ok(())
+    }
+}
+
+/// Parquet partitions in `dir`, oldest first.
+fn partition_files(dir: &Path) -> InfraResult<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| InfraError::Msg(format!("Failed to read {:?}: {}", dir, e)))?;
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "parquet"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn persists_only_changed_days() {
+        let dir = TempDir(
+            std::env::temp_dir().join(format!("feature_store_test_{}", std::process::id())),
+        );
+        let dir_name = dir.0.to_string_lossy().to_string();
+        let day = 20_000 * PARTITION_MS;
+        let frame = |ts: &[u64], x: &[f64]| df!("timestamp" => ts, "x" => x).unwrap();
+
+        let mut store = FeatureStore::new(&dir_name);
+        store.upsert_frame("BTC", &frame(&[day, day + PARTITION_MS], &[1.0, 2.0])).unwrap();
+        store.persist("BTC").unwrap();
+
+        let first_day = store.dir.join("BTC").join(format!("{}.parquet", partition_name(20_000)));
+        let written = fs::metadata(&first_day).unwrap().modified().unwrap();
+        store.upsert_frame("BTC", &frame(&[day + PARTITION_MS + 1], &[3.0])).unwrap();
+        store.persist("BTC").unwrap();
+        let untouched = fs::metadata(&first_day).unwrap().modified().unwrap() == written;
+
+        let mut reloaded = FeatureStore::new(&dir_name);
+        reloaded.load().unwrap();
+
+        assert!(untouched);
+        assert_eq!(reloaded.column_since("BTC", "x", 0).len(), 3);
+    }
+}
@@ -0,0 +1,86 @@
+use polars::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+use extrema_infra::prelude::*;
+
+use super::alt_df_build::closes_to_lf;
+
+/// Frames kept before the least recently used one is dropped.
+pub const MAX_POOLED_FRAMES: usize = 512;
+
+#[derive(Clone, Debug)]
+struct PooledFrame {
+    col_name: String,
+    last_used: u64,
+    df: DataFrame,
+}
+
+/// Candle-close frames per instrument, built once per history change.
+#[derive(Clone, Debug)]
+pub struct FramePool {
+    frames: HashMap<String, PooledFrame>,
+    uses: u64,
+    capacity: usize,
+}
+
+impl Default for FramePool {
+    fn default() -> Self {
+        Self::new(MAX_POOLED_FRAMES)
+    }
+}
+
+impl FramePool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: HashMap::new(),
+            uses: 0,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// `[timestamp, <col_name>]` frame of `closes`, rebuilt only after `invalidate(inst)`.
+    pub fn closes(
+        &mut self,
+        inst: &str,
+        closes: &VecDeque<(u64, f64)>,
+        col_name: &str,
+    ) -> InfraResult<DataFrame> {
+        self.uses += 1;
+        if let Some(frame) = self.frames.get_mut(inst)
+            && frame.col_name == col_name
+        {
+            frame.last_used = self.uses;
+            return Ok(frame.df.clone());
+        }
+
+        let df = closes_to_lf(closes, col_name)?.collect()?;
+        if self.frames.len() >= self.capacity && !self.frames.contains_key(inst) {
+            self.evict_least_used();
+        }
+        self.frames.insert(
+            inst.to_string(),
+            PooledFrame {
+                col_name: col_name.to_string(),
+                last_used: self.uses,
+                df: df.clone(),
+            },
+        );
+        Ok(df)
+    }
+
+    /// Call whenever the history of `inst` changes.
+    pub fn invalidate(&mut self, inst: &str) {
+        self.frames.remove(inst);
+    }
+
+    fn evict_least_used(&mut self) {
+        let oldest = self
+            .frames
+            .iter()
+            .min_by_key(|(_, frame)| frame.last_used)
+            .map(|(inst, _)| inst.clone());
+        if let Some(inst) = oldest {
+            self.frames.remove(&inst);
+        }
+    }
+}
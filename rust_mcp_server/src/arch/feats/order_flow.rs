@@ -0,0 +1,116 @@
+use dashmap::DashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+use extrema_infra::prelude::*;
+
+/// Latest VPIN per instrument, published by the server for the account risk checks.
+pub type ToxicityMap = Arc<DashMap<String, f64>>;
+
+/// Volume buckets VPIN is averaged over.
+pub const VPIN_BUCKETS: usize = 50;
+/// Buckets needed before the estimate is reported.
+const VPIN_MIN_BUCKETS: usize = 10;
+/// Trade time used to size the buckets; one bucket holds about a minute of this volume.
+const VPIN_CALIBRATION_MS: u64 = 300_000;
+const VPIN_BUCKET_MS: f64 = 60_000.0;
+
+/// Volume-synchronized probability of informed trading for one venue instrument.
+#[derive(Clone, Debug, Default)]
+pub struct VpinEstimator {
+    bucket_volume: f64,
+    calibration_start: Option<u64>,
+    calibration_volume: f64,
+    buy: f64,
+    sell: f64,
+    imbalances: VecDeque<f64>,
+}
+
+impl VpinEstimator {
+    /// Folds in the taker buy and sell volume traded up to `timestamp` (ms).
+    pub fn push(&mut self, mut buy: f64, mut sell: f64, timestamp: u64) {
+        if self.bucket_volume <= 0.0 {
+            self.calibrate(buy + sell, timestamp);
+            return;
+        }
+
+        // Anything beyond a full window of buckets would be overwritten anyway
+        for _ in 0..=VPIN_BUCKETS {
+            let room = self.bucket_volume - self.buy - self.sell;
+            let total = buy + sell;
+            if total < room {
+                self.buy += buy;
+                self.sell += sell;
+                return;
+            }
+
+            let share = room / total;
+            self.buy += buy * share;
+            self.sell += sell * share;
+            buy -= buy * share;
+            sell -= sell * share;
+            self.close_bucket();
+        }
+    }
+
+    pub fn vpin(&self) -> Option<f64> {
+        if self.imbalances.len() < VPIN_MIN_BUCKETS {
+            return None;
+        }
+        Some(self.imbalances.iter().sum::<f64>() / self.imbalances.len() as f64)
+    }
+
+    fn calibrate(&mut self, volume: f64, timestamp: u64) {
+        let start = *self.calibration_start.get_or_insert(timestamp);
+        self.calibration_volume += volume;
+
+        let elapsed = timestamp.saturating_sub(start);
+        if elapsed >= VPIN_CALIBRATION_MS && self.calibration_volume > 0.0 {
+            self.bucket_volume = self.calibration_volume * VPIN_BUCKET_MS / elapsed as f64;
+        }
+    }
+
+    fn close_bucket(&mut self) {
+        self.imbalances
+            .push_back((self.buy - self.sell).abs() / self.bucket_volume);
+        while self.imbalances.len() > VPIN_BUCKETS {
+            self.imbalances.pop_front();
+        }
+        self.buy = 0.0;
+        self.sell = 0.0;
+    }
+}
+
+/// VPIN estimators of every traded venue instrument.
+#[derive(Clone, Debug, Default)]
+pub struct OrderFlow {
+    estimators: HashMap<(Market, String), VpinEstimator>,
+}
+
+impl OrderFlow {
+    pub fn push(&mut self, key: &(Market, String), buy: f64, sell: f64, timestamp: u64) {
+        self.estimators
+            .entry(key.clone())
+            .or_default()
+            .push(buy, sell, timestamp);
+    }
+
+    /// Highest VPIN of `inst` across the venues it trades on.
+    pub fn vpin(&self, inst: &str) -> Option<f64> {
+        self.estimators
+            .iter()
+            .filter(|((_, i), _)| i == inst)
+            .filter_map(|(_, est)| est.vpin())
+            .reduce(f64::max)
+    }
+
+    pub fn insts(&self) -> Vec<String> {
+        self.estimators.keys().map(|(_, inst)| inst.clone()).collect()
+    }
+
+    pub fn evict(&mut self, inst: &str) {
+        self.estimators.retain(|(_, i), _| i != inst);
+    }
+}
@@ -0,0 +1,199 @@
+use polars::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+use extrema_infra::prelude::*;
+
+use super::expr_operators::EPSILON;
+
+/// Fixed-window mean and sample variance updated in O(1) per row (Welford with removal).
+#[derive(Clone, Debug)]
+pub struct RollingStats {
+    window: usize,
+    values: VecDeque<f64>,
+    mean: f64,
+    m2: f64,
+}
+
+impl RollingStats {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            values: VecDeque::with_capacity(window),
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, x: f64) {
+        self.values.push_back(x);
+        let n = self.values.len() as f64;
+        let delta = x - self.mean;
+        self.mean += delta / n;
+        self.m2 += delta * (x - self.mean);
+
+        if self.values.len() > self.window
+            && let Some(old) = self.values.pop_front()
+        {
+            let n = self.values.len() as f64;
+            let delta = old - self.mean;
+            self.mean -= delta / n;
+            self.m2 = (self.m2 - delta * (old - self.mean)).max(0.0);
+        }
+    }
+
+    /// Same convention as `z_score_expr`: sample std, zero until two values, clipped at ±3.
+    pub fn z_score(&self, x: f64) -> f64 {
+        if self.values.len() < 2 {
+            return 0.0;
+        }
+
+        let std = (self.m2 / (self.values.len() - 1) as f64).sqrt();
+        let z = (x - self.mean) / (std + EPSILON);
+        if z.is_finite() { z.clamp(-3.0, 3.0) } else { 0.0 }
+    }
+}
+
+/// Per-instrument rolling stats updated with the new rows only.
+#[derive(Clone, Debug, Default)]
+pub struct ZScoreCache {
+    stats: HashMap<(String, String), RollingStats>,
+    last_ts: HashMap<String, u64>,
+}
+
+impl ZScoreCache {
+    /// Folds new rows of `df` into the stats of `cols` and returns the z-scores of the last row.
+    pub fn update(
+        &mut self,
+        inst: &str,
+        df: &DataFrame,
+        cols: &[String],
+        window: usize,
+    ) -> InfraResult<Vec<(String, f64)>> {
+        if df.height() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let ts = df.column("timestamp")?.cast(&DataType::UInt64)?;
+        let ts = ts.u64()?;
+        let last_seen = self.last_ts.get(inst).copied();
+        let new_rows: Vec<usize> = (0..df.height())
+            .filter(|&i| ts.get(i).is_some_and(|t| last_seen.is_none_or(|l| t > l)))
+            .collect();
+
+        let last_idx = df.height() - 1;
+        let mut z_scores = Vec::with_capacity(cols.len());
+
+        for name in cols.iter() {
+            let series = df.column(name)?.cast(&DataType::Float64)?;
+            let values = series.f64()?;
+
+            let stats = self
+                .stats
+                .entry((inst.to_string(), name.clone()))
+                .or_insert_with(|| RollingStats::new(window));
+            if stats.window != window.max(1) {
+                *stats = RollingStats::new(window);
+            }
+
+            for idx in new_rows.iter() {
+                if let Some(x) = values.get(*idx).filter(|x| x.is_finite()) {
+                    stats.push(x);
+                }
+            }
+
+            let z = values
+                .get(last_idx)
+                .map(|x| stats.z_score(x))
+                .unwrap_or(0.0);
+            z_scores.push((name.clone(), z));
+        }
+
+        if let Some(t) = ts.get(last_idx) {
+            self.last_ts
+                .insert(inst.to_string(), last_seen.map_or(t, |l| l.max(t)));
+        }
+
+        Ok(z_scores)
+    }
+
+    pub fn evict(&mut self, inst: &str) {
+        self.stats.retain(|(i, _), _| i != inst);
+        self.last_ts.remove(inst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arch::feats::expr_operators::z_score_expr;
+
+    fn stats_of(window: usize, values: &[f64]) -> RollingStats {
+        let mut stats = RollingStats::new(window);
+        values.iter().for_each(|x| stats.push(*x));
+        stats
+    }
+
+    #[test]
+    fn z_score_of_known_values() {
+        // mean 2.5, sample std sqrt(5/3)
+        let stats = stats_of(4, &[1.0, 2.0, 3.0, 4.0]);
+        assert!((stats.z_score(4.0) - 1.5 / (5.0f64 / 3.0).sqrt()).abs() < 1e-6);
+        assert!(stats.z_score(2.5).abs() < 1e-9);
+        assert_eq!(stats.z_score(100.0), 3.0);
+        assert_eq!(stats.z_score(-100.0), -3.0);
+    }
+
+    #[test]
+    fn window_drops_old_values() {
+        // Only 3, 4, 5 remain: mean 4, std 1
+        let stats = stats_of(3, &[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!((stats.z_score(5.0) - 1.0).abs() < 1e-6);
+        assert!(stats.z_score(4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_variance() {
+        let stats = stats_of(5, &[2.0; 5]);
+        assert_eq!(stats.z_score(2.0), 0.0);
+        // Any move off a flat window is clipped, not infinite
+        assert_eq!(stats.z_score(2.5), 3.0);
+    }
+
+    #[test]
+    fn short_window() {
+        assert_eq!(stats_of(20, &[]).z_score(1.0), 0.0);
+        assert_eq!(stats_of(20, &[1.0]).z_score(5.0), 0.0);
+        // A window of one never has two values
+        assert_eq!(stats_of(1, &[1.0, 2.0, 3.0]).z_score(3.0), 0.0);
+        assert_eq!(stats_of(0, &[1.0, 2.0]).z_score(2.0), 0.0);
+    }
+
+    #[test]
+    fn incremental_matches_polars() {
+        let window = 5;
+        let values: Vec<f64> = (0..40).map(|i| ((i * 7919) % 31) as f64 - 15.0).collect();
+        let timestamps: Vec<u64> = (0..values.len() as u64).map(|i| i * 60_000).collect();
+        let df = df!("timestamp" => &timestamps, "x" => &values).unwrap();
+
+        let mut cache = ZScoreCache::default();
+        let cols = vec!["x".to_string()];
+        for end in [window - 1, 12, 13, values.len()] {
+            let incremental = cache.update("BTC", &df.head(Some(end)), &cols, window).unwrap();
+
+            let expected = df
+                .head(Some(end))
+                .lazy()
+                .select([z_score_expr("x", window)])
+                .collect()
+                .unwrap();
+            let expected = expected.column("z_x").unwrap().f64().unwrap().get(end - 1);
+            assert!(
+                (incremental[0].1 - expected.unwrap()).abs() < 1e-6,
+                "rows={}: incremental={} polars={:?}",
+                end,
+                incremental[0].1,
+                expected
+            );
+        }
+    }
+}
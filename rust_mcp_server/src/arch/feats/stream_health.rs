@@ -0,0 +1,116 @@
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use extrema_infra::prelude::*;
+
+/// Span the trade rate is measured over.
+pub const TRADE_RATE_WINDOW_MS: u64 = 60_000;
+/// Span public stream reconnects are counted over.
+pub const RECONNECT_WINDOW_MS: u64 = 3_600_000;
+/// Last-trade age reported for instruments without a trade in this long, or none yet.
+pub const MAX_TRADE_AGE_MS: u64 = 3_600_000;
+
+/// Trade arrivals of one venue instrument: trade count per coalesced window, by receipt time.
+#[derive(Clone, Debug, Default)]
+struct TradeArrivals {
+    windows: VecDeque<(u64, u64)>,
+    last_trade_ms: u64,
+}
+
+/// Market-data health the models see next to the signals.
+#[derive(Clone, Debug, Default)]
+pub struct StreamHealth {
+    arrivals: HashMap<(Market, String), TradeArrivals>,
+    /// Public stream tasks that connected at least once; later connects are reconnects.
+    connected: HashSet<u64>,
+    reconnects: VecDeque<u64>,
+}
+
+impl StreamHealth {
+    /// Records the `count` trades of one coalesced window of a venue instrument.
+    pub fn record_trades(&mut self, key: &(Market, String), count: u64, now_ms: u64) {
+        if count == 0 {
+            return;
+        }
+
+        let arrivals = self.arrivals.entry(key.clone()).or_default();
+        arrivals.windows.push_back((now_ms, count));
+        arrivals.last_trade_ms = now_ms;
+        while arrivals
+            .windows
+            .front()
+            .is_some_and(|(at, _)| now_ms.saturating_sub(*at) > TRADE_RATE_WINDOW_MS)
+        {
+            arrivals.windows.pop_front();
+        }
+    }
+
+    /// Records a connect of a public stream task.
+    pub fn record_connect(&mut self, task_id: Option<u64>, now_ms: u64) {
+        if let Some(task_id) = task_id
+            && self.connected.insert(task_id)
+        {
+            return;
+        }
+        self.reconnects.push_back(now_ms);
+    }
+
+    /// Trades per second of `key` over the last `TRADE_RATE_WINDOW_MS`.
+    pub fn trade_rate(&self, key: &(Market, String), now_ms: u64) -> f64 {
+        let Some(arrivals) = self.arrivals.get(key) else {
+            return 0.0;
+        };
+        let trades: u64 = arrivals
+            .windows
+            .iter()
+            .filter(|(at, _)| now_ms.saturating_sub(*at) <= TRADE_RATE_WINDOW_MS)
+            .map(|(_, count)| count)
+            .sum();
+        trades as f64 / (TRADE_RATE_WINDOW_MS as f64 / 1000.0)
+    }
+
+    /// Seconds since the last trade of `key`, capped at `MAX_TRADE_AGE_MS`.
+    pub fn last_trade_age(&self, key: &(Market, String), now_ms: u64) -> f64 {
+        let age_ms = self
+            .arrivals
+            .get(key)
+            .map_or(MAX_TRADE_AGE_MS, |a| now_ms.saturating_sub(a.last_trade_ms))
+            .min(MAX_TRADE_AGE_MS);
+        age_ms as f64 / 1000.0
+    }
+
+    /// Public stream reconnects within the last `RECONNECT_WINDOW_MS`.
+    pub fn reconnects(&mut self, now_ms: u64) -> usize {
+        while self
+            .reconnects
+            .front()
+            .is_some_and(|at| now_ms.saturating_sub(*at) > RECONNECT_WINDOW_MS)
+        {
+            self.reconnects.pop_front();
+        }
+        self.reconnects.len()
+    }
+
+    /// Adds the stream health columns of `key` to every row of `df`.
+    pub fn attach(
+        &mut self,
+        df: DataFrame,
+        key: &(Market, String),
+        prefix: &str,
+        now_ms: u64,
+    ) -> InfraResult<DataFrame> {
+        let reconnects = self.reconnects(now_ms) as f64;
+        Ok(df
+            .lazy()
+            .with_columns([
+                lit(self.trade_rate(key, now_ms)).alias(format!("{}trade_rate", prefix)),
+                lit(self.last_trade_age(key, now_ms)).alias(format!("{}last_trade_age", prefix)),
+                lit(reconnects).alias(format!("{}ws_reconnects", prefix)),
+            ])
+            .collect()?)
+    }
+
+    pub fn evict(&mut self, inst: &str) {
+        self.arrivals.retain(|(_, i), _| i != inst);
+    }
+}
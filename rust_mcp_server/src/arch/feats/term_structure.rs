@@ -0,0 +1,103 @@
+use polars::prelude::*;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use extrema_infra::prelude::*;
+
+const YEAR_MS: f64 = 365.0 * 86_400_000.0;
+/// Contracts this close to delivery are dropped; their basis is dominated by settlement noise.
+const MIN_DAYS_TO_DELIVERY_MS: u64 = 86_400_000;
+
+/// A dated future listed next to a perpetual.
+#[derive(Clone, Debug)]
+pub struct QuarterlyContract {
+    pub symbol: String,
+    pub delivery_ms: u64,
+}
+
+/// Quarterly contracts per perp instrument, nearest delivery first.
+pub fn quarterly_contracts(exchange_info: &Value) -> HashMap<String, Vec<QuarterlyContract>> {
+    let mut contracts: HashMap<String, Vec<QuarterlyContract>> = HashMap::new();
+    let rows = exchange_info["symbols"].as_array().cloned().unwrap_or_default();
+
+    for row in rows.iter() {
+        let quarterly = matches!(
+            row["contractType"].as_str(),
+            Some("CURRENT_QUARTER" | "NEXT_QUARTER")
+        );
+        if !quarterly || row["status"].as_str() != Some("TRADING") {
+            continue;
+        }
+        let (Some(symbol), Some(base), Some(quote), Some(delivery_ms)) = (
+            row["symbol"].as_str(),
+            row["baseAsset"].as_str(),
+            row["quoteAsset"].as_str(),
+            row["deliveryDate"].as_u64(),
+        ) else {
+            continue;
+        };
+
+        contracts
+            .entry(format!("{}_{}_PERP", base, quote))
+            .or_default()
+            .push(QuarterlyContract {
+                symbol: symbol.to_string(),
+                delivery_ms,
+            });
+    }
+
+    for list in contracts.values_mut() {
+        list.sort_by_key(|c| c.delivery_ms);
+    }
+    contracts
+}
+
+/// Basis and slope of the futures curve against the perp.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TermStructure {
+    pub basis_near: f64,
+    pub basis_near_ann: f64,
+    pub calendar_spread: f64,
+    pub slope_ann: f64,
+}
+
+impl TermStructure {
+    /// `points` are `(delivery_ms, price)` nearest first.
+    pub fn from_prices(perp_px: f64, points: &[(u64, f64)], now_ms: u64) -> Self {
+        let points: Vec<(f64, f64)> = points
+            .iter()
+            .filter(|(delivery, px)| {
+                *px > 0.0 && delivery.saturating_sub(now_ms) >= MIN_DAYS_TO_DELIVERY_MS
+            })
+            .map(|(delivery, px)| ((delivery - now_ms) as f64 / YEAR_MS, *px))
+            .collect();
+        if perp_px <= 0.0 {
+            return Self::default();
+        }
+
+        let basis = |px: f64| px / perp_px - 1.0;
+        let mut ts = Self::default();
+        if let Some(&(years, near)) = points.first() {
+            ts.basis_near = basis(near);
+            ts.basis_near_ann = ts.basis_near / years;
+        }
+        if let [(_, near), (far_years, far), ..] = points[..] {
+            ts.calendar_spread = far / near - 1.0;
+            ts.slope_ann = basis(far) / far_years - ts.basis_near_ann;
+        }
+        ts
+    }
+
+    /// Adds the four features as `<prefix><name>` columns.
+    pub fn attach(&self, df: DataFrame, prefix: &str) -> InfraResult<DataFrame> {
+        Ok(df
+            .lazy()
+            .with_columns([
+                lit(self.basis_near).alias(format!("{}basis_near", prefix)),
+                lit(self.basis_near_ann).alias(format!("{}basis_near_ann", prefix)),
+                lit(self.calendar_spread).alias(format!("{}calendar_spread", prefix)),
+                lit(self.slope_ann).alias(format!("{}slope_ann", prefix)),
+            ])
+            .collect()?)
+    }
+}
@@ -0,0 +1,187 @@
+use serde::Deserialize;
+use std::{collections::HashMap, env, fs, path::PathBuf, sync::OnceLock};
+use tracing::Level;
+
+use extrema_infra::errors::*;
+
+use crate::arch::account_module::acc_ratelimit::RateLimitConfig;
+
+/// Environment variable naming the profile, overridden by `--profile <name>`.
+pub const PROFILE_ENV: &str = "MCP_PROFILE";
+/// Command line switch forcing observer mode on top of any profile.
+pub const OBSERVE_FLAG: &str = "--observe";
+/// Command line switch serving the Model Context Protocol on stdio; logs move to stderr.
+pub const MCP_STDIO_FLAG: &str = "--mcp-stdio";
+/// `--mcp-http <addr>` overrides the profile's `mcp_http_addr`.
+pub const MCP_HTTP_FLAG: &str = "--mcp-http";
+/// `--record-golden <n>` overrides the profile's `record_golden`.
+pub const RECORD_GOLDEN_FLAG: &str = "--record-golden";
+/// Command line switch that lints the configs, prints the report and exits.
+pub const VALIDATE_CONFIG_FLAG: &str = "--validate-config";
+/// Command line switch that prints the model protocol spec and exits, see `protocol_spec`.
+pub const PROTOCOL_SPEC_FLAG: &str = "--protocol-spec";
+/// Named profiles, read from the working directory. See `profiles.example.json`.
+pub const PROFILES_FILE: &str = "profiles.json";
+
+static ACTIVE_PROFILE: OnceLock<Profile> = OnceLock::new();
+
+/// Per-environment settings (dev / staging / prod) so one binary runs everywhere.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    #[serde(skip)]
+    pub name: String,
+    /// Directory of the model, account and feature configs.
+    pub config_dir: String,
+    /// Route every venue endpoint to its demo environment.
+    pub testnet: bool,
+    /// One of `trace`, `debug`, `info`, `warn`, `error`.
+    pub log_level: String,
+    /// Treat every account as a paper account whatever `account_config.json` says.
+    pub paper_only: bool,
+    /// Observer mode: accounts are read but every order is refused.
+    pub observe: bool,
+    /// Number of golden sessions to capture under `contracts/recorded`.
+    pub record_golden: usize,
+    /// Serve MCP tools as JSON-RPC on stdin/stdout, for MCP clients that spawn the process.
+    pub mcp_stdio: bool,
+    /// Serve MCP tools over HTTP/SSE on this address, e.g. `127.0.0.1:8765`.
+    pub mcp_http_addr: Option<String>,
+    /// REST and order budgets per venue, shared by every account on this host.
+    pub rate_limits: RateLimitConfig,
+    /// Lint the configs of this profile instead of running, see `lint_configs`.
+    #[serde(skip)]
+    pub validate_config: bool,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            config_dir: String::new(),
+            testnet: false,
+            log_level: "info".to_string(),
+            paper_only: false,
+            observe: false,
+            record_golden: 0,
+            mcp_stdio: false,
+            mcp_http_addr: None,
+            rate_limits: RateLimitConfig::default(),
+            validate_config: false,
+        }
+    }
+}
+
+impl Profile {
+    /// Checked by `select_profile`, so an unknown level never reaches here.
+    pub fn log_level(&self) -> Level {
+        self.log_level.parse::<Level>().unwrap_or(Level::INFO)
+    }
+
+    /// Orders are simulated for paper-only profiles; testnet ones send them to the demo venues.
+    pub fn simulate_orders(&self) -> bool {
+        !self.observe && self.paper_only
+    }
+
+    /// Gate in front of every venue write. Errors in observer mode.
+    pub fn ensure_orders_allowed(&self, action: &str) -> InfraResult<()> {
+        if self.observe {
+            return Err(InfraError::Msg(format!("{} refused: observer mode", action)));
+        }
+        Ok(())
+    }
+}
+
+/// Resolves the profile from the command line or `MCP_PROFILE` and fixes it for the process.
+pub fn select_profile() -> InfraResult<&'static Profile> {
+    let args: Vec<String> = env::args().collect();
+    let cli_name = args.iter().enumerate().find_map(|(idx, arg)| match arg.as_str() {
+        "--profile" => args.get(idx + 1).cloned(),
+        _ => arg.strip_prefix("--profile=").map(String::from),
+    });
+
+    let mut profile = match cli_name.or_else(|| env::var(PROFILE_ENV).ok()) {
+        Some(name) => load_profile(&name)?,
+        None => Profile::default(),
+    };
+    profile.observe |= args.iter().any(|arg| arg == OBSERVE_FLAG);
+    profile.mcp_stdio |= args.iter().any(|arg| arg == MCP_STDIO_FLAG);
+    profile.validate_config = args.iter().any(|arg| arg == VALIDATE_CONFIG_FLAG);
+    if let Some(idx) = args.iter().position(|arg| arg == MCP_HTTP_FLAG) {
+        let addr = args
+            .get(idx + 1)
+            .ok_or_else(|| InfraError::Msg(format!("usage: {} <addr>", MCP_HTTP_FLAG)))?;
+        profile.mcp_http_addr = Some(addr.clone());
+    }
+    if let Some(idx) = args.iter().position(|arg| arg == RECORD_GOLDEN_FLAG) {
+        profile.record_golden = args
+            .get(idx + 1)
+            .and_then(|n| n.parse::<usize>().ok())
+            .ok_or_else(|| InfraError::Msg(format!("usage: {} <sessions>", RECORD_GOLDEN_FLAG)))?;
+    }
+    if profile.log_level.parse::<Level>().is_err() {
+        return Err(InfraError::Msg(format!(
+            "Unknown log_level {:?} in profile {}",
+            profile.log_level, profile.name
+        )));
+    }
+
+    Ok(ACTIVE_PROFILE.get_or_init(|| profile))
+}
+
+fn load_profile(name: &str) -> InfraResult<Profile> {
+    let mut path = env::current_dir()?;
+    path.push(PROFILES_FILE);
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| InfraError::Msg(format!("Failed to read {:?}: {}", path, e)))?;
+    let mut profiles: HashMap<String, Profile> = serde_json::from_str(&content)
+        .map_err(|e| InfraError::Msg(format!("Failed to parse profiles: {}", e)))?;
+
+    let mut profile = profiles
+        .remove(name)
+        .ok_or_else(|| InfraError::Msg(format!("Unknown profile: {}", name)))?;
+    profile.name = name.to_string();
+
+    Ok(profile)
+}
+
+pub fn active_profile() -> &'static Profile {
+    ACTIVE_PROFILE.get_or_init(Profile::default)
+}
+
+/// Path of a config file under the active profile's config directory.
+pub fn config_path(file_name: &str) -> InfraResult<PathBuf> {
+    let mut path = env::current_dir()?;
+    path.push(&active_profile().config_dir);
+    path.push(file_name);
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn testnet_sends_orders_and_paper_only_simulates_them() {
+        let testnet = Profile {
+            testnet: true,
+            ..Profile::default()
+        };
+        assert!(!testnet.simulate_orders());
+
+        let paper = Profile {
+            testnet: true,
+            paper_only: true,
+            ..Profile::default()
+        };
+        assert!(paper.simulate_orders());
+
+        let observer = Profile {
+            paper_only: true,
+            observe: true,
+            ..Profile::default()
+        };
+        assert!(!observer.simulate_orders());
+    }
+}
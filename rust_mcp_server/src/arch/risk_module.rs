@@ -0,0 +1,4 @@
+pub mod risk_alert;
+pub mod risk_base;
+pub mod risk_core;
+pub mod risk_schedule;
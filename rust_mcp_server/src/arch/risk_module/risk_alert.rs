@@ -0,0 +1,220 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+use tracing::{info, warn};
+
+use extrema_infra::prelude::*;
+
+/// Alerts without `ttl_sec` lapse after this long.
+const RISK_ALERT_TTL_SEC: u64 = 3_600;
+/// Target scale of a `warning` alert without `scale`.
+const WARNING_SCALE: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertSeverity {
+    /// Scales the affected targets down by `scale`.
+    Warning,
+    /// Zeroes the affected targets.
+    Critical,
+    /// Lifts the same model's earlier alert on the same instruments.
+    Clear,
+}
+
+impl AlertSeverity {
+    fn parse(severity: &str) -> InfraResult<Self> {
+        match severity.trim().to_ascii_lowercase().as_str() {
+            "" | "warning" | "warn" | "medium" => Ok(Self::Warning),
+            "critical" | "high" => Ok(Self::Critical),
+            "clear" | "resolved" => Ok(Self::Clear),
+            other => Err(InfraError::Msg(format!(
+                "risk_alert: unknown severity {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// One MCP `risk_alert`. An empty `insts` covers the whole book.
+#[derive(Clone, Debug)]
+pub struct RiskAlert {
+    pub model_id: String,
+    pub severity: AlertSeverity,
+    pub insts: Vec<String>,
+    /// Factor applied to the affected target weights.
+    pub scale: f64,
+    pub reason: String,
+    pub expires_ms: u64,
+}
+
+impl RiskAlert {
+    /// Reads a risk alert from the tensor metadata.
+    pub fn from_metadata(meta: &HashMap<String, String>, now_ms: u64) -> InfraResult<Self> {
+        let severity =
+            AlertSeverity::parse(meta.get("severity").map(|x| x.as_str()).unwrap_or_default())?;
+        let insts = meta
+            .get("insts")
+            .or_else(|| meta.get("inst"))
+            .map(|insts| {
+                insts
+                    .split(',')
+                    .map(|inst| inst.trim().to_string())
+                    .filter(|inst| !inst.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let scale = match severity {
+            AlertSeverity::Warning => meta
+                .get("scale")
+                .map(|s| {
+                    s.parse::<f64>()
+                        .ok()
+                        .filter(|s| (0.0..=1.0).contains(s))
+                        .ok_or_else(|| {
+                            InfraError::Msg(format!("risk_alert: scale {} not in [0, 1]", s))
+                        })
+                })
+                .transpose()?
+                .unwrap_or(WARNING_SCALE),
+            AlertSeverity::Critical => 0.0,
+            AlertSeverity::Clear => 1.0,
+        };
+        let ttl_sec = meta
+            .get("ttl_sec")
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(RISK_ALERT_TTL_SEC);
+
+        Ok(Self {
+            model_id: meta.get("model_id").cloned().unwrap_or_default(),
+            severity,
+            insts,
+            scale,
+            reason: meta.get("reason").cloned().unwrap_or_default(),
+            expires_ms: now_ms.saturating_add(ttl_sec * 1000),
+        })
+    }
+
+    pub fn covers(&self, inst: &str) -> bool {
+        self.insts.is_empty() || self.insts.iter().any(|i| i == inst)
+    }
+
+    /// Same model and the same instruments in any order.
+    fn same_scope(&self, other: &RiskAlert) -> bool {
+        self.model_id == other.model_id
+            && self.insts.iter().collect::<HashSet<_>>()
+                == other.insts.iter().collect::<HashSet<_>>()
+    }
+}
+
+#[derive(Debug, Default)]
+struct RiskAlertState {
+    active: Vec<RiskAlert>,
+    /// Raised alerts not yet taken by `AccountManager`.
+    events: VecDeque<RiskAlert>,
+}
+
+/// Risk alerts shared by `McpServer` and `AccountManager`.
+#[derive(Clone, Debug, Default)]
+pub struct RiskAlerts {
+    state: Arc<Mutex<RiskAlertState>>,
+}
+
+impl RiskAlerts {
+    /// Applies `alert`: a `clear` lifts it, anything else replaces and broadcasts it.
+    pub fn raise(&self, alert: RiskAlert) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        if alert.severity == AlertSeverity::Clear {
+            let before = state.active.len();
+            state.active.retain(|active| !active.same_scope(&alert));
+            info!(
+                "[Risk] Alert cleared by model={} insts={:?}: {} lifted",
+                alert.model_id,
+                alert.insts,
+                before - state.active.len()
+            );
+            return;
+        }
+
+        warn!(
+            "[Risk] {:?} alert from model={} insts={:?} scale={}: {}",
+            alert.severity, alert.model_id, alert.insts, alert.scale, alert.reason
+        );
+        state.active.retain(|active| !active.same_scope(&alert));
+        state.active.push(alert.clone());
+        state.events.push_back(alert);
+    }
+
+    /// Drops lapsed alerts. Returns true when any was dropped.
+    pub fn expire(&self, now_ms: u64) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+
+        let before = state.active.len();
+        state.active.retain(|alert| alert.expires_ms > now_ms);
+        let expired = before - state.active.len();
+        if expired > 0 {
+            info!("[Risk] {} alert(s) expired", expired);
+        }
+        expired > 0
+    }
+
+    /// Smallest scale of the active alerts covering `inst`, 1.0 when none does.
+    pub fn scale(&self, inst: &str) -> f64 {
+        let Ok(state) = self.state.lock() else {
+            return 1.0;
+        };
+
+        state
+            .active
+            .iter()
+            .filter(|alert| alert.covers(inst))
+            .map(|alert| alert.scale)
+            .fold(1.0, f64::min)
+    }
+
+    /// Raised alerts since the last call.
+    pub fn take_events(&self) -> Vec<RiskAlert> {
+        self.state
+            .lock()
+            .map(|mut state| state.events.drain(..).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(model_id: &str, severity: &str, insts: &str) -> RiskAlert {
+        let meta = HashMap::from([
+            ("model_id".to_string(), model_id.to_string()),
+            ("severity".to_string(), severity.to_string()),
+            ("insts".to_string(), insts.to_string()),
+        ]);
+        RiskAlert::from_metadata(&meta, 0).unwrap()
+    }
+
+    #[test]
+    fn clear_lifts_only_own_alert_on_same_insts() {
+        let alerts = RiskAlerts::default();
+        alerts.raise(alert("a", "critical", "BTC_USDT_PERP,ETH_USDT_PERP"));
+        alerts.raise(alert("b", "critical", "BTC_USDT_PERP"));
+
+        alerts.raise(alert("a", "clear", "BTC_USDT_PERP"));
+        alerts.raise(alert("b", "clear", "BTC_USDT_PERP,ETH_USDT_PERP"));
+        assert_eq!(alerts.scale("ETH_USDT_PERP"), 0.0);
+        assert_eq!(alerts.scale("BTC_USDT_PERP"), 0.0);
+
+        alerts.raise(alert("a", "clear", "ETH_USDT_PERP,BTC_USDT_PERP"));
+        assert_eq!(alerts.scale("ETH_USDT_PERP"), 1.0);
+        assert_eq!(alerts.scale("BTC_USDT_PERP"), 0.0);
+
+        alerts.raise(alert("b", "clear", "BTC_USDT_PERP"));
+        assert_eq!(alerts.scale("BTC_USDT_PERP"), 1.0);
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use extrema_infra::prelude::*;
+
+use crate::arch::{
+    account_module::acc_base::TargetWeights,
+    control_module::{ctl_auth::CommandAuth, ctl_base::ControlState, ctl_risk::RiskLimits},
+};
+
+/// Strategy module feeding the one kill switch, `ControlState::kill`.
+#[derive(Clone, Debug)]
+pub struct RiskManager {
+    pub control: ControlState,
+    pub target_weights: TargetWeights,
+    pub risk: RiskLimits,
+    pub command_auth: CommandAuth,
+    /// `kill_switch` of the risk config as last applied.
+    pub config_kill: bool,
+    pub command_handles: Vec<Arc<CommandHandle>>,
+}
+
+impl Default for RiskManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RiskManager {
+    pub fn new() -> Self {
+        Self {
+            control: ControlState::default(),
+            target_weights: TargetWeights::default(),
+            risk: RiskLimits::default(),
+            command_auth: CommandAuth::default(),
+            config_kill: false,
+            command_handles: Vec::new(),
+        }
+    }
+
+    pub fn with_control_state(
+        &mut self,
+        control: ControlState,
+        target_weights: TargetWeights,
+    ) -> &mut Self {
+        self.control = control;
+        self.target_weights = target_weights;
+        self
+    }
+
+    pub fn with_risk_limits(&mut self, risk: RiskLimits) -> &mut Self {
+        self.risk = risk;
+        self
+    }
+
+    pub fn with_command_auth(&mut self, command_auth: CommandAuth) -> &mut Self {
+        self.command_auth = command_auth;
+        self
+    }
+
+    /// Follows `kill_switch` of the risk config: kills when it is set.
+    pub fn sync_config(&mut self) {
+        let config_kill = self.risk.get().kill_switch;
+        if config_kill == self.config_kill {
+            return;
+        }
+
+        self.config_kill = config_kill;
+        if config_kill {
+            self.engage("risk config", "kill_switch set");
+        } else if self.control.is_killed() {
+            warn!("[Risk] kill_switch cleared in the risk config, still killed until rearm");
+        }
+    }
+
+    /// MCP `kill` and `resume`.
+    pub fn handle_command(&mut self, alt_tensor: &AltTensor) -> InfraResult<()> {
+        let meta = &alt_tensor.metadata;
+        let cmd = meta.get("cmd").map(|x| x.as_str()).unwrap_or("noop");
+        if !matches!(cmd, "kill" | "resume") {
+            return Ok(());
+        }
+        self.command_auth.authorize(meta)?;
+
+        let source = format!(
+            "MCP model={}",
+            meta.get("model_id").map(|x| x.as_str()).unwrap_or_default()
+        );
+
+        match cmd {
+            "kill" => {
+                let reason = meta.get("reason").map(|x| x.as_str()).unwrap_or_default();
+                self.engage(&source, reason);
+            },
+            "resume" => {
+                return Err(InfraError::Msg(format!(
+                    "resume from {} refused: the kill switch is re-armed by the admins \
+                     with `rearm` on the control socket",
+                    source
+                )));
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    fn engage(&self, source: &str, reason: &str) {
+        if self.control.is_killed() {
+            info!("[Risk] Kill switch already engaged, {} ignored", source);
+            return;
+        }
+
+        self.control
+            .kill(&self.target_weights, &format!("{}: {}", source, reason));
+        error!(
+            "[Risk] Kill switch engaged by {} ({}): targets flattened until admin rearm",
+            source, reason
+        );
+    }
+}
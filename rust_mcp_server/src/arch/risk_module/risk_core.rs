@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use extrema_infra::prelude::*;
+
+use super::risk_base::RiskManager;
+
+impl Strategy for RiskManager {
+    async fn initialize(&mut self) {
+        // A switch left set in the config kills from the first tick
+        self.sync_config();
+        info!(
+            "Risk manager initialized, kill switch engaged={}",
+            self.control.is_killed()
+        );
+    }
+}
+
+impl CommandEmitter for RiskManager {
+    fn command_init(&mut self, command_handle: Arc<CommandHandle>) {
+        self.command_handles.push(command_handle);
+    }
+
+    fn command_registry(&self) -> Vec<Arc<CommandHandle>> {
+        self.command_handles.clone()
+    }
+}
+
+impl EventHandler for RiskManager {
+    async fn on_schedule(&mut self, _msg: InfraMsg<AltScheduleEvent>) {
+        self.sync_config();
+    }
+
+    async fn on_preds(&mut self, msg: InfraMsg<AltTensor>) {
+        if let Err(e) = self.handle_command(&msg.data) {
+            warn!("[Risk] {:?}, task: {:?}", e, msg.task_id);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use serde::Deserialize;
+
+use crate::arch::account_module::acc_rest::iso8601_millis;
+
+const DAY_MS: u64 = 86_400_000;
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// UTC window in which an instrument's target weight is held at zero.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskOffWindow {
+    /// Days the window starts on, `mon` to `sun`; empty means every day.
+    pub days: Vec<String>,
+    /// Dates the window starts on as `YYYY-MM-DD`; when set, `days` is ignored.
+    pub dates: Vec<String>,
+    /// `HH:MM` UTC, defaults to the start of the day.
+    pub start: Option<String>,
+    /// `HH:MM` UTC, exclusive, defaults to the end of the day.
+    pub end: Option<String>,
+}
+
+impl RiskOffWindow {
+    pub fn validate(&self) -> Result<(), String> {
+        for day in self.days.iter() {
+            if weekday_index(day).is_none() {
+                return Err(format!("unknown day {}", day));
+            }
+        }
+        for date in self.dates.iter() {
+            let parts: Vec<&str> = date.split('-').collect();
+            let valid = parts.len() == 3
+                && [4, 2, 2].iter().zip(parts.iter()).all(|(len, part)| {
+                    part.len() == *len && part.bytes().all(|b| b.is_ascii_digit())
+                });
+            if !valid {
+                return Err(format!("date {} not YYYY-MM-DD", date));
+            }
+        }
+        for time in [&self.start, &self.end].into_iter().flatten() {
+            parse_minutes(time).ok_or_else(|| format!("time {} not HH:MM", time))?;
+        }
+        Ok(())
+    }
+
+    /// Whether `now_ms` falls inside the window.
+    pub fn is_active(&self, now_ms: u64) -> bool {
+        let start = self.start.as_deref().and_then(parse_minutes).unwrap_or(0);
+        let end = self.end.as_deref().and_then(parse_minutes).unwrap_or(1440);
+        let minute = ((now_ms % DAY_MS) / 60_000) as u32;
+
+        if start < end {
+            start <= minute && minute < end && self.starts_on(now_ms)
+        } else {
+            // Runs past midnight: the evening of a start day or the morning after one
+            (minute >= start && self.starts_on(now_ms))
+                || (minute < end && self.starts_on(now_ms.saturating_sub(DAY_MS)))
+        }
+    }
+
+    fn starts_on(&self, day_ms: u64) -> bool {
+        if !self.dates.is_empty() {
+            let date = &iso8601_millis(day_ms)[..10];
+            return self.dates.iter().any(|d| d == date);
+        }
+
+        // 1970-01-01 was a Thursday, shift so Monday is 0
+        let weekday = ((day_ms / DAY_MS + 3) % 7) as usize;
+        self.days.is_empty()
+            || self
+                .days
+                .iter()
+                .any(|day| weekday_index(day) == Some(weekday))
+    }
+}
+
+fn weekday_index(day: &str) -> Option<usize> {
+    let day = day.trim().to_ascii_lowercase();
+    WEEKDAYS.iter().position(|d| day.starts_with(d))
+}
+
+/// Minutes since midnight of `HH:MM`; `24:00` is the end of the day.
+fn parse_minutes(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    match (hours, minutes) {
+        (24, 0) => Some(1440),
+        (0..=23, 0..=59) => Some(hours * 60 + minutes),
+        _ => None,
+    }
+}
@@ -1,3 +1,13 @@
 pub mod server_base;
+pub mod server_contract;
 pub mod server_core;
+pub mod server_delta;
+pub mod server_ensemble;
+pub mod server_golden;
+pub mod server_heartbeat;
+pub mod server_mcp;
+pub mod server_mcp_http;
+pub mod server_shadow;
+pub mod server_term;
+pub mod server_trades;
 pub mod server_utils;
\ No newline at end of file
@@ -1,5 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use dashmap::DashMap;
 use tracing::{error, info, warn};
 use polars::prelude::*;
@@ -15,22 +16,138 @@ use extrema_infra::{
 use extrema_infra::arch::market_assets::api_general::get_micros_timestamp;
 use tokio::sync::oneshot;
 use crate::arch::{
-    account_module::acc_base::TargetWeights,
+    account_module::{
+        acc_base::{
+            AccountSnapshots, ExecutionQueue, PriceLimits, RejectionQueue, TargetWeights,
+        },
+        acc_rest::profile_ws_url,
+        acc_utils::{
+            ExecutionReport, IdleInstruments, MAX_PENDING_REJECTIONS, PriceLimit,
+            SchedulerIntervals, TradeRejection, interval_elapsed,
+        },
+    },
+    control_module::{
+        ctl_attribution::{
+            ATTRIBUTION_INTERVAL, AttributionHistory, AttributionRow, CONSOLIDATED_SOURCE,
+        },
+        ctl_audit::{CommandAudit, weight_snapshot},
+        ctl_auth::CommandAuth,
+        ctl_base::ControlState,
+        ctl_portfolio::{PortfolioSnapshot, PortfolioView},
+        ctl_risk::RiskLimits,
+        ctl_watchdog::{TaskHeartbeats, WatchdogConfig},
+    },
     feats::{
-        alt_df_build::oi_to_lf,
+        alt_df_build::{oi_to_lf, with_calendar_feats},
+        data_gaps::{
+            GAP_COL, KLINE_PERIOD_MS, OI_PAGE_LIMIT, OI_PERIOD, OI_PERIOD_MS, find_gaps,
+            merge_oi, missing_bars, window_gap_bars,
+        },
         expr_operators::*,
+        feat_config::{
+            FeatureConfig, SOURCE_CALENDAR, SOURCE_HEALTH, SOURCE_KLINE, SOURCE_OPEN_INTEREST,
+            SOURCE_TERM, SOURCE_TRADES, ScalingMethod, load_feature_config,
+        },
+        feat_store::{FeatureStore, history_key},
+        frame_pool::FramePool,
+        order_flow::{OrderFlow, ToxicityMap},
+        term_structure::TermStructure,
+        rolling_stats::ZScoreCache,
+        stream_health::StreamHealth,
     },
+    risk_module::risk_alert::{RiskAlert, RiskAlerts},
 };
-use super::{server_utils::{ModelConfig, load_model_config}};
+use super::{server_contract::{
+    Direction, SCHEMA_VERSION, protocol_spec, validate_metadata,
+}, server_delta::{DELTA_BASELINE_ERROR, DeltaEncoder},
+server_ensemble::{EnsembleConfig, load_ensemble_config},
+server_golden::{GoldenOutcome, GoldenRecorder, GoldenSession},
+server_heartbeat::{DEFAULT_MAX_MISSED_HEARTBEATS, ModelHeartbeats, ModelReply},
+server_shadow::{SHADOW_REPORT_INTERVAL, ShadowBook},
+server_mcp::{FeatureRow, InjectedCommands, LatestFeatures},
+server_term::TermStructureFeed,
+server_trades::TradeCoalescer, server_utils::{
+    CANDLE_CLOSE_COL, ColumnPins, DEFAULT_INST, DeadLetterQueue, MAX_CANDLE_HISTORY, ModelConfig,
+    ModelMode, RoutingTable, consolidate_weights, load_model_config, load_universe, model_scale,
+}};
 
 #[derive(Clone, Debug)]
 pub struct McpServer {
     binance_cm_cli: BinanceCmCli,
     binance_um_cli: BinanceUmCli, // Public Binance UM Futures client (no API keys)
-    pub px: HashMap<String, f64>,
+    okx_cli: OkxCli,              // Public OKX client (no API keys)
+    /// Last price per venue and instrument; venues are never mixed under one key.
+    pub px: HashMap<(Market, String), f64>,
+    /// Candle closes per venue and instrument; features read the `price_market` ones.
+    pub candles: HashMap<(Market, String), VecDeque<(u64, f64)>>,
+    /// OI ranges per instrument the venue had no rows for on backfill, not requested again.
+    pub oi_unfillable: HashMap<String, HashSet<(u64, u64)>>,
+    /// Candle-close frames, rebuilt only after a candle update.
+    pub candle_frames: FramePool,
+    pub trades: TradeCoalescer,
+    pub order_flow: OrderFlow,
+    /// Latest VPIN per instrument, read by the account risk checks.
+    pub toxicity: ToxicityMap,
+    pub stream_health: StreamHealth,
+    pub term: TermStructureFeed,
     pub model_config: HashMap<String, ModelConfig>,
+    pub routing: RoutingTable,
+    /// Instruments streamed whether or not a model lists them, from `universe.json`.
+    pub universe: Vec<String>,
+    pub dead_letters: DeadLetterQueue,
+    pub feature_config: FeatureConfig,
+    pub ensemble: EnsembleConfig,
+    pub feature_store: FeatureStore,
+    pub column_pins: ColumnPins,
+    pub delta: DeltaEncoder,
+    pub zscore_cache: ZScoreCache,
+    pub model_targets: HashMap<String, HashMap<String, f64>>,
+    /// Time-boxed raw targets per model and instrument.
+    pub target_expiry: HashMap<(String, String), (u64, f64)>,
+    pub last_preds: HashMap<String, Instant>,
+    pub model_heartbeats: ModelHeartbeats,
+    /// Models in `fallback`; their `adjust_position` commands are ignored.
+    pub fallback_models: HashSet<String>,
+    pub decay: HashMap<String, f64>,
     pub target_weights: TargetWeights,
+    /// Price bounds models attached to their `adjust_position`, read by the executors.
+    pub price_limits: PriceLimits,
+    pub rejections: RejectionQueue,
+    pub executions: ExecutionQueue,
+    pub snapshots: AccountSnapshots,
+    pub portfolio: PortfolioView,
+    pub intervals: SchedulerIntervals,
+    pub last_data_send: Option<Instant>,
+    /// Last feature send per model with its own `interval_sec`.
+    pub last_model_send: HashMap<String, Option<Instant>>,
+    pub reload_task_id: Option<u64>,
+    pub control: ControlState,
+    /// Model credentials, reloaded with `model_config.json`.
+    pub command_auth: CommandAuth,
+    pub command_audit: CommandAudit,
+    pub attribution: AttributionHistory,
+    pub last_attribution: Option<Instant>,
+    /// Targets and hypothetical PnL of the models in shadow mode.
+    pub shadow: ShadowBook,
+    pub last_shadow_report: Option<Instant>,
+    pub risk: RiskLimits,
+    /// Active MCP `risk_alert`s, scaling the published targets.
+    pub risk_alerts: RiskAlerts,
+    /// Instruments inside a `risk_off_windows` entry, published at zero weight.
+    pub risk_off: HashSet<String>,
+    pub heartbeats: TaskHeartbeats,
+    pub watchdog: WatchdogConfig,
+    /// Task id and spec of the candle stream, kept for watchdog reconnects.
+    pub candle_task: Option<(u64, WsTaskInfo)>,
+    pub golden: GoldenRecorder,
+    /// Latest feature row per instrument, read by the MCP transports.
+    pub latest_features: LatestFeatures,
+    /// Commands from MCP clients, mediated on the next tick.
+    pub injected: InjectedCommands,
     pub command_handles: Vec<Arc<CommandHandle>>,
+    pub idle_insts: IdleInstruments,
+    /// Instruments idle for this long are dropped from the caches.
+    pub idle_inst_ttl: Duration,
 }
 
 impl Default for McpServer {
@@ -43,11 +160,62 @@ impl McpServer {
     pub fn new() -> Self {
         Self {
             px: HashMap::new(),
+            candles: HashMap::new(),
+            oi_unfillable: HashMap::new(),
+            candle_frames: FramePool::default(),
+            trades: TradeCoalescer::default(),
+            order_flow: OrderFlow::default(),
+            toxicity: Arc::new(DashMap::new()),
+            stream_health: StreamHealth::default(),
+            term: TermStructureFeed::default(),
             binance_cm_cli: BinanceCmCli::default(),
             binance_um_cli: BinanceUmCli::default(),
+            okx_cli: OkxCli::default(),
             model_config: HashMap::new(),
+            routing: RoutingTable::default(),
+            universe: vec![DEFAULT_INST.to_string()],
+            dead_letters: DeadLetterQueue::default(),
+            feature_config: FeatureConfig::default(),
+            ensemble: EnsembleConfig::default(),
+            feature_store: FeatureStore::new("feature_store"),
+            column_pins: ColumnPins::default(),
+            delta: DeltaEncoder::default(),
+            zscore_cache: ZScoreCache::default(),
+            model_targets: HashMap::new(),
+            target_expiry: HashMap::new(),
+            last_preds: HashMap::new(),
+            model_heartbeats: ModelHeartbeats::default(),
+            fallback_models: HashSet::new(),
+            decay: HashMap::new(),
             target_weights: Arc::new(DashMap::default()),
+            price_limits: Arc::new(DashMap::new()),
+            rejections: RejectionQueue::default(),
+            executions: ExecutionQueue::default(),
+            snapshots: Arc::new(DashMap::new()),
+            portfolio: PortfolioView::default(),
+            intervals: SchedulerIntervals::default(),
+            last_data_send: None,
+            last_model_send: HashMap::new(),
+            reload_task_id: None,
+            control: ControlState::default(),
+            command_auth: CommandAuth::default(),
+            command_audit: CommandAudit::default(),
+            attribution: AttributionHistory::default(),
+            last_attribution: None,
+            shadow: ShadowBook::default(),
+            last_shadow_report: None,
+            risk: RiskLimits::default(),
+            risk_alerts: RiskAlerts::default(),
+            risk_off: HashSet::new(),
+            heartbeats: TaskHeartbeats::default(),
+            watchdog: WatchdogConfig::default(),
+            candle_task: None,
+            golden: GoldenRecorder::default(),
+            latest_features: Arc::new(DashMap::new()),
+            injected: InjectedCommands::default(),
             command_handles: Vec::new(),
+            idle_insts: IdleInstruments::default(),
+            idle_inst_ttl: Duration::from_secs(86_400),
         }
     }
 
@@ -56,28 +224,181 @@ impl McpServer {
         self
     }
 
+    pub fn with_price_limits(&mut self, price_limits: PriceLimits) -> &mut Self {
+        self.price_limits = price_limits;
+        self
+    }
+
+    pub fn with_toxicity(&mut self, toxicity: ToxicityMap) -> &mut Self {
+        self.toxicity = toxicity;
+        self
+    }
+
+    pub fn with_scheduler_intervals(&mut self, intervals: SchedulerIntervals) -> &mut Self {
+        self.intervals = intervals;
+        self
+    }
+
+    pub fn with_control_state(&mut self, control: ControlState) -> &mut Self {
+        self.control = control;
+        self
+    }
+
+    pub fn with_risk_limits(&mut self, risk: RiskLimits) -> &mut Self {
+        self.risk = risk;
+        self
+    }
+
+    pub fn with_mcp_clients(
+        &mut self,
+        latest_features: LatestFeatures,
+        injected: InjectedCommands,
+    ) -> &mut Self {
+        self.latest_features = latest_features;
+        self.injected = injected;
+        self
+    }
+
+    pub fn with_risk_alerts(&mut self, risk_alerts: RiskAlerts) -> &mut Self {
+        self.risk_alerts = risk_alerts;
+        self
+    }
+
+    pub fn with_command_auth(&mut self, command_auth: CommandAuth) -> &mut Self {
+        self.command_auth = command_auth;
+        self
+    }
+
+    pub fn with_command_audit(&mut self, command_audit: CommandAudit) -> &mut Self {
+        self.command_audit = command_audit;
+        self
+    }
+
+    pub fn with_attribution(&mut self, attribution: AttributionHistory) -> &mut Self {
+        self.attribution = attribution;
+        self
+    }
+
+    pub fn with_account_snapshots(&mut self, snapshots: AccountSnapshots) -> &mut Self {
+        self.snapshots = snapshots;
+        self
+    }
+
+    pub fn with_portfolio_view(&mut self, portfolio: PortfolioView) -> &mut Self {
+        self.portfolio = portfolio;
+        self
+    }
+
+    pub fn with_rejection_queue(&mut self, rejections: RejectionQueue) -> &mut Self {
+        self.rejections = rejections;
+        self
+    }
+
+    pub fn with_execution_queue(&mut self, executions: ExecutionQueue) -> &mut Self {
+        self.executions = executions;
+        self
+    }
+
+    pub fn with_watchdog(
+        &mut self,
+        heartbeats: TaskHeartbeats,
+        config: WatchdogConfig,
+    ) -> &mut Self {
+        self.heartbeats = heartbeats;
+        self.watchdog = config;
+        self
+    }
+
+    /// Captures feature/prediction pairs while `record_golden` sessions remain.
+    pub fn with_golden_recorder(&mut self, golden: GoldenRecorder) -> &mut Self {
+        self.golden = golden;
+        self
+    }
+
+    /// Scheduler task on which `model_config.json` is re-read.
+    pub fn with_reload_task_id(&mut self, task_id: u64) -> &mut Self {
+        self.reload_task_id = Some(task_id);
+        self
+    }
+
+    pub fn with_idle_inst_ttl(&mut self, ttl_sec: u64) -> &mut Self {
+        self.idle_inst_ttl = Duration::from_secs(ttl_sec);
+        self
+    }
+
     pub fn model_data_init(&mut self) -> InfraResult<()> {
         info!("Starting model data initialization...");
 
-        let configs = load_model_config()
-            .map_err(|e| InfraError::Msg(format!("Failed to load model config: {}", e)))?;
+        self.load_models(None)?;
 
-        for cfg in configs {
-            info!(
-                "Initialized model: ModelID={} AccountID={}, Port={}",
-                cfg.model_id,
-                cfg.account_id,
-                cfg.port,
-            );
+        self.feature_config = load_feature_config()?;
+        info!(
+            "Feature config: exclude_cols={:?}, prefixes={:?}, zscore_window={}",
+            self.feature_config.exclude_cols,
+            self.feature_config.prefixes,
+            self.feature_config.zscore_window,
+        );
 
-            self.model_config.insert(cfg.model_id.clone(), cfg);
+        self.ensemble = load_ensemble_config()?;
+        info!(
+            "Ensemble config: method={:?}, overrides={:?}",
+            self.ensemble.method, self.ensemble.overrides
+        );
+
+        if let Err(e) = self.feature_store.load() {
+            warn!("Failed to load feature store: {:?}", e);
         }
+        self.warm_start_candles();
+        self.column_pins = ColumnPins::load()?;
 
         Ok(())
     }
 
+    /// `mcp_mediator` with the command appended to the command audit.
+    pub async fn audited_mediator(&mut self, alt_tensor: &AltTensor) -> InfraResult<()> {
+        let before = weight_snapshot(&self.target_weights);
+        let result = self.mcp_mediator(alt_tensor).await;
+        let after = weight_snapshot(&self.target_weights);
+        self.command_audit.record_command(
+            &alt_tensor.metadata,
+            alt_tensor.timestamp,
+            &result,
+            &before,
+            &after,
+        );
+        result
+    }
+
+    /// Audits a command refused before it reached `mcp_mediator`.
+    pub fn audit_unchanged(&self, alt_tensor: &AltTensor, result: &InfraResult<()>) {
+        let unchanged = HashMap::new();
+        self.command_audit.record_command(
+            &alt_tensor.metadata,
+            alt_tensor.timestamp,
+            result,
+            &unchanged,
+            &unchanged,
+        );
+    }
+
     pub async fn mcp_mediator(&mut self, alt_tensor: &AltTensor) -> InfraResult<()> {
+        // The host lost the full frame a delta referred to; resend full frames
+        if alt_tensor.metadata.get("error").map(|e| e.as_str()) == Some(DELTA_BASELINE_ERROR)
+            && let Some(model_id) = alt_tensor.metadata.get("model_id")
+        {
+            self.delta.reset(model_id);
+        }
         check_alt_tensor_error(alt_tensor)?;
+        self.check_contract(Direction::Prediction, alt_tensor)?;
+
+        // In-flight predictions of models removed by a reload are drained here
+        if let Some(model_id) = alt_tensor.metadata.get("model_id")
+            && !model_id.is_empty()
+            && !self.model_config.contains_key(model_id)
+        {
+            warn!("[Models] Dropping prediction from removed model={}", model_id);
+            return Ok(());
+        }
         let cmd = alt_tensor
             .metadata
             .get("cmd")
@@ -86,45 +407,225 @@ impl McpServer {
 
         match cmd {
             "adjust_position" => {
-                let inst = alt_tensor
+                let model_id = alt_tensor
                     .metadata
-                    .get("inst")
+                    .get("model_id")
                     .cloned()
-                    .unwrap_or_else(|| "DOGE_USDT_PERP".to_string());
+                    .unwrap_or_default();
 
-                let new_target = alt_tensor
+                let inst = match alt_tensor.metadata.get("inst") {
+                    Some(inst) => inst.clone(),
+                    None => self.default_inst(&model_id).ok_or_else(|| {
+                        InfraError::Msg(format!(
+                            "MCP adjust_position: inst missing, model={} trades {:?}",
+                            model_id,
+                            self.routing.insts_for(&model_id)
+                        ))
+                    })?,
+                };
+
+                let raw_target = alt_tensor
                     .metadata
                     .get("target_position")
-                    .or_else(|| alt_tensor.metadata.get("pos_weight"))
-                    .and_then(|s| s.parse::<f64>().ok())
-                    .unwrap_or(0.0);
+                    .or_else(|| alt_tensor.metadata.get("pos_weight"));
+                let new_target = match raw_target.and_then(|s| s.parse::<f64>().ok()) {
+                    Some(target) if target.is_finite() => target,
+                    _ => {
+                        let detail = format!("target_position {:?} not a number", raw_target);
+                        self.reject_target(&model_id, &inst, 0.0, "prediction_rejected", &detail);
+                        return Err(InfraError::Msg(format!(
+                            "MCP adjust_position: model={}, inst={} rejected, {}",
+                            model_id, inst, detail
+                        )));
+                    },
+                };
+                let limit = PriceLimit::from_metadata(&model_id, &alt_tensor.metadata)
+                    .map_err(|e| InfraError::Msg(format!("MCP adjust_position: {:?}", e)))?;
+                let expires_at = match alt_tensor.metadata.get("expires_at") {
+                    Some(v) => {
+                        let now_ms = get_micros_timestamp() / 1000;
+                        let at = v.parse::<u64>().ok().filter(|at| *at > now_ms).ok_or_else(|| {
+                            InfraError::Msg(format!(
+                                "MCP adjust_position: expires_at {:?} not a future ms timestamp",
+                                v
+                            ))
+                        })?;
+                        Some(at)
+                    },
+                    None => None,
+                };
+                if let Some(cfg) = self.model_config.get(&model_id) {
+                    let now_ms = get_micros_timestamp() / 1000;
+                    let checked = cfg.check_prediction(
+                        &alt_tensor.metadata,
+                        new_target,
+                        self.price(&inst),
+                        now_ms,
+                    );
+                    if let Err(e) = checked {
+                        self.reject_target(&model_id, &inst, new_target, "prediction_rejected", &e);
+                        return Err(InfraError::Msg(format!(
+                            "MCP adjust_position: model={}, inst={} rejected, {}",
+                            model_id, inst, e
+                        )));
+                    }
+                }
 
-                let px_val = *self.px.entry(inst.clone()).or_insert(0.0);
+                if self.is_shadow(&model_id) {
+                    self.mark_shadow_models();
+                    self.shadow.set_target(&model_id, &inst, new_target);
+                    info!(
+                        "[Shadow] adjust_position: model={}, inst={}, target={} not traded",
+                        model_id, inst, new_target
+                    );
+                    return Ok(());
+                }
+
+                if self.fallback_models.contains(&model_id) {
+                    warn!(
+                        "MCP adjust_position: model={} in fallback, inst={} target {} ignored",
+                        model_id, inst, new_target
+                    );
+                    let detail = "model in fallback, targets ignored until it recovers";
+                    self.reject_target(&model_id, &inst, new_target, "model_fallback", detail);
+                    return Ok(());
+                }
 
                 let old = self
                     .target_weights
                     .get(&inst)
-                    .map(|v| *v)
-                    .unwrap_or((px_val, 0.0));
+                    .map(|v| *v);
+
+                let previous = self
+                    .model_targets
+                    .entry(model_id.clone())
+                    .or_default()
+                    .insert(inst.clone(), new_target)
+                    .unwrap_or(0.0);
+                // A time-boxed target reverts to the last open-ended one, also when it is
+                // replaced by another time-boxed target before expiring
+                let key = (model_id.clone(), inst.clone());
+                match expires_at {
+                    Some(at) => {
+                        let revert_to = self
+                            .target_expiry
+                            .get(&key)
+                            .map_or(previous, |(_, revert_to)| *revert_to);
+                        info!(
+                            "MCP adjust_position: model={}, inst={} expires at {}, then {}",
+                            model_id, inst, at, revert_to
+                        );
+                        self.target_expiry.insert(key, (at, revert_to));
+                    },
+                    None => {
+                        self.target_expiry.remove(&key);
+                    },
+                }
+                // The latest command on the instrument sets its limit; one without a limit
+                // only clears the model's own
+                match limit {
+                    Some(limit) => {
+                        info!(
+                            "MCP adjust_position: model={}, inst={}, {:?}",
+                            model_id, inst, limit
+                        );
+                        self.price_limits.insert(inst.clone(), limit);
+                    },
+                    None => {
+                        self.price_limits
+                            .remove_if(&inst, |_, l| l.model_id == model_id);
+                    },
+                }
+
+                // Keep recording raw targets so the first command after `resume` republishes them
+                if self.control.is_flattened() {
+                    warn!(
+                        "MCP adjust_position: targets flattened by operator, model={} inst={} held",
+                        model_id, inst
+                    );
+                    let detail = "targets flattened by operator, held until resume";
+                    self.reject_target(&model_id, &inst, new_target, "targets_flattened", detail);
+                    return Ok(());
+                }
+                self.publish_target_weights();
+
+                let new = self
+                    .target_weights
+                    .get(&inst)
+                    .map(|v| *v);
+
+                info!(
+                    "MCP adjust_position: model={}, inst={}, raw={}, old={:?}, new={:?}",
+                    model_id, inst, new_target, old, new
+                );
+            },
+            "set_interval" => {
+                let scheduler = alt_tensor
+                    .metadata
+                    .get("scheduler")
+                    .map(|x| x.as_str())
+                    .unwrap_or_default();
 
-                let new = (px_val, new_target);
+                let interval_sec = alt_tensor
+                    .metadata
+                    .get("interval_sec")
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .ok_or_else(|| {
+                        InfraError::Msg("MCP set_interval: interval_sec missing or invalid".into())
+                    })?;
 
-                self.target_weights.insert(inst.clone(), new);
+                self.intervals.set_interval(scheduler, interval_sec)?;
 
                 info!(
-                    "MCP adjust_position: inst={}, old={:?}, new={:?}",
-                    inst, old, new
+                    "MCP set_interval: scheduler={}, interval_sec={}",
+                    scheduler, interval_sec
                 );
             },
             "risk_alert" => {
-                todo!()
+                let now_ms = get_micros_timestamp() / 1000;
+                let alert = RiskAlert::from_metadata(&alt_tensor.metadata, now_ms)?;
+                self.risk_alerts.raise(alert);
+
+                if !self.control.is_flattened() {
+                    self.publish_target_weights();
+                }
             },
             "fallback" => {
-                todo!()
+                let model_id = alt_tensor
+                    .metadata
+                    .get("model_id")
+                    .cloned()
+                    .unwrap_or_default();
+                let mode = alt_tensor
+                    .metadata
+                    .get("mode")
+                    .map(|x| x.as_str())
+                    .unwrap_or("hold");
+                let reason = alt_tensor
+                    .metadata
+                    .get("reason")
+                    .map(|x| x.as_str())
+                    .unwrap_or_default();
+
+                self.apply_fallback(&model_id, mode, reason)?;
             },
             "query" => {
-                todo!()
+                let model_id = alt_tensor
+                    .metadata
+                    .get("model_id")
+                    .cloned()
+                    .unwrap_or_default();
+
+                match alt_tensor.metadata.get("scope").map(|x| x.as_str()) {
+                    Some("portfolio") => self.send_portfolio_response(&model_id).await?,
+                    Some("protocol") => self.send_protocol_response(&model_id).await?,
+                    _ => self.send_query_response(&model_id).await?,
+                }
             },
+            // Handled by `RiskManager`
+            "kill" | "resume" => {},
+            // Handled by `AccountManager`
+            "cancel_order" | "amend_order" | "cancel_all" => {},
             "noop" => {
                 info!("MCP mediator: noop for timestamp={}", alt_tensor.timestamp);
             },
@@ -136,210 +637,2169 @@ impl McpServer {
         Ok(())
     }
 
-    pub async fn periodic_send_data_to_model(&mut self) -> InfraResult<()> {
-        let oi_data = self.fetch_oi().await?;
-        let df = self.process_oi(oi_data)?;
-        self.send_data_to_model(&df).await?;
+    /// MCP `fallback`: reverts a self-reported degraded model to a safe state.
+    fn apply_fallback(&mut self, model_id: &str, mode: &str, reason: &str) -> InfraResult<()> {
+        if model_id.is_empty() {
+            return Err(InfraError::Msg("MCP fallback: model_id missing".into()));
+        }
+
+        match mode {
+            "hold" => {},
+            "weights" => {
+                let weights = self
+                    .model_config
+                    .get(model_id)
+                    .map(|cfg| cfg.fallback_weights.clone())
+                    .filter(|weights| !weights.is_empty())
+                    .ok_or_else(|| {
+                        InfraError::Msg(format!(
+                            "MCP fallback: no fallback_weights configured for model={}",
+                            model_id
+                        ))
+                    })?;
+
+                // The fallback replaces the model's targets, expiring ones included
+                self.target_expiry.retain(|(id, _), _| id != model_id);
+                if self.is_shadow(model_id) {
+                    // Shadow targets go to the shadow book only, never into the live ones
+                    self.mark_shadow_models();
+                    for (inst, weight) in weights.iter() {
+                        self.shadow.set_target(model_id, inst, *weight);
+                    }
+                } else {
+                    self.model_targets.insert(model_id.to_string(), weights);
+                    if !self.control.is_flattened() {
+                        self.publish_target_weights();
+                    }
+                }
+            },
+            "off" => {
+                if self.fallback_models.remove(model_id) {
+                    info!("[Fallback] Model {} resumed: {}", model_id, reason);
+                }
+                return Ok(());
+            },
+            other => {
+                return Err(InfraError::Msg(format!("MCP fallback: unknown mode {}", other)));
+            },
+        }
 
+        self.fallback_models.insert(model_id.to_string());
+        warn!(
+            "[Fallback] Model {} degraded ({}), mode={}, targets={:?}",
+            model_id,
+            reason,
+            mode,
+            self.model_targets.get(model_id)
+        );
         Ok(())
     }
 
-    async fn fetch_oi(&mut self) -> InfraResult<Vec<OpenInterest>> {
-        let oi = self.binance_cm_cli.get_open_interest_history(
-            "DOGE_USDT_PERP",
-            "5m",
-            InstrumentType::Perpetual,
-            None,
-            None,
-            None,
-        ).await?;
+    /// Loads `model_config.json`.
+    fn load_models(&mut self, previous: Option<&HashMap<String, ModelConfig>>) -> InfraResult<()> {
+        let configs = load_model_config()
+            .map_err(|e| InfraError::Msg(format!("Failed to load model config: {}", e)))?;
 
-        Ok(oi)
+        self.model_config.clear();
+        for mut cfg in configs {
+            if let Some(previous) = previous
+                && !self.port_bound(cfg.port)
+            {
+                match previous.get(&cfg.model_id).filter(|old| self.port_bound(old.port)) {
+                    Some(old) => {
+                        warn!(
+                            "[Models] model={} port {} has no ZeroMQ task, keeping port {} \
+                             until a restart",
+                            cfg.model_id, cfg.port, old.port
+                        );
+                        cfg.port = old.port;
+                    },
+                    None => {
+                        warn!(
+                            "[Models] model={} not loaded: no ZeroMQ task on port {}, restart \
+                             to bind it",
+                            cfg.model_id, cfg.port
+                        );
+                        continue;
+                    },
+                }
+            }
+
+            info!(
+                "Initialized model: ModelID={} AccountID={}, Port={}, TargetLeverage={:?}",
+                cfg.model_id,
+                cfg.account_id,
+                cfg.port,
+                cfg.target_leverage,
+            );
+
+            self.model_config.insert(cfg.model_id.clone(), cfg);
+        }
+
+        match load_universe() {
+            Ok(universe) => self.universe = universe,
+            Err(e) => warn!("[Models] Keeping universe {:?}: {:?}", self.universe, e),
+        }
+        self.routing = RoutingTable::from_configs(&self.model_config, &self.universe);
+        self.command_auth.load(self.model_config.values());
+        self.last_model_send
+            .retain(|model_id, _| self.model_config.contains_key(model_id));
+        for inst in self.routing.insts() {
+            info!("Model routing: inst={} -> models={:?}", inst, self.routing.models_for(&inst));
+        }
+
+        Ok(())
     }
 
-    fn process_oi(&mut self, oi_data: Vec<OpenInterest>) -> InfraResult<DataFrame> {
-        let oi_lf = oi_to_lf(oi_data)
-            .map_err(|e| InfraError::Msg(format!("Polars oi_to_lf err: {:?}", e)))?;
+    fn port_bound(&self, port: u64) -> bool {
+        self.find_alt_handle(&AltTaskType::ModelPreds(port), port).is_some()
+    }
 
-        let converted_oi_lf = convert_all_to_float64_except_timestamp(oi_lf)?;
+    /// Re-reads `model_config.json` and applies the diff.
+    pub fn reload_models(&mut self) -> InfraResult<()> {
+        let old_configs = self.model_config.clone();
+        self.load_models(Some(&old_configs))?;
 
-        let schema = collect_schema_safe(&converted_oi_lf)?;
-        let mut zscore_exprs = Vec::new();
+        let old_ids: HashSet<String> = old_configs.keys().cloned().collect();
+        let new_ids: HashSet<String> = self.model_config.keys().cloned().collect();
 
-        let exclude_cols = vec![
-            "timestamp",
-            "funding_funding_interval_hours",
-            "funding_last_funding_rate",
-            "premium_funding_spread",
-            "adjusted_funding_rate",
-            "funding_premium",
-            "premium_open",
-        ];
+        for model_id in new_ids.difference(&old_ids) {
+            info!("[Models] New model detected: {}", model_id);
+        }
 
-        for field in schema.iter_fields() {
-            let name = field.name();
-            let dtype = field.dtype();
+        for model_id in new_ids.intersection(&old_ids) {
+            if old_configs.get(model_id) != self.model_config.get(model_id) {
+                info!("[Models] Model updated: {} (diff detected)", model_id);
+            }
+        }
+
+        let mut retargeted = false;
+        for model_id in old_ids.difference(&new_ids) {
+            info!("[Models] Model removed from config: {}", model_id);
+            self.shadow.forget(model_id);
+            retargeted |= self.model_targets.remove(model_id).is_some();
+            self.target_expiry.retain(|(id, _), _| id != model_id);
+            self.last_preds.remove(model_id);
+            self.model_heartbeats.forget(model_id);
+            self.fallback_models.remove(model_id);
+            self.decay.remove(model_id);
+        }
 
-            if exclude_cols.contains(&name.as_str()) {
+        // A model moved to shadow keeps trading only on paper; one that went live starts over
+        for (model_id, cfg) in self.model_config.iter() {
+            if cfg.mode != ModelMode::Shadow {
+                self.shadow.forget(model_id);
                 continue;
             }
-
-            if *dtype == DataType::Float64 {
-                zscore_exprs.push(z_score_expr(name, 20));
+            if self.model_targets.remove(model_id).is_some() {
+                info!("[Shadow] Model {} now in shadow mode, live targets dropped", model_id);
+                retargeted = true;
             }
+            self.target_expiry.retain(|(id, _), _| id != model_id);
+            self.price_limits.retain(|_, limit| limit.model_id != *model_id);
         }
 
-        let z_score_oi_df = converted_oi_lf
-            .with_columns(zscore_exprs)
-            .drop_nulls(None)
-            .collect()?;
-
-        Ok(z_score_oi_df)
-    }
+        // Removed models no longer contribute to the consolidated weights
+        if retargeted && !self.control.is_flattened() {
+            self.publish_target_weights();
+        }
 
-    async fn send_data_to_model(&self, data: &DataFrame) -> InfraResult<()> {
-        for (model_id, _cfg) in &self.model_config {
-            let inst = "DOGE_USDT_PERP".to_string();
-            let px = self.px.get(&inst).copied().unwrap_or(0.0);
-            
-            if px == 0.0 {
-                warn!("Price for {} not available yet, using 0.0. Waiting for data...", inst);
-                // 可以选择跳过这次发送，等待价格数据
+        let live_ports: HashSet<u64> = self.model_config.values().map(|c| c.port).collect();
+        for port in old_configs.values().map(|c| c.port) {
+            if live_ports.contains(&port) {
                 continue;
             }
 
-            let ts = get_micros_timestamp();
-            let port = 5001;
+            let dropped = self.dead_letters.drop_port(port);
+            if dropped > 0 {
+                info!("[DLQ] Dropped {} tensor(s) for retired port {}", dropped, port);
+            }
+        }
 
-            let pos_weight = self
-                .target_weights
-                .get(&inst)
-                .map(|v| v.1)
-                .unwrap_or(0.0);
+        Ok(())
+    }
 
-            let tensor = df_to_tensor(
-                data,
-                model_id.clone(),
-                px,
-                pos_weight,
-                ts,
-            )?;
+    /// Validates tensor metadata against the protocol contract.
+    fn check_contract(&self, direction: Direction, alt_tensor: &AltTensor) -> InfraResult<()> {
+        let Err(violations) = validate_metadata(direction, &alt_tensor.metadata) else {
+            return Ok(());
+        };
 
-            println!("tensor: {:?}", tensor);
+        let model_id = alt_tensor
+            .metadata
+            .get("model_id")
+            .map(|x| x.as_str())
+            .unwrap_or_default();
+        let strict = self
+            .model_config
+            .get(model_id)
+            .is_some_and(|cfg| cfg.strict_contract);
 
-            if let Some(handle) = self.find_alt_handle(&AltTaskType::ModelPreds(port), port) {
-                let cmd = TaskCommand::FeatInput(tensor);
-                handle.send_command(cmd, None).await?;
-            } else {
-                error!("No model handle found for Model port: {}", port);
-            }
+        if strict {
+            return Err(InfraError::Msg(format!(
+                "Contract violation ({:?}) model={}: {:?}",
+                direction, model_id, violations
+            )));
         }
 
+        warn!(
+            "Contract violation ({:?}) model={}: {:?}",
+            direction, model_id, violations
+        );
         Ok(())
     }
 
-    pub async fn connect_channel(&self, channel: &WsChannel) -> InfraResult<()> {
-        if let Some(handle) = self.find_ws_handle(channel, 1) {
-            info!("[BinanceStrategy] Sending connect to {:?}", handle);
+    /// Pairs a prediction with the feature tensor it answers and writes the session.
+    pub async fn record_golden(&mut self, prediction: &AltTensor) {
+        let target_leverage = prediction
+            .metadata
+            .get("model_id")
+            .and_then(|id| self.model_config.get(id))
+            .and_then(|cfg| cfg.target_leverage);
+        let Some(mut session) = self.golden.take_session(prediction, target_leverage) else {
+            return;
+        };
 
-            // Step 1: Request connection URL
-            let ws_url = self.binance_um_cli.get_public_connect_msg(channel).await?;
-            let (tx, rx) = oneshot::channel();
-            let cmd = TaskCommand::WsConnect {
-                msg: ws_url,
-                ack: AckHandle::new(tx),
-            };
-            handle
-                .send_command(cmd, Some((AckStatus::WsConnect, rx)))
-                .await?;
-
-            let ws_msg = self
-                .binance_um_cli
-                .get_public_sub_msg(channel, Some(&["DOGE_USDT_PERP".into()]))
-                .await?;
-
-            let cmd = TaskCommand::WsMessage {
-                msg: ws_msg,
-                ack: AckHandle::none(), // no need to wait for ack
-            };
-            handle.send_command(cmd, None).await?;
-        } else {
-            warn!(
-                "[BinanceStrategy] No handle found for channel {:?}",
-                channel
-            );
+        session.outcome = replay_prediction(&session).await;
+        if let Err(e) = self.golden.write(&session) {
+            warn!("[Golden] Failed to record session: {:?}", e);
         }
-
-        Ok(())
     }
-}
 
-pub fn df_to_tensor(
-    df: &DataFrame,
-    model_id: String,
-    price: f64,
-    weight: f64,
-    timestamp: u64,
-) -> InfraResult<AltTensor> {
-    if df.height() == 0 {
-        return Err(InfraError::Msg("df is empty".into()));
+    /// Checks a reply against the model's heartbeats.
+    pub fn accept_reply(&mut self, meta: &HashMap<String, String>) -> bool {
+        let Some(model_id) = meta
+            .get("model_id")
+            .filter(|id| self.model_config.contains_key(*id))
+        else {
+            return true;
+        };
+
+        let now_ms = get_micros_timestamp() / 1000;
+        let ts = meta
+            .get("ts")
+            .and_then(|ts| ts.parse::<u64>().ok())
+            .unwrap_or(now_ms);
+        match self.model_heartbeats.reply(model_id, ts, now_ms) {
+            ModelReply::Stale => {
+                warn!(
+                    "[Models] Dropping stale {} of unresponsive model={}, ts={}",
+                    meta.get("cmd").map(|x| x.as_str()).unwrap_or("noop"),
+                    model_id,
+                    ts
+                );
+                false
+            },
+            ModelReply::Recovered => {
+                info!("[Models] Model {} responding again", model_id);
+                true
+            },
+            ModelReply::Fresh => true,
+        }
     }
 
-    let last_idx = df.height() - 1;
+    /// Marks the model as connected and restores the full weight of its faded targets.
+    pub fn record_model_reply(&mut self, model_id: &str) {
+        if !self.model_config.contains_key(model_id) {
+            return;
+        }
 
-    let row = df
-        .get_row(last_idx)
-        .map_err(|_| InfraError::Msg("failed to get row".into()))?;
+        self.last_preds.insert(model_id.to_string(), Instant::now());
+        if self.decay.remove(model_id).is_some() {
+            info!("[Decay] Model {} reconnected, targets restored", model_id);
+            if !self.control.is_flattened() {
+                self.publish_target_weights();
+            }
+        }
+    }
 
-    let col_names: Vec<String> = df
-        .get_columns()
-        .iter()
-        .map(|s| s.name().to_string())
-        .collect();
+    /// Pings every `due` model; one that missed `max_missed_heartbeats` cycles is alerted on.
+    async fn send_heartbeats(&mut self, due: &HashSet<String>) {
+        let now_ms = get_micros_timestamp() / 1000;
+        let pings: Vec<(String, u64, u32)> = due
+            .iter()
+            .filter_map(|model_id| {
+                let cfg = self.model_config.get(model_id)?;
+                let max_missed = cfg
+                    .max_missed_heartbeats
+                    .unwrap_or(DEFAULT_MAX_MISSED_HEARTBEATS);
+                Some((model_id.clone(), cfg.port, max_missed))
+            })
+            .collect();
 
-    let mut data = Vec::with_capacity(row.0.len());
-    for val in &row.0 {
-        let f = match val {
-            AnyValue::Float32(v) => *v,
-            AnyValue::Float64(v) => *v as f32,
-            AnyValue::Int64(v) => *v as f32,
-            AnyValue::Int32(v) => *v as f32,
-            AnyValue::UInt64(v) => *v as f32,
-            AnyValue::UInt32(v) => *v as f32,
-            _ => {
-                return Err(InfraError::Msg(format!(
-                    "unsupported type: {} ({:?})",
-                    val,
-                    val.dtype()
-                )));
+        for (model_id, port, max_missed) in pings {
+            if self.model_heartbeats.ping(&model_id, now_ms, max_missed) {
+                error!(
+                    "[Models] ALERT: model={} on port {} missed {} heartbeats, ignoring its \
+                     stale predictions",
+                    model_id, port, max_missed
+                );
             }
-        };
-        data.push(f);
+
+            let tensor = heartbeat_to_tensor(&model_id, now_ms);
+            if let Err(e) = self.try_send_to_model(port, tensor).await {
+                warn!("[Models] Heartbeat to model={} failed: {}", model_id, e);
+            }
+        }
     }
 
-    let shape = vec![data.len()];
+    /// Fades the targets of silent or unresponsive models over their `decay_sec`.
+    pub fn decay_silent_models(&mut self) {
+        let stall_after = self.watchdog.model_stall_sec as f64;
+        let now_ms = get_micros_timestamp() / 1000;
+        let mut changed = false;
 
-    let mut metadata = HashMap::new();
-    metadata.insert("model_id".to_string(), model_id);
-    metadata.insert("price".to_string(), price.to_string());
-    metadata.insert("pos_weight".to_string(), weight.to_string());
-    metadata.insert("col_names".to_string(), serde_json::to_string(&col_names)?);
+        for (model_id, last_pred) in self.last_preds.iter() {
+            let Some(decay_sec) = self.model_config.get(model_id).and_then(|c| c.decay_sec) else {
+                continue;
+            };
 
-    Ok(AltTensor {
-        timestamp,
-        data,
-        shape,
-        metadata,
-    })
-}
+            let unresponsive = self
+                .model_heartbeats
+                .unresponsive_for(model_id, now_ms)
+                .map_or(0.0, |d| d.as_secs_f64());
+            let overdue = (last_pred.elapsed().as_secs_f64() - stall_after).max(unresponsive);
+            if overdue <= 0.0 {
+                continue;
+            }
 
-pub fn check_alt_tensor_error(alt_tensor: &AltTensor) -> InfraResult<()> {
-    if let Some(err_msg) = alt_tensor.metadata.get("error") {
-        warn!(
-            "Skipping AltTensor because Python side sent error: {} | timestamp={}",
-            err_msg, alt_tensor.timestamp
-        );
-        return Err(InfraError::Msg(format!("Python model error: {}", err_msg)));
+            let factor = (1.0 - overdue / decay_sec.max(1) as f64).max(0.0);
+            let prev = self.decay.insert(model_id.clone(), factor);
+            if prev.is_none_or(|p| (p - factor).abs() >= 0.01 || (factor == 0.0 && p > 0.0)) {
+                if prev.is_none() {
+                    warn!("[Decay] Model {} silent, fading targets over {}s", model_id, decay_sec);
+                }
+                changed = true;
+            } else if let Some(p) = prev {
+                // Keep the last published factor until the change is worth a republish
+                self.decay.insert(model_id.clone(), p);
+            }
+        }
+
+        if changed && !self.control.is_flattened() {
+            self.publish_target_weights();
+        }
     }
-    Ok(())
-}
\ No newline at end of file
+
+    /// Restores the targets of time-boxed `adjust_position` commands that expired.
+    pub fn expire_targets(&mut self) {
+        let now_ms = get_micros_timestamp() / 1000;
+        let expired: Vec<((String, String), f64)> = self
+            .target_expiry
+            .iter()
+            .filter(|(_, (at, _))| *at <= now_ms)
+            .map(|(key, (_, revert_to))| (key.clone(), *revert_to))
+            .collect();
+        if expired.is_empty() {
+            return;
+        }
+
+        for ((model_id, inst), revert_to) in expired {
+            self.target_expiry.remove(&(model_id.clone(), inst.clone()));
+            info!(
+                "MCP adjust_position expired: model={}, inst={}, target back to {}",
+                model_id, inst, revert_to
+            );
+            if self.is_shadow(&model_id) {
+                self.shadow.set_target(&model_id, &inst, revert_to);
+                continue;
+            }
+            self.model_targets
+                .entry(model_id)
+                .or_default()
+                .insert(inst, revert_to);
+        }
+
+        if !self.control.is_flattened() {
+            self.publish_target_weights();
+        }
+    }
+
+    /// Samples the model contributions into the attribution history.
+    pub fn record_attribution(&mut self) {
+        if !interval_elapsed(&mut self.last_attribution, ATTRIBUTION_INTERVAL) {
+            return;
+        }
+
+        let timestamp = get_micros_timestamp() / 1000;
+        let portfolio = self.portfolio.read();
+        let row = |inst: &str, source: &str, target: f64| AttributionRow {
+            timestamp,
+            inst: inst.to_string(),
+            source: source.to_string(),
+            target,
+            executed: portfolio
+                .accounts
+                .iter()
+                .map(|(id, acc)| (id.clone(), acc.acc_weights.get(inst).copied().unwrap_or(0.0)))
+                .collect(),
+        };
+
+        let mut sample = Vec::new();
+        for (model_id, targets) in self.model_targets.iter() {
+            let factor = self.decay.get(model_id).copied().unwrap_or(1.0);
+            let scale = model_scale(targets, self.model_config.get(model_id)) * factor;
+            for (inst, weight) in targets.iter() {
+                sample.push(row(inst, model_id, weight * scale));
+            }
+        }
+        for (inst, (_, weight)) in portfolio.targets.iter() {
+            sample.push(row(inst, CONSOLIDATED_SOURCE, *weight));
+        }
+        sample.sort_by(|a, b| (&a.inst, &a.source).cmp(&(&b.inst, &b.source)));
+
+        self.attribution.record(sample);
+    }
+
+    fn is_shadow(&self, model_id: &str) -> bool {
+        self.model_config
+            .get(model_id)
+            .is_some_and(|cfg| cfg.mode == ModelMode::Shadow)
+    }
+
+    /// Marks the shadow models' targets to the local prices.
+    pub fn mark_shadow_models(&mut self) {
+        let market = &self.feature_config.price_market;
+        let px = &self.px;
+        self.shadow.mark(&self.model_config, |inst| {
+            px.get(&(market.clone(), inst.to_string()))
+                .copied()
+                .filter(|px| *px > 0.0)
+        });
+
+        if interval_elapsed(&mut self.last_shadow_report, SHADOW_REPORT_INTERVAL)
+            && let Err(e) = self.shadow.flush(&self.model_config)
+        {
+            warn!("[Shadow] Report failed: {:?}", e);
+        }
+    }
+
+    /// Republishes the targets once risk alerts lapse.
+    pub fn expire_risk_alerts(&mut self) {
+        let now_ms = get_micros_timestamp() / 1000;
+        if self.risk_alerts.expire(now_ms) && !self.control.is_flattened() {
+            self.publish_target_weights();
+        }
+    }
+
+    /// Zeroes the targets of instruments entering a risk-off window and restores them when it ends.
+    pub fn apply_risk_off_windows(&mut self) {
+        let risk_off = self.risk.get().risk_off_insts(get_micros_timestamp() / 1000);
+        if risk_off == self.risk_off {
+            return;
+        }
+
+        for inst in risk_off.difference(&self.risk_off) {
+            warn!("[Risk] {} entering risk-off window, target held at zero", inst);
+        }
+        for inst in self.risk_off.difference(&risk_off) {
+            info!("[Risk] {} leaving risk-off window, target restored", inst);
+        }
+        self.risk_off = risk_off;
+
+        if !self.control.is_flattened() {
+            self.publish_target_weights();
+        }
+    }
+
+    /// Picks up an edited `risk_config.json` and re-applies the caps to the live targets.
+    pub fn reload_risk_limits(&mut self) {
+        if self.risk.reload_if_changed() && !self.control.is_flattened() {
+            self.publish_target_weights();
+        }
+    }
+
+    fn publish_target_weights(&self) {
+        let mut combined = consolidate_weights(
+            &self.model_targets,
+            &self.model_config,
+            &self.decay,
+            &self.ensemble,
+        );
+
+        // Zeroed first so the book caps are not spent on instruments in a risk-off window
+        for inst in self.risk_off.iter() {
+            if let Some(weight) = combined.get_mut(inst) {
+                *weight = 0.0;
+            }
+        }
+
+        // Risk limits on the book, applied after leverage scaling
+        self.risk.get().clamp_exposure("Consolidated", &mut combined);
+        for (inst, weight) in combined.iter_mut() {
+            *weight *= self.risk_alerts.scale(inst);
+        }
+
+        for (inst, weight) in combined.iter() {
+            let px_val = self.price(inst).unwrap_or(0.0);
+            self.target_weights.insert(inst.clone(), (px_val, *weight));
+        }
+
+        self.target_weights
+            .retain(|inst, _| combined.contains_key(inst));
+        self.publish_portfolio();
+    }
+
+    /// Publishes the targets with the prices they are sized at to the portfolio view.
+    pub fn publish_portfolio(&self) {
+        let prices = self
+            .target_weights
+            .iter()
+            .filter_map(|entry| Some((entry.key().clone(), self.price(entry.key())?)))
+            .collect();
+        self.portfolio
+            .publish_targets(&self.target_weights, Some(prices));
+    }
+
+    /// Models whose features are due this tick.
+    pub fn due_models(&mut self, shared_due: bool) -> HashSet<String> {
+        let mut due = HashSet::new();
+        for (model_id, cfg) in self.model_config.iter() {
+            let elapsed = match cfg.interval_sec {
+                Some(sec) => interval_elapsed(
+                    self.last_model_send.entry(model_id.clone()).or_default(),
+                    Duration::from_secs(sec),
+                ),
+                None => shared_due,
+            };
+            if elapsed {
+                due.insert(model_id.clone());
+            }
+        }
+        due
+    }
+
+    /// Builds and sends features for the instruments of the `due` models only.
+    pub async fn periodic_send_data_to_model(&mut self, due: &HashSet<String>) -> InfraResult<()> {
+        for port in self.dead_letters.ports() {
+            self.retry_dead_letters(port).await;
+        }
+        self.send_rejection_feedback().await?;
+        self.send_execution_reports().await?;
+        self.send_heartbeats(due).await;
+
+        for inst in self.routing.insts() {
+            if !self.routing.models_for(&inst).iter().any(|id| due.contains(id)) {
+                continue;
+            }
+            if let Err(e) = self.build_and_send_features(&inst, due).await {
+                warn!("Failed to send features for {}: {:?}", inst, e);
+            }
+        }
+
+        if let Err(e) = self.persist_candles() {
+            warn!("Failed to persist candle history: {:?}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Restores the candle closes persisted before a restart.
+    fn warm_start_candles(&mut self) {
+        let since = (get_micros_timestamp() / 1000)
+            .saturating_sub(MAX_CANDLE_HISTORY as u64 * KLINE_PERIOD_MS);
+
+        for inst in self.feature_store.history_insts(SOURCE_KLINE) {
+            let key = history_key(SOURCE_KLINE, &inst);
+            let closes = self.feature_store.column_since(&key, CANDLE_CLOSE_COL, since);
+            if closes.is_empty() {
+                continue;
+            }
+
+            info!("[Data] Warm start: restored {} candles for {}", closes.len(), inst);
+            let key = (self.feature_config.price_market.clone(), inst);
+            self.candles.insert(key, closes.into_iter().collect());
+        }
+    }
+
+    /// Appends the new or updated candles to the feature store.
+    fn persist_candles(&mut self) -> InfraResult<()> {
+        for ((market, inst), closes) in self.candles.iter() {
+            if *market != self.feature_config.price_market {
+                continue;
+            }
+            let key = history_key(SOURCE_KLINE, inst);
+            let since = self.feature_store.last_timestamp(&key).unwrap_or_default();
+            let (ts, px): (Vec<u64>, Vec<f64>) =
+                closes.iter().filter(|(ts, _)| *ts >= since).copied().unzip();
+            if ts.is_empty() {
+                continue;
+            }
+
+            let df = df!("timestamp" => ts, CANDLE_CLOSE_COL => px)?;
+            self.feature_store.upsert_frame(&key, &df)?;
+            self.feature_store.truncate(&key, MAX_CANDLE_HISTORY);
+            self.feature_store.persist(&key)?;
+        }
+
+        Ok(())
+    }
+
+    async fn build_and_send_features(
+        &mut self,
+        inst: &str,
+        due: &HashSet<String>,
+    ) -> InfraResult<()> {
+        let oi_data = self.fetch_oi(inst).await?;
+        let df = self.process_oi(inst, oi_data)?;
+        let df = self.attach_benchmark_feats(df, inst)?;
+        let df = self.attach_flow_feats(df, inst)?;
+        let df = self.attach_health_feats(df, inst)?;
+        let df = self.attach_term_feats(df, inst).await?;
+
+        // One row per tick: the store only ever holds what was known at that time
+        self.feature_store.upsert_frame(inst, &df)?;
+        if let Err(e) = self.feature_store.persist(inst) {
+            warn!("Failed to persist features for {}: {:?}", inst, e);
+        }
+
+        // Live reads the latest row; replays pass their own clock
+        let row = self.feature_store.as_of_frame(inst, u64::MAX)?;
+        self.publish_latest_features(inst, &row);
+        self.send_data_to_model(inst, &row, due).await?;
+
+        Ok(())
+    }
+
+    fn publish_latest_features(&self, inst: &str, row: &DataFrame) {
+        let columns: Vec<String> = row
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let ts = get_micros_timestamp();
+        match df_to_tensor(row, &columns, String::new(), 0.0, 0.0, ts) {
+            Ok(tensor) => {
+                let values = columns.into_iter().zip(tensor.data).collect();
+                self.latest_features
+                    .insert(inst.to_string(), FeatureRow { timestamp: ts, values });
+            },
+            Err(e) => warn!("[MCP] Failed to publish features of {}: {:?}", inst, e),
+        }
+    }
+
+    /// Runs the commands MCP stdio clients queued since the last tick.
+    pub async fn mediate_injected(&mut self) {
+        let commands: Vec<AltTensor> = match self.injected.lock() {
+            Ok(mut queue) => queue.drain(..).collect(),
+            Err(_) => return,
+        };
+
+        for command in commands.iter() {
+            // The transports authenticated the client; the model it acts for still needs
+            // the permission
+            let meta = &command.metadata;
+            let model_id = meta.get("model_id").map(|x| x.as_str()).unwrap_or_default();
+            let cmd = meta.get("cmd").map(|x| x.as_str()).unwrap_or("noop");
+            let permitted = self.command_auth.permit(model_id, cmd);
+            if let Err(e) = &permitted {
+                warn!("[Auth] Injected command: {:?}", e);
+                self.audit_unchanged(command, &permitted);
+                continue;
+            }
+
+            if let Err(e) = self.audited_mediator(command).await {
+                warn!("[MCP] Injected command failed: {:?}", e);
+            }
+        }
+    }
+
+    async fn fetch_oi(&mut self, inst: &str) -> InfraResult<Vec<OpenInterest>> {
+        // Page backwards from now until the configured lookback is covered
+        let lookback = self.feature_config.lookback(&self.oi_windows(inst));
+        let mut oi: Vec<OpenInterest> = Vec::new();
+        let mut end: Option<u64> = None;
+        while oi.len() < lookback {
+            let limit = (lookback - oi.len()).min(OI_PAGE_LIMIT as usize) as u32;
+            let page = self.binance_cm_cli.get_open_interest_history(
+                inst,
+                OI_PERIOD,
+                InstrumentType::Perpetual,
+                Some(limit),
+                None,
+                end,
+            ).await?;
+
+            let Some(first) = page.iter().map(|r| r.timestamp).min() else {
+                break;
+            };
+            let exhausted = page.len() < limit as usize;
+            oi = merge_oi(oi, page);
+            if exhausted {
+                break;
+            }
+            end = Some(first.saturating_sub(1));
+        }
+
+        let timestamps =
+            |rows: &[OpenInterest]| rows.iter().map(|r| r.timestamp).collect::<Vec<u64>>();
+        let known = self.oi_unfillable.get(inst).cloned().unwrap_or_default();
+        let mut backfilled = Vec::new();
+        for (from, to) in find_gaps(&timestamps(&oi), OI_PERIOD_MS) {
+            if known.contains(&(from, to)) {
+                continue;
+            }
+            warn!("[Data] OI gap for {}: {} -> {}, backfilling", inst, from, to);
+            match self.backfill_oi(inst, from, to).await {
+                Ok(rows) => {
+                    oi = merge_oi(oi, rows);
+                    backfilled.push((from, to));
+                },
+                Err(e) => warn!("[Data] OI backfill failed for {}: {:?}", inst, e),
+            };
+        }
+
+        // Whatever is still missing is marked by the `gap_bars` column. Ranges a backfill
+        // could not fill are remembered, so they are not requested on every tick.
+        let remaining = find_gaps(&timestamps(&oi), OI_PERIOD_MS);
+        let unfillable: Vec<(u64, u64)> = remaining
+            .iter()
+            .filter(|gap| backfilled.contains(gap))
+            .copied()
+            .collect();
+        if !unfillable.is_empty() {
+            warn!(
+                "[Data] {} OI gap(s) cannot be filled for {}: {:?}",
+                unfillable.len(),
+                inst,
+                unfillable
+            );
+        }
+        // Gaps that have scrolled out of the lookback are forgotten
+        let known = self.oi_unfillable.entry(inst.to_string()).or_default();
+        known.retain(|gap| remaining.contains(gap));
+        known.extend(unfillable);
+
+        Ok(oi)
+    }
+
+    /// Pages through `[from, to]` in chunks of the venue's row limit.
+    async fn backfill_oi(&self, inst: &str, from: u64, to: u64) -> InfraResult<Vec<OpenInterest>> {
+        let mut rows = Vec::new();
+        let mut start = from;
+        while start <= to {
+            let page = self.binance_cm_cli.get_open_interest_history(
+                inst,
+                OI_PERIOD,
+                InstrumentType::Perpetual,
+                Some(OI_PAGE_LIMIT),
+                Some(start),
+                Some(to),
+            ).await?;
+
+            let Some(last) = page.iter().map(|r| r.timestamp).max() else {
+                break;
+            };
+            rows.extend(page.into_iter().filter(|r| r.timestamp >= from && r.timestamp <= to));
+            // A page that does not move past `start` would be requested forever
+            let next = last + OI_PERIOD_MS;
+            if next <= start {
+                break;
+            }
+            start = next;
+        }
+
+        Ok(rows)
+    }
+
+    /// Builds the latest feature row.
+    fn process_oi(&mut self, inst: &str, oi_data: Vec<OpenInterest>) -> InfraResult<DataFrame> {
+        let oi_windows = self.oi_windows(inst);
+        let cfg = &self.feature_config;
+        if oi_data.len() < cfg.required_bars(&oi_windows) {
+            return Err(InfraError::Msg(format!(
+                "{} has {} OI rows, rolling features need {}",
+                inst,
+                oi_data.len(),
+                cfg.required_bars(&oi_windows)
+            )));
+        }
+
+        let prefix = cfg.prefix(SOURCE_OPEN_INTEREST)?;
+        let oi_lf = oi_to_lf(oi_data, prefix)
+            .map_err(|e| InfraError::Msg(format!("Polars oi_to_lf err: {:?}", e)))?;
+
+        let converted_oi_lf = convert_all_to_float64_except_timestamp(oi_lf)?
+            .with_columns(oi_change_exprs(prefix, &oi_windows));
+
+        let schema = collect_schema_safe(&converted_oi_lf)?;
+        let window = cfg.zscore_window;
+        let mut zscore_exprs = Vec::new();
+        let mut incremental_cols = Vec::new();
+
+        for field in schema.iter_fields() {
+            let name = field.name();
+            let dtype = field.dtype();
+
+            if cfg.is_excluded(name) || name.ends_with(GAP_COL) || *dtype != DataType::Float64 {
+                continue;
+            }
+
+            match cfg.scaling(name) {
+                ScalingMethod::Standard => incremental_cols.push(name.to_string()),
+                ScalingMethod::Robust => zscore_exprs.push(robust_z_score_expr(name, window)),
+            };
+
+            if cfg.winsorize_cols.iter().any(|c| c == name.as_str()) {
+                zscore_exprs.push(winsorize_expr(name, window, cfg.winsor_sigma));
+                zscore_exprs.push(outlier_flag_expr(name, window, cfg.outlier_sigma));
+            }
+        }
+
+        // Calendar features are bounded already, so they are added after the z-score stage
+        let full_df = with_calendar_feats(
+            converted_oi_lf.with_columns(zscore_exprs),
+            cfg.prefix(SOURCE_CALENDAR)?,
+            cfg.funding_interval_hours,
+        )
+        .collect()?;
+
+        let z_scores = self
+            .zscore_cache
+            .update(inst, &full_df, &incremental_cols, window)?;
+
+        let mut last_df = full_df.tail(Some(1));
+        for (name, z) in z_scores.iter() {
+            last_df.with_column(Column::new(format!("z_{}", name).into(), [*z]))?;
+        }
+
+        let z_score_oi_df = last_df.drop_nulls::<String>(None)?;
+        self.feature_config.check_provenance(z_score_oi_df.schema())?;
+
+        Ok(z_score_oi_df)
+    }
+
+    /// Price of `inst` on the configured `price_market`, the venue models and targets see.
+    pub fn price(&self, inst: &str) -> Option<f64> {
+        let key = (self.feature_config.price_market.clone(), inst.to_string());
+        self.px.get(&key).copied().filter(|px| *px > 0.0)
+    }
+
+    /// Feeds the coalesced window's trade activity to the stream health and order flow.
+    pub fn flush_trades(&mut self) {
+        let (buckets, received, dropped) = self.trades.drain();
+        let now_ms = get_micros_timestamp() / 1000;
+        for (key, bucket) in buckets.iter() {
+            self.stream_health.record_trades(key, bucket.count, now_ms);
+            self.order_flow
+                .push(key, bucket.buy_volume, bucket.sell_volume, bucket.last_ts);
+        }
+        for (_, inst) in buckets.keys() {
+            if let Some(vpin) = self.order_flow.vpin(inst) {
+                self.toxicity.insert(inst.clone(), vpin);
+            }
+        }
+
+        if dropped > 0 {
+            warn!(
+                "[Trades] Dropped {} of {} trades in window ({} insts buffered)",
+                dropped,
+                received,
+                buckets.len()
+            );
+        }
+    }
+
+    /// Upserts the close of the candle at `timestamp`; in-progress candles overwrite.
+    pub fn record_candle(&mut self, market: &Market, inst: &str, timestamp: u64, close: f64) {
+        if *market == self.feature_config.price_market {
+            self.candle_frames.invalidate(inst);
+        }
+        let history = self
+            .candles
+            .entry((market.clone(), inst.to_string()))
+            .or_default();
+        if let Some(last) = history.back() {
+            let missing = missing_bars(last.0, timestamp, KLINE_PERIOD_MS);
+            if missing > 0 {
+                warn!(
+                    "[Data] Kline gap for {}: {} bar(s) missing before {}",
+                    inst, missing, timestamp
+                );
+            }
+        }
+        match history.back_mut() {
+            Some(last) if last.0 == timestamp => last.1 = close,
+            _ => history.push_back((timestamp, close)),
+        };
+
+        while history.len() > MAX_CANDLE_HISTORY {
+            history.pop_front();
+        }
+    }
+
+    /// Adds the rolling correlation and beta of `inst` against the benchmark.
+    fn attach_benchmark_feats(&mut self, df: DataFrame, inst: &str) -> InfraResult<DataFrame> {
+        let prefix = self.feature_config.prefix(SOURCE_KLINE)?;
+        let corr_name = format!("{}corr_bench", prefix);
+        let beta_name = format!("{}beta_bench", prefix);
+        let gap_name = format!("{}{}", prefix, GAP_COL);
+
+        let timestamps: Vec<u64> = self
+            .candle_history(inst)
+            .map(|closes| closes.iter().map(|(ts, _)| *ts).collect())
+            .unwrap_or_default();
+        let gaps = window_gap_bars(
+            &timestamps,
+            self.feature_config.corr_window + 1,
+            KLINE_PERIOD_MS,
+        );
+
+        let (corr, beta) = self.benchmark_corr_beta(inst).unwrap_or_else(|e| {
+            warn!("Benchmark features for {} unavailable: {}", inst, e);
+            (0.0, 0.0)
+        });
+
+        Ok(df
+            .lazy()
+            .with_columns([
+                lit(corr).alias(corr_name.as_str()),
+                lit(beta).alias(beta_name.as_str()),
+                lit(gaps).alias(gap_name.as_str()),
+            ])
+            .collect()?)
+    }
+
+    /// Adds the VPIN toxicity of `inst` when `vpin_feature` is on.
+    fn attach_flow_feats(&self, df: DataFrame, inst: &str) -> InfraResult<DataFrame> {
+        if !self.feature_config.vpin_feature {
+            return Ok(df);
+        }
+
+        let name = format!("{}vpin", self.feature_config.prefix(SOURCE_TRADES)?);
+        let vpin = self.order_flow.vpin(inst).unwrap_or(0.0);
+        Ok(df.lazy().with_columns([lit(vpin).alias(name.as_str())]).collect()?)
+    }
+
+    /// Adds the market-data health of `inst` on `price_market` when `stream_health` is on.
+    fn attach_health_feats(&mut self, df: DataFrame, inst: &str) -> InfraResult<DataFrame> {
+        if !self.feature_config.stream_health {
+            return Ok(df);
+        }
+
+        let prefix = self.feature_config.prefix(SOURCE_HEALTH)?;
+        let key = (self.feature_config.price_market.clone(), inst.to_string());
+        let now_ms = get_micros_timestamp() / 1000;
+        self.stream_health.attach(df, &key, prefix, now_ms)
+    }
+
+    /// Adds the term-structure columns of `inst` when `term_structure` is on.
+    async fn attach_term_feats(&mut self, df: DataFrame, inst: &str) -> InfraResult<DataFrame> {
+        if !self.feature_config.term_structure {
+            return Ok(df);
+        }
+
+        let prefix = self.feature_config.prefix(SOURCE_TERM)?.to_string();
+        let now_ms = get_micros_timestamp() / 1000;
+        let term = self
+            .term
+            .term_structure(inst, now_ms)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Term structure features for {} unavailable: {}", inst, e);
+                TermStructure::default()
+            });
+        term.attach(df, &prefix)
+    }
+
+    /// Candle closes of `inst` on the configured `price_market`.
+    fn candle_history(&self, inst: &str) -> Option<&VecDeque<(u64, f64)>> {
+        let key = (self.feature_config.price_market.clone(), inst.to_string());
+        self.candles.get(&key)
+    }
+
+    fn benchmark_corr_beta(&mut self, inst: &str) -> InfraResult<(f64, f64)> {
+        let bench = &self.feature_config.benchmark_inst;
+        let market = &self.feature_config.price_market;
+        let (Some(inst_px), Some(bench_px)) = (
+            self.candles.get(&(market.clone(), inst.to_string())),
+            self.candles.get(&(market.clone(), bench.clone())),
+        ) else {
+            return Err(InfraError::Msg(format!("no candles for {} or {}", inst, bench)));
+        };
+
+        let closes = |df: DataFrame, name: &str| {
+            df.lazy()
+                .select([col("timestamp"), col(CANDLE_CLOSE_COL).alias(name)])
+        };
+        let inst_df = self.candle_frames.closes(inst, inst_px, CANDLE_CLOSE_COL)?;
+        let bench_df = self.candle_frames.closes(bench, bench_px, CANDLE_CLOSE_COL)?;
+        let joined = closes(inst_df, "inst")
+            .join(
+                closes(bench_df, "bench"),
+                [col("timestamp")],
+                [col("timestamp")],
+                JoinArgs::new(JoinType::Inner),
+            )
+            .sort(["timestamp"], Default::default())
+            .with_columns([simple_return_expr("inst"), simple_return_expr("bench")]);
+
+        let (corr, beta) =
+            rolling_corr_beta_expr("ret_inst", "ret_bench", self.feature_config.corr_window);
+        let df = joined
+            .select([corr.alias("corr"), beta.alias("beta")])
+            .collect()?;
+
+        if df.height() < 2 {
+            return Err(InfraError::Msg("not enough overlapping candles".into()));
+        }
+
+        let last = df.height() - 1;
+        let value = |name: &str| -> InfraResult<f64> {
+            Ok(df.column(name)?.f64()?.get(last).unwrap_or(0.0))
+        };
+
+        Ok((value("corr")?, value("beta")?))
+    }
+
+    /// OI change lookbacks computed for `inst`: the configured ones and those of its models.
+    fn oi_windows(&self, inst: &str) -> Vec<i64> {
+        let mut windows: BTreeSet<i64> =
+            self.feature_config.oi_change_windows.iter().copied().collect();
+        for model_id in self.routing.models_for(inst).iter() {
+            if let Some(own) = self
+                .model_config
+                .get(model_id)
+                .and_then(|cfg| cfg.oi_change_windows.as_ref())
+            {
+                windows.extend(own.iter().copied());
+            }
+        }
+        windows.into_iter().collect()
+    }
+
+    /// Columns of the instrument's frame that `cfg` receives, in frame order.
+    fn model_columns(
+        &self,
+        inst: &str,
+        cfg: &ModelConfig,
+        columns: &[String],
+    ) -> InfraResult<Vec<String>> {
+        let own = cfg
+            .oi_change_windows
+            .as_ref()
+            .unwrap_or(&self.feature_config.oi_change_windows);
+        let mut foreign: HashSet<String> = HashSet::new();
+        for window in self.oi_windows(inst).into_iter().filter(|w| !own.contains(w)) {
+            foreign.extend(self.feature_config.oi_window_cols(window)?);
+        }
+
+        let selects = |name: &str| {
+            let base = name.strip_prefix("z_").unwrap_or(name);
+            let source = base
+                .strip_suffix("_wins")
+                .or_else(|| base.strip_suffix("_outlier"))
+                .unwrap_or(base);
+            if foreign.contains(source) {
+                return false;
+            }
+            cfg.features.is_empty()
+                || cfg
+                    .features
+                    .iter()
+                    .any(|f| name.starts_with(f.as_str()) || base.starts_with(f.as_str()))
+        };
+
+        Ok(columns
+            .iter()
+            .filter(|name| *name == "timestamp" || selects(name))
+            .cloned()
+            .collect())
+    }
+
+    /// Feature tensor of the instrument's frame cut down to the pinned columns of `model_id`.
+    fn model_tensor(
+        &mut self,
+        inst: &str,
+        model_id: &str,
+        data: &DataFrame,
+        px: f64,
+        pos_weight: f64,
+    ) -> InfraResult<AltTensor> {
+        let cfg = self
+            .model_config
+            .get(model_id)
+            .ok_or_else(|| InfraError::Msg(format!("Unknown model={}", model_id)))?;
+        let columns: Vec<String> = data
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let selected = self.model_columns(inst, cfg, &columns)?;
+        let pinned = self.column_pins.order_for(model_id, &selected)?;
+
+        let frame = data.select(pinned.iter().map(|c| c.as_str()))?;
+        let ts = get_micros_timestamp();
+        let tensor = df_to_tensor(&frame, &pinned, model_id.to_string(), px, pos_weight, ts)?;
+        if let Err(e) = self.golden.record_input(inst, &frame, px, pos_weight, &tensor) {
+            warn!("[Golden] Failed to capture input for {}: {:?}", inst, e);
+        }
+
+        Ok(tensor)
+    }
+
+    /// Sends the instrument's frame to the `due` models routed to it, each with its own columns.
+    async fn send_data_to_model(
+        &mut self,
+        inst: &str,
+        data: &DataFrame,
+        due: &HashSet<String>,
+    ) -> InfraResult<()> {
+        let Some(px) = self.price(inst) else {
+            warn!(
+                "Price for {} on {:?} not available yet. Waiting for data...",
+                inst, self.feature_config.price_market
+            );
+            // 可以选择跳过这次发送，等待价格数据
+            return Ok(());
+        };
+
+        let pos_weight = self
+            .target_weights
+            .get(inst)
+            .map(|v| v.1)
+            .unwrap_or(0.0);
+
+        let routed: Vec<(String, u64, bool)> = self
+            .routing
+            .models_for(inst)
+            .iter()
+            .filter(|id| due.contains(*id))
+            .filter_map(|id| self.model_config.get(id))
+            .map(|cfg| (cfg.model_id.clone(), cfg.port, cfg.delta_encoding))
+            .collect();
+
+        for (model_id, port, delta_encoding) in routed {
+            let mut tensor = match self.model_tensor(inst, &model_id, data, px, pos_weight) {
+                Ok(tensor) => tensor,
+                Err(e) => {
+                    error!("[Data] Not sending {} to model={}: {:?}", inst, model_id, e);
+                    continue;
+                },
+            };
+            tensor.metadata.insert("inst".to_string(), inst.to_string());
+            tensor.metadata.insert(
+                "price_market".to_string(),
+                format!("{:?}", self.feature_config.price_market),
+            );
+            if delta_encoding {
+                tensor = self.delta.encode(&model_id, inst, tensor);
+            }
+            if let Err(e) = self.check_contract(Direction::FeatureInput, &tensor) {
+                error!("[Data] Not sending {} to model={}: {:?}", inst, model_id, e);
+                // The model never sees this baseline, so the next send goes out in full
+                if delta_encoding {
+                    self.delta.reset(&model_id);
+                }
+                continue;
+            }
+
+            self.send_to_model(port, tensor).await;
+        }
+
+        Ok(())
+    }
+
+    /// Queues a refused `adjust_position` as feedback for the model that sent it.
+    fn reject_target(&self, model_id: &str, inst: &str, target: f64, reason: &str, detail: &str) {
+        let Some(cfg) = self.model_config.get(model_id) else {
+            return;
+        };
+        let Ok(mut queue) = self.rejections.lock() else {
+            warn!("[MCP] Rejection queue poisoned — dropping feedback to model={}", model_id);
+            return;
+        };
+
+        queue.push_back(TradeRejection {
+            model_id: Some(model_id.to_string()),
+            account_id: cfg.account_id.clone(),
+            inst: inst.to_string(),
+            reason: reason.to_string(),
+            detail: detail.to_string(),
+            diff: if target.is_finite() { target } else { 0.0 },
+            failures: 0,
+            timestamp: get_micros_timestamp(),
+        });
+        while queue.len() > MAX_PENDING_REJECTIONS {
+            queue.pop_front();
+        }
+    }
+
+    /// Routes rejections to the models they concern.
+    async fn send_rejection_feedback(&mut self) -> InfraResult<()> {
+        let rejections: Vec<TradeRejection> = match self.rejections.lock() {
+            Ok(mut queue) => queue.drain(..).collect(),
+            Err(_) => return Err(InfraError::Msg("Rejection queue poisoned".into())),
+        };
+
+        for rejection in rejections.iter() {
+            let recipients: Vec<(String, u64)> = self
+                .model_config
+                .iter()
+                .filter(|(model_id, cfg)| match &rejection.model_id {
+                    Some(issuer) => issuer == *model_id,
+                    None => {
+                        cfg.account_id == rejection.account_id
+                            && self
+                                .model_targets
+                                .get(*model_id)
+                                .is_some_and(|t| t.contains_key(&rejection.inst))
+                    },
+                })
+                .map(|(model_id, cfg)| (model_id.clone(), cfg.port))
+                .collect();
+
+            for (model_id, port) in recipients {
+                let tensor = rejection_to_tensor(rejection, model_id.clone());
+                if self.send_to_model(port, tensor).await {
+                    info!(
+                        "MCP rejection feedback: model={}, account={}, inst={}, failures={}",
+                        model_id, rejection.account_id, rejection.inst, rejection.failures
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends each rebalance fill to the models that target the instrument.
+    async fn send_execution_reports(&mut self) -> InfraResult<()> {
+        let reports: Vec<ExecutionReport> = match self.executions.lock() {
+            Ok(mut queue) => queue.drain(..).collect(),
+            Err(_) => return Err(InfraError::Msg("Execution queue poisoned".into())),
+        };
+
+        for report in reports.iter() {
+            let recipients: Vec<(String, u64)> = self
+                .model_config
+                .iter()
+                .filter(|(model_id, cfg)| {
+                    cfg.account_id == report.account_id
+                        && self
+                            .model_targets
+                            .get(*model_id)
+                            .is_some_and(|t| t.contains_key(&report.inst))
+                })
+                .map(|(model_id, cfg)| (model_id.clone(), cfg.port))
+                .collect();
+
+            for (model_id, port) in recipients {
+                let tensor = execution_to_tensor(report, model_id.clone());
+                if self.send_to_model(port, tensor).await {
+                    info!(
+                        "MCP execution report: model={}, account={}, inst={}, slippage={:.2}bps",
+                        model_id, report.account_id, report.inst, report.slippage_bps
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replies to a `query` with the model's raw and executed targets.
+    async fn send_query_response(&mut self, model_id: &str) -> InfraResult<()> {
+        let cfg = self
+            .model_config
+            .get(model_id)
+            .ok_or_else(|| InfraError::Msg(format!("MCP query: unknown model {}", model_id)))?;
+
+        let raw_targets = self.model_targets.get(model_id).cloned().unwrap_or_default();
+        let scale = model_scale(&raw_targets, Some(cfg));
+        // Targets and account state from one publish, never torn across writers
+        let portfolio = self.portfolio.read();
+
+        let tensor = query_to_tensor(
+            model_id,
+            cfg,
+            &raw_targets,
+            scale,
+            &portfolio,
+            self.control.is_flattened(),
+        );
+
+        let (port, account_id) = (cfg.port, cfg.account_id.clone());
+        if self.send_to_model(port, tensor).await {
+            info!(
+                "MCP query: model={}, account={}, insts={}",
+                model_id,
+                account_id,
+                raw_targets.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Replies to a `query` with `scope=portfolio`.
+    async fn send_portfolio_response(&mut self, model_id: &str) -> InfraResult<()> {
+        let port = self
+            .model_config
+            .get(model_id)
+            .map(|cfg| cfg.port)
+            .ok_or_else(|| InfraError::Msg(format!("MCP query: unknown model {}", model_id)))?;
+
+        let portfolio = self.portfolio.read();
+        let tensor = portfolio_to_tensor(model_id, &portfolio, self.control.is_flattened());
+
+        if self.send_to_model(port, tensor).await {
+            info!(
+                "MCP query: model={}, scope=portfolio, accounts={}, insts={}",
+                model_id,
+                portfolio.accounts.len(),
+                portfolio.targets.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Replies to a `query` with `scope=protocol`.
+    async fn send_protocol_response(&mut self, model_id: &str) -> InfraResult<()> {
+        let port = self
+            .model_config
+            .get(model_id)
+            .map(|cfg| cfg.port)
+            .ok_or_else(|| InfraError::Msg(format!("MCP query: unknown model {}", model_id)))?;
+
+        let tensor = protocol_to_tensor(model_id)?;
+        if self.send_to_model(port, tensor).await {
+            info!(
+                "MCP query: model={}, scope=protocol, schema_version={}",
+                model_id, SCHEMA_VERSION
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Hands a tensor to the model task on `port`.
+    async fn send_to_model(&mut self, port: u64, tensor: AltTensor) -> bool {
+        self.retry_dead_letters(port).await;
+
+        match self.try_send_to_model(port, tensor.clone()).await {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Model send to port {} failed: {}", port, e);
+                self.dead_letters.push(port, tensor, e.to_string());
+                false
+            },
+        }
+    }
+
+    /// Redelivers queued tensors for `port`, e.g. after the model answered again.
+    pub async fn retry_dead_letters(&mut self, port: u64) {
+        let mut letters = self.dead_letters.take_for_port(port);
+
+        while !letters.is_empty() {
+            let letter = letters.remove(0);
+            if let Err(e) = self.try_send_to_model(port, letter.tensor.clone()).await {
+                warn!("[DLQ] Redelivery to port {} failed: {}", port, e);
+                letters.insert(0, letter);
+                self.dead_letters.requeue(letters);
+                return;
+            }
+
+            self.dead_letters.redelivered += 1;
+            info!(
+                "[DLQ] Redelivered tensor to port {} (queued for {:?}, reason: {}), redelivered={}",
+                port,
+                letter.queued_at.elapsed(),
+                letter.reason,
+                self.dead_letters.redelivered
+            );
+        }
+    }
+
+    async fn try_send_to_model(&self, port: u64, tensor: AltTensor) -> InfraResult<()> {
+        let handle = self
+            .find_alt_handle(&AltTaskType::ModelPreds(port), port)
+            .ok_or_else(|| InfraError::Msg(format!("No model handle found for port {}", port)))?;
+
+        handle
+            .send_command(TaskCommand::FeatInput(tensor), None)
+            .await
+    }
+
+    /// Models on ports silent past the watchdog threshold.
+    pub fn stalled_models(&mut self) -> HashSet<String> {
+        let mut stalled = HashSet::new();
+        for task in self.heartbeats.due_restarts("model:", &self.watchdog) {
+            let port = task.strip_prefix("model:").and_then(|p| p.parse::<u64>().ok());
+            let models: Vec<String> = self
+                .model_config
+                .iter()
+                .filter(|(_, cfg)| Some(cfg.port) == port)
+                .map(|(model_id, _)| model_id.clone())
+                .collect();
+
+            if models.is_empty() {
+                self.heartbeats.forget(&task);
+                continue;
+            }
+            info!("[Watchdog] Resending features to stalled models {:?}", models);
+            stalled.extend(models);
+        }
+        stalled
+    }
+
+    /// Resubscribes the candle stream once it has been silent past the watchdog threshold.
+    pub async fn restart_stalled_candles(&mut self) {
+        if self
+            .heartbeats
+            .due_restarts("ws:candles", &self.watchdog)
+            .is_empty()
+        {
+            return;
+        }
+
+        let Some((task_id, task)) = self.candle_task.clone() else {
+            return;
+        };
+
+        info!("[Watchdog] Reconnecting candle channel {:?}", task.ws_channel);
+        self.stream_health
+            .record_connect(None, get_micros_timestamp() / 1000);
+        if let Err(e) = self.connect_channel(&task, task_id).await {
+            warn!("[Watchdog] Candle reconnect failed: {:?}", e);
+        }
+    }
+
+    /// Drops cached state of instruments idle for `idle_inst_ttl`.
+    pub fn collect_idle_insts(&mut self) {
+        let held = self.snapshots.iter().flat_map(|snapshot| {
+            snapshot
+                .acc_weights
+                .iter()
+                .filter(|(_, weight)| weight.abs() > f64::EPSILON)
+                .map(|(inst, _)| inst.clone())
+                .collect::<Vec<_>>()
+        });
+        let targeted = self.target_weights.iter().map(|entry| entry.key().clone());
+        let active: Vec<String> = self
+            .subscribed_insts()
+            .into_iter()
+            .chain(targeted)
+            .chain(held)
+            .collect();
+        for inst in active.iter() {
+            self.idle_insts.touch(inst);
+        }
+
+        let cached: Vec<String> = self
+            .px
+            .keys()
+            .map(|(_, inst)| inst.clone())
+            .chain(self.candles.keys().map(|(_, inst)| inst.clone()))
+            .chain(self.order_flow.insts())
+            .chain(self.feature_store.cached_insts())
+            .collect();
+        let idle = self.idle_insts.expire(cached, self.idle_inst_ttl);
+        if idle.is_empty() {
+            return;
+        }
+
+        for inst in idle.iter() {
+            self.px.retain(|(_, i), _| i != inst);
+            self.candles.retain(|(_, i), _| i != inst);
+            self.oi_unfillable.remove(inst);
+            self.candle_frames.invalidate(inst);
+            self.order_flow.evict(inst);
+            self.toxicity.remove(inst);
+            self.stream_health.evict(inst);
+            self.feature_store.evict(inst);
+            self.zscore_cache.evict(inst);
+            self.delta.evict(inst);
+        }
+        info!("[Data] Dropped cached state of {} idle instruments: {:?}", idle.len(), idle);
+    }
+
+    /// Routed instruments plus the correlation benchmark.
+    fn subscribed_insts(&self) -> Vec<String> {
+        let mut insts = self.routing.insts();
+        for inst in self.universe.iter().chain([&self.feature_config.benchmark_inst]) {
+            if !insts.contains(inst) {
+                insts.push(inst.clone());
+            }
+        }
+        insts
+    }
+
+    /// Instrument of commands that name none: the model's only instrument, if it has one.
+    pub fn default_inst(&self, model_id: &str) -> Option<String> {
+        let insts = match self.model_config.get(model_id) {
+            Some(cfg) if !cfg.insts.is_empty() => cfg.insts.clone(),
+            _ => self.universe.clone(),
+        };
+        match insts.as_slice() {
+            [inst] => Some(inst.clone()),
+            _ => None,
+        }
+    }
+
+    /// Connects a public stream task and subscribes it to the instrument universe.
+    pub async fn connect_channel(&self, task: &WsTaskInfo, task_id: u64) -> InfraResult<()> {
+        let channel = &task.ws_channel;
+        let Some(handle) = self.find_ws_handle(channel, task_id) else {
+            warn!(
+                "[PublicWs] No handle found for {:?} {:?}, task_id={}",
+                task.market, channel, task_id
+            );
+            return Ok(());
+        };
+
+        let insts = self.subscribed_insts();
+        let (ws_url, ws_msg) = match task.market {
+            Market::BinanceUmFutures => (
+                self.binance_um_cli.get_public_connect_msg(channel).await?,
+                self.binance_um_cli
+                    .get_public_sub_msg(channel, Some(&insts))
+                    .await?,
+            ),
+            Market::BinanceCmFutures => (
+                self.binance_cm_cli.get_public_connect_msg(channel).await?,
+                self.binance_cm_cli
+                    .get_public_sub_msg(channel, Some(&insts))
+                    .await?,
+            ),
+            Market::Okx => (
+                self.okx_cli.get_public_connect_msg(channel).await?,
+                self.okx_cli.get_public_sub_msg(channel, Some(&insts)).await?,
+            ),
+            ref other => {
+                return Err(InfraError::Msg(format!(
+                    "Public stream not supported for market {:?}",
+                    other
+                )));
+            },
+        };
+        let ws_url = profile_ws_url(ws_url)?;
+
+        info!(
+            "[PublicWs] Connecting {:?} {:?} task_id={} insts={:?}",
+            task.market, channel, task_id, insts
+        );
+
+        let (tx, rx) = oneshot::channel();
+        let cmd = TaskCommand::WsConnect {
+            msg: ws_url,
+            ack: AckHandle::new(tx),
+        };
+        handle
+            .send_command(cmd, Some((AckStatus::WsConnect, rx)))
+            .await?;
+
+        let cmd = TaskCommand::WsMessage {
+            msg: ws_msg,
+            ack: AckHandle::none(), // no need to wait for ack
+        };
+        handle.send_command(cmd, None).await?;
+
+        Ok(())
+    }
+}
+
+/// Runs the prediction through `mcp_mediator` on a fresh server.
+async fn replay_prediction(session: &GoldenSession) -> GoldenOutcome {
+    let mut server = McpServer::new();
+    server.model_config.insert(
+        session.model_id.clone(),
+        ModelConfig {
+            model_id: session.model_id.clone(),
+            target_leverage: session.target_leverage,
+            ..ModelConfig::default()
+        },
+    );
+
+    let prediction: AltTensor = (&session.prediction).into();
+    let error = server
+        .mcp_mediator(&prediction)
+        .await
+        .err()
+        .map(|e| format!("{:?}", e));
+    let inst = prediction
+        .metadata
+        .get("inst")
+        .cloned()
+        .or_else(|| server.default_inst(&session.model_id))
+        .unwrap_or_default();
+
+    GoldenOutcome {
+        error,
+        target_weight: server.target_weights.get(&inst).map(|v| v.1),
+    }
+}
+
+/// Last row of `df` as a feature tensor, columns in exactly the `columns` order.
+pub fn df_to_tensor(
+    df: &DataFrame,
+    columns: &[String],
+    model_id: String,
+    price: f64,
+    weight: f64,
+    timestamp: u64,
+) -> InfraResult<AltTensor> {
+    if df.height() == 0 {
+        return Err(InfraError::Msg("df is empty".into()));
+    }
+    if df.width() != columns.len() {
+        return Err(InfraError::Msg(format!(
+            "df has {} columns, expected {:?}",
+            df.width(),
+            columns
+        )));
+    }
+    let df = &df.select(columns.iter().map(|c| c.as_str()))?;
+
+    let last_idx = df.height() - 1;
+
+    let row = df
+        .get_row(last_idx)
+        .map_err(|_| InfraError::Msg("failed to get row".into()))?;
+
+    let col_names: Vec<String> = df
+        .get_columns()
+        .iter()
+        .map(|s| s.name().to_string())
+        .collect();
+
+    let mut data = Vec::with_capacity(row.0.len());
+    for val in &row.0 {
+        let f = match val {
+            AnyValue::Float32(v) => *v,
+            AnyValue::Float64(v) => *v as f32,
+            AnyValue::Int64(v) => *v as f32,
+            AnyValue::Int32(v) => *v as f32,
+            AnyValue::UInt64(v) => *v as f32,
+            AnyValue::UInt32(v) => *v as f32,
+            _ => {
+                return Err(InfraError::Msg(format!(
+                    "unsupported type: {} ({:?})",
+                    val,
+                    val.dtype()
+                )));
+            }
+        };
+        data.push(f);
+    }
+
+    let shape = vec![data.len()];
+
+    let mut metadata = HashMap::new();
+    metadata.insert("model_id".to_string(), model_id);
+    metadata.insert("price".to_string(), price.to_string());
+    metadata.insert("pos_weight".to_string(), weight.to_string());
+    metadata.insert("col_names".to_string(), serde_json::to_string(&col_names)?);
+    metadata.insert("schema_version".to_string(), SCHEMA_VERSION.to_string());
+
+    Ok(AltTensor {
+        timestamp,
+        data,
+        shape,
+        metadata,
+    })
+}
+
+pub fn rejection_to_tensor(rejection: &TradeRejection, model_id: String) -> AltTensor {
+    let mut metadata = HashMap::new();
+    metadata.insert("model_id".to_string(), model_id);
+    metadata.insert("msg_type".to_string(), "trade_rejection".to_string());
+    metadata.insert("account_id".to_string(), rejection.account_id.clone());
+    metadata.insert("inst".to_string(), rejection.inst.clone());
+    metadata.insert("reason".to_string(), rejection.reason.clone());
+    metadata.insert("detail".to_string(), rejection.detail.clone());
+    metadata.insert("diff".to_string(), rejection.diff.to_string());
+    metadata.insert("failures".to_string(), rejection.failures.to_string());
+    metadata.insert("schema_version".to_string(), SCHEMA_VERSION.to_string());
+
+    AltTensor {
+        timestamp: rejection.timestamp,
+        data: vec![rejection.diff as f32],
+        shape: vec![1],
+        metadata,
+    }
+}
+
+/// Data is `filled_size, avg_price, decision_price, slippage_bps`.
+pub fn execution_to_tensor(report: &ExecutionReport, model_id: String) -> AltTensor {
+    let side = if report.buy { "buy" } else { "sell" };
+
+    let mut metadata = HashMap::new();
+    metadata.insert("model_id".to_string(), model_id);
+    metadata.insert("msg_type".to_string(), "execution_report".to_string());
+    metadata.insert("account_id".to_string(), report.account_id.clone());
+    metadata.insert("inst".to_string(), report.inst.clone());
+    metadata.insert("side".to_string(), side.to_string());
+    metadata.insert("filled_size".to_string(), report.filled_size.to_string());
+    metadata.insert("avg_price".to_string(), report.avg_price.to_string());
+    metadata.insert("decision_price".to_string(), report.decision_price.to_string());
+    metadata.insert("slippage_bps".to_string(), report.slippage_bps.to_string());
+    metadata.insert("diff".to_string(), report.diff.to_string());
+    metadata.insert("estimated".to_string(), report.estimated.to_string());
+    if let Some(cl_id) = &report.client_order_id {
+        metadata.insert("client_order_id".to_string(), cl_id.clone());
+    }
+    metadata.insert("schema_version".to_string(), SCHEMA_VERSION.to_string());
+
+    AltTensor {
+        timestamp: report.timestamp,
+        data: vec![
+            report.filled_size as f32,
+            report.avg_price as f32,
+            report.decision_price as f32,
+            report.slippage_bps as f32,
+        ],
+        shape: vec![4],
+        metadata,
+    }
+}
+
+/// Liveness probe; the host answers with any reply, e.g. `noop`.
+pub fn heartbeat_to_tensor(model_id: &str, now_ms: u64) -> AltTensor {
+    let mut metadata = HashMap::new();
+    metadata.insert("model_id".to_string(), model_id.to_string());
+    metadata.insert("msg_type".to_string(), "heartbeat".to_string());
+    metadata.insert("ts".to_string(), now_ms.to_string());
+    metadata.insert("schema_version".to_string(), SCHEMA_VERSION.to_string());
+
+    AltTensor {
+        timestamp: get_micros_timestamp(),
+        data: vec![0.0],
+        shape: vec![1],
+        metadata,
+    }
+}
+
+/// The `protocol_spec` JSON as the `spec` metadata field.
+pub fn protocol_to_tensor(model_id: &str) -> InfraResult<AltTensor> {
+    let mut metadata = HashMap::new();
+    metadata.insert("model_id".to_string(), model_id.to_string());
+    metadata.insert("msg_type".to_string(), "protocol_spec".to_string());
+    metadata.insert("spec".to_string(), serde_json::to_string(&protocol_spec())?);
+    metadata.insert("schema_version".to_string(), SCHEMA_VERSION.to_string());
+
+    Ok(AltTensor {
+        timestamp: get_micros_timestamp(),
+        data: vec![0.0],
+        shape: vec![1],
+        metadata,
+    })
+}
+
+/// Rows follow the `insts` metadata order.
+pub fn query_to_tensor(
+    model_id: &str,
+    cfg: &ModelConfig,
+    raw_targets: &HashMap<String, f64>,
+    scale: f64,
+    portfolio: &PortfolioSnapshot,
+    flattened: bool,
+) -> AltTensor {
+    let mut insts: Vec<&String> = raw_targets.keys().collect();
+    insts.sort();
+
+    let snapshot = portfolio
+        .accounts
+        .get(&cfg.account_id)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut data = Vec::with_capacity(insts.len() * 5);
+    let mut prices = serde_json::Map::new();
+    let mut failures = serde_json::Map::new();
+    let mut skips = serde_json::Map::new();
+    for inst in insts.iter() {
+        let raw = raw_targets[*inst];
+        let consolidated = portfolio.targets.get(*inst).map(|v| v.1).unwrap_or(0.0);
+        let current = snapshot.acc_weights.get(*inst).copied().unwrap_or(0.0);
+        let pending = snapshot.pending_diffs.get(*inst).copied().unwrap_or(0.0);
+        data.extend([raw, raw * scale, consolidated, current, pending].map(|v| v as f32));
+
+        if let Some(price) = portfolio.prices.get(*inst) {
+            prices.insert(inst.to_string(), serde_json::json!(price));
+        }
+        if let Some(n) = snapshot.exec_failures.get(*inst) {
+            failures.insert(inst.to_string(), serde_json::json!(n));
+        }
+        if let Some(counts) = snapshot.skips.get(*inst) {
+            skips.insert(inst.to_string(), serde_json::json!(counts));
+        }
+    }
+
+    let mut metadata = HashMap::new();
+    metadata.insert("model_id".to_string(), model_id.to_string());
+    metadata.insert("msg_type".to_string(), "query_response".to_string());
+    metadata.insert("account_id".to_string(), cfg.account_id.clone());
+    metadata.insert(
+        "insts".to_string(),
+        serde_json::to_string(&insts).unwrap_or_default(),
+    );
+    metadata.insert(
+        "col_names".to_string(),
+        r#"["raw","scaled","consolidated","current","pending_diff"]"#.to_string(),
+    );
+    metadata.insert("scale".to_string(), scale.to_string());
+    metadata.insert("flattened".to_string(), flattened.to_string());
+    metadata.insert("paused".to_string(), snapshot.paused.to_string());
+    metadata.insert("drawdown_tripped".to_string(), snapshot.drawdown_tripped.to_string());
+    metadata.insert("total_equity".to_string(), snapshot.total_equity.to_string());
+    metadata.insert("next_update_sec".to_string(), snapshot.next_update_sec.to_string());
+    metadata.insert("snapshot_version".to_string(), portfolio.version.to_string());
+    metadata.insert("snapshot_ts".to_string(), portfolio.timestamp.to_string());
+    metadata.insert(
+        "exec_failures".to_string(),
+        serde_json::Value::Object(failures).to_string(),
+    );
+    metadata.insert("skips".to_string(), serde_json::Value::Object(skips).to_string());
+    metadata.insert("prices".to_string(), serde_json::Value::Object(prices).to_string());
+    metadata.insert("schema_version".to_string(), SCHEMA_VERSION.to_string());
+
+    AltTensor {
+        timestamp: get_micros_timestamp(),
+        shape: vec![insts.len(), 5],
+        data,
+        metadata,
+    }
+}
+
+/// Rows follow the `insts` metadata order.
+pub fn portfolio_to_tensor(
+    model_id: &str,
+    portfolio: &PortfolioSnapshot,
+    flattened: bool,
+) -> AltTensor {
+    let mut accounts: Vec<&String> = portfolio.accounts.keys().collect();
+    accounts.sort();
+    let insts: Vec<&String> = portfolio
+        .targets
+        .keys()
+        .chain(portfolio.prices.keys())
+        .chain(portfolio.accounts.values().flat_map(|a| a.acc_weights.keys()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let cols = 2 + accounts.len();
+    let mut data = Vec::with_capacity(insts.len() * cols);
+    for inst in insts.iter() {
+        data.push(portfolio.prices.get(*inst).copied().unwrap_or(0.0) as f32);
+        data.push(portfolio.targets.get(*inst).map(|v| v.1).unwrap_or(0.0) as f32);
+        for account_id in accounts.iter() {
+            let weight = portfolio.accounts[*account_id].acc_weights.get(*inst);
+            data.push(weight.copied().unwrap_or(0.0) as f32);
+        }
+    }
+
+    let col_names: Vec<String> = ["price".to_string(), "target".to_string()]
+        .into_iter()
+        .chain(accounts.iter().map(|id| format!("weight:{}", id)))
+        .collect();
+    let equity: HashMap<&String, f64> = portfolio
+        .accounts
+        .iter()
+        .map(|(id, snapshot)| (id, snapshot.total_equity))
+        .collect();
+    let paused: Vec<&String> = accounts
+        .iter()
+        .copied()
+        .filter(|id| portfolio.accounts[*id].paused)
+        .collect();
+
+    let mut metadata = HashMap::new();
+    metadata.insert("model_id".to_string(), model_id.to_string());
+    metadata.insert("msg_type".to_string(), "portfolio_response".to_string());
+    metadata.insert(
+        "insts".to_string(),
+        serde_json::to_string(&insts).unwrap_or_default(),
+    );
+    metadata.insert(
+        "accounts".to_string(),
+        serde_json::to_string(&accounts).unwrap_or_default(),
+    );
+    metadata.insert(
+        "col_names".to_string(),
+        serde_json::to_string(&col_names).unwrap_or_default(),
+    );
+    metadata.insert(
+        "equity".to_string(),
+        serde_json::to_string(&equity).unwrap_or_default(),
+    );
+    metadata.insert(
+        "paused_accounts".to_string(),
+        serde_json::to_string(&paused).unwrap_or_default(),
+    );
+    metadata.insert("flattened".to_string(), flattened.to_string());
+    metadata.insert("snapshot_version".to_string(), portfolio.version.to_string());
+    metadata.insert("snapshot_ts".to_string(), portfolio.timestamp.to_string());
+    metadata.insert("schema_version".to_string(), SCHEMA_VERSION.to_string());
+
+    AltTensor {
+        timestamp: get_micros_timestamp(),
+        shape: vec![insts.len(), cols],
+        data,
+        metadata,
+    }
+}
+
+pub fn check_alt_tensor_error(alt_tensor: &AltTensor) -> InfraResult<()> {
+    if let Some(err_msg) = alt_tensor.metadata.get("error") {
+        warn!(
+            "Skipping AltTensor because Python side sent error: {} | timestamp={}",
+            err_msg, alt_tensor.timestamp
+        );
+        return Err(InfraError::Msg(format!("Python model error: {}", err_msg)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arch::control_module::ctl_risk::RiskConfig;
+    use crate::arch::server_module::server_golden::{GOLDEN_SESSION_DIR, load_golden_sessions};
+    use std::path::Path;
+
+    fn server_with(mode: &str) -> McpServer {
+        let cfg: ModelConfig = serde_json::from_value(serde_json::json!({
+            "port": 5555,
+            "model_id": "m1",
+            "account_id": "acc",
+            "mode": mode,
+            "fallback_weights": { "BTC-USDT-SWAP": 0.5 },
+        }))
+        .unwrap();
+
+        let mut server = McpServer::new();
+        server.model_config.insert(cfg.model_id.clone(), cfg);
+        server
+    }
+
+    #[test]
+    fn shadow_fallback_stays_in_shadow_book() {
+        let mut server = server_with("shadow");
+        server.apply_fallback("m1", "weights", "test").unwrap();
+
+        assert!(server.model_targets.is_empty());
+        assert!(server.target_weights.is_empty());
+        let rows = server.shadow.rows(&server.model_config);
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].weights.contains_key("BTC-USDT-SWAP"));
+    }
+
+    #[test]
+    fn shadow_expiry_stays_in_shadow_book() {
+        let mut server = server_with("shadow");
+        server
+            .target_expiry
+            .insert(("m1".into(), "BTC-USDT-SWAP".into()), (0, 0.25));
+        server.expire_targets();
+
+        assert!(server.model_targets.is_empty());
+        assert!(server.target_weights.is_empty());
+        assert_eq!(server.shadow.rows(&server.model_config).len(), 1);
+    }
+
+    #[test]
+    fn live_fallback_reaches_targets() {
+        let mut server = server_with("live");
+        server.apply_fallback("m1", "weights", "test").unwrap();
+
+        assert_eq!(server.model_targets["m1"]["BTC-USDT-SWAP"], 0.5);
+        assert!(server.target_weights.contains_key("BTC-USDT-SWAP"));
+    }
+
+    #[test]
+    fn fallback_weights_drop_pending_expiry() {
+        let mut server = server_with("live");
+        server
+            .target_expiry
+            .insert(("m1".into(), "BTC-USDT-SWAP".into()), (0, 0.0));
+        server.apply_fallback("m1", "weights", "test").unwrap();
+        server.expire_targets();
+
+        assert!(server.target_expiry.is_empty());
+        assert_eq!(server.model_targets["m1"]["BTC-USDT-SWAP"], 0.5);
+    }
+
+    #[tokio::test]
+    async fn ignored_target_is_fed_back_to_its_model() {
+        let mut server = server_with("live");
+        server.apply_fallback("m1", "hold", "test").unwrap();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("cmd".to_string(), "adjust_position".to_string());
+        metadata.insert("model_id".to_string(), "m1".to_string());
+        metadata.insert("inst".to_string(), "BTC-USDT-SWAP".to_string());
+        metadata.insert("target_position".to_string(), "0.3".to_string());
+        let tensor = AltTensor {
+            timestamp: 0,
+            data: vec![0.0],
+            shape: vec![1],
+            metadata,
+        };
+        server.mcp_mediator(&tensor).await.unwrap();
+
+        let queue = server.rejections.lock().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].model_id.as_deref(), Some("m1"));
+        assert_eq!(queue[0].account_id, "acc");
+        assert_eq!(queue[0].reason, "model_fallback");
+        assert_eq!(queue[0].diff, 0.3);
+    }
+
+    #[test]
+    fn toxic_trade_flow_widens_the_band() {
+        let mut server = McpServer::new();
+        // All-taker-buy flow: the first five minutes size the buckets, the rest fill them
+        for minute in 0..30u64 {
+            server.trades.push(&WsTrade {
+                timestamp: minute * 60_000,
+                market: Market::BinanceUmFutures,
+                inst: "BTC_USDT_PERP".to_string(),
+                price: 100.0,
+                size: 10.0,
+                side: OrderSide::BUY,
+            });
+            server.flush_trades();
+        }
+
+        let vpin = server.toxicity.get("BTC_USDT_PERP").map(|v| *v);
+        assert!(vpin.is_some_and(|v| v > 0.9), "vpin {:?}", vpin);
+
+        let risk = RiskConfig {
+            toxicity_threshold: Some(0.5),
+            ..RiskConfig::default()
+        };
+        assert_eq!(risk.rebalance_band(None), risk.rebalance_threshold);
+        assert_eq!(
+            risk.rebalance_band(vpin),
+            risk.rebalance_threshold * risk.toxicity_band_mult
+        );
+    }
+
+    #[test]
+    fn coalesced_trades_reach_stream_health() {
+        let mut server = McpServer::new();
+        let key = (Market::BinanceUmFutures, "BTC_USDT_PERP".to_string());
+        for _ in 0..6 {
+            server.trades.push(&WsTrade {
+                timestamp: 0,
+                market: key.0.clone(),
+                inst: key.1.clone(),
+                price: 100.0,
+                size: 1.0,
+                side: OrderSide::SELL,
+            });
+        }
+        server.flush_trades();
+
+        let now_ms = get_micros_timestamp() / 1000;
+        assert_eq!(server.stream_health.trade_rate(&key, now_ms), 0.1);
+        assert!(server.stream_health.last_trade_age(&key, now_ms) < 1.0);
+    }
+
+    #[tokio::test]
+    async fn malformed_target_is_rejected() {
+        let mut server = server_with("live");
+        server.apply_fallback("m1", "weights", "test").unwrap();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("cmd".to_string(), "adjust_position".to_string());
+        metadata.insert("model_id".to_string(), "m1".to_string());
+        metadata.insert("inst".to_string(), "BTC-USDT-SWAP".to_string());
+        metadata.insert("target_position".to_string(), "0.3x".to_string());
+        let tensor = AltTensor {
+            timestamp: 0,
+            data: vec![0.0],
+            shape: vec![1],
+            metadata,
+        };
+        assert!(server.mcp_mediator(&tensor).await.is_err());
+
+        assert_eq!(server.model_targets["m1"]["BTC-USDT-SWAP"], 0.5);
+        let queue = server.rejections.lock().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].reason, "prediction_rejected");
+    }
+
+    #[test]
+    fn feature_subset_gets_its_own_columns() {
+        let mut server = server_with("live");
+        let mut subset = server.model_config["m1"].clone();
+        subset.model_id = "m2".to_string();
+        subset.features = vec!["close".to_string()];
+        server.model_config.insert("m2".to_string(), subset);
+        server.column_pins = serde_json::from_value(serde_json::json!({
+            "pins": {
+                "m1": ["timestamp", "close", "volume"],
+                "m2": ["close", "timestamp"],
+            }
+        }))
+        .unwrap();
+
+        let data = df!(
+            "timestamp" => [1u64, 2],
+            "close" => [100.0, 101.0],
+            "volume" => [5.0, 6.0]
+        )
+        .unwrap();
+
+        let subset = server.model_tensor("BTC_USDT_PERP", "m2", &data, 101.0, 0.0).unwrap();
+        assert_eq!(subset.data, vec![101.0, 2.0]);
+        assert_eq!(subset.metadata["col_names"], r#"["close","timestamp"]"#);
+
+        let full = server.model_tensor("BTC_USDT_PERP", "m1", &data, 101.0, 0.0).unwrap();
+        assert_eq!(full.data, vec![2.0, 101.0, 6.0]);
+    }
+
+    /// Replays every session recorded under `contracts/recorded`.
+    #[tokio::test]
+    async fn golden_sessions_replay() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_SESSION_DIR);
+        let sessions = load_golden_sessions(&dir).unwrap();
+        assert!(!sessions.is_empty(), "no golden sessions in {:?}", dir);
+
+        let mut failures = Vec::new();
+        for (path, session) in sessions.iter() {
+            // Live sends take the order from `column_order.json`, recorded as `col_names`
+            let columns: Vec<String> = session
+                .features
+                .metadata
+                .get("col_names")
+                .and_then(|names| serde_json::from_str(names).ok())
+                .unwrap_or_else(|| session.input.iter().map(|c| c.name.clone()).collect());
+            let features = session.input_frame().and_then(|df| {
+                df_to_tensor(
+                    &df,
+                    &columns,
+                    session.model_id.clone(),
+                    session.price,
+                    session.pos_weight,
+                    session.features.timestamp,
+                )
+            });
+            match features {
+                Ok(t) if session.features == (&t).into() => {},
+                Ok(t) => {
+                    failures.push(format!("{} feature tensor changed: {:?}", path, t));
+                    continue;
+                },
+                Err(e) => {
+                    failures.push(format!("{} feature tensor failed: {:?}", path, e));
+                    continue;
+                },
+            }
+
+            let outcome = replay_prediction(session).await;
+            if !outcome.matches(&session.outcome) {
+                failures.push(format!(
+                    "{} outcome changed: recorded {:?}, replayed {:?}",
+                    path, session.outcome, outcome
+                ));
+            }
+        }
+
+        assert!(failures.is_empty(), "golden sessions failed: {:#?}", failures);
+    }
+}
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+/// Protocol version stamped on every tensor as `schema_version`.
+pub const SCHEMA_VERSION: &str = "1";
+/// Feature inputs of models with `delta_encoding`, see `DeltaEncoder`.
+pub const DELTA_SCHEMA_VERSION: &str = "2";
+
+/// Commands models send as `cmd`; any other is logged and ignored by `mcp_mediator`.
+pub const COMMANDS: &[&str] = &[
+    "adjust_position",
+    "set_interval",
+    "risk_alert",
+    "fallback",
+    "query",
+    "kill",
+    "resume",
+    "cancel_order",
+    "amend_order",
+    "cancel_all",
+    "noop",
+];
+/// `msg_type` of the tensors sent to models besides feature inputs, which carry none.
+pub const MESSAGE_TYPES: &[&str] = &[
+    "trade_rejection",
+    "execution_report",
+    "query_response",
+    "portfolio_response",
+    "protocol_spec",
+    "heartbeat",
+];
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Python host -> Rust mediator
+    Prediction,
+    /// Rust server -> Python host
+    FeatureInput,
+}
+
+struct Schema {
+    version: &'static str,
+    direction: Direction,
+    required: &'static [&'static str],
+    /// Metadata field selecting the extra fields in `required_by`.
+    discriminator: &'static str,
+    required_by: &'static [(&'static str, &'static [&'static str])],
+    numeric: &'static [&'static str],
+}
+
+const SCHEMAS: &[Schema] = &[
+    Schema {
+        version: "1",
+        direction: Direction::Prediction,
+        required: &["cmd", "model_id", "ts"],
+        discriminator: "cmd",
+        required_by: &[
+            ("adjust_position", &["inst", "target_position"]),
+            ("cancel_order", &["account_id", "inst"]),
+            ("amend_order", &["account_id", "inst"]),
+            ("set_interval", &["scheduler", "interval_sec"]),
+        ],
+        numeric: &[
+            "ts", "target_position", "interval_sec", "size", "price", "scale", "ttl_sec",
+            "limit_price", "max_slippage_bps", "expires_at",
+        ],
+    },
+    Schema {
+        version: "1",
+        direction: Direction::FeatureInput,
+        required: &["model_id", "schema_version", "inst", "price", "pos_weight", "col_names"],
+        discriminator: "cmd",
+        required_by: &[],
+        numeric: &["price", "pos_weight"],
+    },
+    Schema {
+        version: "2",
+        direction: Direction::FeatureInput,
+        required: &["model_id", "schema_version", "inst", "price", "pos_weight", "encoding", "seq"],
+        discriminator: "encoding",
+        required_by: &[
+            ("full", &["col_names"]),
+            ("delta", &["base_seq", "changed_idx"]),
+        ],
+        numeric: &["price", "pos_weight", "seq", "base_seq"],
+    },
+];
+
+/// The protocol as this build speaks it.
+pub fn protocol_spec() -> Value {
+    let schemas: Vec<Value> = SCHEMAS
+        .iter()
+        .map(|s| {
+            let required_by: Map<String, Value> = s
+                .required_by
+                .iter()
+                .map(|(kind, fields)| (kind.to_string(), json!(fields)))
+                .collect();
+            json!({
+                "version": s.version,
+                "direction": s.direction,
+                "required": s.required,
+                "discriminator": s.discriminator,
+                "required_by": required_by,
+                "numeric": s.numeric,
+            })
+        })
+        .collect();
+
+    json!({
+        "schema_version": SCHEMA_VERSION,
+        "delta_schema_version": DELTA_SCHEMA_VERSION,
+        "commands": COMMANDS,
+        "message_types": MESSAGE_TYPES,
+        "schemas": schemas,
+    })
+}
+
+/// Checks tensor metadata against the schema of its `schema_version`.
+pub fn validate_metadata(
+    direction: Direction,
+    metadata: &HashMap<String, String>,
+) -> Result<(), Vec<String>> {
+    let version = metadata
+        .get("schema_version")
+        .map(|v| v.as_str())
+        .unwrap_or(SCHEMA_VERSION);
+
+    let Some(schema) = SCHEMAS
+        .iter()
+        .find(|s| s.direction == direction && s.version == version)
+    else {
+        return Err(vec![format!(
+            "unsupported schema_version {} for {:?}",
+            version, direction
+        )]);
+    };
+
+    let kind = metadata
+        .get(schema.discriminator)
+        .map(|c| c.as_str())
+        .unwrap_or_default();
+    let kind_required = schema
+        .required_by
+        .iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, fields)| *fields)
+        .unwrap_or_default();
+
+    let mut violations: Vec<String> = schema
+        .required
+        .iter()
+        .chain(kind_required.iter())
+        .filter(|field| metadata.get(**field).is_none_or(|v| v.trim().is_empty()))
+        .map(|field| format!("missing {}", field))
+        .collect();
+
+    for field in schema.numeric.iter() {
+        if let Some(value) = metadata.get(*field)
+            && value.parse::<f64>().map_or(true, |v| !v.is_finite())
+        {
+            violations.push(format!("{} is not a finite number: {}", field, value));
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    #[derive(Debug, Deserialize)]
+    struct GoldenCase {
+        direction: Direction,
+        valid: bool,
+        metadata: HashMap<String, String>,
+    }
+
+    /// Replays the golden protocol samples in `contracts/` against the schemas.
+    #[test]
+    fn golden_files_match_schemas() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("contracts");
+        assert!(dir.is_dir(), "contract golden files missing at {:?}", dir);
+
+        let mut failures = Vec::new();
+        let mut checked = 0;
+        for path in fs::read_dir(&dir).unwrap().flatten().map(|e| e.path()) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let content = fs::read_to_string(&path).unwrap();
+            let case: GoldenCase = serde_json::from_str(&content)
+                .unwrap_or_else(|e| panic!("Failed to parse {:?}: {}", path, e));
+
+            let result = validate_metadata(case.direction, &case.metadata);
+            if result.is_ok() != case.valid {
+                failures.push(format!("{}: {:?}", path.display(), result));
+            }
+            checked += 1;
+        }
+
+        assert!(checked > 0, "no contract golden files in {:?}", dir);
+        assert!(failures.is_empty(), "contract golden files failed: {:#?}", failures);
+    }
+}
@@ -1,8 +1,13 @@
-use extrema_infra::prelude::*;
+use extrema_infra::{arch::market_assets::api_general::get_micros_timestamp, prelude::*};
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
-use super::server_base::McpServer;
+use crate::arch::account_module::acc_utils::interval_elapsed;
+
+use super::{
+    server_base::McpServer,
+    server_trades::TRADE_YIELD_EVERY,
+};
 
 impl Strategy for McpServer {
     async fn initialize(&mut self) {
@@ -24,30 +29,112 @@ impl CommandEmitter for McpServer {
 
 impl EventHandler for McpServer {
     async fn on_schedule(&mut self, msg: InfraMsg<AltScheduleEvent>) {
-        if let Err(e) = self.periodic_send_data_to_model().await {
+        self.heartbeats
+            .beat("loop:mcp_server", self.watchdog.scheduler_stall_sec);
+
+        if self.reload_task_id == Some(msg.task_id) {
+            if let Err(e) = self.reload_models() {
+                error!("Reload models failed: {:?}", e);
+            }
+            self.collect_idle_insts();
+            return;
+        }
+
+        // A window left open by the last trade of a burst still reaches the price map
+        if self.trades.is_due() {
+            self.flush_trades();
+        }
+        self.reload_risk_limits();
+        self.expire_risk_alerts();
+        self.expire_targets();
+        self.apply_risk_off_windows();
+        self.mediate_injected().await;
+        self.publish_portfolio();
+        self.restart_stalled_candles().await;
+        self.decay_silent_models();
+        self.record_attribution();
+        self.mark_shadow_models();
+
+        let shared_due = interval_elapsed(&mut self.last_data_send, self.intervals.model_data());
+        let mut due = self.due_models(shared_due);
+        due.extend(self.stalled_models());
+        if !shared_due && due.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.periodic_send_data_to_model(&due).await {
             warn!("Failed to send data: {:?}, task: {:?}", e, msg.task_id);
         }
     }
 
     async fn on_preds(&mut self, msg: InfraMsg<AltTensor>) {
-        if let Err(e) = self.mcp_mediator(&msg.data).await {
+        let authorized = self.command_auth.authorize(&msg.data.metadata);
+        if let Err(e) = &authorized {
+            warn!("[Auth] {:?}, task: {:?}", e, msg.task_id);
+            self.audit_unchanged(&msg.data, &authorized);
+            return;
+        }
+        if !self.accept_reply(&msg.data.metadata) {
+            return;
+        }
+
+        // A reply proves the model is reachable again
+        let port = msg
+            .data
+            .metadata
+            .get("model_id")
+            .and_then(|id| self.model_config.get(id))
+            .map(|cfg| cfg.port);
+        if let Some(model_id) = msg.data.metadata.get("model_id") {
+            self.record_model_reply(model_id);
+        }
+
+        if let Some(port) = port {
+            let stall_after = self.watchdog.model_stall_sec;
+            self.heartbeats.beat(&format!("model:{}", port), stall_after);
+            self.retry_dead_letters(port).await;
+        }
+
+        if let Err(e) = self.audited_mediator(&msg.data).await {
             warn!("Failed to process MCP Mediator: {:?}, task: {:?}", e, msg.task_id);
         }
+        if self.golden.is_recording() {
+            self.record_golden(&msg.data).await;
+        }
     }
 
     async fn on_ws_event(&mut self, msg: InfraMsg<WsTaskInfo>) {
-        if !matches!(msg.data.ws_channel, WsChannel::Candles(..)) {
-            return;
-        }
+        match msg.data.ws_channel {
+            WsChannel::Candles(..) => self.candle_task = Some((msg.task_id, msg.data.clone())),
+            WsChannel::Trades(..) => {},
+            _ => return,
+        };
+        self.stream_health
+            .record_connect(Some(msg.task_id), get_micros_timestamp() / 1000);
 
-        if let Err(e) = self.connect_channel(&msg.data.ws_channel).await {
+        if let Err(e) = self.connect_channel(&msg.data, msg.task_id).await {
             error!("Failed to connect channel: {:?}", e);
         }
     }
 
     async fn on_candle(&mut self, msg: InfraMsg<Vec<WsCandle>>) {
+        self.heartbeats.beat("ws:candles", self.watchdog.candle_stall_sec);
         for t in msg.data.iter() {
-            self.px.insert(t.inst.to_string(), t.open);
+            self.px.insert((t.market.clone(), t.inst.to_string()), t.open);
+            self.record_candle(&t.market, &t.inst, t.timestamp, t.close);
+        }
+    }
+
+    async fn on_trade(&mut self, msg: InfraMsg<Vec<WsTrade>>) {
+        for (idx, trade) in msg.data.iter().enumerate() {
+            self.trades.push(trade);
+            if (idx + 1) % TRADE_YIELD_EVERY == 0 {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        if self.trades.is_due() {
+            self.flush_trades();
         }
     }
 }
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use tracing::info;
+
+use extrema_infra::prelude::*;
+
+use super::server_contract::DELTA_SCHEMA_VERSION;
+
+/// A full frame is re-sent after this many deltas even if few columns changed.
+pub const FULL_FRAME_EVERY: u32 = 60;
+/// A delta touching more than this share of the columns goes out as a new full frame.
+const MAX_DELTA_SHARE: f64 = 0.5;
+/// Error the Python host replies with when it lacks the baseline a delta refers to.
+pub const DELTA_BASELINE_ERROR: &str = "ERROR_DELTA_BASELINE";
+
+/// Last full frame sent for one model and instrument.
+#[derive(Clone, Debug)]
+struct Baseline {
+    seq: u64,
+    col_names: String,
+    data: Vec<f32>,
+    deltas_sent: u32,
+}
+
+/// Delta encoding of feature tensors for models with `delta_encoding`.
+#[derive(Clone, Debug, Default)]
+pub struct DeltaEncoder {
+    seq: HashMap<String, u64>,
+    baselines: HashMap<(String, String), Baseline>,
+}
+
+impl DeltaEncoder {
+    /// Rewrites a full feature tensor of `model_id` into the delta protocol.
+    pub fn encode(&mut self, model_id: &str, inst: &str, mut tensor: AltTensor) -> AltTensor {
+        let seq = self.seq.entry(model_id.to_string()).or_default();
+        *seq += 1;
+        let seq = *seq;
+
+        let col_names = tensor.metadata.get("col_names").cloned().unwrap_or_default();
+        let key = (model_id.to_string(), inst.to_string());
+        let delta: Option<(u64, Vec<usize>)> = self.baselines.get(&key).and_then(|base| {
+            if base.col_names != col_names
+                || base.data.len() != tensor.data.len()
+                || base.deltas_sent >= FULL_FRAME_EVERY
+            {
+                return None;
+            }
+
+            let changed: Vec<usize> = (0..tensor.data.len())
+                .filter(|&i| tensor.data[i].to_bits() != base.data[i].to_bits())
+                .collect();
+            let share = changed.len() as f64 / tensor.data.len().max(1) as f64;
+            (share <= MAX_DELTA_SHARE).then_some((base.seq, changed))
+        });
+
+        tensor
+            .metadata
+            .insert("schema_version".to_string(), DELTA_SCHEMA_VERSION.to_string());
+        tensor.metadata.insert("seq".to_string(), seq.to_string());
+
+        let Some((base_seq, changed)) = delta else {
+            tensor.metadata.insert("encoding".to_string(), "full".to_string());
+            self.baselines.insert(
+                key,
+                Baseline {
+                    seq,
+                    col_names,
+                    data: tensor.data.clone(),
+                    deltas_sent: 0,
+                },
+            );
+            return tensor;
+        };
+
+        if let Some(base) = self.baselines.get_mut(&key) {
+            base.deltas_sent += 1;
+        }
+
+        tensor.metadata.remove("col_names");
+        tensor.metadata.insert("encoding".to_string(), "delta".to_string());
+        tensor
+            .metadata
+            .insert("base_seq".to_string(), base_seq.to_string());
+        tensor.metadata.insert(
+            "changed_idx".to_string(),
+            serde_json::to_string(&changed).unwrap_or_default(),
+        );
+        tensor.data = changed.iter().map(|&i| tensor.data[i]).collect();
+        tensor.shape = vec![tensor.data.len()];
+        tensor
+    }
+
+    /// Forgets every baseline of `model_id` so its next tensors go out as full frames.
+    pub fn reset(&mut self, model_id: &str) {
+        self.baselines.retain(|(id, _), _| id != model_id);
+        info!("[Data] Delta baselines reset for model={}", model_id);
+    }
+
+    /// Forgets the baselines of `inst` for every model.
+    pub fn evict(&mut self, inst: &str) {
+        self.baselines.retain(|(_, i), _| i != inst);
+    }
+}
@@ -0,0 +1,93 @@
+use serde::Deserialize;
+use std::{collections::HashMap, fs};
+use tracing::info;
+
+use extrema_infra::prelude::*;
+
+use crate::arch::profile::config_path;
+
+pub const ENSEMBLE_FILE: &str = "ensemble_config.json";
+
+/// How the scaled targets of the models trading one instrument combine into its shared weight.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnsembleMethod {
+    /// Targets add up, each model trading its own book.
+    #[default]
+    Sum,
+    Mean,
+    /// Mean weighted by each model's `ensemble_weight`.
+    Weighted,
+    Median,
+}
+
+impl EnsembleMethod {
+    /// Combines the `(scaled target, ensemble weight)` of every model targeting the instrument.
+    pub fn combine(&self, votes: &[(f64, f64)]) -> f64 {
+        if votes.is_empty() {
+            return 0.0;
+        }
+
+        match self {
+            EnsembleMethod::Sum => votes.iter().map(|(target, _)| target).sum(),
+            EnsembleMethod::Mean => {
+                votes.iter().map(|(target, _)| target).sum::<f64>() / votes.len() as f64
+            },
+            EnsembleMethod::Weighted => {
+                let total: f64 = votes.iter().map(|(_, weight)| weight).sum();
+                if total <= f64::EPSILON {
+                    return 0.0;
+                }
+                votes
+                    .iter()
+                    .map(|(target, weight)| target * weight)
+                    .sum::<f64>()
+                    / total
+            },
+            EnsembleMethod::Median => {
+                let mut targets: Vec<f64> = votes.iter().map(|(target, _)| *target).collect();
+                targets.sort_by(|a, b| a.total_cmp(b));
+                let mid = targets.len() / 2;
+                if targets.len().is_multiple_of(2) {
+                    (targets[mid - 1] + targets[mid]) / 2.0
+                } else {
+                    targets[mid]
+                }
+            },
+        }
+    }
+}
+
+/// Ensemble layer between the per-model targets and `TargetWeights`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnsembleConfig {
+    /// Method of instruments without an override.
+    pub method: EnsembleMethod,
+    /// Method per instrument, e.g. `median` for one traded by many models.
+    pub overrides: HashMap<String, EnsembleMethod>,
+}
+
+impl EnsembleConfig {
+    pub fn method_for(&self, inst: &str) -> EnsembleMethod {
+        self.overrides.get(inst).copied().unwrap_or(self.method)
+    }
+}
+
+pub fn load_ensemble_config() -> InfraResult<EnsembleConfig> {
+    let path = config_path(ENSEMBLE_FILE)?;
+
+    if !path.exists() {
+        info!(
+            "{} not found at {:?}, summing model targets",
+            ENSEMBLE_FILE, path
+        );
+        return Ok(EnsembleConfig::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| InfraError::Msg(format!("Failed to read ensemble config file: {}", e)))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| InfraError::Msg(format!("Failed to parse ensemble config: {}", e)))
+}
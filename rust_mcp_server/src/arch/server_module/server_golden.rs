@@ -0,0 +1,274 @@
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, env::current_dir, fs, path::PathBuf};
+use tracing::info;
+
+use extrema_infra::prelude::*;
+
+/// Recorded sessions live next to the hand-written contract samples.
+pub const GOLDEN_SESSION_DIR: &str = "contracts/recorded";
+/// Replayed target weights may differ by float noise only.
+#[cfg(test)]
+const WEIGHT_TOLERANCE: f64 = 1e-9;
+/// Predictions the recorder pairs with a feature tensor.
+const RECORDED_CMDS: &[&str] = &["adjust_position", "set_interval", "noop"];
+
+/// One feature column of the row sent to the model, with its polars dtype.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GoldenColumn {
+    pub name: String,
+    pub dtype: String,
+    pub value: f64,
+}
+
+/// Serializable copy of an `AltTensor`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct GoldenTensor {
+    pub timestamp: u64,
+    pub data: Vec<f32>,
+    pub shape: Vec<usize>,
+    pub metadata: HashMap<String, String>,
+}
+
+impl From<&AltTensor> for GoldenTensor {
+    fn from(t: &AltTensor) -> Self {
+        Self {
+            timestamp: t.timestamp,
+            data: t.data.clone(),
+            shape: t.shape.clone(),
+            metadata: t.metadata.clone(),
+        }
+    }
+}
+
+impl From<&GoldenTensor> for AltTensor {
+    fn from(t: &GoldenTensor) -> Self {
+        Self {
+            timestamp: t.timestamp,
+            data: t.data.clone(),
+            shape: t.shape.clone(),
+            metadata: t.metadata.clone(),
+        }
+    }
+}
+
+/// What `mcp_mediator` did with the prediction, on a server holding only this model.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct GoldenOutcome {
+    pub error: Option<String>,
+    pub target_weight: Option<f64>,
+}
+
+impl GoldenOutcome {
+    #[cfg(test)]
+    pub fn matches(&self, other: &Self) -> bool {
+        let weights_match = match (self.target_weight, other.target_weight) {
+            (Some(a), Some(b)) => (a - b).abs() <= WEIGHT_TOLERANCE,
+            (a, b) => a == b,
+        };
+        weights_match && self.error.is_some() == other.error.is_some()
+    }
+}
+
+/// A real feature tensor and the prediction the model answered it with.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GoldenSession {
+    pub model_id: String,
+    pub inst: String,
+    pub target_leverage: Option<f64>,
+    pub input: Vec<GoldenColumn>,
+    pub price: f64,
+    pub pos_weight: f64,
+    pub features: GoldenTensor,
+    pub prediction: GoldenTensor,
+    pub outcome: GoldenOutcome,
+}
+
+impl GoldenSession {
+    /// Rebuilds the one-row frame `df_to_tensor` was called with.
+    #[cfg(test)]
+    pub fn input_frame(&self) -> InfraResult<DataFrame> {
+        let columns = self
+            .input
+            .iter()
+            .map(|col| {
+                let series = Series::new(col.name.as_str().into(), &[col.value]);
+                let series = match col.dtype.as_str() {
+                    "f32" => series.cast(&DataType::Float32)?,
+                    "i64" => series.cast(&DataType::Int64)?,
+                    "i32" => series.cast(&DataType::Int32)?,
+                    "u64" => series.cast(&DataType::UInt64)?,
+                    "u32" => series.cast(&DataType::UInt32)?,
+                    _ => series,
+                };
+                Ok(series.into())
+            })
+            .collect::<PolarsResult<Vec<Column>>>()?;
+
+        Ok(DataFrame::new(columns)?)
+    }
+}
+
+/// Feature input sent to a model, waiting for its next prediction.
+#[derive(Clone, Debug)]
+struct PendingInput {
+    inst: String,
+    input: Vec<GoldenColumn>,
+    price: f64,
+    pos_weight: f64,
+    features: GoldenTensor,
+}
+
+/// `record_golden` mode: pairs feature tensors with the predictions they answer.
+#[derive(Clone, Debug, Default)]
+pub struct GoldenRecorder {
+    remaining: usize,
+    pending: HashMap<String, PendingInput>,
+}
+
+impl GoldenRecorder {
+    pub fn new(sessions: usize) -> Self {
+        if sessions > 0 {
+            info!("[Golden] Recording {} sessions to {}", sessions, GOLDEN_SESSION_DIR);
+        }
+        Self {
+            remaining: sessions,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Keeps the last row of `df` and the tensor built from it for `model_id`.
+    pub fn record_input(
+        &mut self,
+        inst: &str,
+        df: &DataFrame,
+        price: f64,
+        pos_weight: f64,
+        features: &AltTensor,
+    ) -> InfraResult<()> {
+        if !self.is_recording() || df.height() == 0 {
+            return Ok(());
+        }
+        let Some(model_id) = features.metadata.get("model_id") else {
+            return Ok(());
+        };
+
+        let last_idx = df.height() - 1;
+        let input = df
+            .get_columns()
+            .iter()
+            .map(|col| {
+                let value = col.get(last_idx)?;
+                let dtype = match value {
+                    AnyValue::Float32(_) => "f32",
+                    AnyValue::Int64(_) => "i64",
+                    AnyValue::Int32(_) => "i32",
+                    AnyValue::UInt64(_) => "u64",
+                    AnyValue::UInt32(_) => "u32",
+                    _ => "f64",
+                };
+                Ok(GoldenColumn {
+                    name: col.name().to_string(),
+                    dtype: dtype.to_string(),
+                    value: value.extract::<f64>().unwrap_or(f64::NAN),
+                })
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+
+        self.pending.insert(
+            model_id.clone(),
+            PendingInput {
+                inst: inst.to_string(),
+                input,
+                price,
+                pos_weight,
+                features: features.into(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Session for a replayable prediction answering a recorded input.
+    pub fn take_session(
+        &mut self,
+        prediction: &AltTensor,
+        target_leverage: Option<f64>,
+    ) -> Option<GoldenSession> {
+        if !self.is_recording() {
+            return None;
+        }
+        let cmd = prediction.metadata.get("cmd")?;
+        if !RECORDED_CMDS.contains(&cmd.as_str()) {
+            return None;
+        }
+        let model_id = prediction.metadata.get("model_id")?;
+        let pending = self.pending.remove(model_id)?;
+
+        Some(GoldenSession {
+            model_id: model_id.clone(),
+            inst: pending.inst,
+            target_leverage,
+            input: pending.input,
+            price: pending.price,
+            pos_weight: pending.pos_weight,
+            features: pending.features,
+            prediction: prediction.into(),
+            outcome: GoldenOutcome::default(),
+        })
+    }
+
+    pub fn write(&mut self, session: &GoldenSession) -> InfraResult<()> {
+        let dir = golden_session_dir()?;
+        fs::create_dir_all(&dir)
+            .map_err(|e| InfraError::Msg(format!("Failed to create {:?}: {}", dir, e)))?;
+
+        let path = dir.join(format!(
+            "{}_{}_{}.json",
+            session.model_id, session.inst, session.features.timestamp
+        ));
+        let content = serde_json::to_string_pretty(session)?;
+        fs::write(&path, content)
+            .map_err(|e| InfraError::Msg(format!("Failed to write {:?}: {}", path, e)))?;
+
+        self.remaining = self.remaining.saturating_sub(1);
+        info!("[Golden] Recorded {:?}, {} left", path, self.remaining);
+        if !self.is_recording() {
+            self.pending.clear();
+        }
+        Ok(())
+    }
+}
+
+/// Every session recorded in `dir` with its file name.
+#[cfg(test)]
+pub fn load_golden_sessions(dir: &std::path::Path) -> InfraResult<Vec<(String, GoldenSession)>> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| InfraError::Msg(format!("Failed to read {:?}: {}", dir, e)))?;
+
+    let mut sessions = Vec::new();
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(session) => sessions.push((path.display().to_string(), session)),
+            Err(e) => tracing::warn!("[Golden] Skipping {:?}: {}", path, e),
+        }
+    }
+
+    Ok(sessions)
+}
+
+fn golden_session_dir() -> InfraResult<PathBuf> {
+    let mut dir = current_dir()?;
+    dir.push(GOLDEN_SESSION_DIR);
+    Ok(dir)
+}
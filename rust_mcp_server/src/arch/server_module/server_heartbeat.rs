@@ -0,0 +1,78 @@
+use std::{collections::HashMap, time::Duration};
+
+/// Missed heartbeats before a model without `max_missed_heartbeats` is unresponsive.
+pub const DEFAULT_MAX_MISSED_HEARTBEATS: u32 = 3;
+
+#[derive(Clone, Debug, Default)]
+struct Liveness {
+    /// Receipt times in milliseconds.
+    pinged_at: Option<u64>,
+    replied_at: Option<u64>,
+    missed: u32,
+    unresponsive_since: Option<u64>,
+}
+
+/// How a reply of a model was taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelReply {
+    Fresh,
+    /// First fresh reply after the model was declared unresponsive.
+    Recovered,
+    /// Computed before the model was declared unresponsive; not acted on.
+    Stale,
+}
+
+/// Heartbeats sent to each model on its data cycle and the replies they got.
+#[derive(Clone, Debug, Default)]
+pub struct ModelHeartbeats {
+    models: HashMap<String, Liveness>,
+}
+
+impl ModelHeartbeats {
+    /// Records a heartbeat sent at `now_ms`. True when the model just became unresponsive.
+    pub fn ping(&mut self, model_id: &str, now_ms: u64, max_missed: u32) -> bool {
+        let liveness = self.models.entry(model_id.to_string()).or_default();
+        if let Some(pinged_at) = liveness.pinged_at
+            && liveness.replied_at.is_none_or(|at| at < pinged_at)
+        {
+            liveness.missed += 1;
+        }
+        liveness.pinged_at = Some(now_ms);
+
+        if liveness.missed >= max_missed.max(1) && liveness.unresponsive_since.is_none() {
+            liveness.unresponsive_since = Some(now_ms);
+            return true;
+        }
+        false
+    }
+
+    /// Records a reply whose model timestamp is `ts_ms`, received at `now_ms`.
+    pub fn reply(&mut self, model_id: &str, ts_ms: u64, now_ms: u64) -> ModelReply {
+        let liveness = self.models.entry(model_id.to_string()).or_default();
+        if liveness
+            .unresponsive_since
+            .is_some_and(|since| ts_ms < since)
+        {
+            return ModelReply::Stale;
+        }
+
+        liveness.replied_at = Some(now_ms);
+        liveness.missed = 0;
+        match liveness.unresponsive_since.take() {
+            Some(_) => ModelReply::Recovered,
+            None => ModelReply::Fresh,
+        }
+    }
+
+    /// Time since the model was declared unresponsive, if it is.
+    pub fn unresponsive_for(&self, model_id: &str, now_ms: u64) -> Option<Duration> {
+        self.models
+            .get(model_id)
+            .and_then(|l| l.unresponsive_since)
+            .map(|since| Duration::from_millis(now_ms.saturating_sub(since)))
+    }
+
+    pub fn forget(&mut self, model_id: &str) {
+        self.models.remove(model_id);
+    }
+}
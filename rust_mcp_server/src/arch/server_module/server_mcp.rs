@@ -0,0 +1,412 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, stdin, stdout},
+    task::JoinHandle,
+};
+use tracing::{error, info, warn};
+
+use extrema_infra::{arch::market_assets::api_general::get_micros_timestamp, prelude::*};
+
+use super::server_contract::SCHEMA_VERSION;
+use crate::arch::{
+    account_module::{
+        acc_exposure::ExposureReport,
+        acc_requests::{AccountRequest, AccountRequests},
+    },
+    control_module::{ctl_attribution::AttributionHistory, ctl_portfolio::PortfolioView},
+};
+
+/// MCP revision implemented by `McpHandler`.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Order management tools wait this long for `AccountManager`, which answers once a tick.
+const ACCOUNT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Rows `get_attribution_history` returns without a `limit`.
+const DEFAULT_ATTRIBUTION_ROWS: usize = 1000;
+
+/// Latest feature row per instrument, as last sent to the models.
+#[derive(Clone, Debug, Serialize)]
+pub struct FeatureRow {
+    pub timestamp: u64,
+    pub values: BTreeMap<String, f32>,
+}
+
+pub type LatestFeatures = Arc<DashMap<String, FeatureRow>>;
+/// Commands from MCP clients, run through `McpServer::mcp_mediator`.
+pub type InjectedCommands = Arc<Mutex<VecDeque<AltTensor>>>;
+
+/// Model Context Protocol tools shared by the stdio and HTTP transports.
+#[derive(Clone)]
+pub struct McpHandler {
+    portfolio: PortfolioView,
+    features: LatestFeatures,
+    commands: InjectedCommands,
+    /// Configured models an MCP client may act as in `adjust_position`.
+    model_ids: Vec<String>,
+    account_requests: AccountRequests,
+    attribution: AttributionHistory,
+}
+
+/// MCP over stdio: one JSON-RPC 2.0 message per line.
+pub struct McpStdio {
+    handler: McpHandler,
+}
+
+impl McpStdio {
+    pub fn new(handler: McpHandler) -> Self {
+        Self { handler }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            if let Err(e) = self.run().await {
+                error!("[MCP] Stdio transport stopped: {}", e);
+            }
+        })
+    }
+
+    async fn run(self) -> InfraResult<()> {
+        info!(
+            "[MCP] Serving JSON-RPC on stdio, models={:?}",
+            self.handler.model_ids
+        );
+        let mut lines = BufReader::new(stdin()).lines();
+        let mut out = stdout();
+
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| InfraError::Msg(format!("MCP stdin read failed: {}", e)))?
+        {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let Some(reply) = self.handler.handle_line(&line, "mcp_stdio").await else {
+                continue;
+            };
+            out.write_all(format!("{}\n", reply).as_bytes())
+                .await
+                .and(out.flush().await)
+                .map_err(|e| InfraError::Msg(format!("MCP stdout write failed: {}", e)))?;
+        }
+
+        info!("[MCP] Stdin closed");
+        Ok(())
+    }
+}
+
+impl McpHandler {
+    pub fn new(
+        portfolio: PortfolioView,
+        features: LatestFeatures,
+        commands: InjectedCommands,
+        model_ids: Vec<String>,
+    ) -> Self {
+        Self {
+            portfolio,
+            features,
+            commands,
+            model_ids,
+            account_requests: AccountRequests::default(),
+            attribution: AttributionHistory::default(),
+        }
+    }
+
+    pub fn with_account_requests(&mut self, account_requests: AccountRequests) -> &mut Self {
+        self.account_requests = account_requests;
+        self
+    }
+
+    pub fn with_attribution(&mut self, attribution: AttributionHistory) -> &mut Self {
+        self.attribution = attribution;
+        self
+    }
+
+    /// Reply to one message; notifications get none. `source` tags the commands it queues.
+    pub async fn handle_line(&self, line: &str, source: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(rpc_error(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        let id = request.get("id").cloned()?;
+        let method = request
+            .get("method")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "extrema_mcp_server",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            }),
+            "ping" => json!({}),
+            "tools/list" => json!({ "tools": self.tools() }),
+            "tools/call" => {
+                let Some(name) = params.get("name").and_then(|n| n.as_str()) else {
+                    return Some(rpc_error(id, INVALID_PARAMS, "tools/call: name missing"));
+                };
+                let args = params.get("arguments").cloned().unwrap_or(json!({}));
+
+                // Tool failures are results the client shows the model, not protocol errors
+                match self.call_tool(name, &args, source).await {
+                    Ok(content) => tool_result(&content, false),
+                    Err(e) => {
+                        warn!("[MCP] Tool {} failed: {}", name, e);
+                        tool_result(&json!(e.to_string()), true)
+                    },
+                }
+            },
+            other => {
+                return Some(rpc_error(
+                    id,
+                    METHOD_NOT_FOUND,
+                    &format!("unknown method {}", other),
+                ));
+            },
+        };
+
+        Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    fn tools(&self) -> Value {
+        json!([
+            {
+                "name": "get_positions",
+                "description": "Equity, actual weights, pending diffs and state of every \
+                    account, and the net exposure per underlying asset across accounts.",
+                "inputSchema": { "type": "object", "properties": {} },
+            },
+            {
+                "name": "get_target_weights",
+                "description": "Consolidated target weight and last price per instrument.",
+                "inputSchema": { "type": "object", "properties": {} },
+            },
+            {
+                "name": "get_features",
+                "description": "Latest feature row of an instrument, as sent to the models.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "inst": { "type": "string", "description": "e.g. DOGE_USDT_PERP" },
+                    },
+                    "required": ["inst"],
+                },
+            },
+            {
+                "name": "adjust_position",
+                "description": "Sets the raw target weight of an instrument for a model, \
+                    like the model's own adjust_position command. Applied on the next tick, \
+                    combined with the other models' targets, scaled to their target_leverage \
+                    and capped by the risk limits. With limit_price or max_slippage_bps, \
+                    orders go out as IOC limits at that bound and are skipped while the \
+                    market is past it. With expires_at \
+                    (ms), the weight reverts to the model's previous target then.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "model_id": { "type": "string", "enum": self.model_ids },
+                        "inst": { "type": "string" },
+                        "target_weight": { "type": "number", "minimum": -1.0, "maximum": 1.0 },
+                        "limit_price": { "type": "number", "exclusiveMinimum": 0.0 },
+                        "max_slippage_bps": { "type": "number", "minimum": 0.0 },
+                        "expires_at": { "type": "integer", "minimum": 0 },
+                    },
+                    "required": ["model_id", "inst", "target_weight"],
+                },
+            },
+            {
+                "name": "get_attribution_history",
+                "description": "Time series of each model's scaled target, the consolidated \
+                    target (source \"consolidated\") and every account's executed weight per \
+                    instrument, sampled every minute, to see how quickly and faithfully the \
+                    accounts followed each signal. Oldest first.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "inst": { "type": "string" },
+                        "source": { "type": "string" },
+                        "since": { "type": "integer", "minimum": 0, "description": "ms" },
+                        "limit": { "type": "integer", "minimum": 1 },
+                    },
+                },
+            },
+            {
+                "name": "list_open_orders",
+                "description": "Resting orders of an account with side, size, price and age.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": { "account_id": { "type": "string" } },
+                    "required": ["account_id"],
+                },
+            },
+            {
+                "name": "cancel_all_orders",
+                "description": "Cancels the resting orders of an account, or only those on \
+                    one instrument, e.g. to clean up stuck orders. Returns the cancelled and \
+                    failed counts.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "account_id": { "type": "string" },
+                        "inst": { "type": "string" },
+                    },
+                    "required": ["account_id"],
+                },
+            },
+        ])
+    }
+
+    async fn call_tool(&self, name: &str, args: &Value, source: &str) -> InfraResult<Value> {
+        let arg = |key: &str| {
+            args.get(key)
+                .ok_or_else(|| InfraError::Msg(format!("{}: {} missing", name, key)))
+        };
+
+        match name {
+            "get_positions" => {
+                let snapshot = self.portfolio.read();
+                Ok(json!({
+                    "accounts": snapshot.accounts,
+                    "exposure": ExposureReport::from_accounts(&snapshot.accounts),
+                    "snapshot_version": snapshot.version,
+                    "snapshot_ts": snapshot.timestamp,
+                }))
+            },
+            "get_target_weights" => {
+                let snapshot = self.portfolio.read();
+                let targets: BTreeMap<&String, f64> = snapshot
+                    .targets
+                    .iter()
+                    .map(|(inst, (_, weight))| (inst, *weight))
+                    .collect();
+                Ok(json!({
+                    "targets": targets,
+                    "prices": snapshot.prices,
+                    "snapshot_version": snapshot.version,
+                    "snapshot_ts": snapshot.timestamp,
+                }))
+            },
+            "get_features" => {
+                let inst = arg("inst")?.as_str().unwrap_or_default();
+                let row = self.features.get(inst).ok_or_else(|| {
+                    InfraError::Msg(format!("get_features: no features for {} yet", inst))
+                })?;
+                Ok(json!({ "inst": inst, "features": *row }))
+            },
+            "adjust_position" => {
+                let model_id = arg("model_id")?.as_str().unwrap_or_default();
+                let inst = arg("inst")?.as_str().unwrap_or_default();
+                let target = arg("target_weight")?
+                    .as_f64()
+                    .filter(|w| w.is_finite() && w.abs() <= 1.0)
+                    .ok_or_else(|| {
+                        InfraError::Msg("adjust_position: target_weight not in [-1, 1]".into())
+                    })?;
+                if !self.model_ids.iter().any(|id| id == model_id) {
+                    return Err(InfraError::Msg(format!(
+                        "adjust_position: unknown model {}",
+                        model_id
+                    )));
+                }
+                if inst.is_empty() {
+                    return Err(InfraError::Msg("adjust_position: inst missing".into()));
+                }
+
+                let mut metadata: HashMap<String, String> = [
+                    ("cmd", "adjust_position".to_string()),
+                    ("model_id", model_id.to_string()),
+                    ("inst", inst.to_string()),
+                    ("target_position", target.to_string()),
+                    ("ts", (get_micros_timestamp() / 1000).to_string()),
+                    ("schema_version", SCHEMA_VERSION.to_string()),
+                    ("source", source.to_string()),
+                ]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect();
+                for key in ["limit_price", "max_slippage_bps"] {
+                    if let Some(value) = args.get(key).and_then(|v| v.as_f64()) {
+                        metadata.insert(key.to_string(), value.to_string());
+                    }
+                }
+                if let Some(expires_at) = args.get("expires_at").and_then(|v| v.as_u64()) {
+                    metadata.insert("expires_at".to_string(), expires_at.to_string());
+                }
+                self.commands
+                    .lock()
+                    .map_err(|_| InfraError::Msg("MCP command queue poisoned".into()))?
+                    .push_back(AltTensor {
+                        timestamp: get_micros_timestamp(),
+                        data: vec![0.0],
+                        shape: vec![1],
+                        metadata,
+                    });
+
+                info!(
+                    "[MCP] adjust_position queued via {}: model={} inst={} target={}",
+                    source, model_id, inst, target
+                );
+                Ok(json!({ "queued": true, "model_id": model_id, "inst": inst, "target": target }))
+            },
+            "get_attribution_history" => {
+                let inst = args.get("inst").and_then(|i| i.as_str());
+                let source = args.get("source").and_then(|s| s.as_str());
+                let since = args.get("since").and_then(|s| s.as_u64()).unwrap_or(0);
+                let limit = args
+                    .get("limit")
+                    .and_then(|l| l.as_u64())
+                    .map_or(DEFAULT_ATTRIBUTION_ROWS, |l| l as usize);
+                let rows = self.attribution.query(inst, source, since, limit);
+                Ok(json!({ "rows": rows }))
+            },
+            "list_open_orders" => {
+                let account_id = arg("account_id")?.as_str().unwrap_or_default().to_string();
+                let request = AccountRequest::ListOpenOrders { account_id };
+                self.account_requests
+                    .call(request, ACCOUNT_REQUEST_TIMEOUT)
+                    .await
+            },
+            "cancel_all_orders" => {
+                let account_id = arg("account_id")?.as_str().unwrap_or_default().to_string();
+                let inst = args.get("inst").and_then(|i| i.as_str()).map(String::from);
+                info!(
+                    "[MCP] cancel_all_orders via {}: account={} inst={:?}",
+                    source, account_id, inst
+                );
+                let request = AccountRequest::CancelAll { account_id, inst };
+                self.account_requests
+                    .call(request, ACCOUNT_REQUEST_TIMEOUT)
+                    .await
+            },
+            other => Err(InfraError::Msg(format!("unknown tool {}", other))),
+        }
+    }
+}
+
+fn tool_result(content: &Value, is_error: bool) -> Value {
+    let text = match content {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
@@ -0,0 +1,238 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{get, post},
+};
+use dashmap::DashMap;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    convert::Infallible,
+    env,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::{
+    net::TcpListener,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+    task::JoinHandle,
+};
+use tokio_stream::{StreamExt, wrappers::UnboundedReceiverStream};
+use tracing::{error, info, warn};
+
+use extrema_infra::{arch::market_assets::api_general::get_micros_timestamp, prelude::*};
+
+use super::server_mcp::McpHandler;
+use crate::arch::control_module::ctl_admin::tokens_match;
+
+/// Comma-separated bearer tokens accepted by the HTTP transport.
+pub const MCP_HTTP_TOKENS_ENV: &str = "MCP_HTTP_TOKENS";
+/// Shorter tokens are ignored at startup.
+pub const MIN_HTTP_TOKEN_LEN: usize = 16;
+/// Open SSE sessions beyond this are refused until one disconnects.
+const MAX_SSE_SESSIONS: usize = 32;
+
+/// Open SSE stream: the client that opened it and the sender of its replies.
+type SseSession = (usize, UnboundedSender<Value>);
+
+#[derive(Deserialize)]
+struct SessionQuery {
+    session_id: String,
+}
+
+#[derive(Clone)]
+struct HttpState {
+    handler: McpHandler,
+    tokens: Arc<Vec<String>>,
+    sessions: Arc<DashMap<String, SseSession>>,
+    next_session: Arc<AtomicU64>,
+}
+
+/// MCP over HTTP for remote agents and internal tools, next to `McpStdio`.
+pub struct McpHttp {
+    addr: String,
+    state: HttpState,
+}
+
+impl McpHttp {
+    pub fn new(addr: &str, handler: McpHandler) -> Self {
+        Self {
+            addr: addr.to_string(),
+            state: HttpState {
+                handler,
+                tokens: Arc::new(load_tokens()),
+                sessions: Arc::new(DashMap::new()),
+                next_session: Arc::new(AtomicU64::new(0)),
+            },
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            if let Err(e) = self.run().await {
+                error!("[MCP] HTTP transport stopped: {}", e);
+            }
+        })
+    }
+
+    async fn run(self) -> InfraResult<()> {
+        if self.state.tokens.is_empty() {
+            return Err(InfraError::Msg(format!(
+                "{} not set, refusing to serve MCP over HTTP without authentication",
+                MCP_HTTP_TOKENS_ENV
+            )));
+        }
+
+        let listener = TcpListener::bind(&self.addr)
+            .await
+            .map_err(|e| InfraError::Msg(format!("MCP HTTP bind {} failed: {}", self.addr, e)))?;
+        info!(
+            "[MCP] Serving HTTP/SSE on {}, {} client token(s)",
+            self.addr,
+            self.state.tokens.len()
+        );
+
+        let app = Router::new()
+            .route("/sse", get(open_sse))
+            .route("/message", post(post_message))
+            .route("/mcp", post(post_mcp))
+            .with_state(self.state);
+        axum::serve(listener, app)
+            .await
+            .map_err(|e| InfraError::Msg(format!("MCP HTTP server failed: {}", e)))
+    }
+}
+
+fn load_tokens() -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    for token in env::var(MCP_HTTP_TOKENS_ENV).unwrap_or_default().split(',') {
+        let token = token.trim();
+        if token.is_empty() || tokens.iter().any(|t| t == token) {
+            continue;
+        }
+        if token.len() < MIN_HTTP_TOKEN_LEN {
+            warn!(
+                "[MCP] Ignoring HTTP token #{}: shorter than {} chars",
+                tokens.len() + 1,
+                MIN_HTTP_TOKEN_LEN
+            );
+            continue;
+        }
+        tokens.push(token.to_string());
+    }
+    tokens
+}
+
+impl HttpState {
+    /// Index of the client whose bearer token the request carries.
+    fn authorize(&self, headers: &HeaderMap) -> Option<usize> {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| {
+                self.tokens
+                    .iter()
+                    .position(|t| tokens_match(t, token.trim()))
+            })
+    }
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        "missing or unknown bearer token",
+    )
+        .into_response()
+}
+
+async fn open_sse(State(state): State<HttpState>, headers: HeaderMap) -> Response {
+    let Some(client) = state.authorize(&headers) else {
+        return unauthorized();
+    };
+
+    state.sessions.retain(|_, (_, tx)| !tx.is_closed());
+    if state.sessions.len() >= MAX_SSE_SESSIONS {
+        warn!(
+            "[MCP] SSE session refused for client#{}: {} open",
+            client + 1,
+            MAX_SSE_SESSIONS
+        );
+        return (StatusCode::SERVICE_UNAVAILABLE, "too many MCP sessions").into_response();
+    }
+
+    let session_id = format!(
+        "{:x}{:04x}",
+        get_micros_timestamp(),
+        state.next_session.fetch_add(1, Ordering::Relaxed) & 0xffff
+    );
+    let (tx, rx) = unbounded_channel::<Value>();
+    state.sessions.insert(session_id.clone(), (client, tx));
+    info!(
+        "[MCP] SSE session {} opened by client#{}",
+        session_id,
+        client + 1
+    );
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("/message?session_id={}", session_id));
+    let replies = UnboundedReceiverStream::new(rx)
+        .map(|reply| Event::default().event("message").data(reply.to_string()));
+    let events = tokio_stream::once(endpoint)
+        .chain(replies)
+        .map(Ok::<_, Infallible>);
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn post_message(
+    State(state): State<HttpState>,
+    Query(query): Query<SessionQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let Some(client) = state.authorize(&headers) else {
+        return unauthorized();
+    };
+
+    // Sessions are bound to the client that opened them
+    let Some(tx) = state
+        .sessions
+        .get(&query.session_id)
+        .filter(|session| session.0 == client)
+        .map(|session| session.1.clone())
+    else {
+        return (StatusCode::NOT_FOUND, "unknown MCP session").into_response();
+    };
+
+    if let Some(reply) = state.handler.handle_line(&body, "mcp_http").await
+        && tx.send(reply).is_err()
+    {
+        state.sessions.remove(&query.session_id);
+        info!("[MCP] SSE session {} closed", query.session_id);
+        return (StatusCode::GONE, "MCP session closed").into_response();
+    }
+    StatusCode::ACCEPTED.into_response()
+}
+
+async fn post_mcp(State(state): State<HttpState>, headers: HeaderMap, body: String) -> Response {
+    if state.authorize(&headers).is_none() {
+        return unauthorized();
+    }
+
+    match state.handler.handle_line(&body, "mcp_http").await {
+        Some(reply) => Json(reply).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
@@ -0,0 +1,146 @@
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env::current_dir,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    time::Duration,
+};
+use tracing::info;
+
+use extrema_infra::prelude::*;
+
+use super::server_utils::{ModelConfig, model_scale};
+use crate::arch::account_module::acc_rest::now_millis;
+
+pub const SHADOW_REPORT_FILE: &str = "shadow_report.jsonl";
+/// Spacing of the shadow report lines.
+pub const SHADOW_REPORT_INTERVAL: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Debug, Default)]
+struct ShadowModel {
+    /// Raw targets as the model sent them.
+    targets: HashMap<String, f64>,
+    /// Price each targeted instrument was last marked at.
+    marks: HashMap<String, f64>,
+    cum_return: f64,
+    predictions: u64,
+}
+
+/// Hypothetical performance of one shadow model.
+#[derive(Clone, Debug, Serialize)]
+pub struct ShadowRow {
+    pub model_id: String,
+    pub predictions: u64,
+    /// Return on equity of holding the model's scaled targets.
+    pub cum_return: f64,
+    /// Scaled target weight per instrument, as it would have been consolidated.
+    pub weights: BTreeMap<String, f64>,
+}
+
+/// Targets of models in `shadow` mode, marked to market every tick.
+#[derive(Clone, Debug)]
+pub struct ShadowBook {
+    path: PathBuf,
+    models: HashMap<String, ShadowModel>,
+}
+
+impl Default for ShadowBook {
+    fn default() -> Self {
+        Self::new(SHADOW_REPORT_FILE)
+    }
+}
+
+impl ShadowBook {
+    pub fn new(file_name: &str) -> Self {
+        let mut path = current_dir().unwrap_or_default();
+        path.push(file_name);
+        Self {
+            path,
+            models: HashMap::new(),
+        }
+    }
+
+    /// Records a prediction.
+    pub fn set_target(&mut self, model_id: &str, inst: &str, target: f64) {
+        let model = self.models.entry(model_id.to_string()).or_default();
+        model.targets.insert(inst.to_string(), target);
+        model.predictions += 1;
+    }
+
+    /// Accrues the return of each model's scaled weights since the last mark.
+    pub fn mark(
+        &mut self,
+        model_config: &HashMap<String, ModelConfig>,
+        price: impl Fn(&str) -> Option<f64>,
+    ) {
+        for (model_id, model) in self.models.iter_mut() {
+            let scale = model_scale(&model.targets, model_config.get(model_id));
+            for (inst, target) in model.targets.iter() {
+                let Some(px) = price(inst) else {
+                    continue;
+                };
+                if let Some(last) = model.marks.insert(inst.clone(), px)
+                    && last > 0.0
+                {
+                    model.cum_return += target * scale * (px / last - 1.0);
+                }
+            }
+        }
+    }
+
+    pub fn rows(&self, model_config: &HashMap<String, ModelConfig>) -> Vec<ShadowRow> {
+        let mut rows: Vec<ShadowRow> = self
+            .models
+            .iter()
+            .map(|(model_id, model)| {
+                let scale = model_scale(&model.targets, model_config.get(model_id));
+                ShadowRow {
+                    model_id: model_id.clone(),
+                    predictions: model.predictions,
+                    cum_return: model.cum_return,
+                    weights: model
+                        .targets
+                        .iter()
+                        .map(|(inst, target)| (inst.clone(), target * scale))
+                        .collect(),
+                }
+            })
+            .collect();
+
+        rows.sort_by(|a, b| a.model_id.cmp(&b.model_id));
+        rows
+    }
+
+    /// Logs and appends the current rows.
+    pub fn flush(&self, model_config: &HashMap<String, ModelConfig>) -> InfraResult<()> {
+        let rows = self.rows(model_config);
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        for r in rows.iter() {
+            info!(
+                "[Shadow] model={} predictions={} cum_return={:.4} weights={:?}",
+                r.model_id, r.predictions, r.cum_return, r.weights,
+            );
+        }
+
+        let line = serde_json::json!({ "timestamp": now_millis(), "rows": rows });
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| InfraError::Msg(format!("Failed to open shadow report: {}", e)))?;
+        writeln!(file, "{}", line)
+            .map_err(|e| InfraError::Msg(format!("Failed to write shadow report: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Drops a model that was removed or went live.
+    pub fn forget(&mut self, model_id: &str) {
+        self.models.remove(model_id);
+    }
+}
@@ -0,0 +1,129 @@
+use reqwest::Client;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+use extrema_infra::prelude::*;
+
+use crate::arch::{
+    account_module::acc_rest::binance_base_url,
+    feats::term_structure::{QuarterlyContract, TermStructure, quarterly_contracts},
+    profile::active_profile,
+};
+
+/// Listings only roll at quarterly delivery; hourly discovery catches new contracts in time.
+const CONTRACTS_REFRESH: Duration = Duration::from_secs(3600);
+/// First wait after a failed discovery, doubled per failure up to `CONTRACTS_REFRESH`.
+const CONTRACTS_RETRY: Duration = Duration::from_secs(30);
+/// Every instrument of one feature pass reads the same mark price snapshot.
+const MARKS_REFRESH: Duration = Duration::from_secs(10);
+
+/// Public Binance UM prices of the quarterly futures of each perp.
+#[derive(Clone, Debug, Default)]
+pub struct TermStructureFeed {
+    client: Client,
+    contracts: HashMap<String, Vec<QuarterlyContract>>,
+    /// Earliest next discovery; `None` before the first.
+    next_refresh: Option<Instant>,
+    failures: u32,
+    marks: HashMap<String, f64>,
+    marks_at: Option<Instant>,
+}
+
+impl TermStructureFeed {
+    /// Curve of `inst` at `now_ms`; all zero for instruments without quarterlies.
+    pub async fn term_structure(&mut self, inst: &str, now_ms: u64) -> InfraResult<TermStructure> {
+        if self.next_refresh.is_none_or(|at| Instant::now() >= at) {
+            self.refresh_contracts().await?;
+        }
+        let Some(contracts) = self.contracts.get(inst).cloned() else {
+            return Ok(TermStructure::default());
+        };
+
+        if self.marks_at.is_none_or(|at| at.elapsed() >= MARKS_REFRESH) {
+            self.refresh_marks().await?;
+        }
+        let perp_px = self.mark_price(&inst.trim_end_matches("_PERP").replace('_', ""))?;
+        let mut points = Vec::with_capacity(contracts.len());
+        for contract in contracts.iter() {
+            points.push((contract.delivery_ms, self.mark_price(&contract.symbol)?));
+        }
+
+        Ok(TermStructure::from_prices(perp_px, &points, now_ms))
+    }
+
+    /// Rediscovers the listings.
+    async fn refresh_contracts(&mut self) -> InfraResult<()> {
+        let info = match self.get("/fapi/v1/exchangeInfo").await {
+            Ok(info) => info,
+            Err(e) => {
+                let backoff = CONTRACTS_RETRY
+                    .saturating_mul(1 << self.failures.min(16))
+                    .min(CONTRACTS_REFRESH);
+                self.failures += 1;
+                self.next_refresh = Some(Instant::now() + backoff);
+                warn!("[Data] Term structure discovery failed, retrying in {:?}", backoff);
+                return Err(e);
+            },
+        };
+
+        self.contracts = quarterly_contracts(&info);
+        self.failures = 0;
+        self.next_refresh = Some(Instant::now() + CONTRACTS_REFRESH);
+        info!(
+            "[Data] Term structure: {} perps with quarterly futures",
+            self.contracts.len()
+        );
+        Ok(())
+    }
+
+    /// Reads the mark prices of all symbols in one request.
+    async fn refresh_marks(&mut self) -> InfraResult<()> {
+        let resp = self.get("/fapi/v1/premiumIndex").await?;
+        self.marks = resp
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                let symbol = row["symbol"].as_str()?;
+                let px = row["markPrice"].as_str()?.parse::<f64>().ok()?;
+                Some((symbol.to_string(), px))
+            })
+            .collect();
+        self.marks_at = Some(Instant::now());
+        Ok(())
+    }
+
+    fn mark_price(&self, symbol: &str) -> InfraResult<f64> {
+        self.marks
+            .get(symbol)
+            .copied()
+            .ok_or_else(|| InfraError::Msg(format!("No mark price for {}", symbol)))
+    }
+
+    async fn get(&self, path: &str) -> InfraResult<Value> {
+        let base_url = binance_base_url(active_profile().testnet);
+        let resp = self
+            .client
+            .get(format!("{}{}", base_url, path))
+            .send()
+            .await
+            .map_err(|e| InfraError::Msg(format!("Binance request {} failed: {}", path, e)))?;
+
+        let status = resp.status();
+        let value: Value = resp.json().await.map_err(|e| {
+            InfraError::Msg(format!("Binance response {} parse failed: {}", path, e))
+        })?;
+        if !status.is_success() {
+            return Err(InfraError::Msg(format!(
+                "Binance {} error ({}): {}",
+                path, status, value
+            )));
+        }
+
+        Ok(value)
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use extrema_infra::prelude::*;
+
+/// Trades of one window are folded into a single activity update per instrument.
+pub const TRADE_COALESCE_MS: u64 = 250;
+/// Venue instruments buffered per window; trades of further instruments are dropped.
+pub const MAX_TRADE_INSTS: usize = 512;
+/// Trades handled per yield, so a burst cannot hold the executor away from other handlers.
+pub const TRADE_YIELD_EVERY: usize = 1024;
+
+/// Activity of one instrument within the current window.
+#[derive(Clone, Debug, Default)]
+pub struct TradeBucket {
+    pub last_ts: u64,
+    pub volume: f64,
+    /// Taker buy and sell volume, for the order-flow toxicity estimate.
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub count: u64,
+}
+
+/// Bounded, time-coalesced buffer between the trade stream and the order-flow state.
+#[derive(Clone, Debug)]
+pub struct TradeCoalescer {
+    window: Duration,
+    buckets: HashMap<(Market, String), TradeBucket>,
+    window_start: Option<Instant>,
+    received: u64,
+    dropped: u64,
+}
+
+impl Default for TradeCoalescer {
+    fn default() -> Self {
+        Self::new(TRADE_COALESCE_MS)
+    }
+}
+
+impl TradeCoalescer {
+    pub fn new(window_ms: u64) -> Self {
+        Self {
+            window: Duration::from_millis(window_ms),
+            buckets: HashMap::new(),
+            window_start: None,
+            received: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn push(&mut self, trade: &WsTrade) {
+        self.window_start.get_or_insert_with(Instant::now);
+        self.received += 1;
+
+        if !trade.price.is_finite() || trade.price <= 0.0 {
+            self.dropped += 1;
+            return;
+        }
+
+        let key = (trade.market.clone(), trade.inst.clone());
+        if !self.buckets.contains_key(&key) && self.buckets.len() >= MAX_TRADE_INSTS {
+            self.dropped += 1;
+            return;
+        }
+
+        let bucket = self.buckets.entry(key).or_default();
+        // Venues may deliver a batch out of order
+        bucket.last_ts = bucket.last_ts.max(trade.timestamp);
+        bucket.volume += trade.size.abs();
+        match trade.side {
+            OrderSide::BUY => bucket.buy_volume += trade.size.abs(),
+            _ => bucket.sell_volume += trade.size.abs(),
+        };
+        bucket.count += 1;
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.window_start
+            .is_some_and(|start| start.elapsed() >= self.window)
+    }
+
+    /// Closes the window: returns its buckets with the window's received and dropped counts.
+    pub fn drain(&mut self) -> (HashMap<(Market, String), TradeBucket>, u64, u64) {
+        let stats = (self.received, self.dropped);
+        self.window_start = None;
+        self.received = 0;
+        self.dropped = 0;
+
+        (std::mem::take(&mut self.buckets), stats.0, stats.1)
+    }
+}
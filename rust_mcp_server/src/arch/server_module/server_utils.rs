@@ -1,12 +1,62 @@
-use serde::Deserialize;
-use std::{env::current_dir, fs};
-use tracing::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    fs,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{error, info, warn};
 
-use extrema_infra::errors::*;
+use extrema_infra::{errors::*, prelude::*};
+
+use super::server_ensemble::EnsembleConfig;
+use crate::arch::{control_module::ctl_auth::ModelPermission, profile::config_path};
+
+/// One day of one-minute candles per instrument.
+pub const MAX_CANDLE_HISTORY: usize = 1440;
+/// Column of the persisted candle closes in the feature store.
+pub const CANDLE_CLOSE_COL: &str = "close";
+/// Undelivered model tensors kept for redelivery, across all ports.
+pub const MAX_DEAD_LETTERS: usize = 256;
+/// Older dead letters are stale market data and are dropped instead of redelivered.
+pub const DEAD_LETTER_TTL: Duration = Duration::from_secs(300);
+
+/// Pinned feature column order per model, see `ColumnPins`.
+pub const COLUMN_ORDER_FILE: &str = "column_order.json";
+
+/// Instrument universe streamed by the server.
+pub const UNIVERSE_FILE: &str = "universe.json";
+/// Universe when no `universe.json` exists.
+pub const DEFAULT_INST: &str = "DOGE_USDT_PERP";
+
+/// Instruments of `universe.json`, or `DEFAULT_INST` alone when the file does not exist.
+pub fn load_universe() -> InfraResult<Vec<String>> {
+    let path = config_path(UNIVERSE_FILE)?;
+    if !path.exists() {
+        info!("{} not found at {:?}, universe is {}", UNIVERSE_FILE, path, DEFAULT_INST);
+        return Ok(vec![DEFAULT_INST.to_string()]);
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| InfraError::Msg(format!("Failed to read universe file: {}", e)))?;
+    let insts: Vec<String> = serde_json::from_str(&content)
+        .map_err(|e| InfraError::Msg(format!("Failed to parse universe file: {}", e)))?;
+
+    let mut universe: Vec<String> = Vec::new();
+    for inst in insts {
+        let inst = inst.trim().to_string();
+        if !inst.is_empty() && !universe.contains(&inst) {
+            universe.push(inst);
+        }
+    }
+    if universe.is_empty() {
+        return Err(InfraError::Msg(format!("{} lists no instruments", UNIVERSE_FILE)));
+    }
+    Ok(universe)
+}
 
 pub fn load_model_config() -> InfraResult<Vec<ModelConfig>> {
-    let mut path = current_dir()?;
-    path.push("model_config.json");
+    let path = config_path("model_config.json")?;
 
     info!("model_config path: {:?}", path);
 
@@ -26,12 +76,103 @@ pub fn load_model_config() -> InfraResult<Vec<ModelConfig>> {
     Ok(configs)
 }
 
+/// One ZeroMQ prediction task per distinct configured port.
+pub fn build_model_tasks(configs: &[ModelConfig], reserved_task_ids: &[u64]) -> Vec<TaskInfo> {
+    let ports: BTreeSet<u64> = configs
+        .iter()
+        .filter(|cfg| {
+            let valid = cfg.port > 0 && cfg.port <= u16::MAX as u64;
+            if !valid {
+                warn!("Skipping model={} with invalid port {}", cfg.model_id, cfg.port);
+            } else if reserved_task_ids.contains(&cfg.port) {
+                error!(
+                    "Skipping model={}: port {} collides with a task id",
+                    cfg.model_id, cfg.port
+                );
+                return false;
+            }
+            valid
+        })
+        .map(|cfg| cfg.port)
+        .collect();
 
-#[derive(Clone, Debug, Deserialize)]
+    ports
+        .into_iter()
+        .map(|port| {
+            info!("Model task bound to ZeroMQ port {}", port);
+            TaskInfo::AltTask(Arc::new(AltTaskInfo {
+                alt_task_type: AltTaskType::ModelPreds(port),
+                chunk: 1,
+                task_base_id: Some(port),
+            }))
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct ModelConfig {
     pub port: u64,
     pub model_id: String,
     pub account_id: String,
+    /// Gross exposure the model brings to the combined book; without one, its raw gross.
+    #[serde(default)]
+    pub target_leverage: Option<f64>,
+    /// Instruments whose feature frames this model receives.
+    #[serde(default)]
+    pub insts: Vec<String>,
+    /// Reject tensors that break the protocol contract instead of only logging them.
+    #[serde(default)]
+    pub strict_contract: bool,
+    /// Once the model stops replying, its targets fade linearly to zero over this many seconds.
+    #[serde(default)]
+    pub decay_sec: Option<u64>,
+    /// Send feature tensors as deltas against a periodic full frame (schema version 2).
+    #[serde(default)]
+    pub delta_encoding: bool,
+    /// Raw targets the model falls back to on a `fallback` command with `mode=weights`.
+    #[serde(default)]
+    pub fallback_weights: HashMap<String, f64>,
+    /// Key the model's commands are signed with; `env:NAME` reads it from the environment.
+    #[serde(default)]
+    pub auth_secret: Option<String>,
+    /// `read_only` models may only `query`; `trading` (the default) may send any command.
+    #[serde(default)]
+    pub permission: ModelPermission,
+    /// Feature columns or column prefixes the model receives.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// OI change lookbacks in rows this model receives.
+    #[serde(default)]
+    pub oi_change_windows: Option<Vec<i64>>,
+    /// Seconds between feature sends to this model.
+    #[serde(default)]
+    pub interval_sec: Option<u64>,
+    /// Heartbeat cycles without a reply before the model counts as unresponsive.
+    #[serde(default)]
+    pub max_missed_heartbeats: Option<u32>,
+    /// Oldest `adjust_position` by its `ts` that is still applied, in milliseconds.
+    #[serde(default)]
+    pub max_prediction_age_ms: Option<u64>,
+    /// Bound of `adjust_position` targets, which must lie within `[-max, max]`.
+    #[serde(default)]
+    pub max_abs_target: Option<f64>,
+    /// Farthest an `adjust_position` price may be from the local price, in bps.
+    #[serde(default)]
+    pub max_price_deviation_bps: Option<f64>,
+    /// Weight of the model's targets in the `weighted` ensemble.
+    #[serde(default)]
+    pub ensemble_weight: Option<f64>,
+    /// Operating mode of the model.
+    #[serde(default)]
+    pub mode: ModelMode,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelMode {
+    #[default]
+    Live,
+    Shadow,
 }
 
 impl Default for ModelConfig {
@@ -40,6 +181,398 @@ impl Default for ModelConfig {
             port: 0,
             model_id: "".to_string(),
             account_id: "".to_string(),
+            target_leverage: None,
+            insts: Vec::new(),
+            strict_contract: false,
+            decay_sec: None,
+            delta_encoding: false,
+            fallback_weights: HashMap::new(),
+            auth_secret: None,
+            permission: ModelPermission::Trading,
+            features: Vec::new(),
+            oi_change_windows: None,
+            interval_sec: None,
+            max_missed_heartbeats: None,
+            max_prediction_age_ms: None,
+            max_abs_target: None,
+            max_price_deviation_bps: None,
+            ensemble_weight: None,
+            mode: ModelMode::Live,
+        }
+    }
+}
+
+impl ModelConfig {
+    /// Sanity checks of an `adjust_position` before it moves any target.
+    pub fn check_prediction(
+        &self,
+        meta: &HashMap<String, String>,
+        target: f64,
+        local_px: Option<f64>,
+        now_ms: u64,
+    ) -> Result<(), String> {
+        if !target.is_finite() {
+            return Err(format!("target {} not finite", target));
+        }
+        if let Some(max) = self.max_abs_target
+            && target.abs() > max
+        {
+            return Err(format!("target {} outside [-{}, {}]", target, max, max));
+        }
+        if let Some(max_age_ms) = self.max_prediction_age_ms
+            && let Some(ts) = meta.get("ts").and_then(|ts| ts.parse::<u64>().ok())
+            && now_ms.saturating_sub(ts) > max_age_ms
+        {
+            return Err(format!(
+                "ts {} is {}ms old, max {}ms",
+                ts,
+                now_ms.saturating_sub(ts),
+                max_age_ms
+            ));
+        }
+        if let Some(max_bps) = self.max_price_deviation_bps
+            && let Some(price) = meta.get("price").and_then(|p| p.parse::<f64>().ok())
+            && let Some(local_px) = local_px
+        {
+            let deviation_bps = (price / local_px - 1.0).abs() * 10_000.0;
+            if deviation_bps.is_nan() || deviation_bps > max_bps {
+                return Err(format!(
+                    "price {} is {:.1}bps off the local price {}, max {}bps",
+                    price, deviation_bps, local_px, max_bps
+                ));
+            }
         }
+        Ok(())
+    }
+}
+
+/// Combines per-model raw targets into shared weights, then scales the combined book to the
+/// gross the models' `target_leverage` combine to under the default ensemble method.
+pub fn consolidate_weights(
+    model_targets: &HashMap<String, HashMap<String, f64>>,
+    model_config: &HashMap<String, ModelConfig>,
+    decay: &HashMap<String, f64>,
+    ensemble: &EnsembleConfig,
+) -> HashMap<String, f64> {
+    let mut votes: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
+    let mut budgets: Vec<(f64, f64)> = Vec::new();
+    let mut leveraged = false;
+
+    for (model_id, targets) in model_targets {
+        let config = model_config.get(model_id);
+        let factor = decay.get(model_id).copied().unwrap_or(1.0);
+        let ensemble_weight = config.and_then(|c| c.ensemble_weight).unwrap_or(1.0);
+        for (inst, weight) in targets {
+            votes
+                .entry(inst.clone())
+                .or_default()
+                .push((weight * factor, ensemble_weight));
+        }
+
+        let gross: f64 = targets.values().map(|w| w.abs()).sum();
+        let leverage = config.and_then(|c| c.target_leverage);
+        leveraged |= leverage.is_some();
+        let budget = match leverage {
+            Some(leverage) if gross > f64::EPSILON => leverage,
+            Some(_) => 0.0,
+            None => gross,
+        };
+        budgets.push((budget * factor, ensemble_weight));
+    }
+
+    let mut combined: HashMap<String, f64> = votes
+        .into_iter()
+        .map(|(inst, votes)| {
+            let weight = ensemble.method_for(&inst).combine(&votes);
+            (inst, weight)
+        })
+        .collect();
+
+    // Without any declared leverage the combined raw targets are the book
+    let gross: f64 = combined.values().map(|w| w.abs()).sum();
+    if leveraged && gross > f64::EPSILON {
+        let scale = ensemble.method.combine(&budgets) / gross;
+        combined.values_mut().for_each(|w| *w *= scale);
+    }
+
+    combined
+}
+
+/// Factor taking one model's raw targets, on their own, to its `target_leverage`.
+pub fn model_scale(targets: &HashMap<String, f64>, config: Option<&ModelConfig>) -> f64 {
+    let gross: f64 = targets.values().map(|w| w.abs()).sum();
+    match config.and_then(|c| c.target_leverage) {
+        Some(leverage) if gross > f64::EPSILON => leverage / gross,
+        Some(_) => 0.0,
+        None => 1.0,
+    }
+}
+
+/// Many-to-many map from instruments to the models consuming their feature frames.
+#[derive(Clone, Debug, Default)]
+pub struct RoutingTable {
+    by_inst: HashMap<String, Vec<String>>,
+}
+
+impl RoutingTable {
+    pub fn from_configs(model_config: &HashMap<String, ModelConfig>, universe: &[String]) -> Self {
+        let mut by_inst: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (model_id, cfg) in model_config.iter() {
+            let insts = if cfg.insts.is_empty() {
+                universe.to_vec()
+            } else {
+                cfg.insts.clone()
+            };
+
+            for inst in insts {
+                let models = by_inst.entry(inst).or_default();
+                if !models.contains(model_id) {
+                    models.push(model_id.clone());
+                }
+            }
+        }
+
+        by_inst.values_mut().for_each(|models| models.sort());
+        Self { by_inst }
+    }
+
+    /// Every routed instrument, sorted.
+    pub fn insts(&self) -> Vec<String> {
+        let mut insts: Vec<String> = self.by_inst.keys().cloned().collect();
+        insts.sort();
+        insts
+    }
+
+    /// Instruments routed to `model_id`, sorted.
+    pub fn insts_for(&self, model_id: &str) -> Vec<String> {
+        let mut insts: Vec<String> = self
+            .by_inst
+            .iter()
+            .filter(|(_, models)| models.iter().any(|m| m == model_id))
+            .map(|(inst, _)| inst.clone())
+            .collect();
+        insts.sort();
+        insts
+    }
+
+    pub fn models_for(&self, inst: &str) -> &[String] {
+        self.by_inst.get(inst).map(|m| m.as_slice()).unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    pub port: u64,
+    pub tensor: AltTensor,
+    pub reason: String,
+    pub queued_at: Instant,
+}
+
+/// Bounded buffer of tensors that could not be handed to a model task.
+#[derive(Clone, Debug, Default)]
+pub struct DeadLetterQueue {
+    letters: VecDeque<DeadLetter>,
+    pub enqueued: u64,
+    pub redelivered: u64,
+    pub dropped: u64,
+}
+
+impl DeadLetterQueue {
+    pub fn push(&mut self, port: u64, tensor: AltTensor, reason: String) {
+        self.enqueued += 1;
+        self.letters.push_back(DeadLetter {
+            port,
+            tensor,
+            reason,
+            queued_at: Instant::now(),
+        });
+
+        while self.letters.len() > MAX_DEAD_LETTERS {
+            self.letters.pop_front();
+            self.dropped += 1;
+        }
+
+        warn!(
+            "[DLQ] Model send to port {} failed, queued={} enqueued={} dropped={}",
+            port,
+            self.letters.len(),
+            self.enqueued,
+            self.dropped
+        );
+    }
+
+    /// Puts letters back at the front after a failed redelivery, keeping their age and order.
+    pub fn requeue(&mut self, letters: Vec<DeadLetter>) {
+        for letter in letters.into_iter().rev() {
+            self.letters.push_front(letter);
+        }
+
+        while self.letters.len() > MAX_DEAD_LETTERS {
+            self.letters.pop_back();
+            self.dropped += 1;
+        }
+    }
+
+    /// Removes and returns the port's letters in send order, dropping expired ones.
+    pub fn take_for_port(&mut self, port: u64) -> Vec<DeadLetter> {
+        let (taken, kept): (VecDeque<_>, VecDeque<_>) =
+            self.letters.drain(..).partition(|l| l.port == port);
+        self.letters = kept;
+
+        let (fresh, expired): (Vec<_>, Vec<_>) = taken
+            .into_iter()
+            .partition(|l| l.queued_at.elapsed() < DEAD_LETTER_TTL);
+
+        if !expired.is_empty() {
+            self.dropped += expired.len() as u64;
+            warn!(
+                "[DLQ] Dropped {} expired tensor(s) for port {}, dropped={}",
+                expired.len(),
+                port,
+                self.dropped
+            );
+        }
+
+        fresh
+    }
+
+    /// Discards every letter for the port, e.g. once no configured model listens on it.
+    pub fn drop_port(&mut self, port: u64) -> usize {
+        let before = self.letters.len();
+        self.letters.retain(|l| l.port != port);
+
+        let removed = before - self.letters.len();
+        self.dropped += removed as u64;
+        removed
+    }
+
+    pub fn ports(&self) -> Vec<u64> {
+        let mut ports: Vec<u64> = self.letters.iter().map(|l| l.port).collect();
+        ports.sort();
+        ports.dedup();
+        ports
+    }
+}
+
+/// Feature column order each model was first sent.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ColumnPins {
+    pins: HashMap<String, Vec<String>>,
+}
+
+impl ColumnPins {
+    pub fn load() -> InfraResult<Self> {
+        let path = config_path(COLUMN_ORDER_FILE)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| InfraError::Msg(format!("Failed to read {:?}: {}", path, e)))?;
+        let pins: HashMap<String, Vec<String>> = serde_json::from_str(&content)
+            .map_err(|e| InfraError::Msg(format!("Failed to parse {:?}: {}", path, e)))?;
+        info!("[Data] Column order pinned for {} models", pins.len());
+
+        Ok(Self { pins })
+    }
+
+    /// Pinned column order of `model_id`.
+    pub fn order_for(&mut self, model_id: &str, columns: &[String]) -> InfraResult<Vec<String>> {
+        let Some(pinned) = self.pins.get(model_id) else {
+            let mut pins = self.pins.clone();
+            pins.insert(model_id.to_string(), columns.to_vec());
+            Self::persist(&pins)?;
+            self.pins = pins;
+            info!("[Data] Pinned {} feature columns for model={}", columns.len(), model_id);
+            return Ok(columns.to_vec());
+        };
+
+        let missing: Vec<&String> = pinned.iter().filter(|c| !columns.contains(c)).collect();
+        let extra: Vec<&String> = columns.iter().filter(|c| !pinned.contains(c)).collect();
+        if !missing.is_empty() || !extra.is_empty() {
+            return Err(InfraError::Msg(format!(
+                "Feature columns of model={} differ from {}: missing {:?}, extra {:?}",
+                model_id, COLUMN_ORDER_FILE, missing, extra
+            )));
+        }
+
+        if pinned.as_slice() != columns {
+            warn!("[Data] Feature columns reordered for model={}, sending pinned order", model_id);
+        }
+        Ok(pinned.clone())
+    }
+
+    fn persist(pins: &HashMap<String, Vec<String>>) -> InfraResult<()> {
+        let path = config_path(COLUMN_ORDER_FILE)?;
+        let content = serde_json::to_string_pretty(pins)?;
+        fs::write(&path, content)
+            .map_err(|e| InfraError::Msg(format!("Failed to write {:?}: {}", path, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arch::server_module::server_ensemble::EnsembleMethod;
+
+    fn models(leverage: [Option<f64>; 2]) -> HashMap<String, ModelConfig> {
+        ["m1", "m2"]
+            .into_iter()
+            .zip(leverage)
+            .map(|(model_id, target_leverage)| {
+                let cfg = ModelConfig {
+                    model_id: model_id.to_string(),
+                    target_leverage,
+                    ..ModelConfig::default()
+                };
+                (model_id.to_string(), cfg)
+            })
+            .collect()
+    }
+
+    fn targets() -> HashMap<String, HashMap<String, f64>> {
+        let book = |weights: &[(&str, f64)]| {
+            weights
+                .iter()
+                .map(|(inst, w)| (inst.to_string(), *w))
+                .collect::<HashMap<String, f64>>()
+        };
+        HashMap::from([
+            ("m1".to_string(), book(&[("BTC", 0.5), ("ETH", 0.5)])),
+            ("m2".to_string(), book(&[("BTC", -0.2), ("SOL", 0.2)])),
+        ])
+    }
+
+    fn gross(weights: &HashMap<String, f64>) -> f64 {
+        weights.values().map(|w| w.abs()).sum()
+    }
+
+    #[test]
+    fn overlapping_models_reach_combined_leverage() {
+        let config = models([Some(2.0), Some(1.0)]);
+        let sum = EnsembleConfig::default();
+        let combined = consolidate_weights(&targets(), &config, &HashMap::new(), &sum);
+        assert!((gross(&combined) - 3.0).abs() < 1e-9, "{:?}", combined);
+
+        let mean = EnsembleConfig {
+            method: EnsembleMethod::Mean,
+            ..EnsembleConfig::default()
+        };
+        let combined = consolidate_weights(&targets(), &config, &HashMap::new(), &mean);
+        assert!((gross(&combined) - 1.5).abs() < 1e-9, "{:?}", combined);
+    }
+
+    #[test]
+    fn undeclared_models_keep_their_raw_targets() {
+        let combined = consolidate_weights(
+            &targets(),
+            &models([None, None]),
+            &HashMap::new(),
+            &EnsembleConfig::default(),
+        );
+        assert!((combined["BTC"] - 0.3).abs() < 1e-9);
+        assert!((combined["ETH"] - 0.5).abs() < 1e-9);
+        assert!((combined["SOL"] - 0.2).abs() < 1e-9);
     }
 }
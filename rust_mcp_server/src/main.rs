@@ -1,6 +1,6 @@
 use dashmap::DashMap;
-use std::{sync::Arc, time::Duration};
-use tracing::info;
+use std::{path::Path, sync::Arc, time::Duration};
+use tracing::{error, info, warn};
 use tracing_subscriber;
 
 use extrema_infra::prelude::*;
@@ -9,12 +9,50 @@ use extrema_infra::prelude::*;
 mod arch;
 use arch::{
     account_module::{
-        acc_base::{AccountManager, TargetWeights},
-        acc_utils::AccountInitConfig,
+        acc_base::{
+            AccountManager, AccountSnapshots, ExecutionQueue, PriceLimits, RejectionQueue,
+            TargetWeights,
+        },
+        acc_own_orders::OwnOrderBook,
+        acc_requests::AccountRequests,
+        acc_retry::RetryPolicy,
+        acc_utils::{AccountInitConfig, SchedulerIntervals},
+    },
+    control_module::{
+        ctl_admin::{AdminTokens, set_audit_path},
+        ctl_attribution::AttributionHistory,
+        ctl_audit::CommandAudit,
+        ctl_auth::CommandAuth,
+        ctl_base::{CONTROL_STATE_FILE, ControlState},
+        ctl_lint::lint_configs,
+        ctl_portfolio::PortfolioView,
+        ctl_risk::RiskLimits,
+        ctl_socket::ControlSocket,
+        ctl_systemd::SystemdNotify,
+        ctl_watchdog::{TaskHeartbeats, Watchdog},
+    },
+    profile::{PROTOCOL_SPEC_FLAG, select_profile},
+    risk_module::{
+        risk_alert::RiskAlerts,
+        risk_base::RiskManager,
+    },
+    server_module::{
+        server_base::McpServer,
+        server_contract::protocol_spec,
+        server_golden::GoldenRecorder,
+        server_mcp::{InjectedCommands, LatestFeatures, McpHandler, McpStdio},
+        server_mcp_http::McpHttp,
+        server_utils::{build_model_tasks, load_model_config},
     },
-    server_module::server_base::McpServer,
 };
 
+fn task_base_id(task: &TaskInfo) -> Option<u64> {
+    match task {
+        TaskInfo::WsTask(info) => info.task_base_id,
+        TaskInfo::AltTask(info) => info.task_base_id,
+    }
+}
+
 fn build_account_ws_tasks() -> Vec<TaskInfo> {
     vec![
         TaskInfo::WsTask(Arc::new(WsTaskInfo {
@@ -31,29 +69,149 @@ fn build_account_ws_tasks() -> Vec<TaskInfo> {
             chunk: 1,
             task_base_id: Some(1150),
         })),
+        TaskInfo::WsTask(Arc::new(WsTaskInfo {
+            market: Market::Bybit,
+            ws_channel: WsChannel::AccountOrders,
+            filter_channels: false,
+            chunk: 1,
+            task_base_id: Some(1200),
+        })),
+        TaskInfo::WsTask(Arc::new(WsTaskInfo {
+            market: Market::Bybit,
+            ws_channel: WsChannel::AccountBalAndPos,
+            filter_channels: false,
+            chunk: 1,
+            task_base_id: Some(1250),
+        })),
+        TaskInfo::WsTask(Arc::new(WsTaskInfo {
+            market: Market::HyperLiquid,
+            ws_channel: WsChannel::AccountOrders,
+            filter_channels: false,
+            chunk: 1,
+            task_base_id: Some(1300),
+        })),
+        TaskInfo::WsTask(Arc::new(WsTaskInfo {
+            market: Market::HyperLiquid,
+            ws_channel: WsChannel::AccountBalAndPos,
+            filter_channels: false,
+            chunk: 1,
+            task_base_id: Some(1350),
+        })),
     ]
 }
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
-    info!("Logger initialized");
+    // `--protocol-spec`: the model protocol of this build as JSON, for the Python side and
+    // its docs; needs no profile
+    if std::env::args().any(|arg| arg == PROTOCOL_SPEC_FLAG) {
+        println!("{:#}", protocol_spec());
+        return;
+    }
 
-    let shared_inst_target_weight: TargetWeights = Arc::new(DashMap::new());
+    // dev / staging / prod: config directory, endpoints and log level
+    let profile = match select_profile() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to select profile: {:?}", e);
+            std::process::exit(1);
+        },
+    };
+
+    // Under `--mcp-stdio` and `--validate-config` stdout carries the protocol or the report,
+    // so logs go to stderr
+    let logger = tracing_subscriber::fmt().with_max_level(profile.log_level());
+    if profile.mcp_stdio || profile.validate_config {
+        logger.with_writer(std::io::stderr).init();
+    } else {
+        logger.init();
+    }
+    info!(
+        "Logger initialized, profile={} config_dir={:?} testnet={} paper_only={} observe={}",
+        profile.name, profile.config_dir, profile.testnet, profile.paper_only, profile.observe
+    );
+    if profile.observe {
+        warn!("Observer mode: accounts are read-only, all order placement is disabled");
+    }
 
+    let shared_inst_target_weight: TargetWeights = Arc::new(DashMap::new());
+    let shared_rejections = RejectionQueue::default();
+    let shared_executions = ExecutionQueue::default();
+    let mut shared_control = ControlState::default();
+    shared_control.with_admin_tokens(AdminTokens::from_env());
+    if let Err(e) = shared_control.restore(Path::new(CONTROL_STATE_FILE)) {
+        error!("Failed to restore control state: {:?}", e);
+        std::process::exit(1);
+    }
+    let shared_snapshots: AccountSnapshots = Arc::new(DashMap::new());
+    let shared_portfolio = PortfolioView::default();
+    let shared_own_orders = OwnOrderBook::default();
+    let shared_toxicity = Arc::new(DashMap::new());
+    let shared_price_limits: PriceLimits = Arc::new(DashMap::new());
+    let shared_heartbeats = TaskHeartbeats::default();
     let acc_config = AccountInitConfig {
         reload_task_id: 2,
         update_task_id: 3,
         reload_interval_sec: 3600,
         update_interval_sec: 30,
+        model_data_interval_sec: 30,
+        tick_interval_sec: 1,
+        update_jitter_ms: 2000,
+        intent_log_path: "order_intents.jsonl".to_string(),
+        limit_order_timeout_sec: 30,
+        journal_dir: "journals".to_string(),
+        tracking_report_path: "tracking_report.jsonl".to_string(),
+        admin_audit_path: "admin_audit.jsonl".to_string(),
+        retry: RetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 250,
+            max_delay_ms: 4000,
+        },
+        rate_limits: profile.rate_limits.clone(),
+        idle_inst_ttl_sec: 86_400,
     };
+    set_audit_path(&acc_config.admin_audit_path);
 
-    // Machine Learning models
-    let model_task = AltTaskInfo {
-        alt_task_type: AltTaskType::ModelPreds(5001), // Zeromq port
-        chunk: 1,
-        task_base_id: Some(5001), // Custom task ID
+    // Model ports double as task ids and must not take one of these
+    let account_ws_tasks = build_account_ws_tasks();
+    let reserved_task_ids: Vec<u64> = account_ws_tasks
+        .iter()
+        .filter_map(task_base_id)
+        .chain([acc_config.reload_task_id, acc_config.update_task_id])
+        .collect();
+
+    // `--validate-config`: cross-check the configs, print the report and exit non-zero on
+    // errors, e.g. as a deploy gate
+    if profile.validate_config {
+        let report = lint_configs(&reserved_task_ids).await;
+        println!("{:#}", report.to_json());
+        std::process::exit(if report.has_errors() { 1 } else { 0 });
+    }
+
+    let risk_limits = match RiskLimits::load() {
+        Ok(limits) => limits,
+        Err(e) => {
+            error!("Failed to load risk config: {:?}", e);
+            std::process::exit(1);
+        },
+    };
+    let watchdog_config = risk_limits.get().watchdog;
+
+    // Adjustable at runtime through the `set_interval` MCP command
+    let scheduler_intervals = SchedulerIntervals::new(
+        acc_config.update_interval_sec,
+        acc_config.model_data_interval_sec,
+    );
+
+    // Machine Learning models, one ZeroMQ task per port in model_config.json
+    let model_configs = match load_model_config() {
+        Ok(configs) => configs,
+        Err(e) => {
+            error!("Failed to load model config, no model tasks bound: {:?}", e);
+            Vec::new()
+        },
     };
+    let model_tasks = build_model_tasks(&model_configs, &reserved_task_ids);
 
     // For periodic reload account info from config
     let acc_reload_scheduler_task = AltTaskInfo {
@@ -64,10 +222,10 @@ async fn main() {
         task_base_id: Some(acc_config.reload_task_id),
     };
 
-    // Update account Pos & Bal info
+    // Update account Pos & Bal info, gated by scheduler_intervals
     let acc_update_scheduler_task = AltTaskInfo {
         alt_task_type: AltTaskType::TimeScheduler(Duration::from_secs(
-            acc_config.update_interval_sec,
+            acc_config.tick_interval_sec,
         )),
         chunk: 1,
         task_base_id: Some(acc_config.update_task_id),
@@ -81,27 +239,131 @@ async fn main() {
         task_base_id: None,
     };
 
-    let mut account_module = AccountManager::new(acc_config);
+    // Trade flow for the coalescer; prices stay candle-only
+    let binance_ws_trades = WsTaskInfo {
+        market: Market::BinanceUmFutures,
+        ws_channel: WsChannel::Trades(None),
+        filter_channels: false,
+        chunk: 1,
+        task_base_id: None,
+    };
+
     let mut mcp_server = McpServer::new();
+    mcp_server.with_reload_task_id(acc_config.reload_task_id);
+    mcp_server.with_idle_inst_ttl(acc_config.idle_inst_ttl_sec);
+    mcp_server.with_golden_recorder(GoldenRecorder::new(profile.record_golden));
+    let mut account_module = AccountManager::new(acc_config);
 
     account_module.with_target_weights(shared_inst_target_weight.clone());
     mcp_server.with_target_weights(shared_inst_target_weight.clone());
+    account_module.with_scheduler_intervals(scheduler_intervals.clone());
+    mcp_server.with_scheduler_intervals(scheduler_intervals.clone());
+    account_module.with_rejection_queue(shared_rejections.clone());
+    mcp_server.with_rejection_queue(shared_rejections.clone());
+    account_module.with_execution_queue(shared_executions.clone());
+    mcp_server.with_execution_queue(shared_executions.clone());
+    account_module.with_control_state(shared_control.clone());
+    mcp_server.with_control_state(shared_control.clone());
+    account_module.with_account_snapshots(shared_snapshots.clone());
+    mcp_server.with_account_snapshots(shared_snapshots.clone());
+    account_module.with_portfolio_view(shared_portfolio.clone());
+    mcp_server.with_portfolio_view(shared_portfolio.clone());
+    account_module.with_own_orders(shared_own_orders.clone());
+    account_module.with_toxicity(shared_toxicity.clone());
+    mcp_server.with_toxicity(shared_toxicity);
+    account_module.with_price_limits(shared_price_limits.clone());
+    mcp_server.with_price_limits(shared_price_limits);
+    account_module.with_risk_limits(risk_limits.clone());
+    mcp_server.with_risk_limits(risk_limits.clone());
+
+    // MCP `kill` and `kill_switch` in the risk config feed the control kill switch
+    let mut risk_manager = RiskManager::new();
+    risk_manager
+        .with_control_state(shared_control.clone(), shared_inst_target_weight.clone())
+        .with_risk_limits(risk_limits);
+
+    // Per-model `auth_secret` and `permission`, checked wherever MCP commands are acted on
+    let command_auth = CommandAuth::default();
+    command_auth.load(&model_configs);
+    // Every module reads the same command tensors, so each keeps its own replay window
+    risk_manager.with_command_auth(command_auth.scoped());
+    account_module.with_command_auth(command_auth.scoped());
+    mcp_server.with_command_auth(command_auth);
+
+    // Every MCP command, the weights it moved and the orders that followed
+    let command_audit = CommandAudit::default();
+    account_module.with_command_audit(command_audit.clone());
+    mcp_server.with_command_audit(command_audit);
+
+    // MCP `risk_alert`: the server scales the targets, the accounts cancel resting orders
+    let risk_alerts = RiskAlerts::default();
+    account_module.with_risk_alerts(risk_alerts.clone());
+    mcp_server.with_risk_alerts(risk_alerts);
+    account_module.with_watchdog(shared_heartbeats.clone(), watchdog_config.clone());
+    mcp_server.with_watchdog(shared_heartbeats.clone(), watchdog_config.clone());
+
+    // MCP clients (`--mcp-stdio`, `--mcp-http <addr>`): positions, targets and features as
+    // tools, and `adjust_position` on behalf of a configured model
+    let shared_features: LatestFeatures = Arc::new(DashMap::new());
+    let shared_injected = InjectedCommands::default();
+    mcp_server.with_mcp_clients(shared_features.clone(), shared_injected.clone());
+    let mut mcp_handler = McpHandler::new(
+        shared_portfolio.clone(),
+        shared_features,
+        shared_injected,
+        model_configs.iter().map(|cfg| cfg.model_id.clone()).collect(),
+    );
+    // `list_open_orders` / `cancel_all_orders`, answered by the accounts on their next tick
+    let account_requests = AccountRequests::default();
+    account_module.with_account_requests(account_requests.clone());
+    mcp_handler.with_account_requests(account_requests);
+    // Target versus executed weights per model and instrument, kept across restarts
+    let attribution = AttributionHistory::default();
+    match attribution.load() {
+        Ok(rows) => info!("[Attribution] Loaded {} history rows", rows),
+        Err(e) => warn!("[Attribution] History not loaded: {:?}", e),
+    }
+    mcp_server.with_attribution(attribution.clone());
+    mcp_handler.with_attribution(attribution);
+    if profile.mcp_stdio {
+        McpStdio::new(mcp_handler.clone()).spawn();
+    }
+    if let Some(addr) = &profile.mcp_http_addr {
+        McpHttp::new(addr, mcp_handler).spawn();
+    }
+
+    // Operator commands on the box: pause / resume / flatten / status / orders / set-target
+    let mut control_socket =
+        ControlSocket::new("mcp_control.sock", shared_control, shared_inst_target_weight.clone());
+    control_socket
+        .with_portfolio_view(shared_portfolio)
+        .with_own_orders(shared_own_orders);
+    control_socket.spawn();
+
+    // Alerts on silent WS, model and scheduler tasks; the owning modules restart them.
+    // Under systemd it also sends READY, then WATCHDOG pings while the event loops tick
+    let mut watchdog = Watchdog::new(shared_heartbeats, watchdog_config);
+    watchdog.with_systemd(SystemdNotify::from_env());
+    watchdog.spawn();
 
     let env = EnvBuilder::new()
         .with_board_cast_channel(BoardCastChannel::default_alt_event())
         .with_board_cast_channel(BoardCastChannel::default_ws_event())
         .with_board_cast_channel(BoardCastChannel::default_candle())
+        .with_board_cast_channel(BoardCastChannel::default_trade())
         .with_board_cast_channel(BoardCastChannel::default_scheduler())
         .with_board_cast_channel(BoardCastChannel::default_model_preds())
         .with_board_cast_channel(BoardCastChannel::default_account_order())
         .with_board_cast_channel(BoardCastChannel::default_account_bal_pos())
-        .with_task(TaskInfo::AltTask(Arc::new(model_task)))
+        .with_tasks(model_tasks)
         .with_task(TaskInfo::AltTask(Arc::new(acc_reload_scheduler_task)))
         .with_task(TaskInfo::AltTask(Arc::new(acc_update_scheduler_task)))
         .with_task(TaskInfo::WsTask(Arc::new(binance_ws_candle)))
-        .with_tasks(build_account_ws_tasks())
+        .with_task(TaskInfo::WsTask(Arc::new(binance_ws_trades)))
+        .with_tasks(account_ws_tasks)
         .with_strategy_module(account_module)
         .with_strategy_module(mcp_server)
+        .with_strategy_module(risk_manager)
         .build();
 
     // Start event loop (spawns all tasks, connects strategies, begins message flow)